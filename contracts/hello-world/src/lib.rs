@@ -1,30 +1,203 @@
 #![no_std]
+#![allow(clippy::too_many_arguments)]
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, panic_with_error, 
-    Address, BytesN, Env, Symbol, Vec, token, Map
+    contract, contracterror, contractclient, contractimpl, contracttype, symbol_short, panic_with_error, 
+    Address, Bytes, BytesN, Env, IntoVal, String, Symbol, TryFromVal, Val, Vec, token, Map,
+    xdr::ToXdr,
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
 };
 
 // ======================
 // CONSTANTS & EVENT TYPES
 // ======================
+// Job events publish topics (EVENT, job_id, actor) with a versioned payload
+// struct from EVENT PAYLOADS below. Account and admin events, which have no
+// job, publish topics (EVENT, actor).
 const JOB_CRT: Symbol = symbol_short!("JOB_CRT");      // Job created event
 const JOB_FUND: Symbol = symbol_short!("JOB_FUND");    // Job funded event
-const TAL_SEL: Symbol = symbol_short!("TAL_SEL");      // Talent selected event
+const JOB_SPN: Symbol = symbol_short!("JOB_SPN");      // Job funded by a sponsor event
+const CONTRIB: Symbol = symbol_short!("CONTRIB");      // Crowdfunding contribution event
+const JOB_META: Symbol = symbol_short!("JOB_META");    // Job metadata set event
+const JOB_ARC: Symbol = symbol_short!("JOB_ARC");      // Finished job archived event
+const ESC_IN: Symbol = symbol_short!("ESC_IN");        // Tokens received by the contract event
+const ESC_OUT: Symbol = symbol_short!("ESC_OUT");      // Tokens paid out by the contract event
+const TOP_UP: Symbol = symbol_short!("TOP_UP");        // Escrow topped up event
+const SURPLUS: Symbol = symbol_short!("SURPLUS");      // Surplus escrow withdrawn event
+const TAL_SEL: Symbol = symbol_short!("TAL_SEL");      // Talent accepted offer event
+const OFFER: Symbol = symbol_short!("OFFER");          // Job offered to talent event
+const OFF_DEC: Symbol = symbol_short!("OFF_DEC");      // Offer declined event
+const TAL_INV: Symbol = symbol_short!("TAL_INV");      // Talent invited to private job event
+const INV_REV: Symbol = symbol_short!("INV_REV");      // Invitation revoked event
+const JAC_PROP: Symbol = symbol_short!("JAC_PROP");    // Multisig job action proposed event
+const JAC_APPR: Symbol = symbol_short!("JAC_APPR");    // Multisig job action approved event
+const MGR_SET: Symbol = symbol_short!("MGR_SET");      // Job manager added or updated event
+const MGR_REM: Symbol = symbol_short!("MGR_REM");      // Job manager removed event
+const JOB_XFER: Symbol = symbol_short!("JOB_XFER");    // Job ownership transferred event
 const WRK_SUB: Symbol = symbol_short!("WRK_SUB");      // Work submitted event
 const MIL_APR: Symbol = symbol_short!("MIL_APR");      // Milestone approved event
+const MIL_REJ: Symbol = symbol_short!("MIL_REJ");      // Milestone submission rejected event
+const DL_REQ: Symbol = symbol_short!("DL_REQ");        // Deadline extension requested event
+const DL_EXT: Symbol = symbol_short!("DL_EXT");        // Deadline extended event
+const MIL_BAT: Symbol = symbol_short!("MIL_BAT");      // Milestones batch-approved event
+const MIL_VST: Symbol = symbol_short!("MIL_VST");      // Approved payment vesting event
+const VST_CLM: Symbol = symbol_short!("VST_CLM");      // Vested payments claimed event
+const VST_FRZ: Symbol = symbol_short!("VST_FRZ");      // Vesting payment frozen into a dispute event
+const BONUS: Symbol = symbol_short!("BONUS");          // Bonus paid on approval event
+const TIP: Symbol = symbol_short!("TIP");              // Tip paid after completion event
+const PAY_SPL: Symbol = symbol_short!("PAY_SPL");      // Payout split set event
+const SUB_CRT: Symbol = symbol_short!("SUB_CRT");      // Subjob created event
 const DIS_RIS: Symbol = symbol_short!("DIS_RIS");      // Dispute raised event
 const DIS_RES: Symbol = symbol_short!("DIS_RES");      // Dispute resolved event
+const DIS_PNL: Symbol = symbol_short!("DIS_PNL");      // Panel dispute raised event
+const DIS_VOTE: Symbol = symbol_short!("DIS_VOTE");    // Panel vote cast event
+const DIS_SPL: Symbol = symbol_short!("DIS_SPL");      // Dispute resolved with split event
+const APPEAL: Symbol = symbol_short!("APPEAL");        // Ruling appealed event
+const RUL_EXE: Symbol = symbol_short!("RUL_EXE");      // Ruling executed event
+const DIS_TOUT: Symbol = symbol_short!("DIS_TOUT");    // Dispute timed out event
+const ARB_FEEU: Symbol = symbol_short!("ARB_FEEU");    // Arbitrator fee updated event
+const ARB_RATE: Symbol = symbol_short!("ARB_RATE");    // Arbitrator rated event
+const ARB_DREG: Symbol = symbol_short!("ARB_DREG");    // Arbitrator deregistered event
+const REVIEW: Symbol = symbol_short!("REVIEW");        // Review left event
+const TAL_REG: Symbol = symbol_short!("TAL_REG");      // Talent registered event
+const TAL_UPD: Symbol = symbol_short!("TAL_UPD");      // Talent profile updated event
+const TPL_CRT: Symbol = symbol_short!("TPL_CRT");      // Template created event
+const RET_ADV: Symbol = symbol_short!("RET_ADV");      // Retainer period advanced event
+const STR_WDR: Symbol = symbol_short!("STR_WDR");      // Streamed funds withdrawn event
+const STR_STOP: Symbol = symbol_short!("STR_STOP");    // Stream stopped event
+const HRS_LOG: Symbol = symbol_short!("HRS_LOG");      // Hours logged event
+const TS_APR: Symbol = symbol_short!("TS_APR");        // Timesheet approved event
+const CON_ENT: Symbol = symbol_short!("CON_ENT");      // Contest entry submitted event
+const CON_WIN: Symbol = symbol_short!("CON_WIN");      // Contest prize awarded event
+const ARB_PROP: Symbol = symbol_short!("ARB_PROP");    // Arbitrator proposed event
+const ARB_APPR: Symbol = symbol_short!("ARB_APPR");    // Arbitrator approved event
+const EVID_SUB: Symbol = symbol_short!("EVID_SUB");    // Evidence submitted event
 const JOB_CANC: Symbol = symbol_short!("JOB_CANC");    // Job cancelled event
-const RE_ENTRY: Symbol = symbol_short!("RE_ENTRY");    // Reentrancy guard
-const TOKEN_ID: Symbol = symbol_short!("TOKEN_ID");    // Payment token ID
-const ARB_REG: Symbol = symbol_short!("ARB_REG");      // Arbitrator registry
+const CAN_REM: Symbol = symbol_short!("CAN_REM");      // Remaining milestones cancelled event
+const CAN_NOT: Symbol = symbol_short!("CAN_NOT");      // Cancellation notice given event
+const JOB_ABND: Symbol = symbol_short!("JOB_ABND");    // Abandoned job reclaimed event
+const JOB_EXP: Symbol = symbol_short!("JOB_EXP");      // Unfunded job expired event
+const UNSTAFF: Symbol = symbol_short!("UNSTAFF");      // Unstaffed job refunded event
+const TAL_RES: Symbol = symbol_short!("TAL_RES");      // Talent resigned event
+const TAL_RPL: Symbol = symbol_short!("TAL_RPL");      // Talent replaced mid-job event
+const BOND_FFT: Symbol = symbol_short!("BOND_FFT");    // Talent bond forfeited event
+const CAN_PROP: Symbol = symbol_short!("CAN_PROP");    // Cancellation proposed event
+const CAN_ACC: Symbol = symbol_short!("CAN_ACC");      // Mutual cancellation accepted event
+const AMD_PROP: Symbol = symbol_short!("AMD_PROP");    // Milestone amendment proposed or countered event
+const AMD_ACC: Symbol = symbol_short!("AMD_ACC");      // Milestone amendment accepted event
+const AMD_DEC: Symbol = symbol_short!("AMD_DEC");      // Milestone amendment declined event
+const ARB_REG: Symbol = symbol_short!("ARB_REG");      // Arbitrator registered event
+const FEE_SET: Symbol = symbol_short!("FEE_SET");      // Platform fee updated event
+const FEE_WDR: Symbol = symbol_short!("FEE_WDR");      // Platform fees withdrawn event
+const ADM_PROP: Symbol = symbol_short!("ADM_PROP");    // Admin transfer proposed event
+const ADM_ACC: Symbol = symbol_short!("ADM_ACC");      // Admin transfer accepted event
+const PAUSED: Symbol = symbol_short!("PAUSED");        // Contract paused event
+const UNPAUSED: Symbol = symbol_short!("UNPAUSED");    // Contract unpaused event
+const UPGRADE: Symbol = symbol_short!("UPGRADE");      // Contract code upgraded event
+const MIGRATED: Symbol = symbol_short!("MIGRATED");    // State migrated event
+const ACT_QUE: Symbol = symbol_short!("ACT_QUE");      // Admin action queued event
+const ACT_EXE: Symbol = symbol_short!("ACT_EXE");      // Admin action executed event
+const ACT_CAN: Symbol = symbol_short!("ACT_CAN");      // Admin action cancelled event
+const ARB_UNST: Symbol = symbol_short!("ARB_UNST");    // Arbitrator unstake event
+const ARB_SLSH: Symbol = symbol_short!("ARB_SLSH");    // Arbitrator slashed event
+const YLD_SET: Symbol = symbol_short!("YLD_SET");      // Yield strategy changed event
+const YLD_HRV: Symbol = symbol_short!("YLD_HRV");      // Job yield position unwound event
+const YLD_FAIL: Symbol = symbol_short!("YLD_FAIL");    // Yield strategy failed to pay back event
+const YLD_RCV: Symbol = symbol_short!("YLD_RCV");      // Stranded yield position recovered event
+const INS_CLM: Symbol = symbol_short!("INS_CLM");      // Insurance claim filed event
+const INS_VOTE: Symbol = symbol_short!("INS_VOTE");    // Insurance claim vote event
+const INS_RES: Symbol = symbol_short!("INS_RES");      // Insurance claim resolved event
+const REF_PAY: Symbol = symbol_short!("REF_PAY");      // Referral reward credited event
+const REF_CLM: Symbol = symbol_short!("REF_CLM");      // Referral earnings claimed event
+const REPRICE: Symbol = symbol_short!("REPRICE");      // Priced milestone converted at payout event
+const PAY_PREF: Symbol = symbol_short!("PAY_PREF");    // Payout token preference set event
+const PAY_SWP: Symbol = symbol_short!("PAY_SWP");      // Payout swapped through a router event
+const REP_SNAP: Symbol = symbol_short!("REP_SNAP");    // Reputation snapshot recorded event
+const HOOK_ADD: Symbol = symbol_short!("HOOK_ADD");    // Hook registered event
+const HOOK_DEL: Symbol = symbol_short!("HOOK_DEL");    // Hook removed event
+const KEEP_PAY: Symbol = symbol_short!("KEEP_PAY");    // Keeper bounty paid event
+const INSOLV: Symbol = symbol_short!("INSOLV");        // Token balance below liabilities alert
+const SES_GRT: Symbol = symbol_short!("SES_GRT");      // Session key granted event
+const SES_REV: Symbol = symbol_short!("SES_REV");      // Session key revoked event
+const DRF_NEW: Symbol = symbol_short!("DRF_NEW");      // Draft job posted event
+const DRF_PROP: Symbol = symbol_short!("DRF_PROP");    // Draft counter-proposal event
+const DRF_ACC: Symbol = symbol_short!("DRF_ACC");      // Draft proposal accepted event
+const HOOK_FN: Symbol = symbol_short!("on_event");     // Callback invoked on hook targets
+const EVENT_VERSION: u32 = 1;                         // Schema version of event payloads
+const REPUTATION_VERSION: u32 = 1;                    // Schema version of ReputationSummary
+const TERMS_VERSION: u32 = 2;                         // Schema version of JobTerms
+const NO_JOB: u32 = 0;                                // Job id on events for contract-level balances
+const DERIVED_ID_FLAG: u32 = 0x8000_0000;             // High bit marks ids derived from a client nonce
+const ARCHIVE_RETENTION: u64 = 7_776_000;             // Finished jobs stay whole this long (90 days)
+const GENERAL: Symbol = symbol_short!("general");     // Category of jobs created without one
 const ARB_FEE: i128 = 5;                              // Default arbitration fee (5%)
+const DEFAULT_ARB_FEE_MAX: i128 = 10;                 // Default arbitrator fee cap (10%)
+const BPS_DENOM: i128 = 10_000;                       // Basis point denominator
+const MAX_PLAT_FEE_BPS: u32 = 1_000;                  // Platform fee cap (10%)
+const STATE_VERSION: u32 = 3;                         // Current storage layout version
+const DEFAULT_TL_DELAY: u64 = 172_800;                // Default timelock delay (48 hours)
+const MIN_TL_DELAY: u64 = 86_400;                     // Timelock delay floor (24 hours)
+const DEFAULT_MIN_STAKE: i128 = 1_000;                // Default minimum arbitrator stake
+const UNSTAKE_COOLDOWN: u64 = 604_800;                // Arbitrator unstake cooldown (7 days)
+const VOTE_WINDOW: u64 = 604_800;                     // Panel voting window (7 days)
+const APPROVAL_WINDOW: u64 = 259_200;                 // Arbitrator approval window (3 days)
+const MAX_EVIDENCE: u32 = 20;                         // Evidence entries per dispute
+const MAX_NOTE_LEN: u32 = 256;                        // Evidence note size (bytes)
+const DEFAULT_URI_MAX: u32 = 256;                     // Default metadata/deliverable URI cap (bytes)
+const MAX_SUBMISSIONS: u32 = 20;                      // Submissions kept per milestone
+const APPEAL_WINDOW: u64 = 259_200;                   // Appeal window after ruling (3 days)
+const APPEAL_BOND_BPS: i128 = 1_000;                  // Appeal bond (10% of disputed amount)
+const RULING_WINDOW: u64 = 1_209_600;                 // Time for arbitrators to rule (14 days)
+const MAX_REASSIGN: u32 = 2;                          // Reassignments before default ruling
+const RATING_WEIGHT: u32 = 20;                        // Weight of a new rating in reputation (%)
+const MAX_PAGE: u32 = 50;                             // Max results per list query
+const INDEX_PAGE: u32 = 100;                          // Entries per paged index storage slot
+const MAX_SKILLS: u32 = 20;                           // Skills per talent profile
+const MAX_BATCH: u32 = 10;                            // Jobs per batch creation
+const MAX_TIMESHEETS: u32 = 200;                      // Work logs per hourly job
+const MAX_ENTRIES: u32 = 100;                         // Entries per contest
+const MAX_SPLIT: u32 = 10;                            // Recipients per payout split
+const MAX_POLICY_ARBS: u32 = 10;                      // Pre-agreed arbitrators per job
+const MAX_INVITES: u32 = 50;                          // Invited talents per private job
+const MAX_TAGS: u32 = 5;                              // Tags per job
+const MAX_APPROVERS: u32 = 10;                        // Approvers per multisig client
+const MAX_MANAGERS: u32 = 5;                          // Delegated managers per job
+const OFFER_WINDOW: u64 = 604_800;                    // Time to accept a job offer (7 days)
+const DEFAULT_CANCEL_FEE_BPS: u32 = 1_000;            // Default cancellation fee (10% of unpaid value)
+const DEFAULT_CANCEL_FEE_MAX: u32 = 2_000;            // Default cancellation fee cap (20%)
+const DEFAULT_FUND_WINDOW: u64 = 2_592_000;           // Time to fund a new job (30 days)
+const DEFAULT_STAFF_WINDOW: u64 = 2_592_000;          // Time to hire once funded (30 days)
+const DEFAULT_INS_SHARE_BPS: u32 = 1_000;             // Share of platform fees to insurance (10%)
+const CLAIM_PANEL: u32 = 3;                           // Matching arbitrator votes to decide a claim
+const DEFAULT_REFERRAL_BPS: u32 = 1_000;              // Referrer share of a job's platform fees (10%)
+const RATE_SCALE: i128 = 10_000_000;                  // Fixed-point scale of oracle rates
+const MAX_PRICE_AGE: u64 = 3_600;                     // Oracle prices older than this use the fallback
+const MAX_SLIPPAGE_BPS: u32 = 5_000;                  // Widest allowed slippage band (50%)
+const MAX_HOOKS: u32 = 3;                             // Hook targets per event kind
+const DEFAULT_KEEPER_BOUNTY: i128 = 100_000;          // Keeper reward in the default token
+const MAX_SESSION_JOBS: u32 = 20;                     // Jobs one session key may work on
+const MAX_KILL_TIERS: u32 = 5;                        // Kill-fee schedule entries per job
+const MAX_PROPOSALS: u32 = 20;                        // Counter-proposals per draft
+const MAX_AMENDMENTS: u32 = 20;                       // Rounds per amendment negotiation
+const MAX_CONTRIBUTORS: u32 = 50;                     // Contributors per crowdfunded job
+const MAX_RELEASE_DELAY: u64 = 2_592_000;             // Longest vesting delay after approval (30 days)
+const DEFAULT_TITLE_MAX: u32 = 128;                   // Default job title length cap (bytes)
+const DEFAULT_DESC_MAX: u32 = 1_024;                  // Default milestone description cap (bytes)
+const MAX_TEXT_LEN: u32 = 4_096;                      // Hard cap for configurable text limits
+const DEFAULT_MAX_MILESTONES: u32 = 50;               // Default milestones per job
+const DEFAULT_MAX_OPEN_JOBS: u32 = 100;               // Default unfinished jobs per client
+const MAX_MILESTONES_CAP: u32 = 200;                  // Hard cap for the configurable milestone limit
+const DAY_IN_LEDGERS: u32 = 17_280;                   // Ledgers per day (~5s close time)
+const INSTANCE_TTL: u32 = 30 * DAY_IN_LEDGERS;        // Config/instance storage lifetime
+const INSTANCE_TTL_THRESHOLD: u32 = INSTANCE_TTL - DAY_IN_LEDGERS; // Extend config below this
+const PERSISTENT_TTL: u32 = 120 * DAY_IN_LEDGERS;     // Job/registry entry lifetime
+const PERSISTENT_TTL_THRESHOLD: u32 = PERSISTENT_TTL - 7 * DAY_IN_LEDGERS; // Extend entries below this
 
 // ==============
 // ERROR HANDLING
 // ==============
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
 pub enum Error {
     Unauthorized = 1,       // Caller lacks permission
     InvalidState = 2,       // Invalid contract state
@@ -44,6 +217,38 @@ pub enum Error {
     JobCompleted = 16,      // Job already finished
     ClientOnly = 17,        // Client-restricted action
     TalentOnly = 18,        // Talent-restricted action
+    FeeTooHigh = 19,        // Fee exceeds allowed cap
+    AdminNotSet = 20,       // Contract not initialized with admin
+    ContractPaused = 21,    // Circuit breaker engaged
+    TimelockNotReady = 22,  // Queued action delay not elapsed
+    ActionNotFound = 23,    // No such queued action
+    TokenNotAllowed = 24,   // Payment token not allowlisted
+    StakeTooLow = 25,       // Arbitrator stake below minimum
+    CooldownActive = 26,    // Unstake cooldown not elapsed
+    AlreadyVoted = 27,      // Panel member already voted
+    NotEnoughArbitrators = 28, // Registry too small for panel
+    ArbitratorNotApproved = 29, // Counterparty has not approved arbitrator
+    EvidenceLimit = 30,     // Evidence log is full
+    RulingPending = 31,     // Ruling awaits appeal window
+    AppealWindowClosed = 32, // Too late to appeal
+    ArbitratorBusy = 33,    // Arbitrator has open disputes
+    AlreadyReviewed = 34,   // Party already reviewed this job
+    TalentNotRegistered = 35, // No talent profile
+    TalentNotEligible = 36, // Talent below job reputation thresholds
+    TooEarly = 37,          // Called before the time it becomes allowed
+    SubmissionLimit = 38,   // Milestone resubmitted too many times
+    EscrowMismatch = 39,    // Escrow accounting overflow or invariant broken
+    ApprovalsPending = 40,  // Multisig threshold not met for this action
+    NoticeRequired = 41,    // Cancellation notice not given or not yet run out
+    ClaimNotFound = 42,     // No such insurance claim
+    NothingVested = 43,     // No approved payment has vested yet
+    TooManyMilestones = 44, // Milestone count above the configured limit
+    TooManyOpenJobs = 45,   // Client already has the maximum unfinished jobs
+    InputTooLong = 46,      // Text or URI above the configured length
+    AlreadySubmitted = 47,  // Milestone is already awaiting review
+    AlreadyApproved = 48,   // Milestone is already approved or paid
+    DuplicateSubmission = 49, // Same deliverable as an earlier round
+    StaleState = 50,        // Job changed since the caller read it
 }
 
 // ================
@@ -58,6 +263,7 @@ pub enum JobState {
     Completed,      // All milestones approved
     Disputed,       // Dispute raised
     Cancelled,      // Job cancelled by client
+    Expired,        // Never funded within the funding window
 }
 
 #[contracttype]
@@ -69,626 +275,9727 @@ pub enum MilestoneState {
     Rejected,       // Client rejected
     Paid,           // Payment released
     Disputed,       // Under arbitration
+    Void,           // Dropped when the talent resigned
 }
 
 // =================
 // DATA STRUCTURES
 // =================
+/// Declares a contracttype stand-in for `Option<T>`. The SDK's test XDR
+/// conversions can't encode `Option` of a user-defined type, a `BytesN` or
+/// a tuple, so stored and argument types hold these instead.
+macro_rules! optional {
+    ($name:ident, $($ty:tt)+) => {
+        #[contracttype]
+        #[derive(Clone, Debug, Default, Eq, PartialEq)]
+        pub enum $name {
+            #[default]
+            None,
+            Some($($ty)+),
+        }
+
+        #[allow(dead_code)]
+        impl $name {
+            fn get(&self) -> Option<$($ty)+> {
+                self.as_ref().cloned()
+            }
+
+            fn as_ref(&self) -> Option<&$($ty)+> {
+                match self {
+                    Self::None => None,
+                    Self::Some(value) => Some(value),
+                }
+            }
+
+            fn as_mut(&mut self) -> Option<&mut $($ty)+> {
+                match self {
+                    Self::None => None,
+                    Self::Some(value) => Some(value),
+                }
+            }
+
+            fn is_some(&self) -> bool {
+                matches!(self, Self::Some(_))
+            }
+
+            fn is_none(&self) -> bool {
+                matches!(self, Self::None)
+            }
+        }
+
+        impl From<Option<$($ty)+>> for $name {
+            fn from(value: Option<$($ty)+>) -> Self {
+                match value {
+                    Some(value) => Self::Some(value),
+                    None => Self::None,
+                }
+            }
+        }
+    };
+}
+
+optional!(OptHash, BytesN<32>);
+optional!(OptRuling, Ruling);
+optional!(OptAppeal, Appeal);
+optional!(OptRetainer, RetainerTerms);
+optional!(OptStream, Stream);
+optional!(OptParent, (u32, u32));
+optional!(OptOffer, Offer);
+optional!(OptMetadata, Metadata);
+optional!(OptJobState, JobState);
+optional!(OptSubmission, Submission);
+optional!(OptDisputePolicy, DisputePolicy);
+optional!(OptMultisig, Multisig);
+optional!(OptYieldConfig, YieldConfig);
+optional!(OptAgency, Agency);
+optional!(OptPricing, Pricing);
+optional!(OptPriceTerms, PriceTerms);
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Text {
+    Plain(String),           // Readable text, length-capped by config
+    Hash(BytesN<32>),        // Hash of off-chain text, for privacy
+}
+
 #[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Milestone {
-    description: BytesN<32>, // Milestone title/description
+    description: Text,       // Milestone title/description
     amount: i128,            // Payment amount
     state: MilestoneState,   // Current status
     submission_data: BytesN<32>, // Work deliverables
+    submission_uri: Option<String>, // Where the deliverables can be fetched
     deadline: u64,           // Completion deadline (timestamp)
     submitted_at: Option<u64>, // Submission time
+    vests_at: Option<u64>,   // Approved payment becomes claimable at this time
+    subjobs: Vec<u32>,       // Child jobs funded from this payout
+    payee: Option<Address>,  // Paid instead of the hired talent (None = talent)
 }
 
 #[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Job {
     client: Address,         // Job creator
+    funder: Option<Address>, // Sponsor that funded escrow and takes refunds (None = client)
+    crowdfunded: bool,       // Funded by contributions, refunded to contributors pro-rata
+    contest: bool,           // Open to entries; milestones are prizes by rank
     talent: Option<Address>, // Hired professional
-    title: BytesN<32>,       // Job title
+    offer: OptOffer,         // Pending offer awaiting talent acceptance
+    title: Text,             // Job title
+    token: Address,          // Payment token contract (SAC or custom)
+    specialization: OptHash, // Expertise required from arbitrators
+    min_talent_rating: u32,  // Required average rating x100 (0 = none)
+    min_jobs_completed: u32, // Required completed jobs (0 = none)
+    payment: PaymentModel,   // Retainer, stream, hourly or priced pay (fixed milestones if empty)
+    payout_split: Vec<(Address, u32)>, // Team shares in bps (empty = all to talent)
+    parent: OptParent,       // Parent job and milestone funding this subjob
+    talent_bond: i128,       // Deposit required from the talent on acceptance
+    bond_posted: i128,       // Talent deposit currently held
     total_value: i128,       // Total contract value
     amount_paid: i128,       // Total paid out
     state: JobState,         // Current status
+    action_seq: u64,         // Bumped on every change, for stale-state checks
     milestones: Vec<Milestone>, // Payment milestones
     escrow_balance: i128,    // Funds held in contract
+    deposited: i128,         // Total ever funded into escrow
+    fees_charged: i128,      // Arbitration fees taken from escrow
+    platform_fees: i128,     // Platform fees withheld from talent payouts
+    refunded: i128,          // Returned to the client (or parent escrow)
     created_at: u64,         // Creation timestamp
-    dispute_raised_by: Option<Address>, // Dispute initiator
-    selected_arbitrator: Option<Address>, // Chosen arbitrator
-    cancellation_fee: i128,  // Penalty for early cancel
+    expires_at: u64,         // Must be funded before this time
+    staffing_window: u64,    // Time allowed to hire once funded
+    staffing_deadline: u64,  // Unstaffed after this, the client may take a refund
+    cancel_terms: CancelTerms, // Kill fees and notice owed to a hired talent on cancel
+    release_delay: u64,      // Approved payments vest after this many seconds (0 = immediate)
+    metadata: OptMetadata,   // Off-chain job specification
+    dispute_policy: OptDisputePolicy, // Pre-agreed arbitration terms (global defaults if None)
+    invited: Option<Vec<Address>>, // Invite-only talent set (None = open to all)
+    category: Symbol,        // Marketplace category (indexed)
+    tags: Vec<Symbol>,       // Free-form search tags
+    roles: JobRoles,         // Approvers, delegates, referrer and agency
 }
 
 #[contracttype]
-pub struct Arbitrator {
-    address: Address,        // Arbiter address
-    fee_percentage: i128,    // Service fee (0-100)
-    reputation: u32,         // Success score (0-100)
-    cases_handled: u32,      // Total disputes resolved
-    specialization: BytesN<32>, // Area of expertise
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentModel {
+    retainer: OptRetainer,   // Recurring payment terms
+    next_period_at: u64,     // When the next retainer milestone can be added (set at hire)
+    periods_advanced: u32,   // Retainer milestones added so far
+    stream: OptStream,       // Linear payout instead of milestone approvals
+    hourly_rate: i128,       // Pay per approved hour (0 = fixed milestones)
+    pricing: OptPricing,     // Milestones denominated in a reference unit
 }
 
-#[contract]
-pub struct DecentralizedJobMarket;
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JobRoles {
+    multisig: OptMultisig,   // Approvers gating client actions (None = client alone)
+    managers: Map<Address, Vec<Permission>>, // Delegates acting for the client
+    referrer: Option<Address>, // Rewarded from platform fees on completion
+    agency: OptAgency,       // Co-signs talent selection and takes a cut of payouts
+}
 
-#[contractimpl]
-impl DecentralizedJobMarket {
-    // ==============
-    // INITIALIZATION
-    // ==============
-    /// Initialize contract with payment token
-    /// @param env: Soroban environment
-    /// @param token_id: Stellar asset contract ID
-    pub fn initialize(env: Env, token_id: BytesN<32>) {
-        if env.storage().has(&TOKEN_ID) {
-            panic_with_error!(&env, Error::InvalidState);
-        }
-        env.storage().set(&TOKEN_ID, &token_id);
-    }
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CancelTerms {
+    cancellation_fee_bps: u32, // Share of unpaid value owed to a hired talent on cancel
+    kill_fee_tiers: Vec<KillFeeTier>, // Kill fee by progress (empty = flat cancellation_fee_bps)
+    cancellation_notice: u64, // Notice before cancelling an Active job (0 = none)
+    cancel_effective_at: Option<u64>, // Noticed cancellation can be finalized from here
+}
 
-    // ================
-    // JOB LIFE CYCLE
-    // ================
-    /// Create new job with milestones
-    /// @param env: Soroban environment
-    /// @param client: Job creator address
-    /// @param title: Job title (32 bytes max)
-    /// @param descriptions: Milestone descriptions
-    /// @param amounts: Milestone payments
-    /// @param deadlines: Milestone deadlines (timestamps)
-    /// @return job_id: Created job identifier
-    pub fn create_job(
-        env: Env,
-        client: Address,
-        title: BytesN<32>,
-        descriptions: Vec<BytesN<32>>,
-        amounts: Vec<i128>,
-        deadlines: Vec<u64>,
-    ) -> u32 {
-        client.require_auth();
-        Self::check_reentrancy(&env);
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KillFeeTier {
+    min_progress_bps: u32,   // Applies once this share of milestones has been worked on
+    fee_bps: u32,            // Kill fee on unpaid value at this stage
+}
 
-        // Validate inputs
-        if descriptions.len() != amounts.len() || amounts.len() != deadlines.len() {
-            panic_with_error!(&env, Error::InvalidInput);
-        }
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceTerms {
+    oracle: Address,         // Whitelisted price oracle
+    max_slippage_bps: u32,   // Payout rate may move this far from the funding rate
+    fallback_rate: i128,     // Rate used when the oracle fails or is stale
+}
 
-        let total_value: i128 = amounts.iter().sum();
-        if total_value <= 0 {
-            panic_with_error!(&env, Error::AmountRequired);
-        }
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pricing {
+    terms: PriceTerms,       // Oracle and slippage bounds
+    reference_amounts: Vec<i128>, // Milestone prices in the reference unit
+    funded_rate: i128,       // Rate when escrow was funded (0 before funding)
+}
 
-        // Create milestones
-        let mut milestones = Vec::new(&env);
-        for i in 0..descriptions.len() {
-            if *amounts.get(i).unwrap() <= 0 {
-                panic_with_error!(&env, Error::AmountRequired);
-            }
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Agency {
+    address: Address,        // Agency account
+    cut_bps: u32,            // Share of each milestone payout
+}
 
-            milestones.push_back(
-                &env,
-                Milestone {
-                    description: *descriptions.get(i).unwrap(),
-                    amount: *amounts.get(i).unwrap(),
-                    state: MilestoneState::Pending,
-                    submission_data: BytesN::from_array(&env, &[0; 32]),
-                    deadline: *deadlines.get(i).unwrap(),
-                    submitted_at: None,
-                },
-            );
-        }
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Permission {
+    ApproveMilestones,       // Approve or reject work and timesheets
+    SelectTalent,            // Invite talents and make offers
+}
 
-        // Create job
-        let job = Job {
-            client: client.clone(),
-            talent: None,
-            title,
-            total_value,
-            amount_paid: 0,
-            state: JobState::Created,
-            milestones,
-            escrow_balance: 0,
-            created_at: env.ledger().timestamp(),
-            dispute_raised_by: None,
-            selected_arbitrator: None,
-            cancellation_fee: total_value / 10, // 10% cancellation fee
-        };
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Multisig {
+    approvers: Vec<Address>, // Addresses that sign off on client actions
+    threshold: u32,          // Approvals needed per action
+}
 
-        let job_id = Self::save_job(&env, &job);
-        env.events().publish(
-            (JOB_CRT, client),
-            (job_id, title, total_value)
-        );
-        job_id
-    }
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum JobAction {
+    ApproveMilestone(u32),      // Release a milestone payment
+    Cancel,                     // Cancel unilaterally or by agreement
+    RaiseDispute(Option<u32>),  // Dispute a milestone or the whole job
+    ReplaceTalent(Address),     // Hand the remaining work to a new talent
+}
 
-    /// Fund job escrow with payment tokens
-    /// @param env: Soroban environment
-    /// @param client: Job creator
-    /// @param job_id: Job identifier
-    pub fn fund_job(env: Env, client: Address, job_id: u32) {
-        client.require_auth();
-        Self::check_reentrancy(&env);
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingAction {
+    action: JobAction,       // Client action awaiting sign-off
+    approvals: Vec<Address>, // Approvers that signed off
+    proposed_at: u64,        // Proposal timestamp
+}
 
-        let mut job = Self::get_job(&env, job_id);
-        if job.client != client {
-            panic_with_error!(&env, Error::Unauthorized);
-        }
-        if job.state != JobState::Created {
-            panic_with_error!(&env, Error::InvalidState);
-        }
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct YieldPosition {
+    strategy: Address,       // Yield contract holding the funds
+    token: Address,          // Deployed token
+    shares: i128,            // Strategy shares owned for the job
+    principal: i128,         // Escrow deployed
+    client_share_bps: u32,   // Client's share of yield, fixed at deployment
+    stranded_at: Option<u64>, // Strategy failed to pay back; treasury fronted the principal
+}
 
-        // Transfer tokens to escrow
-        let token_id = Self::get_token_id(&env);
-        token::Client::new(&env, &token_id).transfer(
-            &client,
-            &env.current_contract_address(),
-            &job.total_value
-        );
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArchivedJob {
+    client: Address,         // Job creator
+    talent: Option<Address>, // Hired professional
+    token: Address,          // Payment token
+    outcome: JobState,       // Completed, Cancelled or Expired
+    total_value: i128,       // Final contract value
+    amount_paid: i128,       // Paid out for work
+    fees_charged: i128,      // Arbitration fees taken from escrow
+    refunded: i128,          // Returned to the client
+    final_hash: BytesN<32>,  // sha256 of the final Job record XDR
+    finished_at: u64,        // When the job reached its outcome
+    archived_at: u64,        // When the full record was dropped
+}
 
-        job.escrow_balance = job.total_value;
-        job.state = JobState::Funded;
-        Self::update_job(&env, job_id, &job);
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JobArchived {
+    version: u32,            // Payload schema version
+    final_hash: BytesN<32>,  // sha256 of the final Job record XDR
+}
 
-        env.events().publish(
-            (JOB_FUND, client),
-            (job_id, job.total_value)
-        );
-    }
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Transition {
+    from: OptJobState,       // Previous state (None when the job is created)
+    to: JobState,            // New state
+    actor: Address,          // Caller that caused the change (contract for cascades)
+    at: u64,                 // Transition timestamp
+}
 
-    /// Select talent for funded job
-    /// @param env: Soroban environment
-    /// @param client: Job creator
-    /// @param job_id: Job identifier
-    /// @param talent: Freelancer address
-    pub fn select_talent(env: Env, client: Address, job_id: u32, talent: Address) {
-        client.require_auth();
-        Self::check_reentrancy(&env);
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Submission {
+    data: BytesN<32>,        // Hash of the submitted deliverables
+    uri: Option<String>,     // Where the deliverables can be fetched
+    submitted_at: u64,       // Submission timestamp
+    round: u32,              // 0 for the first submission, +1 per resubmission
+}
 
-        let mut job = Self::get_job(&env, job_id);
-        if job.client != client {
-            panic_with_error!(&env, Error::Unauthorized);
-        }
-        if job.state != JobState::Funded {
-            panic_with_error!(&env, Error::InvalidState);
-        }
-        if job.talent.is_some() {
-            panic_with_error!(&env, Error::TalentExists);
-        }
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Metadata {
+    uri: String,             // Content location (e.g. ipfs://...)
+    hash: BytesN<32>,        // Hash of the content at `uri`
+    updated_at: u64,         // When the metadata was last set
+}
 
-        job.talent = Some(talent.clone());
-        job.state = JobState::Active;
-        Self::update_job(&env, job_id, &job);
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Offer {
+    talent: Address,         // Offered freelancer
+    expires_at: u64,         // Acceptance deadline
+}
 
-        env.events().publish(
-            (TAL_SEL, client),
-            (job_id, talent)
-        );
-    }
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Arbitrator {
+    address: Address,        // Arbiter address
+    fee_percentage: i128,    // Service fee (0-100)
+    reputation: u32,         // Success score (0-100)
+    cases_handled: u32,      // Total disputes resolved
+    specialization: BytesN<32>, // Area of expertise
+    stake: i128,             // Tokens locked as collateral
+    unstake_requested_at: Option<u64>, // Cooldown start (inactive while set)
+    open_disputes: u32,      // Disputes currently assigned
+    last_active: u64,        // Last registration, fee change or ruling
+}
 
-    // ====================
-    // MILESTONE OPERATIONS
-    // ====================
-    /// Submit work for milestone
-    /// @param env: Soroban environment
-    /// @param talent: Freelancer address
-    /// @param job_id: Job identifier
-    /// @param milestone_idx: Milestone index
-    /// @param data: Work submission data
-    pub fn submit_milestone(
-        env: Env,
-        talent: Address,
-        job_id: u32,
-        milestone_idx: u32,
-        data: BytesN<32>,
-    ) {
-        talent.require_auth();
-        Self::check_reentrancy(&env);
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Dispute {
+    raised_by: Address,          // Dispute initiator
+    milestone_idx: Option<u32>,  // Disputed milestone (None = whole job)
+    arbitrators: Vec<Address>,   // Sole arbitrator or panel members
+    votes: Map<Address, bool>,   // Panel votes (true=approve)
+    raised_at: u64,              // Dispute timestamp
+    voting_deadline: u64,        // Panel voting window end
+    awaiting_approval: Option<Address>, // Party that must approve the arbitrator
+    response_deadline: u64,      // Arbitrator approval window end
+    pending_ruling: OptRuling,   // First-instance ruling awaiting appeal window
+    deciders: Vec<Address>,      // Arbitrators paid when the ruling executes
+    appeal_deadline: u64,        // Appeal window end
+    appeal: OptAppeal,           // Appeal in progress
+    ruling_deadline: u64,        // Arbitrator must rule before this time
+    reassignments: u32,          // Times reassigned after a timeout
+    fee_deposit: i128,           // Arbitration fee posted by the initiator
+    fee_reserved: i128,          // Client escrow set aside for the fee (talent-initiated)
+    submission: OptSubmission,   // Disputed milestone's latest submission
+}
 
-        let mut job = Self::get_job(&env, job_id);
-        if job.state != JobState::Active {
-            panic_with_error!(&env, Error::InvalidState);
-        }
-        if job.talent != Some(talent.clone()) {
-            panic_with_error!(&env, Error::Unauthorized);
-        }
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Appeal {
+    appellant: Address,          // Losing party that appealed
+    bond: i128,                  // Bond posted (forfeited if ruling stands)
+    original_ruling: Ruling,     // Ruling under appeal
+}
 
-        let mut milestone = job.milestones.get(milestone_idx)
-            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidIndex));
-            
-        if milestone.state != MilestoneState::Pending {
-            panic_with_error!(&env, Error::MilestonePending);
-        }
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Ruling {
+    Approve,        // Release disputed payment to talent
+    Reject,         // Reject disputed work
+    Split(u32),     // Talent share in basis points, rest refunded
+}
 
-        // Check deadline
-        if env.ledger().timestamp() > milestone.deadline {
-            panic_with_error!(&env, Error::DeadlinePassed);
-        }
+impl Ruling {
+    fn from_decision(decision: bool) -> Self {
+        if decision { Ruling::Approve } else { Ruling::Reject }
+    }
+}
 
-        milestone.state = MilestoneState::Submitted;
-        milestone.submission_data = data;
-        milestone.submitted_at = Some(env.ledger().timestamp());
-        job.milestones.set(milestone_idx, milestone);
-        Self::update_job(&env, job_id, &job);
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RatingWindow {
+    deciders: Vec<Address>,      // Arbitrators who decided the last dispute
+    rated_by: Vec<Address>,      // Parties that already rated them
+}
 
-        env.events().publish(
-            (WRK_SUB, talent),
-            (job_id, milestone_idx, data)
-        );
-    }
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Evidence {
+    submitter: Address,          // Client or talent
+    evidence_hash: BytesN<32>,   // Hash of off-chain evidence
+    note: Bytes,                 // Short statement
+    submitted_at: u64,           // Submission timestamp
+}
 
-    /// Approve milestone and release payment
-    /// @param env: Soroban environment
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Review {
+    job_id: u32,                 // Reviewed engagement
+    author: Address,             // Client or talent
+    score: u32,                  // Rating (1-5)
+    comment_hash: BytesN<32>,    // Hash of off-chain comment
+    created_at: u64,             // Review timestamp
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RatingStats {
+    total_score: u32,            // Sum of review scores
+    count: u32,                  // Number of reviews
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TalentProfile {
+    skills: Vec<Symbol>,         // Declared skills
+    profile_hash: BytesN<32>,    // Hash of off-chain profile
+    jobs_completed: u32,         // Jobs finished as talent
+    total_earned: i128,          // Net payouts received
+    rating: u32,                 // Average review score x100
+    registered_at: u64,          // Registration timestamp
+    active_jobs: u32,            // Jobs hired on and not yet finished
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SessionKey {
+    talent: Address,             // Talent the key acts for
+    job_ids: Vec<u32>,           // Jobs the key may work on
+    expires_at: u64,             // Key is unusable from this time
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ClientProfile {
+    jobs_posted: u32,            // Jobs created
+    total_spent: i128,           // Escrow released to talents
+    disputes_raised: u32,        // Disputes opened by the client
+    cancellations: u32,          // Jobs cancelled by the client
+    first_job_at: u64,           // First job timestamp
+    open_jobs: u32,              // Jobs not yet completed, cancelled or expired
+    total_funded: i128,          // Funds deposited into escrow
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MarketplaceStats {
+    total_jobs: u32,             // Jobs ever created (subjobs included)
+    active_jobs: u32,            // Jobs not yet completed, cancelled or expired
+    total_escrowed: Map<Address, i128>, // Client funds deposited, per token
+    total_paid_out: Map<Address, i128>, // Gross payouts for work, per token
+    disputes_raised: u32,        // Disputes opened
+    disputes_resolved: u32,      // Disputes settled by a ruling
+    resolution_time_total: u64,  // Seconds from raise to settlement, summed
+    avg_resolution_time: u64,    // Mean seconds from raise to settlement
+}
+
+/// Marketplace history of an address in a stable shape for other
+/// contracts. New fields are only ever appended, with `version` bumped.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReputationSummary {
+    version: u32,                // REPUTATION_VERSION
+    address: Address,            // Subject
+    rating: u32,                 // Average review score x100 (0 = unrated)
+    reviews: u32,                // Reviews received
+    talent_registered: bool,     // Has a talent profile
+    jobs_completed: u32,         // Jobs finished as talent
+    total_earned: i128,          // Net payouts received as talent
+    jobs_posted: u32,            // Jobs created as client
+    total_spent: i128,           // Escrow released to talents as client
+    disputes_raised: u32,        // Disputes opened as client
+    cancellations: u32,          // Jobs cancelled as client
+    as_of: u64,                  // Ledger timestamp of the read
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReputationSnapshot {
+    summary: ReputationSummary,  // Reputation at `ledger`
+    ledger: u32,                 // Ledger sequence of the snapshot
+    digest: BytesN<32>,          // sha256 of (contract, ledger, summary) XDR
+}
+
+/// Canonical terms of a job, hashed by `job_commitment`. Only agreed
+/// terms are included, never progress (states, balances, timestamps).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JobTerms {
+    version: u32,                // TERMS_VERSION
+    job_id: u32,                 // Job identifier
+    client: Address,             // Job creator
+    talent: Option<Address>,     // Hired professional
+    agency: OptAgency,           // Agency and its cut
+    token: Address,              // Payment token
+    title: Text,                 // Job title
+    descriptions: Vec<Text>,     // Milestone descriptions
+    amounts: Vec<i128>,          // Milestone amounts (reference units if priced)
+    deadlines: Vec<u64>,         // Milestone deadlines
+    payees: Vec<Option<Address>>, // Milestone payees (None = talent)
+    pricing: OptPriceTerms,      // Oracle pricing terms
+    talent_bond: i128,           // Work bond
+    cancellation_fee_bps: u32,   // Flat kill fee
+    kill_fee_tiers: Vec<KillFeeTier>, // Kill fee schedule
+    cancellation_notice: u64,    // Notice period
+    release_delay: u64,          // Vesting delay after approval
+    dispute_policy: OptDisputePolicy, // Pre-agreed arbitration terms
+    spec_hash: OptHash,          // Hash of the off-chain specification
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct JobOptions {
+    token: Option<Address>, // Allowlisted payment token (default token if None)
+    specialization: OptHash, // Expertise required from arbitrators
+    min_talent_rating: Option<u32>, // Minimum talent average rating x100
+    min_jobs_completed: Option<u32>, // Minimum jobs completed by talent
+    retainer: OptRetainer,   // Run as a recurring retainer
+    stream_duration: Option<u64>, // Stream total value over this many seconds
+    hourly_rate: Option<i128>, // Settle approved timesheets at this rate
+    talent_bond: Option<i128>, // Work bond the talent must lock to accept
+    cancellation_fee_bps: Option<u32>, // Kill fee owed to a hired talent (default 10%)
+    kill_fee_tiers: Option<Vec<KillFeeTier>>, // Kill fee schedule by progress (overrides the flat fee)
+    cancellation_notice_period: Option<u64>, // Notice in seconds before cancelling Active work
+    release_delay: Option<u64>, // Vesting delay in seconds after each approval
+    expiry: Option<u64>,     // Funding window in seconds (default 30 days)
+    staffing_window: Option<u64>, // Hiring window once funded in seconds (default 30 days)
+    dispute_policy: OptDisputePolicy, // Pre-agreed arbitration terms
+    category: Option<Symbol>, // Marketplace category (default "general")
+    tags: Option<Vec<Symbol>>, // Search tags (5 max)
+    multisig: OptMultisig,   // Approvers gating client actions
+    referrer: Option<Address>, // Address that referred the client
+    agency: OptAgency,       // Agency co-signing selection for a cut
+    pricing: OptPriceTerms,  // Treat amounts as reference units (e.g. USD cents)
+    client_nonce: Option<u64>, // Derive the job id from (client, nonce) instead of the counter
+    crowdfunded: bool,       // Accept escrow contributions from anyone until fully funded
+    contest: bool,           // Run as a contest: first milestone is the winner's prize, the rest runner-up prizes
+    milestone_payees: Option<Vec<Option<Address>>>, // Address paid for each milestone (None entries = talent)
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputePolicy {
+    arbitrators: Vec<Address>, // Pre-agreed arbitrators (empty = any registered)
+    panel_size: Option<u32>, // Disputes must go to a panel of this size
+    max_fee: Option<i128>,   // Arbitrator fee cap in percent (global cap if lower)
+    ruling_window: Option<u64>, // Time allowed for a ruling (default 14 days)
+    appeals: bool,           // Whether first-instance rulings can be appealed
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TimeLog {
+    period_hash: BytesN<32>,     // Hash of off-chain work log for the period
+    hours: u32,                  // Hours claimed
+    amount: i128,                // Payment at the job's hourly rate
+    approved: bool,              // Paid out by client approval
+    logged_at: u64,              // Submission timestamp
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContestEntry {
+    talent: Address,             // Entrant
+    data: BytesN<32>,            // Hash of the submitted work
+    uri: Option<String>,         // Where the work can be fetched
+    submitted_at: u64,           // Latest submission timestamp
+    prize: i128,                 // Amount awarded (0 = not placed)
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Stream {
+    duration: u64,               // Seconds over which total value vests
+    started_at: Option<u64>,     // Vesting start (None until talent is hired)
+    stopped_at: Option<u64>,     // Vesting end if stopped early by client
+    withdrawn: i128,             // Vested amount already paid out
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RetainerTerms {
+    period: u64,                 // Seconds between milestones
+    payment_per_period: i128,    // Amount of each generated milestone
+    max_periods: u32,            // Milestones `advance_period` may add before the retainer ends
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JobSpec {
+    title: Text,                     // Job title
+    descriptions: Vec<Text>,         // Milestone descriptions
+    amounts: Vec<i128>,              // Milestone payments
+    deadlines: Vec<u64>,             // Milestone deadlines (timestamps)
+    options: JobOptions,             // Optional job terms
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MilestoneSpec {
+    description: Text,               // Milestone title/description
+    amount: i128,                    // Payment amount
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JobTemplate {
+    owner: Address,                  // Template author
+    title: Text,                     // Job title
+    milestones: Vec<MilestoneSpec>,  // Milestone structure
+    created_at: u64,                 // Creation timestamp
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Draft {
+    client: Address,                 // Client negotiating the job
+    title: Text,                     // Job title
+    descriptions: Vec<Text>,         // Milestone descriptions
+    amounts: Vec<i128>,              // Client's asking amounts
+    deadlines: Vec<u64>,             // Client's asking deadlines
+    proposals: Vec<TermsProposal>,   // Talent counter-proposals (one per talent)
+    job_id: Option<u32>,             // Job created once a proposal is accepted
+    created_at: u64,                 // Posting timestamp
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TermsProposal {
+    talent: Address,                 // Proposing talent
+    amounts: Vec<i128>,              // Proposed milestone amounts
+    deadlines: Vec<u64>,             // Proposed milestone deadlines
+    proposed_at: u64,                // Proposal timestamp
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AmendmentTerms {
+    amount: i128,                    // New milestone amount
+    deadline: u64,                   // New milestone deadline
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AmendmentStatus {
+    Open,                            // Awaiting the other party
+    Accepted,                        // Applied to the milestone
+    Declined,                        // Rejected outright
+    Countered,                       // Rejected with a counter-proposal
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Amendment {
+    proposer: Address,               // Client, or talent countering
+    milestone_idx: u32,              // Milestone being amended
+    terms: AmendmentTerms,           // Proposed amount and deadline
+    proposed_at: u64,                // Proposal timestamp
+    status: AmendmentStatus,         // Outcome so far
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CancelProposal {
+    proposer: Address,               // Client or talent
+    talent_amount: i128,             // Escrow share released to talent
+    client_amount: i128,             // Escrow share refunded to client
+    proposed_at: u64,                // Proposal timestamp
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum KeeperTask {
+    ExpireUnfunded,          // `expire_unfunded_job`
+    AdvancePeriod,           // `advance_period`
+    TimeoutDispute,          // `timeout_dispute`
+    FinalizeDispute,         // `finalize_dispute`
+    ExecuteRuling,           // `execute_ruling`
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ClaimStatus {
+    Pending,                 // Awaiting a decision
+    Paid,                    // Approved and paid from the pool
+    Denied,                  // Rejected
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InsuranceClaim {
+    job_id: u32,                     // Job the loss happened on
+    claimant: Address,               // Client or talent of the job
+    token: Address,                  // Pool the claim is paid from
+    amount: i128,                    // Amount claimed
+    evidence_hash: BytesN<32>,       // Hash of off-chain evidence
+    filed_at: u64,                   // Filing timestamp
+    votes: Map<Address, bool>,       // Arbitrator votes (true = pay)
+    status: ClaimStatus,             // Current status
+}
+
+// ==============
+// EVENT PAYLOADS
+// ==============
+// Every payload starts with `version` (EVENT_VERSION) so indexers can
+// decode older events after the schema changes.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JobNotice {
+    version: u32,            // Payload schema version
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JobCreated {
+    version: u32,            // Payload schema version
+    title: Text,             // Job title
+    total_value: i128,       // Sum of milestone payments
+    token: Address,          // Payment token
+    parent: OptParent,       // Parent job and milestone for subjobs
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EscrowChanged {
+    version: u32,            // Payload schema version
+    amount: i128,            // Tokens moved in or out of escrow
+    escrow_balance: i128,    // Escrow balance afterwards
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JobSponsored {
+    version: u32,            // Payload schema version
+    client: Address,         // Job creator the sponsor paid for
+    amount: i128,            // Tokens escrowed by the sponsor
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeeperPaid {
+    version: u32,            // Payload schema version
+    task: KeeperTask,        // Maintenance performed
+    bounty: i128,            // Reward paid from the treasury
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InsuranceClaimed {
+    version: u32,            // Payload schema version
+    claim_id: u32,           // Claim identifier
+    amount: i128,            // Amount claimed
+    evidence_hash: BytesN<32>, // Hash of off-chain evidence
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClaimDecision {
+    version: u32,            // Payload schema version
+    claim_id: u32,           // Claim identifier
+    approved: bool,          // Vote or final decision
+    amount: i128,            // Paid from the pool (0 unless resolved and approved)
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReferralRewarded {
+    version: u32,            // Payload schema version
+    referrer: Address,       // Credited address
+    token: Address,          // Reward token
+    amount: i128,            // Reward credited
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct YieldHarvested {
+    version: u32,            // Payload schema version
+    principal: i128,         // Escrow that was deployed
+    earned: i128,            // Redeemed minus principal (negative on a loss)
+    client_amount: i128,     // Client's share of the yield
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenMoved {
+    version: u32,            // Payload schema version
+    token: Address,          // Token contract
+    amount: i128,            // Tokens moved
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutSwapped {
+    version: u32,            // Payload schema version
+    token_in: Address,       // Escrow token
+    amount_in: i128,         // Escrow tokens swapped
+    token_out: Address,      // Token the payee received
+    amount_out: i128,        // Tokens the payee received
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TalentInvited {
+    version: u32,            // Payload schema version
+    talent: Address,         // Invited (or uninvited) freelancer
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionApproved {
+    version: u32,            // Payload schema version
+    action_id: u32,          // Multisig action identifier
+    action: JobAction,       // Client action being signed off
+    approvals: u32,          // Approvals collected so far
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ManagerSet {
+    version: u32,            // Payload schema version
+    manager: Address,        // Delegate address
+    permissions: Vec<Permission>, // Granted scopes (empty when removed)
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OwnershipTransferred {
+    version: u32,            // Payload schema version
+    new_client: Address,     // Client from now on
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OfferMade {
+    version: u32,            // Payload schema version
+    talent: Address,         // Offered freelancer
+    expires_at: u64,         // Acceptance deadline
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TalentHired {
+    version: u32,            // Payload schema version
+    client: Address,         // Job creator
+    bond: i128,              // Work bond posted by the talent
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TalentReplaced {
+    version: u32,            // Payload schema version
+    old_talent: Address,     // Talent leaving the job
+    new_talent: Address,     // Talent taking over the remaining milestones
+    reassigned: u32,         // Milestones handed over
+    bond_forfeited: i128,    // Old talent's bond paid to the client (missed deadlines)
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MetadataSet {
+    version: u32,            // Payload schema version
+    uri: String,             // Content location
+    hash: BytesN<32>,        // Hash of the content at `uri`
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WorkSubmitted {
+    version: u32,            // Payload schema version
+    milestone_idx: u32,      // Milestone index
+    data: BytesN<32>,        // Hash of the deliverables
+    uri: String,             // Deliverable location (may be empty)
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeadlineExtension {
+    version: u32,            // Payload schema version
+    milestone_idx: u32,      // Milestone index
+    deadline: u64,           // Requested (or granted) deadline
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MilestoneRejected {
+    version: u32,            // Payload schema version
+    milestone_idx: u32,      // Milestone index
+    round: u32,              // Submission round that was rejected
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MilestonePriced {
+    version: u32,            // Payload schema version
+    milestone_idx: u32,      // Priced milestone
+    reference_amount: i128,  // Price in the reference unit
+    rate: i128,              // Rate applied (RATE_SCALE fixed point)
+    amount: i128,            // Token amount paid out
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentMade {
+    version: u32,            // Payload schema version
+    index: Option<u32>,      // Milestone or timesheet index, if any
+    amount: i128,            // Gross amount paid
+    fee: i128,               // Platform fee withheld
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchApproved {
+    version: u32,            // Payload schema version
+    indices: Vec<u32>,       // Approved milestone indices
+    amount: i128,            // Gross amount paid
+    fee: i128,               // Platform fee withheld
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SplitSet {
+    version: u32,            // Payload schema version
+    recipients: Vec<(Address, u32)>, // Team shares in bps
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HoursLogged {
+    version: u32,            // Payload schema version
+    log_idx: u32,            // Timesheet index
+    period_hash: BytesN<32>, // Hash of the off-chain work log
+    hours: u32,              // Hours claimed
+    amount: i128,            // Pay owed on approval
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContestEntered {
+    version: u32,            // Payload schema version
+    entry_idx: u32,          // Entry index
+    data: BytesN<32>,        // Hash of the submitted work
+    uri: String,             // Work location (may be empty)
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrizeAwarded {
+    version: u32,            // Payload schema version
+    place: u32,              // 0 for the winner, then runners-up
+    entry_idx: u32,          // Winning entry index
+    amount: i128,            // Prize paid (gross of fees)
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeOpened {
+    version: u32,            // Payload schema version
+    milestone_idx: Option<u32>, // Disputed milestone (None = whole job)
+    arbitrators: Vec<Address>, // Assigned or proposed arbitrators
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RulingMade {
+    version: u32,            // Payload schema version
+    milestone_idx: Option<u32>, // Disputed milestone (None = whole job)
+    ruling: Ruling,          // Outcome
+    fee: i128,               // Arbitration fee paid (0 while provisional)
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VoteCast {
+    version: u32,            // Payload schema version
+    decision: bool,          // true = pay the talent
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Appealed {
+    version: u32,            // Payload schema version
+    bond: i128,              // Appeal bond posted
+    arbitrators: Vec<Address>, // Appeal panel
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeTimedOut {
+    version: u32,            // Payload schema version
+    reassigned_to: Option<Vec<Address>>, // New arbitrators, if reassigned
+    ruling: OptRuling,       // Default ruling, if applied
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EvidenceAdded {
+    version: u32,            // Payload schema version
+    index: u32,              // Position in the evidence log
+    evidence_hash: BytesN<32>, // Hash of off-chain evidence
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Settlement {
+    version: u32,            // Payload schema version
+    talent_amount: i128,     // Paid (or proposed) to the talent
+    client_amount: i128,     // Refunded (or proposed) to the client
+    bond_forfeited: i128,    // Talent bond paid to the client
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AmendmentMade {
+    version: u32,            // Payload schema version
+    amendment_idx: u32,      // Index in the job's amendment history
+    milestone_idx: u32,      // Amended milestone
+    amount: i128,            // Proposed or agreed amount
+    deadline: u64,           // Proposed or agreed deadline
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CancellationNoticed {
+    version: u32,            // Payload schema version
+    effective_at: u64,       // When the cancellation can be finalized
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MilestoneVesting {
+    version: u32,            // Payload schema version
+    milestone_idx: u32,      // Approved milestone
+    vests_at: u64,           // When the talent can claim it
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArbitratorsRated {
+    version: u32,            // Payload schema version
+    arbitrators: Vec<Address>, // Rated deciders
+    score: u32,              // Rating given
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReviewLeft {
+    version: u32,            // Payload schema version
+    reviewee: Address,       // Reviewed party
+    score: u32,              // Rating (1-5)
+    comment_hash: BytesN<32>, // Hash of off-chain comment
+}
+
+// ==================
+// GOVERNANCE TYPES
+// ==================
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AdminAction {
+    SetPlatformFee(u32),              // New platform fee (bps)
+    SetTokenAllowed(Address, bool), // Add/remove payment token
+    RemoveArbitrator(Address),        // Drop arbitrator from registry
+    SetTimelockDelay(u64),            // New timelock delay (seconds)
+    SetMinArbStake(i128),             // New minimum arbitrator stake
+    SetDefaultRuling(Ruling),         // Outcome applied when disputes time out
+    SetMaxArbitratorFee(i128),        // Cap on arbitrator fee percentage
+    SetMaxCancellationFee(u32),       // Cap on per-job cancellation fee (bps)
+    SetTextLimits(u32, u32),          // Max title and description lengths (bytes)
+    SetJobLimits(JobLimits),          // Max milestones, open jobs and URI length
+    SetYieldStrategy(OptYieldConfig), // Yield contract for idle escrow (None = off)
+    SetInsuranceShare(u32),           // Share of platform fees to the insurance pool (bps)
+    SetReferralReward(u32),           // Referrer share of a job's platform fees (bps)
+    SetOracleAllowed(Address, bool),  // Add/remove price oracle
+    SetRouterAllowed(Address, bool),  // Add/remove swap router
+    AddHook(Symbol, Address),         // Call a contract on a job event
+    SetKeeperBounty(i128),            // Reward per keeper task (default token)
+}
+
+/// Deployment parameters for `__constructor`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InitConfig {
+    admin: Address,              // Contract administrator
+    token: Address,              // Default payment token (any SAC, incl. native XLM)
+    platform_fee_bps: u32,       // Platform fee on payouts
+    max_arbitrator_fee: i128,    // Cap on arbitrator fee percentage
+    max_cancellation_fee_bps: u32, // Cap on per-job kill fees
+    min_arbitrator_stake: i128,  // Stake needed to arbitrate
+    timelock_delay: u64,         // Delay before queued config changes apply
+    text_limits: (u32, u32),     // Max title and description lengths
+    job_limits: JobLimits,       // Max milestones, open jobs and URI length
+}
+
+/// Operational parameters in one read, for integrators
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Config {
+    admin: Address,              // Contract administrator
+    token: Address,              // Default payment token
+    platform_fee_bps: u32,       // Platform fee on payouts
+    max_arbitrator_fee: i128,    // Cap on arbitrator fee percentage
+    max_cancellation_fee_bps: u32, // Cap on per-job kill fees
+    min_arbitrator_stake: i128,  // Stake needed to arbitrate
+    default_ruling: Ruling,      // Outcome applied when disputes time out
+    ruling_window: u64,          // Default time for arbitrators to rule
+    review_period: u64,          // Time parties have to review (appeal) a ruling before it executes
+    timelock_delay: u64,         // Delay before queued config changes apply
+    text_limits: (u32, u32),     // Max title and description lengths
+    job_limits: JobLimits,       // Max milestones, open jobs and URI length
+    paused: bool,                // Circuit breaker state
+    state_version: u32,          // Storage layout version
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JobLimits {
+    max_milestones: u32,     // Milestones per job
+    max_open_jobs: u32,      // Unfinished jobs per client
+    max_uri_len: u32,        // Metadata and deliverable URI length (bytes)
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutPreference {
+    token: Address,          // Token to be paid in
+    router: Address,         // Whitelisted swap router
+    max_slippage_bps: u32,   // Worst accepted output below the router's quote
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct YieldConfig {
+    strategy: Address,       // Whitelisted yield contract
+    client_share_bps: u32,   // Share of earned yield paid to the client
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QueuedAction {
+    action: AdminAction,     // Scheduled change
+    eta: u64,                // Earliest execution time
+}
+
+// ==============
+// LEGACY LAYOUTS
+// ==============
+// Jobs as stored before v3, decoded by `migrate_jobs` and rewritten as `Job`.
+// Fields keep their original names so the stored maps decode unchanged.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MilestoneV1 {
+    description: BytesN<32>, // Milestone title/description
+    amount: i128,            // Payment amount
+    state: MilestoneState,   // Current status
+    submission_data: BytesN<32>, // Work deliverables
+    deadline: u64,           // Completion deadline (timestamp)
+    submitted_at: Option<u64>, // Submission time
+}
+
+/// Job layout of state version 1
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JobV1 {
+    client: Address,         // Job creator
+    talent: Option<Address>, // Hired professional
+    title: BytesN<32>,       // Job title
+    token: Address,          // Payment token for escrow
+    total_value: i128,       // Total contract value
+    amount_paid: i128,       // Total paid out
+    state: JobState,         // Current status
+    milestones: Vec<MilestoneV1>, // Payment milestones
+    escrow_balance: i128,    // Funds held in contract
+    created_at: u64,         // Creation timestamp
+    dispute_raised_by: Option<Address>, // Dispute initiator
+    selected_arbitrator: Option<Address>, // Chosen arbitrator
+    cancellation_fee: i128,  // Penalty for early cancel
+}
+
+/// Job layout of state version 2
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JobV2 {
+    client: Address,         // Job creator
+    talent: Option<Address>, // Hired professional
+    title: BytesN<32>,       // Job title
+    token: Address,          // Payment token contract (SAC or custom)
+    specialization: OptHash, // Expertise required from arbitrators
+    min_talent_rating: u32,  // Required average rating x100 (0 = none)
+    min_jobs_completed: u32, // Required completed jobs (0 = none)
+    total_value: i128,       // Total contract value
+    amount_paid: i128,       // Total paid out
+    state: JobState,         // Current status
+    milestones: Vec<MilestoneV1>, // Payment milestones
+    escrow_balance: i128,    // Funds held in contract
+    created_at: u64,         // Creation timestamp
+    cancellation_fee: i128,  // Penalty for early cancel
+}
+
+// ==============
+// STORAGE KEYS
+// ==============
+/// Instance storage: contract configuration and counters
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConfigKey {
+    Token,                        // Default payment token
+    Admin,                        // Contract administrator
+    PendingAdmin,                 // Proposed administrator
+    PlatformFee,                  // Platform fee (bps)
+    Paused,                       // Circuit breaker flag
+    StateVersion,                 // Storage layout version
+    LegacyJobLayout,              // State version of jobs still under pre-v3 keys
+    AllowedTokens,                // Allowlisted payment tokens
+    AllowedOracles,               // Whitelisted price oracles
+    AllowedRouters,               // Whitelisted swap routers
+    Hooks,                        // Hook targets per job event name
+    KeeperBounty,                 // Reward per keeper task
+    TimelockDelay,                // Timelock delay (seconds)
+    ActionCount,                  // Queued admin action counter
+    MinArbStake,                  // Minimum arbitrator stake
+    MaxArbitratorFee,             // Arbitrator fee cap (percent)
+    MaxCancellationFee,           // Cancellation fee cap (bps)
+    DefaultRuling,                // Outcome applied when disputes time out
+    TemplateCount,                // Job template counter
+    DraftCount,                   // Draft job counter
+    TextLimits,                   // Max title and description lengths
+    JobLimits,                    // Max milestones, open jobs and URI length
+    Stats,                        // Marketplace-wide counters
+    YieldStrategy,                // Yield contract for idle escrow
+    InsuranceShare,               // Share of platform fees to insurance (bps)
+    ClaimCount,                   // Insurance claim counter
+    ReferralReward,               // Referrer share of platform fees (bps)
+    JobCount,                     // Job counter
+    // Temporary storage
+    ReentrancyLock,               // Held for the duration of one call
+}
+
+/// Persistent storage: per-job records, templates and indexes
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum JobKey {
+    Job(u32),                     // Job record
+    Dispute(u32),                 // Open dispute for a job
+    Evidence(u32),                // Dispute evidence log for a job
+    Timesheets(u32),              // Hourly work logs for a job
+    Entries(u32),                 // Entries submitted to a contest
+    CancelProposal(u32),          // Pending mutual cancellation for a job
+    Amendments(u32),              // Milestone amendment negotiation history for a job
+    RatingWindow(u32),            // Arbitrator rating window for a job
+    Submissions(u32, u32),        // Submission history for a job milestone
+    YieldPosition(u32),           // Escrow deployed to the yield strategy for a job
+    JobActions(u32),              // Multisig actions awaiting sign-off for a job
+    JobActionCount(u32),          // Multisig action counter for a job
+    History(u32),                 // State transition log for a job
+    Template(u32),                // Job template
+    Draft(u32),                   // Job under negotiation
+    Contributions(u32),           // Escrow contributed per address to a crowdfunded job
+    JobAt(u32),                   // Derived job id by creation sequence
+    Archived(u32),                // Compact summary replacing an archived job
+    Reviewed(u32, Address),       // Review left by author on a job
+    CategorySize(Symbol),         // Jobs listed under a category
+    CategoryIndex(Symbol, u32),   // One page of a category's job ids
+    DeadlineRequests(u32),        // Pending deadline extensions per milestone for a job
+}
+
+/// Persistent storage: per-address records, registries and per-token ledgers
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AccountKey {
+    Reviews(Address, u32),        // Reviews received by an address, INDEX_PAGE per page
+    Rating(Address),              // Rating totals for an address
+    Talent(Address),              // Talent profile
+    Client(Address),              // Client profile
+    SessionKey(Address),          // Work-only key granted by a talent
+    PayoutPreference(Address),    // Token a payee wants to be paid in
+    ClientNonce(Address),         // Next nonce for derived job ids
+    NonceJob(Address, u64),       // Derived job id by creating client and nonce
+    Specialization(BytesN<32>),   // Arbitrators indexed by expertise
+    Arbitrators,                  // Arbitrator registry
+    Treasury,                     // Accrued platform fees per token
+    Liabilities,                  // Sum of job escrow balances per token
+    YieldDeployed,                // Escrow principal held by yield strategies per token
+    InsurancePool,                // Insurance pool balance per token
+    InsuranceClaim(u32),          // Insurance claim
+    ReferralEarnings(Address),    // Unclaimed referral rewards per token
+    ActionQueue,                  // Queued admin actions
+}
+
+/// Reentrancy lock held for one state-changing call; released on drop,
+/// including early returns
+#[must_use]
+struct ReentrancyGuard<'a> {
+    env: &'a Env,
+}
+
+impl Drop for ReentrancyGuard<'_> {
+    fn drop(&mut self) {
+        self.env.storage().temporary().remove(&ConfigKey::ReentrancyLock);
+    }
+}
+
+/// Checked escrow accounting for one job. All changes to `escrow_balance`
+/// go through here so that
+/// `escrow_balance == deposited - amount_paid - fees_charged - refunded`
+/// holds after every call. Token transfers stay with the caller.
+struct EscrowLedger<'a> {
+    env: &'a Env,
+    job: &'a mut Job,
+}
+
+impl EscrowLedger<'_> {
+    /// Funds received into escrow
+    fn deposit(&mut self, amount: i128) {
+        self.job.deposited = self.add(self.job.deposited, amount);
+        self.job.escrow_balance = self.add(self.job.escrow_balance, amount);
+        self.track(amount);
+        // Subjobs are funded from their parent's escrow, already counted
+        if self.job.parent.is_none() {
+            // Sponsored or crowdfunded escrow is not the client's own money
+            if self.job.funder.is_none() && !self.job.crowdfunded {
+                let mut profile = DecentralizedJobMarket::client_profile(self.env, &self.job.client);
+                profile.total_funded += amount;
+                DecentralizedJobMarket::save_client_profile(self.env, &self.job.client, &profile);
+            }
+            let token = self.job.token.clone();
+            DecentralizedJobMarket::update_stats(self.env, |stats| {
+                let total = stats.total_escrowed.get(token.clone()).unwrap_or(0);
+                stats.total_escrowed.set(token, total + amount);
+            });
+        }
+    }
+
+    /// Funds paid out for work (gross of platform fees)
+    fn release(&mut self, amount: i128) {
+        self.job.escrow_balance = self.sub(self.job.escrow_balance, amount);
+        self.job.amount_paid = self.add(self.job.amount_paid, amount);
+        self.track(-amount);
+    }
+
+    /// Arbitration fees covered by the escrow
+    fn charge_fee(&mut self, amount: i128) {
+        self.job.escrow_balance = self.sub(self.job.escrow_balance, amount);
+        self.job.fees_charged = self.add(self.job.fees_charged, amount);
+        self.track(-amount);
+    }
+
+    /// Funds returned to the client, or to the parent job's escrow
+    fn refund(&mut self, amount: i128) {
+        self.job.escrow_balance = self.sub(self.job.escrow_balance, amount);
+        self.job.refunded = self.add(self.job.refunded, amount);
+        self.track(-amount);
+    }
+
+    /// Return everything still held; returns the amount
+    fn refund_all(&mut self) -> i128 {
+        let amount = self.job.escrow_balance;
+        self.refund(amount);
+        amount
+    }
+
+    fn add(&self, total: i128, amount: i128) -> i128 {
+        if amount < 0 {
+            panic_with_error!(self.env, Error::EscrowMismatch);
+        }
+        total.checked_add(amount)
+            .unwrap_or_else(|| panic_with_error!(self.env, Error::EscrowMismatch))
+    }
+
+    /// Keep the per-token liability total in step. A subjob's escrow is
+    /// counted in its parent until the parent milestone is paid.
+    fn track(&self, delta: i128) {
+        if delta != 0 && DecentralizedJobMarket::parent_settled(self.env, self.job) {
+            DecentralizedJobMarket::adjust_total(self.env, AccountKey::Liabilities, &self.job.token, delta);
+        }
+    }
+
+    /// Escrow can never go negative
+    fn sub(&self, balance: i128, amount: i128) -> i128 {
+        if amount < 0 {
+            panic_with_error!(self.env, Error::EscrowMismatch);
+        }
+        match balance.checked_sub(amount) {
+            Some(left) if left >= 0 => left,
+            _ => panic_with_error!(self.env, Error::InsufficientFunds),
+        }
+    }
+}
+
+/// Interface a whitelisted yield contract implements. The market transfers
+/// tokens to the strategy before calling `deposit`.
+#[contractclient(name = "YieldStrategyClient")]
+pub trait YieldStrategy {
+    /// Credit `amount` of `token` already received; returns shares minted
+    fn deposit(env: Env, token: Address, amount: i128) -> i128;
+    /// Redeem `shares` and send the tokens to `to`; returns the amount sent
+    fn withdraw(env: Env, token: Address, shares: i128, to: Address) -> i128;
+}
+
+/// DEX/AMM router a whitelisted swap contract implements
+#[contractclient(name = "SwapRouterClient")]
+pub trait SwapRouter {
+    /// Amount of `token_out` that `amount_in` of `token_in` buys now
+    fn quote(env: Env, token_in: Address, token_out: Address, amount_in: i128) -> i128;
+    /// Pull `amount_in` of `token_in` from `from` and send at least
+    /// `min_out` of `token_out` to `to`; returns the amount sent
+    fn swap(
+        env: Env,
+        from: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        min_out: i128,
+        to: Address,
+    ) -> i128;
+}
+
+/// Price feed a whitelisted oracle implements
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    /// Token base units per reference unit, scaled by RATE_SCALE, and the
+    /// time the price was last updated
+    fn price(env: Env, token: Address) -> (i128, u64);
+}
+
+#[contract]
+pub struct DecentralizedJobMarket;
+
+#[contractimpl]
+impl DecentralizedJobMarket {
+    // ==============
+    // INITIALIZATION
+    // ==============
+    /// Configure the contract atomically at deployment. There is no
+    /// separate `initialize` entry point, so nobody can front-run the
+    /// deployer with a malicious admin or token.
+    /// Any Stellar Asset Contract works as the token, including native XLM
+    /// (amounts are then in stroops).
+    /// @param env: Soroban environment
+    /// @param config: Admin, default token, fees, limits and periods
+    pub fn __constructor(env: Env, config: InitConfig) {
+        Self::configure(&env, &config);
+    }
+
+    /// Check whether the contract has been configured
+    /// @param env: Soroban environment
+    pub fn is_initialized(env: Env) -> bool {
+        env.storage().instance().has(&ConfigKey::Token)
+    }
+
+    // ==============
+    // ADMINISTRATION
+    // ==============
+    /// Propose a new administrator (admin only).
+    /// Privileges move only once the proposed address calls `accept_admin`.
+    /// @param env: Soroban environment
+    /// @param admin: Current administrator
+    /// @param new_admin: Proposed administrator address
+    pub fn propose_admin(env: Env, admin: Address, new_admin: Address) {
+        Self::require_admin(&env, &admin);
+        env.storage().instance().set(&ConfigKey::PendingAdmin, &new_admin);
+        env.events().publish(
+            (ADM_PROP, admin),
+            new_admin
+        );
+    }
+
+    /// Accept a pending admin proposal
+    /// @param env: Soroban environment
+    /// @param new_admin: Proposed administrator address
+    pub fn accept_admin(env: Env, new_admin: Address) {
+        new_admin.require_auth();
+
+        let pending: Address = env.storage().instance().get(&ConfigKey::PendingAdmin)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidState));
+        if pending != new_admin {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+
+        let old_admin = Self::get_admin_address(&env);
+        env.storage().instance().set(&ConfigKey::Admin, &new_admin);
+        env.storage().instance().remove(&ConfigKey::PendingAdmin);
+        env.events().publish(
+            (ADM_ACC, new_admin),
+            old_admin
+        );
+    }
+
+    /// Get proposed administrator awaiting acceptance
+    /// @param env: Soroban environment
+    pub fn get_pending_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&ConfigKey::PendingAdmin)
+    }
+
+    /// Get contract administrator
+    /// @param env: Soroban environment
+    pub fn get_admin(env: Env) -> Address {
+        Self::get_admin_address(&env)
+    }
+
+    /// Get all operational parameters in one call
+    /// @param env: Soroban environment
+    pub fn get_config(env: Env) -> Config {
+        Config {
+            admin: Self::get_admin_address(&env),
+            token: Self::get_token_id(&env),
+            platform_fee_bps: Self::platform_fee_bps(&env),
+            max_arbitrator_fee: Self::max_arbitrator_fee(&env),
+            max_cancellation_fee_bps: Self::max_cancellation_fee(&env),
+            min_arbitrator_stake: Self::min_arbitrator_stake(&env),
+            default_ruling: Self::default_ruling(&env),
+            ruling_window: RULING_WINDOW,
+            review_period: APPEAL_WINDOW,
+            timelock_delay: Self::timelock_delay(&env),
+            text_limits: Self::text_limits(&env),
+            job_limits: Self::job_limits(&env),
+            paused: Self::paused(&env),
+            state_version: Self::state_version(&env),
+        }
+    }
+
+    /// Freeze all state-mutating operations (admin only)
+    /// @param env: Soroban environment
+    /// @param admin: Contract administrator
+    pub fn pause(env: Env, admin: Address) {
+        Self::require_admin(&env, &admin);
+        env.storage().instance().set(&ConfigKey::Paused, &true);
+        env.events().publish((PAUSED, admin), ());
+    }
+
+    /// Resume normal operation (admin only)
+    /// @param env: Soroban environment
+    /// @param admin: Contract administrator
+    pub fn unpause(env: Env, admin: Address) {
+        Self::require_admin(&env, &admin);
+        env.storage().instance().set(&ConfigKey::Paused, &false);
+        env.events().publish((UNPAUSED, admin), ());
+    }
+
+    /// Replace contract code, keeping all storage (admin only)
+    /// @param env: Soroban environment
+    /// @param admin: Contract administrator
+    /// @param new_wasm_hash: Hash of uploaded WASM
+    pub fn upgrade(env: Env, admin: Address, new_wasm_hash: BytesN<32>) {
+        Self::require_admin(&env, &admin);
+        env.deployer().update_current_contract_wasm(new_wasm_hash.clone());
+        env.events().publish(
+            (UPGRADE, admin),
+            new_wasm_hash
+        );
+    }
+
+    /// Bring stored state up to the layout expected by this code version.
+    /// Call once after `upgrade`; escrow balances and jobs are kept in place.
+    /// Jobs and accounts are moved afterwards with `migrate_jobs` and
+    /// `migrate_accounts`.
+    /// @param env: Soroban environment
+    /// @param admin: Contract administrator
+    pub fn migrate(env: Env, admin: Address) {
+        if !env.storage().instance().has(&ConfigKey::StateVersion) {
+            // v3: config moves to `ConfigKey` first so the admin check reads it
+            Self::migrate_config_keys(&env);
+        }
+        Self::require_admin(&env, &admin);
+
+        let from_version = Self::state_version(&env);
+        if from_version >= STATE_VERSION {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+
+        // Per-version migrations are applied here in order as layouts change
+        if from_version < 2 {
+            // v2: payment token stored as an Address instead of a raw contract id,
+            // and the treasury and allowlist keyed by it
+            let stored: Option<Val> = env.storage().instance().get(&ConfigKey::Token);
+            if let Some(Ok(legacy_id)) = stored.map(|v| BytesN::<32>::try_from_val(&env, &v)) {
+                env.storage().instance().set(&ConfigKey::Token, &Self::contract_address_from_id(&env, &legacy_id));
+            }
+            let treasury_key = symbol_short!("TREASURY");
+            let treasury: Option<Map<BytesN<32>, i128>> = env.storage().persistent().get(&treasury_key);
+            if let Some(treasury) = treasury {
+                env.storage().persistent().set(&treasury_key, &Self::key_by_address(&env, treasury));
+            }
+            let tokens: Option<Map<BytesN<32>, bool>> = env.storage().instance().get(&ConfigKey::AllowedTokens);
+            if let Some(tokens) = tokens {
+                env.storage().instance().set(&ConfigKey::AllowedTokens, &Self::key_by_address(&env, tokens));
+            }
+        }
+        if from_version < 3 {
+            // v3: registries and templates keyed by `AccountKey` and `JobKey`; `migrate_jobs`
+            // decodes jobs in the layout they were written with
+            env.storage().instance().set(&ConfigKey::LegacyJobLayout, &from_version.max(1));
+            Self::move_persistent(&env, &symbol_short!("TREASURY"), &AccountKey::Treasury);
+            Self::move_persistent(&env, &symbol_short!("ARB_REG"), &AccountKey::Arbitrators);
+            Self::move_persistent(&env, &symbol_short!("TL_QUEUE"), &AccountKey::ActionQueue);
+            for record in Self::get_arbitrators(&env).values().iter() {
+                let tag = record.specialization;
+                Self::move_persistent(&env, &(symbol_short!("ARB_SPEC"), tag.clone()), &AccountKey::Specialization(tag));
+            }
+            let templates: u32 = env.storage().instance().get(&ConfigKey::TemplateCount).unwrap_or(0);
+            for template_id in 1..=templates {
+                Self::move_persistent(&env, &(symbol_short!("TEMPLATE"), template_id), &JobKey::Template(template_id));
+            }
+        }
+
+        env.storage().instance().set(&ConfigKey::StateVersion, &STATE_VERSION);
+        env.events().publish(
+            (MIGRATED, admin),
+            (from_version, STATE_VERSION)
+        );
+    }
+
+    /// Move a range of pre-v3 jobs, their per-job records and their
+    /// participants' profiles to keyed storage (admin only, after `migrate`).
+    /// Jobs are decoded in their v1 or v2 layout and rewritten as `Job`.
+    /// @param env: Soroban environment
+    /// @param admin: Contract administrator
+    /// @param start_id: First job ID to move
+    /// @param limit: Jobs to move in this call (max 50)
+    /// @return Next job ID to pass in, or 0 once all jobs are moved
+    pub fn migrate_jobs(env: Env, admin: Address, start_id: u32, limit: u32) -> u32 {
+        Self::require_admin(&env, &admin);
+        if Self::state_version(&env) < STATE_VERSION || start_id == 0 || limit == 0 || limit > MAX_PAGE {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        let layout: u32 = env.storage().instance().get(&ConfigKey::LegacyJobLayout)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidState));
+
+        let count: u32 = env.storage().instance().get(&ConfigKey::JobCount).unwrap_or(0);
+
+        let end = start_id.saturating_add(limit - 1).min(count);
+        for job_id in start_id..=end {
+            let legacy_key = Self::legacy_job_key(&env, job_id);
+            let stored: Option<Val> = env.storage().persistent().get(&legacy_key);
+            let Some(stored) = stored else { continue };
+            let job = if layout == 1 {
+                Self::upgrade_job_v1(&env, JobV1::try_from_val(&env, &stored)
+                    .unwrap_or_else(|_| panic_with_error!(&env, Error::InvalidState)))
+            } else {
+                Self::upgrade_job_v2(&env, JobV2::try_from_val(&env, &stored)
+                    .unwrap_or_else(|_| panic_with_error!(&env, Error::InvalidState)))
+            };
+            env.storage().persistent().remove(&legacy_key);
+            Self::update_job(&env, job_id, &job);
+            Self::index_category(&env, &job.category, job_id);
+
+            Self::move_persistent(&env, &(symbol_short!("DISPUTE"), job_id), &JobKey::Dispute(job_id));
+            Self::move_persistent(&env, &(symbol_short!("EVIDENCE"), job_id), &JobKey::Evidence(job_id));
+            Self::move_persistent(&env, &(symbol_short!("TIMESHT"), job_id), &JobKey::Timesheets(job_id));
+            Self::move_persistent(&env, &(symbol_short!("CAN_PROP"), job_id), &JobKey::CancelProposal(job_id));
+            Self::move_persistent(&env, &(symbol_short!("ARB_RATE"), job_id), &JobKey::RatingWindow(job_id));
+
+            let mut parties = Vec::from_array(&env, [job.client.clone()]);
+            if let Some(talent) = job.talent {
+                parties.push_back(talent);
+            }
+            for party in parties.iter() {
+                Self::move_persistent(
+                    &env,
+                    &(symbol_short!("REVIEWED"), job_id, party.clone()),
+                    &JobKey::Reviewed(job_id, party.clone())
+                );
+                Self::migrate_account(&env, &party);
+            }
+        }
+
+        if end >= count { 0 } else { end + 1 }
+    }
+
+    /// Move profiles, reviews and ratings of accounts not reached through
+    /// `migrate_jobs` (e.g. talents never hired) to keyed storage
+    /// @param env: Soroban environment
+    /// @param admin: Contract administrator
+    /// @param accounts: Addresses to move (max 50)
+    pub fn migrate_accounts(env: Env, admin: Address, accounts: Vec<Address>) {
+        Self::require_admin(&env, &admin);
+        if Self::state_version(&env) < STATE_VERSION || accounts.len() > MAX_PAGE {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        for account in accounts.iter() {
+            Self::migrate_account(&env, &account);
+        }
+    }
+
+    /// Get stored state layout version
+    /// @param env: Soroban environment
+    pub fn get_state_version(env: Env) -> u32 {
+        Self::state_version(&env)
+    }
+
+    /// Check whether the circuit breaker is engaged
+    /// @param env: Soroban environment
+    pub fn is_paused(env: Env) -> bool {
+        Self::paused(&env)
+    }
+
+    /// Schedule an automation contract to be called whenever a job event
+    /// of this kind is published (admin only); shorthand for `queue_action`
+    /// with `AdminAction::AddHook`. Targets implement
+    /// `on_event(event_kind: Symbol, job_id: u32, actor: Address)`; a target
+    /// that fails is ignored and never reverts the marketplace call.
+    ///
+    /// Trust: the call runs inside the user's transaction. The target can't
+    /// move escrow (the market's authorization does not reach past it) or
+    /// re-enter (reentrancy guard), but it spends the caller's resource
+    /// budget, and running out of budget can't be caught. A hostile target
+    /// could therefore block every call that publishes its event, hence the
+    /// timelock; `unregister_hook` takes effect at once.
+    /// @param env: Soroban environment
+    /// @param admin: Contract administrator
+    /// @param event_kind: Job event name, e.g. MIL_APR or DIS_RIS
+    /// @param target: Contract to notify
+    /// @return action_id: Queued action identifier
+    pub fn register_hook(env: Env, admin: Address, event_kind: Symbol, target: Address) -> u32 {
+        Self::queue_action(env, admin, AdminAction::AddHook(event_kind, target))
+    }
+
+    /// Stop calling a hook target for an event kind
+    /// @param env: Soroban environment
+    /// @param admin: Contract administrator
+    /// @param event_kind: Job event name
+    /// @param target: Registered contract
+    pub fn unregister_hook(env: Env, admin: Address, event_kind: Symbol, target: Address) {
+        Self::require_admin(&env, &admin);
+
+        let mut hooks = Self::get_hook_map(&env);
+        let mut targets = hooks.get(event_kind.clone()).unwrap_or_else(|| Vec::new(&env));
+        let idx = targets.first_index_of(&target)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidInput));
+        targets.remove(idx);
+        if targets.is_empty() {
+            hooks.remove(event_kind.clone());
+        } else {
+            hooks.set(event_kind.clone(), targets);
+        }
+        env.storage().instance().set(&ConfigKey::Hooks, &hooks);
+
+        env.events().publish(
+            (HOOK_DEL, admin),
+            (event_kind, target)
+        );
+    }
+
+    /// Get the contracts notified for an event kind
+    /// @param env: Soroban environment
+    /// @param event_kind: Job event name
+    pub fn get_hooks(env: Env, event_kind: Symbol) -> Vec<Address> {
+        Self::get_hook_map(&env).get(event_kind).unwrap_or_else(|| Vec::new(&env))
+    }
+
+    // ===================
+    // STORAGE MAINTENANCE
+    // ===================
+    /// Extend the storage lifetime of a job and its related records so
+    /// long-running escrows stay reachable. Anyone may call.
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn bump_job(env: Env, job_id: u32) {
+        let job = Self::get_job(&env, job_id);
+        Self::extend_persistent(&env, &JobKey::History(job_id));
+        for idx in 0..job.milestones.len() {
+            Self::extend_persistent(&env, &JobKey::Submissions(job_id, idx));
+        }
+        Self::extend_persistent(&env, &JobKey::Dispute(job_id));
+        Self::extend_persistent(&env, &JobKey::Evidence(job_id));
+        Self::extend_persistent(&env, &JobKey::Timesheets(job_id));
+        Self::extend_persistent(&env, &JobKey::Entries(job_id));
+        Self::extend_persistent(&env, &JobKey::Amendments(job_id));
+        Self::extend_persistent(&env, &JobKey::CancelProposal(job_id));
+        Self::extend_persistent(&env, &JobKey::RatingWindow(job_id));
+        Self::extend_persistent(&env, &JobKey::Contributions(job_id));
+        Self::extend_instance(&env);
+    }
+
+    /// Replace a finished job with a compact summary once the retention
+    /// period has passed, dropping its per-job records to stop paying rent
+    /// on them. The state transition log is kept.
+    /// @param env: Soroban environment
+    /// @param party: Job client or talent
+    /// @param job_id: Job identifier
+    /// @return Hash of the final job record, for `verify_archived_job`
+    pub fn archive_job(env: Env, party: Address, job_id: u32) -> BytesN<32> {
+        party.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let job = Self::get_job(&env, job_id);
+        if job.client != party && job.talent != Some(party.clone()) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if !matches!(job.state, JobState::Completed | JobState::Cancelled | JobState::Expired) {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        // Ratings of the last arbitrators must be given or lapse first
+        if env.storage().persistent().has(&JobKey::RatingWindow(job_id)) {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        let history: Vec<Transition> = env.storage().persistent().get(&JobKey::History(job_id))
+            .unwrap_or_else(|| Vec::new(&env));
+        let finished_at = history.last().map_or(job.created_at, |transition| transition.at);
+        if env.ledger().timestamp() < finished_at + ARCHIVE_RETENTION {
+            panic_with_error!(&env, Error::TooEarly);
+        }
+
+        let final_hash: BytesN<32> = env.crypto().sha256(&job.clone().to_xdr(&env)).into();
+        let key = JobKey::Archived(job_id);
+        env.storage().persistent().set(&key, &ArchivedJob {
+            client: job.client.clone(),
+            talent: job.talent.clone(),
+            token: job.token.clone(),
+            outcome: job.state.clone(),
+            total_value: job.total_value,
+            amount_paid: job.amount_paid,
+            fees_charged: job.fees_charged,
+            refunded: job.refunded,
+            final_hash: final_hash.clone(),
+            finished_at,
+            archived_at: env.ledger().timestamp(),
+        });
+        Self::extend_persistent(&env, &key);
+
+        for idx in 0..job.milestones.len() {
+            env.storage().persistent().remove(&JobKey::Submissions(job_id, idx));
+        }
+        env.storage().persistent().remove(&JobKey::Evidence(job_id));
+        env.storage().persistent().remove(&JobKey::Timesheets(job_id));
+        env.storage().persistent().remove(&JobKey::Entries(job_id));
+        env.storage().persistent().remove(&JobKey::Amendments(job_id));
+        env.storage().persistent().remove(&JobKey::DeadlineRequests(job_id));
+        env.storage().persistent().remove(&JobKey::CancelProposal(job_id));
+        env.storage().persistent().remove(&JobKey::JobActions(job_id));
+        env.storage().persistent().remove(&JobKey::JobActionCount(job_id));
+        env.storage().persistent().remove(&JobKey::Contributions(job_id));
+        env.storage().persistent().remove(&JobKey::Job(job_id));
+
+        Self::emit(&env, JOB_ARC, job_id, &party, JobArchived {
+            version: EVENT_VERSION,
+            final_hash: final_hash.clone(),
+        });
+        final_hash
+    }
+
+    /// Get the summary an archived job was replaced with
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn get_archived_job(env: Env, job_id: u32) -> Option<ArchivedJob> {
+        env.storage().persistent().get(&JobKey::Archived(job_id))
+    }
+
+    /// Prove details of an archived job: `job` must be the exact final
+    /// record (e.g. rebuilt from ledger history) that was archived
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    /// @param job: Claimed final job record
+    pub fn verify_archived_job(env: Env, job_id: u32, job: Job) -> bool {
+        let archived: Option<ArchivedJob> = env.storage().persistent().get(&JobKey::Archived(job_id));
+        archived.is_some_and(|archived| {
+            let hash: BytesN<32> = env.crypto().sha256(&job.to_xdr(&env)).into();
+            hash == archived.final_hash
+        })
+    }
+
+    // ================
+    // JOB LIFE CYCLE
+    // ================
+    /// Create new job with milestones
+    /// @param env: Soroban environment
+    /// @param client: Job creator address
+    /// @param title: Job title (plain text or hash)
+    /// @param descriptions: Milestone descriptions
+    /// @param amounts: Milestone payments
+    /// @param deadlines: Milestone deadlines (timestamps)
+    /// @return job_id: Created job identifier
+    pub fn create_job(
+        env: Env,
+        client: Address,
+        title: Text,
+        descriptions: Vec<Text>,
+        amounts: Vec<i128>,
+        deadlines: Vec<u64>,
+    ) -> u32 {
+        Self::create_job_with_options(
+            env,
+            client,
+            title,
+            descriptions,
+            amounts,
+            deadlines,
+            JobOptions::default(),
+        )
+    }
+
+    /// Create new job with milestones and optional terms
+    /// @param env: Soroban environment
+    /// @param client: Job creator address
+    /// @param title: Job title (plain text or hash)
+    /// @param descriptions: Milestone descriptions
+    /// @param amounts: Milestone payments
+    /// @param deadlines: Milestone deadlines (timestamps)
+    /// @param options: Optional job terms (payment token, ...)
+    /// @return job_id: Created job identifier
+    pub fn create_job_with_options(
+        env: Env,
+        client: Address,
+        title: Text,
+        descriptions: Vec<Text>,
+        amounts: Vec<i128>,
+        deadlines: Vec<u64>,
+        options: JobOptions,
+    ) -> u32 {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        Self::create_job_internal(&env, &client, title, descriptions, amounts, deadlines, options)
+    }
+
+    /// Create a job that rewards a referrer out of the platform fees once
+    /// the job completes
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param title: Job title (plain text or hash)
+    /// @param descriptions: Milestone descriptions
+    /// @param amounts: Milestone payments
+    /// @param deadlines: Milestone deadlines (timestamps)
+    /// @param referrer: Address that referred the client
+    /// @return job_id: Created job identifier
+    pub fn create_job_with_referrer(
+        env: Env,
+        client: Address,
+        title: Text,
+        descriptions: Vec<Text>,
+        amounts: Vec<i128>,
+        deadlines: Vec<u64>,
+        referrer: Address,
+    ) -> u32 {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let options = JobOptions { referrer: Some(referrer), ..JobOptions::default() };
+        Self::create_job_internal(&env, &client, title, descriptions, amounts, deadlines, options)
+    }
+
+    /// Create several jobs atomically (10 max per call)
+    /// @param env: Soroban environment
+    /// @param client: Job creator address
+    /// @param specs: Terms of each job
+    /// @return job_ids: Created job identifiers, in spec order
+    pub fn create_jobs_batch(env: Env, client: Address, specs: Vec<JobSpec>) -> Vec<u32> {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        if specs.is_empty() || specs.len() > MAX_BATCH {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+
+        let mut job_ids = Vec::new(&env);
+        for spec in specs.iter() {
+            job_ids.push_back(Self::create_job_internal(
+                &env,
+                &client,
+                spec.title,
+                spec.descriptions,
+                spec.amounts,
+                spec.deadlines,
+                spec.options,
+            ));
+        }
+        job_ids
+    }
+
+    /// Retire a job that was never funded within its funding window.
+    /// Anyone may call.
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn expire_unfunded_job(env: Env, job_id: u32) {
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.state != JobState::Created {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if env.ledger().timestamp() < job.expires_at {
+            panic_with_error!(&env, Error::TooEarly);
+        }
+
+        // A crowdfunded job may hold contributions short of its total
+        let refund_amount = Self::ledger(&env, &mut job).refund_all();
+        if refund_amount > 0 {
+            Self::refund_escrow(&env, job_id, &job, refund_amount);
+        }
+        Self::set_state(&env, job_id, &mut job, JobState::Expired, &env.current_contract_address());
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, JOB_EXP, job_id, &job.client, JobNotice { version: EVENT_VERSION });
+    }
+
+    /// Refund a funded job that never hired anyone once its staffing
+    /// deadline has passed. No cancellation fee applies and the job ends
+    /// Expired rather than Cancelled.
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    pub fn refund_unstaffed(env: Env, client: Address, job_id: u32) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if job.state != JobState::Funded || job.talent.is_some() || !Self::parent_settled(&env, &job) {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        let now = env.ledger().timestamp();
+        if now <= job.staffing_deadline {
+            panic_with_error!(&env, Error::TooEarly);
+        }
+        // A live offer may still be accepted
+        if job.offer.as_ref().is_some_and(|offer| now <= offer.expires_at) {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+
+        let refund_amount = Self::ledger(&env, &mut job).refund_all();
+        if refund_amount > 0 {
+            Self::refund_escrow(&env, job_id, &job, refund_amount);
+        }
+        Self::set_state(&env, job_id, &mut job, JobState::Expired, &client);
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, UNSTAFF, job_id, &client, EscrowChanged {
+            version: EVENT_VERSION,
+            amount: refund_amount,
+            escrow_balance: 0,
+        });
+    }
+
+    /// Store a reusable job structure
+    /// @param env: Soroban environment
+    /// @param owner: Template author
+    /// @param title: Job title
+    /// @param milestone_specs: Milestone descriptions and payments
+    /// @return template_id: Created template identifier
+    pub fn create_template(
+        env: Env,
+        owner: Address,
+        title: Text,
+        milestone_specs: Vec<MilestoneSpec>,
+    ) -> u32 {
+        owner.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        if milestone_specs.is_empty() {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        if milestone_specs.len() > Self::job_limits(&env).max_milestones {
+            panic_with_error!(&env, Error::TooManyMilestones);
+        }
+        let (title_max, description_max) = Self::text_limits(&env);
+        Self::check_text(&env, &title, title_max);
+        for spec in milestone_specs.iter() {
+            if spec.amount <= 0 {
+                panic_with_error!(&env, Error::AmountRequired);
+            }
+            Self::check_text(&env, &spec.description, description_max);
+        }
+
+        let template_id = env.storage().instance().get(&ConfigKey::TemplateCount)
+            .unwrap_or(0u32) + 1;
+        env.storage().instance().set(&ConfigKey::TemplateCount, &template_id);
+        env.storage().persistent().set(&JobKey::Template(template_id), &JobTemplate {
+            owner: owner.clone(),
+            title: title.clone(),
+            milestones: milestone_specs.clone(),
+            created_at: env.ledger().timestamp(),
+        });
+
+        env.events().publish(
+            (TPL_CRT, owner),
+            (template_id, title, milestone_specs.len())
+        );
+        template_id
+    }
+
+    /// Create a job from a stored template. Any client may instantiate
+    /// any template; deadlines are set relative to the current time.
+    /// @param env: Soroban environment
+    /// @param client: Job creator address
+    /// @param template_id: Template identifier
+    /// @param deadline_offsets: Seconds from now until each milestone is due
+    /// @return job_id: Created job identifier
+    pub fn create_job_from_template(
+        env: Env,
+        client: Address,
+        template_id: u32,
+        deadline_offsets: Vec<u64>,
+    ) -> u32 {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let template = Self::get_template_record(&env, template_id);
+        if deadline_offsets.len() != template.milestones.len() {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+
+        let now = env.ledger().timestamp();
+        let mut descriptions = Vec::new(&env);
+        let mut amounts = Vec::new(&env);
+        let mut deadlines = Vec::new(&env);
+        for (spec, offset) in template.milestones.iter().zip(deadline_offsets.iter()) {
+            descriptions.push_back(spec.description);
+            amounts.push_back(spec.amount);
+            let deadline = now.checked_add(offset)
+                .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidInput));
+            deadlines.push_back(deadline);
+        }
+
+        Self::create_job_internal(
+            &env,
+            &client,
+            template.title,
+            descriptions,
+            amounts,
+            deadlines,
+            JobOptions::default(),
+        )
+    }
+
+    /// Get job template
+    /// @param env: Soroban environment
+    /// @param template_id: Template identifier
+    pub fn get_template(env: Env, template_id: u32) -> Option<JobTemplate> {
+        env.storage().persistent().get(&JobKey::Template(template_id))
+    }
+
+    /// Subcontract part of a milestone: carve a child job out of its
+    /// expected payout, with the talent as the child's client. The child is
+    /// funded from the parent escrow and can only pay out once the parent
+    /// milestone has been paid.
+    /// @param env: Soroban environment
+    /// @param talent: Talent hired on the parent job
+    /// @param parent_job_id: Parent job identifier
+    /// @param parent_milestone_idx: Parent milestone funding the subjob
+    /// @param title: Subjob title
+    /// @param descriptions: Milestone descriptions
+    /// @param amounts: Milestone payments
+    /// @param deadlines: Milestone deadlines (timestamps)
+    /// @return job_id: Created subjob identifier
+    pub fn create_subjob(
+        env: Env,
+        talent: Address,
+        parent_job_id: u32,
+        parent_milestone_idx: u32,
+        title: Text,
+        descriptions: Vec<Text>,
+        amounts: Vec<i128>,
+        deadlines: Vec<u64>,
+    ) -> u32 {
+        talent.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut parent = Self::get_job(&env, parent_job_id);
+        if parent.talent != Some(talent.clone()) {
+            panic_with_error!(&env, Error::TalentOnly);
+        }
+        // Priced milestones shrink at payout, which could strand a reservation
+        if parent.state != JobState::Active || parent.payment.pricing.is_some() {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        let mut milestone = parent.milestones.get(parent_milestone_idx)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidIndex));
+        if !matches!(milestone.state, MilestoneState::Pending | MilestoneState::Submitted) {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+
+        let options = JobOptions { token: Some(parent.token.clone()), ..JobOptions::default() };
+        let job_id = Self::create_job_internal(
+            &env,
+            &talent,
+            title,
+            descriptions,
+            amounts,
+            deadlines,
+            options,
+        );
+
+        let mut job = Self::get_job(&env, job_id);
+        if Self::reserved_for_subjobs(&env, &milestone) + job.total_value > milestone.amount {
+            panic_with_error!(&env, Error::InsufficientFunds);
+        }
+        // Tokens stay in the contract; the reservation moves to the child
+        Self::unwind_yield(&env, parent_job_id);
+        job.parent = Some((parent_job_id, parent_milestone_idx)).into();
+        let total_value = job.total_value;
+        Self::ledger(&env, &mut job).deposit(total_value);
+        job.staffing_deadline = env.ledger().timestamp() + job.staffing_window;
+        Self::set_state(&env, job_id, &mut job, JobState::Funded, &talent);
+        Self::update_job(&env, job_id, &job);
+
+        milestone.subjobs.push_back(job_id);
+        parent.milestones.set(parent_milestone_idx, milestone);
+        Self::update_job(&env, parent_job_id, &parent);
+
+        Self::emit(&env, SUB_CRT, job_id, &talent, JobCreated {
+            version: EVENT_VERSION,
+            title: job.title,
+            total_value: job.total_value,
+            token: job.token,
+            parent: job.parent,
+        });
+        job_id
+    }
+
+    /// Fund job escrow with payment tokens
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    pub fn fund_job(env: Env, client: Address, job_id: u32) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        Self::fund_escrow(&env, &client, &client, job_id, false);
+    }
+
+    /// Fund job escrow on the client's behalf. The sponsor is recorded as
+    /// the funder and receives every refund of the job's escrow.
+    /// @param env: Soroban environment
+    /// @param sponsor: Address paying for the job
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    pub fn fund_job_for(env: Env, sponsor: Address, client: Address, job_id: u32) {
+        sponsor.require_auth();
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        // Retainer periods are pulled from the client, not the sponsor
+        let job = Self::get_job(&env, job_id);
+        if sponsor == client || job.payment.retainer.is_some() {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        Self::fund_escrow(&env, &client, &sponsor, job_id, false);
+
+        let job = Self::get_job(&env, job_id);
+        Self::emit(&env, JOB_SPN, job_id, &sponsor, JobSponsored {
+            version: EVENT_VERSION,
+            client,
+            amount: job.total_value,
+        });
+    }
+
+    /// Add tokens to a funded job's escrow to cover increased scope
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    /// @param amount: Extra tokens to escrow
+    pub fn top_up_escrow(env: Env, client: Address, job_id: u32, amount: i128) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        // Stream vesting is fixed to the value at hire time, and sponsored
+        // or crowdfunded escrow is refunded to its funders, not the client
+        // topping it up
+        if !matches!(job.state, JobState::Funded | JobState::Active)
+            || job.payment.stream.is_some()
+            || job.funder.is_some()
+            || job.crowdfunded
+        {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if amount <= 0 {
+            panic_with_error!(&env, Error::AmountRequired);
+        }
+
+        Self::escrow_in(&env, job_id, &job.token, &client, amount, false);
+        Self::ledger(&env, &mut job).deposit(amount);
+        job.total_value += amount;
+        Self::check_escrow_covers(&env, &job);
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, TOP_UP, job_id, &client, EscrowChanged {
+            version: EVENT_VERSION,
+            amount,
+            escrow_balance: job.escrow_balance,
+        });
+    }
+
+    /// Refund escrow in excess of what the job can still owe the talent
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    /// @return amount: Surplus refunded
+    pub fn withdraw_surplus(env: Env, client: Address, job_id: u32) -> i128 {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if !matches!(job.state, JobState::Funded | JobState::Active | JobState::Completed)
+            || !Self::parent_settled(&env, &job)
+        {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        // Arbitration fees may still be charged to escrow
+        if Self::has_open_dispute(&env, job_id) {
+            panic_with_error!(&env, Error::ArbitrationPending);
+        }
+
+        let surplus = Self::escrow_surplus(&env, job_id, &job);
+        if surplus <= 0 {
+            panic_with_error!(&env, Error::AmountRequired);
+        }
+
+        Self::refund_escrow(&env, job_id, &job, surplus);
+        Self::ledger(&env, &mut job).refund(surplus);
+        job.total_value -= surplus;
+        Self::check_escrow_covers(&env, &job);
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, SURPLUS, job_id, &client, EscrowChanged {
+            version: EVENT_VERSION,
+            amount: surplus,
+            escrow_balance: job.escrow_balance,
+        });
+        surplus
+    }
+
+    /// Get escrow the client could withdraw as surplus right now
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn get_escrow_surplus(env: Env, job_id: u32) -> Option<i128> {
+        Self::find_job(&env, job_id).map(|job| Self::escrow_surplus(&env, job_id, &job).max(0))
+    }
+
+    /// Fund job escrow by pulling tokens against an allowance the client
+    /// granted to this contract with `approve`
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    pub fn fund_job_with_allowance(env: Env, client: Address, job_id: u32) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        Self::fund_escrow(&env, &client, &client, job_id, true);
+    }
+
+    /// Contribute to a crowdfunded job's escrow. The job becomes Funded once
+    /// contributions reach its total value; refunds are shared among
+    /// contributors in proportion to what each put in.
+    /// @param env: Soroban environment
+    /// @param contributor: Address adding funds (may be the client)
+    /// @param job_id: Job identifier
+    /// @param amount: Tokens to contribute, at most what the job still lacks
+    pub fn contribute(env: Env, contributor: Address, job_id: u32, amount: i128) {
+        contributor.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if !job.crowdfunded || job.state != JobState::Created {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if env.ledger().timestamp() >= job.expires_at {
+            panic_with_error!(&env, Error::DeadlinePassed);
+        }
+        if amount <= 0 {
+            panic_with_error!(&env, Error::AmountRequired);
+        }
+        if amount > job.total_value - job.escrow_balance {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        let mut contributions = Self::load_contributions(&env, job_id);
+        let previous = contributions.get(contributor.clone()).unwrap_or(0);
+        if previous == 0 && contributions.len() >= MAX_CONTRIBUTORS {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+
+        Self::escrow_in(&env, job_id, &job.token, &contributor, amount, false);
+        Self::ledger(&env, &mut job).deposit(amount);
+        contributions.set(contributor.clone(), previous + amount);
+        env.storage().persistent().set(&JobKey::Contributions(job_id), &contributions);
+
+        let funded = job.escrow_balance == job.total_value;
+        if funded {
+            job.staffing_deadline = env.ledger().timestamp() + job.staffing_window;
+            Self::set_state(&env, job_id, &mut job, JobState::Funded, &contributor);
+        }
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, CONTRIB, job_id, &contributor, EscrowChanged {
+            version: EVENT_VERSION,
+            amount,
+            escrow_balance: job.escrow_balance,
+        });
+        if funded {
+            Self::deploy_yield(&env, job_id, &job);
+            Self::emit(&env, JOB_FUND, job_id, &job.client, EscrowChanged {
+                version: EVENT_VERSION,
+                amount: job.total_value,
+                escrow_balance: job.escrow_balance,
+            });
+        }
+    }
+
+    /// Get what each address has contributed to a crowdfunded job
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn get_contributions(env: Env, job_id: u32) -> Option<Map<Address, i128>> {
+        Self::find_job(&env, job_id)
+            .filter(|job| job.crowdfunded)
+            .map(|_| Self::load_contributions(&env, job_id))
+    }
+
+    /// Offer a funded job to a talent. The job becomes Active only once
+    /// the talent accepts; a new offer replaces any pending one. Agency
+    /// jobs also need the agency's signature.
+    /// @param env: Soroban environment
+    /// @param client: Job creator (or manager with SelectTalent)
+    /// @param job_id: Job identifier
+    /// @param talent: Freelancer address
+    pub fn offer_job(env: Env, client: Address, job_id: u32, talent: Address) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        Self::check_client_or_manager(&env, &job, &client, Permission::SelectTalent);
+        // Agency jobs need the agency to co-sign the selection
+        if let Some(agency) = job.roles.agency.as_ref() {
+            agency.address.require_auth();
+        }
+        // Contest winners are picked from entries, not hired
+        if job.state != JobState::Funded || job.contest {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if job.talent.is_some() {
+            panic_with_error!(&env, Error::TalentExists);
+        }
+        Self::check_invited(&env, &job, &talent);
+        Self::check_talent_eligibility(&env, &job, &talent);
+        // Milestone payees must meet the same bar as the talent
+        for milestone in job.milestones.iter() {
+            if let Some(payee) = &milestone.payee {
+                Self::check_talent_eligibility(&env, &job, payee);
+            }
+        }
+
+        let expires_at = env.ledger().timestamp() + OFFER_WINDOW;
+        job.offer = Some(Offer { talent: talent.clone(), expires_at }).into();
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, OFFER, job_id, &client, OfferMade { version: EVENT_VERSION, talent, expires_at });
+    }
+
+    /// Accept a pending job offer and start work
+    /// @param env: Soroban environment
+    /// @param talent: Offered freelancer
+    /// @param job_id: Job identifier
+    pub fn accept_offer(env: Env, talent: Address, job_id: u32) {
+        talent.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        let offer = Self::pending_offer(&env, &job, &talent);
+        if env.ledger().timestamp() > offer.expires_at {
+            panic_with_error!(&env, Error::DeadlinePassed);
+        }
+        if job.state != JobState::Funded {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        // Invitation and reputation may have changed since the offer was made
+        Self::check_invited(&env, &job, &talent);
+        Self::check_talent_eligibility(&env, &job, &talent);
+
+        // Lock the work bond, returned on completion
+        if job.talent_bond > 0 {
+            Self::escrow_in(&env, job_id, &job.token, &talent, job.talent_bond, false);
+            job.bond_posted = job.talent_bond;
+        }
+
+        job.talent = Some(talent.clone());
+        job.offer = None.into();
+        Self::adjust_active_jobs(&env, &talent, true);
+        Self::set_state(&env, job_id, &mut job, JobState::Active, &talent);
+        Self::start_work(&env, &mut job);
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, TAL_SEL, job_id, &talent, TalentHired {
+            version: EVENT_VERSION,
+            client: job.client,
+            bond: job.bond_posted,
+        });
+    }
+
+    /// Decline a pending job offer
+    /// @param env: Soroban environment
+    /// @param talent: Offered freelancer
+    /// @param job_id: Job identifier
+    pub fn decline_offer(env: Env, talent: Address, job_id: u32) {
+        talent.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        Self::pending_offer(&env, &job, &talent);
+        job.offer = None.into();
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, OFF_DEC, job_id, &talent, JobNotice { version: EVENT_VERSION });
+    }
+
+    /// Invite a talent to a job, making it private: from the first
+    /// invitation on, only invited talents can be offered the job
+    /// @param env: Soroban environment
+    /// @param client: Job creator (or manager with SelectTalent)
+    /// @param job_id: Job identifier
+    /// @param talent: Freelancer to invite
+    pub fn invite_talent(env: Env, client: Address, job_id: u32, talent: Address) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_hiring_job(&env, &client, job_id);
+        let mut invited = job.invited.clone().unwrap_or_else(|| Vec::new(&env));
+        if invited.contains(&talent) {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if invited.len() >= MAX_INVITES {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        invited.push_back(talent.clone());
+        job.invited = Some(invited);
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, TAL_INV, job_id, &client, TalentInvited { version: EVENT_VERSION, talent });
+    }
+
+    /// Withdraw an invitation; the job stays private. A pending offer to
+    /// the talent can no longer be accepted.
+    /// @param env: Soroban environment
+    /// @param client: Job creator (or manager with SelectTalent)
+    /// @param job_id: Job identifier
+    /// @param talent: Invited freelancer
+    pub fn revoke_invite(env: Env, client: Address, job_id: u32, talent: Address) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_hiring_job(&env, &client, job_id);
+        let mut invited = job.invited.clone().unwrap_or_else(|| Vec::new(&env));
+        let idx = invited.first_index_of(&talent)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidInput));
+        invited.remove(idx);
+        job.invited = Some(invited);
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, INV_REV, job_id, &client, TalentInvited { version: EVENT_VERSION, talent });
+    }
+
+    /// Get the invited talents of a private job (None for public jobs)
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn get_invited(env: Env, job_id: u32) -> Option<Vec<Address>> {
+        Self::find_job(&env, job_id).and_then(|job| job.invited)
+    }
+
+    /// Let another address act for the client within `permissions`.
+    /// Managers can never cancel, withdraw or move the client's own funds.
+    /// Calling again replaces the manager's permissions.
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    /// @param manager: Delegate address
+    /// @param permissions: Granted scopes
+    pub fn add_manager(
+        env: Env,
+        client: Address,
+        job_id: u32,
+        manager: Address,
+        permissions: Vec<Permission>,
+    ) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if matches!(job.state, JobState::Completed | JobState::Cancelled | JobState::Expired) {
+            panic_with_error!(&env, Error::JobCompleted);
+        }
+        if permissions.is_empty() || manager == client || Some(manager.clone()) == job.talent {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        if !job.roles.managers.contains_key(manager.clone()) && job.roles.managers.len() >= MAX_MANAGERS {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        job.roles.managers.set(manager.clone(), permissions.clone());
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, MGR_SET, job_id, &client, ManagerSet { version: EVENT_VERSION, manager, permissions });
+    }
+
+    /// Revoke a manager's permissions
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    /// @param manager: Delegate address
+    pub fn remove_manager(env: Env, client: Address, job_id: u32, manager: Address) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if !job.roles.managers.contains_key(manager.clone()) {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        job.roles.managers.remove(manager.clone());
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, MGR_REM, job_id, &client, ManagerSet {
+            version: EVENT_VERSION,
+            manager,
+            permissions: Vec::new(&env),
+        });
+    }
+
+    /// Get a job's managers and their permissions
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn get_managers(env: Env, job_id: u32) -> Option<Map<Address, Vec<Permission>>> {
+        Self::find_job(&env, job_id).map(|job| job.roles.managers)
+    }
+
+    /// Hand an unfinished job over to a new client, for key rotation or a
+    /// change of company. Escrow stays where it is; refunds not owed to a
+    /// sponsor or contributors go to the new client. Managers, pending
+    /// multisig actions, the old client's cancellation proposal and any open
+    /// amendment are dropped so no authority carries over.
+    /// @param env: Soroban environment
+    /// @param client: Current job creator
+    /// @param job_id: Job identifier
+    /// @param new_client: Address taking over the job
+    pub fn transfer_job_ownership(env: Env, client: Address, job_id: u32, new_client: Address) {
+        client.require_auth();
+        new_client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if matches!(job.state, JobState::Completed | JobState::Cancelled | JobState::Expired) {
+            panic_with_error!(&env, Error::JobCompleted);
+        }
+        if new_client == client || job.talent.as_ref() == Some(&new_client) {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        // Rulings settle in favour of the party that raised the dispute
+        if Self::has_open_dispute(&env, job_id) {
+            panic_with_error!(&env, Error::ArbitrationPending);
+        }
+
+        // The job counts towards the new client's open job limit instead
+        let mut new_profile = Self::client_profile(&env, &new_client);
+        if new_profile.open_jobs >= Self::job_limits(&env).max_open_jobs {
+            panic_with_error!(&env, Error::TooManyOpenJobs);
+        }
+        new_profile.open_jobs += 1;
+        Self::save_client_profile(&env, &new_client, &new_profile);
+        let mut old_profile = Self::client_profile(&env, &client);
+        old_profile.open_jobs = old_profile.open_jobs.saturating_sub(1);
+        Self::save_client_profile(&env, &client, &old_profile);
+
+        if let Some(mut multisig) = job.roles.multisig.get() {
+            if let Some(idx) = multisig.approvers.first_index_of(&client) {
+                // Two seats for one signer would lower the effective threshold
+                if multisig.approvers.contains(&new_client) {
+                    panic_with_error!(&env, Error::InvalidInput);
+                }
+                multisig.approvers.set(idx, new_client.clone());
+            }
+            job.roles.multisig = Some(multisig).into();
+        }
+        env.storage().persistent().remove(&JobKey::JobActions(job_id));
+        let proposal: Option<CancelProposal> =
+            env.storage().persistent().get(&JobKey::CancelProposal(job_id));
+        if proposal.is_some_and(|proposal| proposal.proposer == client) {
+            env.storage().persistent().remove(&JobKey::CancelProposal(job_id));
+        }
+        Self::lapse_amendment(&env, job_id);
+        job.roles.managers = Map::new(&env);
+        job.client = new_client.clone();
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, JOB_XFER, job_id, &client, OwnershipTransferred {
+            version: EVENT_VERSION,
+            new_client,
+        });
+    }
+
+    /// Get a job's category and tags
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn get_job_tags(env: Env, job_id: u32) -> Option<(Symbol, Vec<Symbol>)> {
+        Self::find_job(&env, job_id).map(|job| (job.category, job.tags))
+    }
+
+    /// List job ids in a category, oldest first. Jobs stay listed whatever
+    /// their state; filter on the client side.
+    /// @param env: Soroban environment
+    /// @param category: Category symbol (e.g. "design")
+    /// @param start: Index of first result
+    /// @param limit: Max results (capped at 50)
+    pub fn list_jobs_by_category(env: Env, category: Symbol, start: u32, limit: u32) -> Vec<u32> {
+        let size: u32 = env.storage().persistent().get(&JobKey::CategorySize(category.clone()))
+            .unwrap_or(0);
+        let end = start.saturating_add(limit.min(MAX_PAGE)).min(size);
+        let mut page = Vec::new(&env);
+        let mut bucket: Option<(u32, Vec<u32>)> = None;
+        for i in start..end {
+            let number = i / INDEX_PAGE;
+            if bucket.as_ref().map(|(loaded, _)| *loaded) != Some(number) {
+                let ids = env.storage().persistent()
+                    .get(&JobKey::CategoryIndex(category.clone(), number))
+                    .unwrap_or_else(|| Vec::new(&env));
+                bucket = Some((number, ids));
+            }
+            if let Some((_, ids)) = &bucket {
+                page.push_back(ids.get(i % INDEX_PAGE).unwrap());
+            }
+        }
+        page
+    }
+
+    /// Attach an off-chain specification to a job; the hash lets anyone
+    /// verify the document served at `uri`
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    /// @param uri: Content location (e.g. ipfs://...)
+    /// @param hash: Hash of the content at `uri`
+    pub fn set_job_metadata(env: Env, client: Address, job_id: u32, uri: String, hash: BytesN<32>) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if matches!(job.state, JobState::Completed | JobState::Cancelled | JobState::Expired) {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if uri.is_empty() {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        if uri.len() > Self::job_limits(&env).max_uri_len {
+            panic_with_error!(&env, Error::InputTooLong);
+        }
+
+        job.metadata = Some(Metadata {
+            uri: uri.clone(),
+            hash: hash.clone(),
+            updated_at: env.ledger().timestamp(),
+        }).into();
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, JOB_META, job_id, &client, MetadataSet { version: EVENT_VERSION, uri, hash });
+    }
+
+    /// Get a job's off-chain specification, if any
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn get_job_metadata(env: Env, job_id: u32) -> Option<Metadata> {
+        Self::find_job(&env, job_id).and_then(|job| job.metadata.get())
+    }
+
+    /// Get a job, or None if it does not exist (or was never created)
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn try_get_job(env: Env, job_id: u32) -> Option<Job> {
+        Self::find_job(&env, job_id)
+    }
+
+    /// Get the nonce a client's next derived job id must use
+    /// @param env: Soroban environment
+    /// @param client: Client address
+    pub fn get_client_nonce(env: Env, client: Address) -> u64 {
+        env.storage().persistent().get(&AccountKey::ClientNonce(client)).unwrap_or(0)
+    }
+
+    /// Find the job a client created with `JobOptions.client_nonce`
+    /// @param env: Soroban environment
+    /// @param client: Client address
+    /// @param nonce: Nonce used at creation
+    pub fn get_job_id_by_nonce(env: Env, client: Address, nonce: u64) -> Option<u32> {
+        env.storage().persistent().get(&AccountKey::NonceJob(client, nonce))
+    }
+
+    /// Get the job id created at a sequence number (1-based), whether it
+    /// was numbered by the counter or derived from a client nonce
+    /// @param env: Soroban environment
+    /// @param seq: Creation sequence number
+    pub fn get_job_id_at(env: Env, seq: u32) -> Option<u32> {
+        let count: u32 = env.storage().instance().get(&ConfigKey::JobCount).unwrap_or(0);
+        if seq == 0 || seq > count {
+            return None;
+        }
+        Some(Self::job_id_at(&env, seq))
+    }
+
+    /// Hash a job's canonical terms (parties, milestones, amounts,
+    /// deadlines and policies) so off-chain agreements can reference them.
+    /// The hash changes whenever the terms do; progress does not affect it.
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    /// @return sha256 of (contract, JobTerms) XDR, None if the job does not exist
+    pub fn job_commitment(env: Env, job_id: u32) -> Option<BytesN<32>> {
+        let job = Self::find_job(&env, job_id)?;
+        let terms = Self::job_terms(&env, job_id, &job);
+        Some(env.crypto()
+            .sha256(&(env.current_contract_address(), terms).to_xdr(&env))
+            .into())
+    }
+
+    /// Check an off-chain reference against the job's current terms
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    /// @param commitment: Previously recorded `job_commitment`
+    pub fn verify_job_commitment(env: Env, job_id: u32, commitment: BytesN<32>) -> bool {
+        Self::job_commitment(env, job_id) == Some(commitment)
+    }
+
+    /// Get a job's current sequence number, for the `*_checked` entry points
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn get_action_seq(env: Env, job_id: u32) -> Option<u64> {
+        Self::find_job(&env, job_id).map(|job| job.action_seq)
+    }
+
+    /// Get a page of a job's state transitions, oldest first
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    /// @param start: Index of first result
+    /// @param limit: Max results (capped at 50)
+    pub fn get_job_history(env: Env, job_id: u32, start: u32, limit: u32) -> Vec<Transition> {
+        let history: Vec<Transition> = env.storage().persistent().get(&JobKey::History(job_id))
+            .unwrap_or_else(|| Vec::new(&env));
+        let end = start.saturating_add(limit.min(MAX_PAGE)).min(history.len());
+        let mut page = Vec::new(&env);
+        for i in start..end {
+            page.push_back(history.get(i).unwrap());
+        }
+        page
+    }
+
+    /// Check a job's escrow accounting:
+    /// `escrow_balance == deposited - amount_paid - fees_charged - refunded`
+    /// with no negative counters. Every write already enforces this; the
+    /// entry point lets auditors and tests confirm it on stored state.
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    /// @return true if the invariant holds (None if the job does not exist)
+    pub fn verify_job_invariants(env: Env, job_id: u32) -> Option<bool> {
+        Self::find_job(&env, job_id).map(|job| Self::ledger_balanced(&job))
+    }
+
+    /// Get the total held in job escrows for a token
+    /// @param env: Soroban environment
+    /// @param token: Payment token contract
+    pub fn get_liabilities(env: Env, token: Address) -> i128 {
+        Self::token_total(&env, AccountKey::Liabilities, &token)
+    }
+
+    /// Check that the contract holds at least what it owes in a token:
+    /// job escrow plus the treasury and insurance pool. Escrow lent to a
+    /// yield strategy counts as held. Publishes INSOLV on a shortfall.
+    /// @param env: Soroban environment
+    /// @param token: Payment token contract
+    /// @return true when the contract is solvent in this token
+    pub fn check_solvency(env: Env, token: Address) -> bool {
+        let liabilities = Self::token_total(&env, AccountKey::Liabilities, &token)
+            + Self::get_treasury(&env).get(token.clone()).unwrap_or(0)
+            + Self::get_insurance_pool_map(&env).get(token.clone()).unwrap_or(0);
+        let held = token::Client::new(&env, &token).balance(&env.current_contract_address())
+            + Self::token_total(&env, AccountKey::YieldDeployed, &token);
+
+        let solvent = held >= liabilities;
+        if !solvent {
+            env.events().publish(
+                (INSOLV, token),
+                (liabilities, held)
+            );
+        }
+        solvent
+    }
+
+    /// Get marketplace-wide counters for dashboards
+    /// @param env: Soroban environment
+    pub fn get_marketplace_stats(env: Env) -> MarketplaceStats {
+        Self::load_stats(&env)
+    }
+
+    // ====================
+    // NEGOTIATION
+    // ====================
+    /// Post a draft job for talents to bid on. No job exists (and nothing
+    /// is escrowed) until the client accepts a counter-proposal.
+    /// @param env: Soroban environment
+    /// @param client: Client address
+    /// @param title: Job title (plain text or hash)
+    /// @param descriptions: Milestone descriptions
+    /// @param amounts: Asking milestone payments
+    /// @param deadlines: Asking milestone deadlines (timestamps)
+    /// @return draft_id: Draft identifier
+    pub fn post_draft(
+        env: Env,
+        client: Address,
+        title: Text,
+        descriptions: Vec<Text>,
+        amounts: Vec<i128>,
+        deadlines: Vec<u64>,
+    ) -> u32 {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let (title_max, description_max) = Self::text_limits(&env);
+        Self::check_text(&env, &title, title_max);
+        for description in descriptions.iter() {
+            Self::check_text(&env, &description, description_max);
+        }
+        Self::check_terms(&env, descriptions.len(), &amounts, &deadlines);
+
+        let draft_id = env.storage().instance().get(&ConfigKey::DraftCount)
+            .unwrap_or(0u32) + 1;
+        env.storage().instance().set(&ConfigKey::DraftCount, &draft_id);
+        let key = JobKey::Draft(draft_id);
+        env.storage().persistent().set(&key, &Draft {
+            client: client.clone(),
+            title: title.clone(),
+            descriptions,
+            amounts,
+            deadlines,
+            proposals: Vec::new(&env),
+            job_id: None,
+            created_at: env.ledger().timestamp(),
+        });
+        Self::extend_persistent(&env, &key);
+
+        env.events().publish(
+            (DRF_NEW, client),
+            (draft_id, title)
+        );
+        draft_id
+    }
+
+    /// Counter-propose milestone amounts and deadlines on a draft.
+    /// Proposing again replaces the talent's earlier proposal.
+    /// @param env: Soroban environment
+    /// @param talent: Registered talent
+    /// @param draft_id: Draft identifier
+    /// @param amounts: Proposed milestone payments
+    /// @param deadlines: Proposed milestone deadlines (timestamps)
+    /// @return Index of the proposal on the draft
+    pub fn propose_terms(
+        env: Env,
+        talent: Address,
+        draft_id: u32,
+        amounts: Vec<i128>,
+        deadlines: Vec<u64>,
+    ) -> u32 {
+        talent.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        if Self::talent_profile(&env, &talent).is_none() {
+            panic_with_error!(&env, Error::TalentNotRegistered);
+        }
+        let mut draft = Self::get_draft_record(&env, draft_id);
+        if draft.job_id.is_some() {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if talent == draft.client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        Self::check_terms(&env, draft.descriptions.len(), &amounts, &deadlines);
+
+        let proposal = TermsProposal {
+            talent: talent.clone(),
+            amounts: amounts.clone(),
+            deadlines,
+            proposed_at: env.ledger().timestamp(),
+        };
+        let existing = draft.proposals.iter().position(|p| p.talent == talent);
+        let idx = match existing {
+            Some(idx) => {
+                draft.proposals.set(idx as u32, proposal);
+                idx as u32
+            }
+            None => {
+                if draft.proposals.len() >= MAX_PROPOSALS {
+                    panic_with_error!(&env, Error::InvalidInput);
+                }
+                draft.proposals.push_back(proposal);
+                draft.proposals.len() - 1
+            }
+        };
+        Self::save_draft(&env, draft_id, &draft);
+
+        env.events().publish(
+            (DRF_PROP, talent),
+            (draft_id, idx, amounts)
+        );
+        idx
+    }
+
+    /// Accept a counter-proposal. The job is created with the negotiated
+    /// amounts and deadlines and is invite-only for the proposing talent;
+    /// the client then funds it and makes the offer as usual.
+    /// @param env: Soroban environment
+    /// @param client: Draft owner
+    /// @param draft_id: Draft identifier
+    /// @param proposal_idx: Index of the accepted proposal
+    /// @return job_id: Created job identifier
+    pub fn accept_proposal(env: Env, client: Address, draft_id: u32, proposal_idx: u32) -> u32 {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut draft = Self::get_draft_record(&env, draft_id);
+        if draft.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if draft.job_id.is_some() {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        let proposal = draft.proposals.get(proposal_idx)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidIndex));
+
+        let job_id = Self::create_job_internal(
+            &env,
+            &client,
+            draft.title.clone(),
+            draft.descriptions.clone(),
+            proposal.amounts.clone(),
+            proposal.deadlines.clone(),
+            JobOptions::default(),
+        );
+        let mut job = Self::get_job(&env, job_id);
+        job.invited = Some(Vec::from_array(&env, [proposal.talent.clone()]));
+        Self::update_job(&env, job_id, &job);
+
+        draft.job_id = Some(job_id);
+        Self::save_draft(&env, draft_id, &draft);
+
+        env.events().publish(
+            (DRF_ACC, client),
+            (draft_id, job_id, proposal.talent)
+        );
+        job_id
+    }
+
+    /// Get a draft with its counter-proposals
+    /// @param env: Soroban environment
+    /// @param draft_id: Draft identifier
+    pub fn get_draft(env: Env, draft_id: u32) -> Option<Draft> {
+        env.storage().persistent().get(&JobKey::Draft(draft_id))
+    }
+
+    // ====================
+    // MILESTONE OPERATIONS
+    // ====================
+    /// Submit work for milestone
+    /// @param env: Soroban environment
+    /// @param talent: Hired freelancer, the milestone's payee, or a session key
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Milestone index
+    /// @param data: Hash of the submitted deliverables
+    /// @param uri: Where the deliverables can be fetched (empty = none)
+    pub fn submit_milestone(
+        env: Env,
+        talent: Address,
+        job_id: u32,
+        milestone_idx: u32,
+        data: BytesN<32>,
+        uri: String,
+    ) {
+        talent.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        // Streamed and hourly jobs pay out over time, not per milestone
+        if job.state != JobState::Active || job.payment.stream.is_some() || job.payment.hourly_rate > 0 {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        let mut milestone = job.milestones.get(milestone_idx)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidIndex));
+        // A milestone's payee delivers their own work
+        if milestone.payee.as_ref() != Some(&talent) {
+            Self::check_talent_or_session(&env, &job, job_id, &talent);
+        }
+
+        // Rejected work may be redone and resubmitted before the deadline
+        match milestone.state {
+            MilestoneState::Pending | MilestoneState::Rejected => {}
+            MilestoneState::Submitted => panic_with_error!(&env, Error::AlreadySubmitted),
+            MilestoneState::Approved | MilestoneState::Paid => {
+                panic_with_error!(&env, Error::AlreadyApproved)
+            }
+            MilestoneState::Disputed => panic_with_error!(&env, Error::ArbitrationPending),
+            MilestoneState::Void => panic_with_error!(&env, Error::InvalidState),
+        }
+
+        // Check deadline
+        if env.ledger().timestamp() > milestone.deadline {
+            panic_with_error!(&env, Error::DeadlinePassed);
+        }
+        // Work is accepted until a noticed cancellation takes effect
+        if job.cancel_terms.cancel_effective_at.is_some_and(|at| env.ledger().timestamp() >= at) {
+            panic_with_error!(&env, Error::DeadlinePassed);
+        }
+
+        if uri.len() > Self::job_limits(&env).max_uri_len {
+            panic_with_error!(&env, Error::InputTooLong);
+        }
+
+        // Earlier rounds stay on record for arbitrators
+        let mut history = Self::get_submissions(env.clone(), job_id, milestone_idx);
+        if history.len() >= MAX_SUBMISSIONS {
+            panic_with_error!(&env, Error::SubmissionLimit);
+        }
+        // A rejected deliverable sent again unchanged is a replay; the
+        // talent should dispute the rejection instead
+        if history.iter().any(|round| round.data == data) {
+            panic_with_error!(&env, Error::DuplicateSubmission);
+        }
+        history.push_back(Submission {
+            data: data.clone(),
+            uri: if uri.is_empty() { None } else { Some(uri.clone()) },
+            submitted_at: env.ledger().timestamp(),
+            round: history.len(),
+        });
+        let key = JobKey::Submissions(job_id, milestone_idx);
+        env.storage().persistent().set(&key, &history);
+        Self::extend_persistent(&env, &key);
+
+        milestone.state = MilestoneState::Submitted;
+        milestone.submission_data = data.clone();
+        milestone.submission_uri = if uri.is_empty() { None } else { Some(uri.clone()) };
+        milestone.submitted_at = Some(env.ledger().timestamp());
+        job.milestones.set(milestone_idx, milestone);
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, WRK_SUB, job_id, &talent, WorkSubmitted { version: EVENT_VERSION, milestone_idx, data, uri });
+    }
+
+    /// Ask the client for more time on a milestone that has not been
+    /// delivered yet. A new request replaces the pending one.
+    /// @param env: Soroban environment
+    /// @param talent: Hired freelancer (or one of their session keys)
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Milestone index
+    /// @param new_deadline: Proposed deadline (later than the current one)
+    pub fn request_deadline_extension(
+        env: Env,
+        talent: Address,
+        job_id: u32,
+        milestone_idx: u32,
+        new_deadline: u64,
+    ) {
+        talent.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let job = Self::get_job(&env, job_id);
+        if job.state != JobState::Active {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        Self::check_talent_or_session(&env, &job, job_id, &talent);
+        let milestone = job.milestones.get(milestone_idx)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidIndex));
+        if !matches!(milestone.state, MilestoneState::Pending | MilestoneState::Rejected) {
+            panic_with_error!(&env, Error::MilestonePending);
+        }
+        if new_deadline <= milestone.deadline {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+
+        let key = JobKey::DeadlineRequests(job_id);
+        let mut requests = Self::get_deadline_requests(env.clone(), job_id);
+        requests.set(milestone_idx, new_deadline);
+        env.storage().persistent().set(&key, &requests);
+        Self::extend_persistent(&env, &key);
+
+        Self::emit(&env, DL_REQ, job_id, &talent, DeadlineExtension {
+            version: EVENT_VERSION,
+            milestone_idx,
+            deadline: new_deadline,
+        });
+    }
+
+    /// Grant a pending deadline extension
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Milestone index
+    pub fn approve_deadline_extension(env: Env, client: Address, job_id: u32, milestone_idx: u32) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        let mut requests = Self::get_deadline_requests(env.clone(), job_id);
+        let deadline = requests.get(milestone_idx)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidInput));
+        let mut milestone = job.milestones.get(milestone_idx)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidIndex));
+        // Delivered or settled in the meantime
+        if job.state != JobState::Active
+            || !matches!(milestone.state, MilestoneState::Pending | MilestoneState::Rejected)
+        {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+
+        requests.remove(milestone_idx);
+        let key = JobKey::DeadlineRequests(job_id);
+        if requests.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &requests);
+        }
+        milestone.deadline = deadline;
+        job.milestones.set(milestone_idx, milestone);
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, DL_EXT, job_id, &client, DeadlineExtension {
+            version: EVENT_VERSION,
+            milestone_idx,
+            deadline,
+        });
+    }
+
+    /// Get the deadline extensions awaiting the client, by milestone
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn get_deadline_requests(env: Env, job_id: u32) -> Map<u32, u64> {
+        env.storage().persistent().get(&JobKey::DeadlineRequests(job_id))
+            .unwrap_or_else(|| Map::new(&env))
+    }
+
+    /// Get every submission made for a milestone, oldest first
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Milestone index
+    pub fn get_submissions(env: Env, job_id: u32, milestone_idx: u32) -> Vec<Submission> {
+        env.storage().persistent().get(&JobKey::Submissions(job_id, milestone_idx))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Approve milestone and release payment
+    /// @param env: Soroban environment
+    /// @param client: Job creator (or manager with ApproveMilestones)
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Milestone index
+    pub fn approve_milestone(
+        env: Env,
+        client: Address,
+        job_id: u32,
+        milestone_idx: u32,
+    ) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        Self::approve_submitted(&env, &client, job_id, milestone_idx);
+    }
+
+    /// Approve milestone only if the job is still at `expected_seq`, so an
+    /// approval built on stale state (e.g. after a dispute) fails cleanly
+    /// @param env: Soroban environment
+    /// @param client: Job creator (or manager with ApproveMilestones)
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Milestone index
+    /// @param expected_seq: Job `action_seq` the caller last saw
+    pub fn approve_milestone_checked(
+        env: Env,
+        client: Address,
+        job_id: u32,
+        milestone_idx: u32,
+        expected_seq: u64,
+    ) {
+        Self::check_seq(&env, job_id, expected_seq);
+        Self::approve_milestone(env, client, job_id, milestone_idx);
+    }
+
+    /// Approve a milestone from an authorization signed offline and relayed
+    /// by anyone. The signer authorizes exactly
+    /// `(job_id, milestone_idx, submission_data)`, so the approval only
+    /// applies to the work they reviewed. A resubmission voids it.
+    /// Expiry and replay protection come from the Soroban auth entry.
+    /// @param env: Soroban environment
+    /// @param client: Job creator (or manager with ApproveMilestones)
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Milestone index
+    /// @param submission_data: Hash of the reviewed submission
+    pub fn approve_milestone_signed(
+        env: Env,
+        client: Address,
+        job_id: u32,
+        milestone_idx: u32,
+        submission_data: BytesN<32>,
+    ) {
+        client.require_auth_for_args((job_id, milestone_idx, submission_data.clone()).into_val(&env));
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let job = Self::get_job(&env, job_id);
+        let milestone = job.milestones.get(milestone_idx)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidIndex));
+        if milestone.submission_data != submission_data {
+            panic_with_error!(&env, Error::NotSubmitted);
+        }
+
+        Self::approve_submitted(&env, &client, job_id, milestone_idx);
+    }
+
+    /// Reject submitted work. The talent may resubmit before the deadline
+    /// or dispute the rejection.
+    /// @param env: Soroban environment
+    /// @param client: Job creator (or manager with ApproveMilestones)
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Milestone index
+    pub fn reject_submission(env: Env, client: Address, job_id: u32, milestone_idx: u32) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        Self::check_client_or_manager(&env, &job, &client, Permission::ApproveMilestones);
+        if job.state != JobState::Active {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        let milestone = job.milestones.get(milestone_idx)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidIndex));
+        if milestone.state != MilestoneState::Submitted {
+            panic_with_error!(&env, Error::NotSubmitted);
+        }
+
+        Self::reject_milestone(&env, &mut job, milestone_idx);
+        Self::update_job(&env, job_id, &job);
+
+        let round = Self::get_submissions(env.clone(), job_id, milestone_idx).len().saturating_sub(1);
+        Self::emit(&env, MIL_REJ, job_id, &client, MilestoneRejected {
+            version: EVENT_VERSION,
+            milestone_idx,
+            round,
+        });
+    }
+
+    /// Reject a submission only if the job is still at `expected_seq`
+    /// @param env: Soroban environment
+    /// @param client: Job creator (or manager with ApproveMilestones)
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Milestone index
+    /// @param expected_seq: Job `action_seq` the caller last saw
+    pub fn reject_submission_checked(
+        env: Env,
+        client: Address,
+        job_id: u32,
+        milestone_idx: u32,
+        expected_seq: u64,
+    ) {
+        Self::check_seq(&env, job_id, expected_seq);
+        Self::reject_submission(env, client, job_id, milestone_idx);
+    }
+
+    /// Approve milestone and pay an extra bonus straight from the client's
+    /// wallet (outside escrow) in the same transaction
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Milestone index
+    /// @param bonus_amount: Extra payment to the talent
+    pub fn approve_with_bonus(
+        env: Env,
+        client: Address,
+        job_id: u32,
+        milestone_idx: u32,
+        bonus_amount: i128,
+    ) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        if bonus_amount <= 0 {
+            panic_with_error!(&env, Error::AmountRequired);
+        }
+        let job = Self::approve_submitted(&env, &client, job_id, milestone_idx);
+        // The bonus comes from the client's own wallet, not a manager's
+        if job.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        Self::pay_direct(&env, &job, bonus_amount);
+
+        Self::emit(&env, BONUS, job_id, &client, PaymentMade {
+            version: EVENT_VERSION,
+            index: Some(milestone_idx),
+            amount: bonus_amount,
+            fee: 0,
+        });
+    }
+
+    /// Divide future milestone payments among a team (talent lead only).
+    /// Shares are in basis points and must add up to 100%.
+    /// @param env: Soroban environment
+    /// @param talent_lead: Hired freelancer
+    /// @param job_id: Job identifier
+    /// @param recipients: Collaborators and their shares (empty to reset)
+    pub fn set_payout_split(
+        env: Env,
+        talent_lead: Address,
+        job_id: u32,
+        recipients: Vec<(Address, u32)>,
+    ) {
+        talent_lead.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.talent != Some(talent_lead.clone()) {
+            panic_with_error!(&env, Error::TalentOnly);
+        }
+        if matches!(job.state, JobState::Completed | JobState::Cancelled) {
+            panic_with_error!(&env, Error::JobCompleted);
+        }
+        if recipients.len() > MAX_SPLIT {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+
+        if !recipients.is_empty() {
+            let mut total_bps: u32 = 0;
+            for (i, (recipient, bps)) in recipients.iter().enumerate() {
+                if bps == 0 {
+                    panic_with_error!(&env, Error::InvalidInput);
+                }
+                // Each collaborator listed once
+                for (other, _) in recipients.iter().skip(i + 1) {
+                    if other == recipient {
+                        panic_with_error!(&env, Error::InvalidInput);
+                    }
+                }
+                total_bps += bps;
+            }
+            if total_bps as i128 != BPS_DENOM {
+                panic_with_error!(&env, Error::InvalidInput);
+            }
+        }
+
+        job.payout_split = recipients.clone();
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, PAY_SPL, job_id, &talent_lead, SplitSet { version: EVENT_VERSION, recipients });
+    }
+
+    /// Tip the talent of a completed job from the client's wallet
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    /// @param amount: Tip amount
+    pub fn tip_talent(env: Env, client: Address, job_id: u32, amount: i128) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if job.state != JobState::Completed {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if amount <= 0 {
+            panic_with_error!(&env, Error::AmountRequired);
+        }
+        Self::pay_direct(&env, &job, amount);
+
+        Self::emit(&env, TIP, job_id, &client, PaymentMade { version: EVENT_VERSION, index: None, amount, fee: 0 });
+    }
+
+    /// Approve several submitted milestones and release their payments
+    /// atomically. Fails as a whole if any index is not awaiting approval.
+    /// @param env: Soroban environment
+    /// @param client: Job creator (or manager with ApproveMilestones)
+    /// @param job_id: Job identifier
+    /// @param indices: Milestone indexes to approve
+    pub fn approve_milestones(env: Env, client: Address, job_id: u32, indices: Vec<u32>) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        Self::check_client_or_manager(&env, &job, &client, Permission::ApproveMilestones);
+        if job.state != JobState::Active || !Self::parent_settled(&env, &job) {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if indices.is_empty() || indices.len() > job.milestones.len() {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+
+        let mut total_amount: i128 = 0;
+        let mut total_fee: i128 = 0;
+        for idx in indices.iter() {
+            let milestone = job.milestones.get(idx)
+                .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidIndex));
+            // Also rejects repeated indexes, which are already Paid
+            if milestone.state != MilestoneState::Submitted {
+                panic_with_error!(&env, Error::NotSubmitted);
+            }
+            Self::require_approved(&env, job_id, &job, JobAction::ApproveMilestone(idx));
+            total_amount += milestone.amount;
+            total_fee += Self::release_or_vest(&env, job_id, &mut job, idx, &client);
+        }
+
+        Self::check_completion(&env, job_id, &mut job, &client);
+
+        Self::update_job(&env, job_id, &job);
+        Self::emit(&env, MIL_BAT, job_id, &client, BatchApproved {
+            version: EVENT_VERSION,
+            indices,
+            amount: total_amount,
+            fee: total_fee,
+        });
+    }
+
+    /// Claim every approved payment whose release delay has run out
+    /// @param env: Soroban environment
+    /// @param talent: Hired talent
+    /// @param job_id: Job identifier
+    /// @return Gross amount released
+    pub fn claim_vested(env: Env, talent: Address, job_id: u32) -> i128 {
+        talent.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.talent != Some(talent.clone()) {
+            panic_with_error!(&env, Error::TalentOnly);
+        }
+        if job.state != JobState::Active {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+
+        let now = env.ledger().timestamp();
+        let mut amount: i128 = 0;
+        let mut fee: i128 = 0;
+        for i in 0..job.milestones.len() {
+            let milestone = job.milestones.get(i).unwrap();
+            if milestone.state == MilestoneState::Approved
+                && milestone.vests_at.is_some_and(|vests_at| now >= vests_at)
+            {
+                amount += milestone.amount;
+                fee += Self::approve_milestone_internal(&env, job_id, &mut job, i);
+            }
+        }
+        if amount == 0 {
+            panic_with_error!(&env, Error::NothingVested);
+        }
+
+        Self::check_completion(&env, job_id, &mut job, &talent);
+        Self::update_job(&env, job_id, &job);
+        Self::emit(&env, VST_CLM, job_id, &talent, PaymentMade {
+            version: EVENT_VERSION,
+            index: None,
+            amount,
+            fee,
+        });
+        amount
+    }
+
+    /// Freeze a vesting payment before it can be claimed. The only way to
+    /// stop an approved payment is to turn it into a milestone dispute.
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Vesting milestone
+    /// @param arbitrator: Arbitrator for the dispute
+    pub fn freeze_vested(
+        env: Env,
+        client: Address,
+        job_id: u32,
+        milestone_idx: u32,
+        arbitrator: Address,
+    ) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::ClientOnly);
+        }
+        let mut milestone = job.milestones.get(milestone_idx)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidIndex));
+        // The window opens on approval and closes once the payment vests
+        match milestone.state {
+            MilestoneState::Pending | MilestoneState::Submitted | MilestoneState::Rejected => {
+                panic_with_error!(&env, Error::TooEarly)
+            }
+            MilestoneState::Approved => {}
+            _ => panic_with_error!(&env, Error::InvalidState),
+        }
+        if milestone.vests_at.is_none_or(|vests_at| env.ledger().timestamp() >= vests_at) {
+            panic_with_error!(&env, Error::DeadlinePassed);
+        }
+
+        // Back under review: the dispute decides whether it is paid
+        milestone.state = MilestoneState::Submitted;
+        milestone.vests_at = None;
+        job.milestones.set(milestone_idx, milestone);
+
+        Self::validate_dispute(&env, job_id, &job, &client, Some(milestone_idx), None);
+        if !Self::is_active_arbitrator(&env, &arbitrator) || !Self::policy_allows(&job, &arbitrator) {
+            panic_with_error!(&env, Error::NotArbitrator);
+        }
+
+        Self::open_dispute(
+            &env,
+            job_id,
+            &mut job,
+            &client,
+            Some(milestone_idx),
+            Vec::from_array(&env, [arbitrator.clone()]),
+            None,
+        );
+
+        Self::emit(&env, VST_FRZ, job_id, &client, DisputeOpened {
+            version: EVENT_VERSION,
+            milestone_idx: Some(milestone_idx),
+            arbitrators: Vec::from_array(&env, [arbitrator]),
+        });
+    }
+
+    /// Add the next retainer milestone once the current period has elapsed.
+    /// Its payment is pulled from the allowance the client granted to this
+    /// contract, so only the client (or a keeper, via `perform_upkeep`, once
+    /// the period is due) may trigger it. Periods run from the hire, and the
+    /// retainer ends after `max_periods` milestones.
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    /// @return milestone_idx: Index of the generated milestone
+    pub fn advance_period(env: Env, client: Address, job_id: u32) -> u32 {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        if Self::get_job(&env, job_id).client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        Self::advance_retainer(&env, job_id)
+    }
+
+    // ====================
+    // MILESTONE AMENDMENTS
+    // ====================
+    /// Propose a new amount and deadline for an unfinished milestone of an
+    /// Active job. One amendment may be open at a time.
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Milestone to amend
+    /// @param terms: Proposed amount and deadline
+    /// @return amendment_idx: Index in the job's amendment history
+    pub fn propose_amendment(
+        env: Env,
+        client: Address,
+        job_id: u32,
+        milestone_idx: u32,
+        terms: AmendmentTerms,
+    ) -> u32 {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::ClientOnly);
+        }
+        let mut amendments = Self::load_amendments(&env, job_id);
+        if Self::open_amendment(&amendments).is_some() {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        Self::check_amendment(&env, job_id, &job, milestone_idx, &terms);
+
+        Self::push_amendment(&env, job_id, &mut amendments, &client, milestone_idx, terms)
+    }
+
+    /// Accept the other party's open amendment and apply it. A higher amount
+    /// is escrowed from the client: directly when the client accepts, or
+    /// against their allowance when the talent accepts. A lower amount is
+    /// refunded.
+    /// @param env: Soroban environment
+    /// @param party: Client or talent that did not propose the amendment
+    /// @param job_id: Job identifier
+    pub fn accept_amendment(env: Env, party: Address, job_id: u32) {
+        party.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        let mut amendments = Self::load_amendments(&env, job_id);
+        let (amendment_idx, mut amendment) = Self::open_amendment(&amendments)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidState));
+        if Self::counterparty(&env, &job, &party) != amendment.proposer {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        // The job may have moved on since the proposal
+        let idx = amendment.milestone_idx;
+        Self::check_amendment(&env, job_id, &job, idx, &amendment.terms);
+
+        let mut milestone = job.milestones.get_unchecked(idx);
+        let delta = amendment.terms.amount - milestone.amount;
+        if delta > 0 {
+            let from_allowance = party != job.client;
+            if from_allowance {
+                let allowance = token::Client::new(&env, &job.token)
+                    .allowance(&job.client, &env.current_contract_address());
+                if allowance < delta {
+                    panic_with_error!(&env, Error::InsufficientFunds);
+                }
+            }
+            let client = job.client.clone();
+            Self::escrow_in(&env, job_id, &job.token, &client, delta, from_allowance);
+            Self::ledger(&env, &mut job).deposit(delta);
+        } else if delta < 0 {
+            Self::refund_escrow(&env, job_id, &job, -delta);
+            Self::ledger(&env, &mut job).refund(-delta);
+        }
+        milestone.amount = amendment.terms.amount;
+        milestone.deadline = amendment.terms.deadline;
+        job.milestones.set(idx, milestone);
+        job.total_value += delta;
+        Self::check_escrow_covers(&env, &job);
+        Self::update_job(&env, job_id, &job);
+
+        amendment.status = AmendmentStatus::Accepted;
+        amendments.set(amendment_idx, amendment.clone());
+        Self::save_amendments(&env, job_id, &amendments);
+
+        Self::emit(&env, AMD_ACC, job_id, &party, AmendmentMade {
+            version: EVENT_VERSION,
+            amendment_idx,
+            milestone_idx: idx,
+            amount: amendment.terms.amount,
+            deadline: amendment.terms.deadline,
+        });
+    }
+
+    /// Decline the other party's open amendment, optionally with a
+    /// counter-proposal for the same milestone that the proposer can then
+    /// accept or counter in turn. Every round stays in the job's history.
+    /// @param env: Soroban environment
+    /// @param party: Client or talent that did not propose the amendment
+    /// @param job_id: Job identifier
+    /// @param counter: Different amount and deadline to offer instead (None = plain decline)
+    /// @return amendment_idx: Index of the counter-proposal, if one was made
+    pub fn decline_amendment(
+        env: Env,
+        party: Address,
+        job_id: u32,
+        counter: Option<AmendmentTerms>,
+    ) -> Option<u32> {
+        party.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let job = Self::get_job(&env, job_id);
+        let mut amendments = Self::load_amendments(&env, job_id);
+        let (amendment_idx, mut amendment) = Self::open_amendment(&amendments)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidState));
+        if Self::counterparty(&env, &job, &party) != amendment.proposer {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+
+        amendment.status = if counter.is_some() {
+            AmendmentStatus::Countered
+        } else {
+            AmendmentStatus::Declined
+        };
+        amendments.set(amendment_idx, amendment.clone());
+        Self::emit(&env, AMD_DEC, job_id, &party, AmendmentMade {
+            version: EVENT_VERSION,
+            amendment_idx,
+            milestone_idx: amendment.milestone_idx,
+            amount: amendment.terms.amount,
+            deadline: amendment.terms.deadline,
+        });
+
+        match counter {
+            Some(terms) => {
+                Self::check_amendment(&env, job_id, &job, amendment.milestone_idx, &terms);
+                Some(Self::push_amendment(&env, job_id, &mut amendments, &party, amendment.milestone_idx, terms))
+            }
+            None => {
+                Self::save_amendments(&env, job_id, &amendments);
+                None
+            }
+        }
+    }
+
+    /// Get a job's amendment history, oldest first
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn get_amendments(env: Env, job_id: u32) -> Option<Vec<Amendment>> {
+        Self::find_job(&env, job_id).map(|_| Self::load_amendments(&env, job_id))
+    }
+
+    // ==================
+    // STREAMING PAYMENTS
+    // ==================
+    /// Withdraw everything vested so far on a streamed job
+    /// @param env: Soroban environment
+    /// @param talent: Hired freelancer
+    /// @param job_id: Job identifier
+    /// @return amount: Gross amount released (before platform fee)
+    pub fn withdraw_streamed(env: Env, talent: Address, job_id: u32) -> i128 {
+        talent.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.talent != Some(talent.clone()) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if job.state != JobState::Active {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        let mut stream = job.payment.stream.get()
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidState));
+
+        let amount = Self::vested_amount(&env, &job, &stream) - stream.withdrawn;
+        if amount <= 0 {
+            panic_with_error!(&env, Error::AmountRequired);
+        }
+
+        let platform_fee = Self::pay_talent(&env, job_id, &mut job, amount);
+        stream.withdrawn += amount;
+        Self::ledger(&env, &mut job).release(amount);
+
+        // Fully vested (or stopped) and drained: nothing left to stream
+        let ended = stream.stopped_at.is_some()
+            || stream.started_at.is_some_and(|start| env.ledger().timestamp() >= start + stream.duration);
+        if ended && job.escrow_balance == 0 {
+            Self::complete_job(&env, job_id, &mut job, &talent);
+        }
+        job.payment.stream = Some(stream).into();
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, STR_WDR, job_id, &talent, PaymentMade {
+            version: EVENT_VERSION,
+            index: None,
+            amount,
+            fee: platform_fee,
+        });
+        amount
+    }
+
+    /// Stop a stream going forward. Vested funds stay claimable by the
+    /// talent; the unvested remainder is refunded to the client.
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    pub fn stop_stream(env: Env, client: Address, job_id: u32) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if job.state != JobState::Active {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        let mut stream = job.payment.stream.get()
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidState));
+        if stream.stopped_at.is_some() {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+
+        stream.stopped_at = Some(env.ledger().timestamp());
+        let refund = job.total_value - Self::vested_amount(&env, &job, &stream);
+        if refund > 0 {
+            Self::refund_escrow(&env, job_id, &job, refund);
+            Self::ledger(&env, &mut job).refund(refund);
+        }
+        // Nothing vested left to claim
+        if job.escrow_balance == 0 {
+            Self::complete_job(&env, job_id, &mut job, &client);
+        }
+        job.payment.stream = Some(stream).into();
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, STR_STOP, job_id, &client, Settlement {
+            version: EVENT_VERSION,
+            talent_amount: 0,
+            client_amount: refund,
+            bond_forfeited: 0,
+        });
+    }
+
+    /// Get amount the talent can withdraw from a stream right now
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn get_withdrawable(env: Env, job_id: u32) -> Option<i128> {
+        let job = Self::find_job(&env, job_id)?;
+        Some(match job.payment.stream.as_ref() {
+            Some(stream) => Self::vested_amount(&env, &job, stream) - stream.withdrawn,
+            None => 0,
+        })
+    }
+
+    // ==================
+    // HOURLY TIMESHEETS
+    // ==================
+    /// Log hours worked on an hourly job for client approval
+    /// @param env: Soroban environment
+    /// @param talent: Hired freelancer
+    /// @param job_id: Job identifier
+    /// @param period_hash: Hash of off-chain work log
+    /// @param hours: Hours claimed for the period
+    /// @return log_idx: Timesheet entry index
+    pub fn log_hours(
+        env: Env,
+        talent: Address,
+        job_id: u32,
+        period_hash: BytesN<32>,
+        hours: u32,
+    ) -> u32 {
+        talent.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let job = Self::get_job(&env, job_id);
+        if job.talent != Some(talent.clone()) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if job.state != JobState::Active || job.payment.hourly_rate == 0 {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if hours == 0 {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+
+        let mut logs = Self::load_timesheets(&env, job_id);
+        if logs.len() >= MAX_TIMESHEETS {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        let amount = job.payment.hourly_rate * hours as i128;
+        logs.push_back(TimeLog {
+            period_hash: period_hash.clone(),
+            hours,
+            amount,
+            approved: false,
+            logged_at: env.ledger().timestamp(),
+        });
+        env.storage().persistent().set(&JobKey::Timesheets(job_id), &logs);
+
+        let log_idx = logs.len() - 1;
+        Self::emit(&env, HRS_LOG, job_id, &talent, HoursLogged {
+            version: EVENT_VERSION,
+            log_idx,
+            period_hash,
+            hours,
+            amount,
+        });
+        log_idx
+    }
+
+    /// Approve a logged timesheet and pay it from escrow
+    /// @param env: Soroban environment
+    /// @param client: Job creator (or manager with ApproveMilestones)
+    /// @param job_id: Job identifier
+    /// @param log_idx: Timesheet entry index
+    pub fn approve_timesheet(env: Env, client: Address, job_id: u32, log_idx: u32) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        Self::check_client_or_manager(&env, &job, &client, Permission::ApproveMilestones);
+        if job.state != JobState::Active || job.payment.hourly_rate == 0 {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+
+        let mut logs = Self::load_timesheets(&env, job_id);
+        let mut log = logs.get(log_idx)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidIndex));
+        if log.approved {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if log.amount > job.escrow_balance {
+            panic_with_error!(&env, Error::InsufficientFunds);
+        }
+
+        let platform_fee = Self::pay_talent(&env, job_id, &mut job, log.amount);
+        Self::ledger(&env, &mut job).release(log.amount);
+
+        // Budget used up: the engagement is over
+        if job.escrow_balance == 0 {
+            Self::complete_job(&env, job_id, &mut job, &client);
+        }
+        Self::update_job(&env, job_id, &job);
+
+        log.approved = true;
+        logs.set(log_idx, log.clone());
+        env.storage().persistent().set(&JobKey::Timesheets(job_id), &logs);
+
+        Self::emit(&env, TS_APR, job_id, &client, PaymentMade {
+            version: EVENT_VERSION,
+            index: Some(log_idx),
+            amount: log.amount,
+            fee: platform_fee,
+        });
+    }
+
+    /// Get work logs of an hourly job
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn get_timesheets(env: Env, job_id: u32) -> Vec<TimeLog> {
+        Self::load_timesheets(&env, job_id)
+    }
+
+    // ==================
+    // CONTESTS
+    // ==================
+    /// Enter a contest job with a piece of work. Re-entering before the
+    /// deadline replaces the talent's earlier entry.
+    /// @param env: Soroban environment
+    /// @param talent: Entrant
+    /// @param job_id: Job identifier
+    /// @param data: Hash of the submitted work
+    /// @param uri: Where the work can be fetched (may be empty)
+    /// @return entry_idx: Entry index
+    pub fn enter_contest(
+        env: Env,
+        talent: Address,
+        job_id: u32,
+        data: BytesN<32>,
+        uri: String,
+    ) -> u32 {
+        talent.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let job = Self::get_job(&env, job_id);
+        if !job.contest || job.state != JobState::Funded {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if talent == job.client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        // Entries close at the first prize's deadline
+        if env.ledger().timestamp() > job.milestones.get_unchecked(0).deadline {
+            panic_with_error!(&env, Error::DeadlinePassed);
+        }
+        if uri.len() > Self::job_limits(&env).max_uri_len {
+            panic_with_error!(&env, Error::InputTooLong);
+        }
+        Self::check_invited(&env, &job, &talent);
+        Self::check_talent_eligibility(&env, &job, &talent);
+
+        let mut entries = Self::load_entries(&env, job_id);
+        // Someone else's work sent in again is not a new entry
+        if entries.iter().any(|entry| entry.data == data && entry.talent != talent) {
+            panic_with_error!(&env, Error::DuplicateSubmission);
+        }
+        let entry = ContestEntry {
+            talent: talent.clone(),
+            data: data.clone(),
+            uri: if uri.is_empty() { None } else { Some(uri.clone()) },
+            submitted_at: env.ledger().timestamp(),
+            prize: 0,
+        };
+        let entry_idx = match entries.iter().position(|existing| existing.talent == talent) {
+            Some(idx) => {
+                entries.set(idx as u32, entry);
+                idx as u32
+            }
+            None => {
+                if entries.len() >= MAX_ENTRIES {
+                    panic_with_error!(&env, Error::SubmissionLimit);
+                }
+                entries.push_back(entry);
+                entries.len() - 1
+            }
+        };
+        let key = JobKey::Entries(job_id);
+        env.storage().persistent().set(&key, &entries);
+        Self::extend_persistent(&env, &key);
+
+        Self::emit(&env, CON_ENT, job_id, &talent, ContestEntered { version: EVENT_VERSION, entry_idx, data, uri });
+        entry_idx
+    }
+
+    /// Pick a contest's winners once entries have closed. The first entry
+    /// listed wins the first milestone's amount, the next ones the runner-up
+    /// prizes in order. Unawarded prizes are refunded; losing entries stay
+    /// on record unpaid.
+    /// @param env: Soroban environment
+    /// @param client: Job creator (or manager with SelectTalent)
+    /// @param job_id: Job identifier
+    /// @param ranking: Entry indexes from first place down
+    pub fn pick_winners(env: Env, client: Address, job_id: u32, ranking: Vec<u32>) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        Self::check_client_or_manager(&env, &job, &client, Permission::SelectTalent);
+        if !job.contest || job.state != JobState::Funded {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if env.ledger().timestamp() <= job.milestones.get_unchecked(0).deadline {
+            panic_with_error!(&env, Error::TooEarly);
+        }
+        if ranking.is_empty() || ranking.len() > job.milestones.len() {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        let mut entries = Self::load_entries(&env, job_id);
+        for (place, entry_idx) in ranking.iter().enumerate() {
+            if entry_idx >= entries.len() || ranking.first_index_of(entry_idx) != Some(place as u32) {
+                panic_with_error!(&env, Error::InvalidIndex);
+            }
+        }
+
+        // The winner is recorded as the job's talent
+        let winner = entries.get_unchecked(ranking.get_unchecked(0)).talent;
+        job.talent = Some(winner.clone());
+        Self::adjust_active_jobs(&env, &winner, true);
+        Self::set_state(&env, job_id, &mut job, JobState::Active, &client);
+
+        let mut unawarded = 0;
+        for place in 0..job.milestones.len() {
+            let mut milestone = job.milestones.get_unchecked(place);
+            match ranking.get(place) {
+                Some(entry_idx) => {
+                    let mut entry = entries.get_unchecked(entry_idx);
+                    Self::pay_worker(&env, job_id, &mut job, &entry.talent, milestone.amount);
+                    Self::ledger(&env, &mut job).release(milestone.amount);
+                    milestone.state = MilestoneState::Paid;
+                    milestone.submission_data = entry.data.clone();
+                    milestone.submission_uri = entry.uri.clone();
+                    milestone.submitted_at = Some(entry.submitted_at);
+                    entry.prize = milestone.amount;
+                    entries.set(entry_idx, entry.clone());
+
+                    Self::emit(&env, CON_WIN, job_id, &entry.talent, PrizeAwarded {
+                        version: EVENT_VERSION,
+                        place,
+                        entry_idx,
+                        amount: milestone.amount,
+                    });
+                }
+                None => {
+                    unawarded += milestone.amount;
+                    milestone.state = MilestoneState::Void;
+                }
+            }
+            job.milestones.set(place, milestone);
+        }
+        env.storage().persistent().set(&JobKey::Entries(job_id), &entries);
+
+        if unawarded > 0 {
+            Self::refund_escrow(&env, job_id, &job, unawarded);
+            Self::ledger(&env, &mut job).refund(unawarded);
+        }
+        Self::complete_job(&env, job_id, &mut job, &client);
+        Self::update_job(&env, job_id, &job);
+    }
+
+    /// Get every entry submitted to a contest, with any prize awarded
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn get_contest_entries(env: Env, job_id: u32) -> Option<Vec<ContestEntry>> {
+        Self::find_job(&env, job_id)
+            .filter(|job| job.contest)
+            .map(|_| Self::load_entries(&env, job_id))
+    }
+
+    // =================
+    // DISPUTE RESOLUTION
+    // =================
+    /// Raise dispute for job/milestone.
+    /// A milestone dispute may cover submitted or rejected work; approval
+    /// releases payment either way.
+    /// On a Funded job, the talent holding the offer (e.g. after it expired
+    /// unaccepted) may raise a job-wide dispute: approval hires them, and
+    /// rejection refunds the client. They post the work bond with the fee.
+    /// @param env: Soroban environment
+    /// @param caller: Dispute initiator
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Optional milestone index
+    /// @param arbitrator: Chosen arbitrator address
+    pub fn raise_dispute(
+        env: Env,
+        caller: Address,
+        job_id: u32,
+        milestone_idx: Option<u32>,
+        arbitrator: Address,
+    ) {
+        caller.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        Self::validate_dispute(&env, job_id, &job, &caller, milestone_idx, None);
+
+        // Verify arbitrator exists, is staked and fits the job's policy
+        if !Self::is_active_arbitrator(&env, &arbitrator) || !Self::policy_allows(&job, &arbitrator) {
+            panic_with_error!(&env, Error::NotArbitrator);
+        }
+
+        Self::open_dispute(
+            &env,
+            job_id,
+            &mut job,
+            &caller,
+            milestone_idx,
+            Vec::from_array(&env, [arbitrator.clone()]),
+            None,
+        );
+
+        Self::emit(&env, DIS_RIS, job_id, &caller, DisputeOpened {
+            version: EVENT_VERSION,
+            milestone_idx,
+            arbitrators: Vec::from_array(&env, [arbitrator]),
+        });
+    }
+
+    /// Raise a dispute only if the job is still at `expected_seq`
+    /// @param env: Soroban environment
+    /// @param caller: Dispute initiator
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Optional milestone index
+    /// @param arbitrator: Chosen arbitrator address
+    /// @param expected_seq: Job `action_seq` the caller last saw
+    pub fn raise_dispute_checked(
+        env: Env,
+        caller: Address,
+        job_id: u32,
+        milestone_idx: Option<u32>,
+        arbitrator: Address,
+        expected_seq: u64,
+    ) {
+        Self::check_seq(&env, job_id, expected_seq);
+        Self::raise_dispute(env, caller, job_id, milestone_idx, arbitrator);
+    }
+
+    /// Raise dispute with an arbitrator the counterparty must approve.
+    /// If the counterparty does not respond in time, a random registered
+    /// arbitrator can be assigned with `assign_fallback_arbitrator`.
+    /// @param env: Soroban environment
+    /// @param caller: Dispute initiator
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Optional milestone index
+    /// @param arbitrator: Proposed arbitrator address
+    pub fn raise_dispute_with_approval(
+        env: Env,
+        caller: Address,
+        job_id: u32,
+        milestone_idx: Option<u32>,
+        arbitrator: Address,
+    ) {
+        caller.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        Self::validate_dispute(&env, job_id, &job, &caller, milestone_idx, None);
+        if !Self::is_active_arbitrator(&env, &arbitrator) || !Self::policy_allows(&job, &arbitrator) {
+            panic_with_error!(&env, Error::NotArbitrator);
+        }
+
+        let counterparty = Self::counterparty(&env, &job, &caller);
+        Self::open_dispute(
+            &env,
+            job_id,
+            &mut job,
+            &caller,
+            milestone_idx,
+            Vec::from_array(&env, [arbitrator.clone()]),
+            Some(counterparty),
+        );
+
+        Self::emit(&env, ARB_PROP, job_id, &caller, DisputeOpened {
+            version: EVENT_VERSION,
+            milestone_idx,
+            arbitrators: Vec::from_array(&env, [arbitrator]),
+        });
+    }
+
+    /// Approve the arbitrator proposed by the other party
+    /// @param env: Soroban environment
+    /// @param party: Party whose approval is pending
+    /// @param job_id: Job identifier
+    pub fn approve_arbitrator(env: Env, party: Address, job_id: u32) {
+        party.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let job = Self::get_job(&env, job_id);
+        let mut dispute = Self::load_dispute(&env, job_id);
+        if dispute.awaiting_approval != Some(party.clone()) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+
+        dispute.awaiting_approval = None;
+        dispute.ruling_deadline = env.ledger().timestamp() + Self::ruling_window(&job);
+        Self::save_dispute(&env, job_id, &dispute);
+
+        Self::emit(&env, ARB_APPR, job_id, &party, DisputeOpened {
+            version: EVENT_VERSION,
+            milestone_idx: dispute.milestone_idx,
+            arbitrators: dispute.arbitrators,
+        });
+    }
+
+    /// Reject the proposed arbitrator and propose another; the other party
+    /// must now approve within a fresh window
+    /// @param env: Soroban environment
+    /// @param party: Party whose approval is pending
+    /// @param job_id: Job identifier
+    /// @param arbitrator: Counter-proposed arbitrator address
+    pub fn counter_propose_arbitrator(env: Env, party: Address, job_id: u32, arbitrator: Address) {
+        party.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let job = Self::get_job(&env, job_id);
+        let mut dispute = Self::load_dispute(&env, job_id);
+        if dispute.awaiting_approval != Some(party.clone()) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if !Self::is_active_arbitrator(&env, &arbitrator) || !Self::policy_allows(&job, &arbitrator) {
+            panic_with_error!(&env, Error::NotArbitrator);
+        }
+
+        let proposed = Vec::from_array(&env, [arbitrator.clone()]);
+        Self::reassign_arbitrators(&env, &dispute.arbitrators, &proposed);
+        dispute.arbitrators = proposed;
+        dispute.awaiting_approval = Some(Self::counterparty(&env, &job, &party));
+        dispute.response_deadline = env.ledger().timestamp() + APPROVAL_WINDOW;
+        Self::save_dispute(&env, job_id, &dispute);
+
+        Self::emit(&env, ARB_PROP, job_id, &party, DisputeOpened {
+            version: EVENT_VERSION,
+            milestone_idx: dispute.milestone_idx,
+            arbitrators: dispute.arbitrators,
+        });
+    }
+
+    /// Assign a random registered arbitrator once the approval window lapses
+    /// @param env: Soroban environment
+    /// @param caller: Any address
+    /// @param job_id: Job identifier
+    pub fn assign_fallback_arbitrator(env: Env, caller: Address, job_id: u32) {
+        caller.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let job = Self::get_job(&env, job_id);
+        let mut dispute = Self::load_dispute(&env, job_id);
+        if dispute.awaiting_approval.is_none() {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if env.ledger().timestamp() <= dispute.response_deadline {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+
+        let fallback = Self::select_panel(&env, &job, 1);
+        Self::reassign_arbitrators(&env, &dispute.arbitrators, &fallback);
+        dispute.arbitrators = fallback;
+        dispute.awaiting_approval = None;
+        dispute.ruling_deadline = env.ledger().timestamp() + Self::ruling_window(&job);
+        Self::save_dispute(&env, job_id, &dispute);
+
+        Self::emit(&env, ARB_APPR, job_id, &caller, DisputeOpened {
+            version: EVENT_VERSION,
+            milestone_idx: dispute.milestone_idx,
+            arbitrators: dispute.arbitrators,
+        });
+    }
+
+    /// Raise dispute with an arbitrator drawn from the registry.
+    /// Candidates must match the job's specialization (if any) and are
+    /// weighted by reputation.
+    /// @param env: Soroban environment
+    /// @param caller: Dispute initiator
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Optional milestone index
+    pub fn raise_dispute_auto(
+        env: Env,
+        caller: Address,
+        job_id: u32,
+        milestone_idx: Option<u32>,
+    ) {
+        caller.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        Self::validate_dispute(&env, job_id, &job, &caller, milestone_idx, None);
+
+        let arbitrator = Self::select_weighted_arbitrator(&env, &job);
+        Self::open_dispute(
+            &env,
+            job_id,
+            &mut job,
+            &caller,
+            milestone_idx,
+            Vec::from_array(&env, [arbitrator.clone()]),
+            None,
+        );
+
+        Self::emit(&env, DIS_RIS, job_id, &caller, DisputeOpened {
+            version: EVENT_VERSION,
+            milestone_idx,
+            arbitrators: Vec::from_array(&env, [arbitrator]),
+        });
+    }
+
+    /// Raise dispute decided by a panel of registered arbitrators.
+    /// Panel members are drawn from the registry, excluding both parties.
+    /// @param env: Soroban environment
+    /// @param caller: Dispute initiator
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Optional milestone index
+    /// @param panel_size: Number of arbitrators (odd, at least 3)
+    pub fn raise_dispute_panel(
+        env: Env,
+        caller: Address,
+        job_id: u32,
+        milestone_idx: Option<u32>,
+        panel_size: u32,
+    ) {
+        caller.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        Self::validate_dispute(&env, job_id, &job, &caller, milestone_idx, Some(panel_size));
+        if panel_size < 3 || panel_size.is_multiple_of(2) {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+
+        let panel = Self::select_panel(&env, &job, panel_size);
+        Self::open_dispute(&env, job_id, &mut job, &caller, milestone_idx, panel.clone(), None);
+
+        Self::emit(&env, DIS_PNL, job_id, &caller, DisputeOpened {
+            version: EVENT_VERSION,
+            milestone_idx,
+            arbitrators: panel,
+        });
+    }
+
+    /// Resolve dispute (arbitrator only).
+    /// The ruling executes after the appeal window via `execute_ruling`.
+    /// @param env: Soroban environment
+    /// @param arbitrator: Arbitrator address
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Optional milestone index
+    /// @param decision: true=approve, false=reject (Funded stage: hire/refund)
+    pub fn resolve_dispute(
+        env: Env,
+        arbitrator: Address,
+        job_id: u32,
+        milestone_idx: Option<u32>,
+        decision: bool,
+    ) {
+        arbitrator.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        let mut dispute = Self::load_sole_arbitrator_dispute(&env, job_id, &arbitrator);
+        if dispute.milestone_idx != milestone_idx {
+            panic_with_error!(&env, Error::InvalidIndex);
+        }
+
+        let deciders = dispute.arbitrators.clone();
+        let fee_amount = Self::apply_ruling(
+            &env,
+            job_id,
+            &mut job,
+            &mut dispute,
+            Ruling::from_decision(decision),
+            deciders,
+            &arbitrator,
+        );
+
+        Self::emit(&env, DIS_RES, job_id, &arbitrator, RulingMade {
+            version: EVENT_VERSION,
+            milestone_idx,
+            ruling: Ruling::from_decision(decision),
+            fee: fee_amount,
+        });
+    }
+
+    /// Resolve milestone dispute with a partial award (arbitrator only).
+    /// The talent receives `talent_bps` of the milestone amount and the
+    /// remainder is refunded to the client.
+    /// @param env: Soroban environment
+    /// @param arbitrator: Arbitrator address
+    /// @param job_id: Job identifier
+    /// @param milestone_idx: Disputed milestone index
+    /// @param talent_bps: Talent share in basis points (0-10000)
+    pub fn resolve_dispute_split(
+        env: Env,
+        arbitrator: Address,
+        job_id: u32,
+        milestone_idx: u32,
+        talent_bps: u32,
+    ) {
+        arbitrator.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        if talent_bps as i128 > BPS_DENOM {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        let mut job = Self::get_job(&env, job_id);
+        let mut dispute = Self::load_sole_arbitrator_dispute(&env, job_id, &arbitrator);
+        if dispute.milestone_idx != Some(milestone_idx) {
+            panic_with_error!(&env, Error::InvalidIndex);
+        }
+
+        let deciders = dispute.arbitrators.clone();
+        let fee_amount = Self::apply_ruling(
+            &env,
+            job_id,
+            &mut job,
+            &mut dispute,
+            Ruling::Split(talent_bps),
+            deciders,
+            &arbitrator,
+        );
+
+        Self::emit(&env, DIS_SPL, job_id, &arbitrator, RulingMade {
+            version: EVENT_VERSION,
+            milestone_idx: Some(milestone_idx),
+            ruling: Ruling::Split(talent_bps),
+            fee: fee_amount,
+        });
+    }
+
+    /// Cast panel vote; majority outcome is applied as soon as it is reached
+    /// @param env: Soroban environment
+    /// @param arbitrator: Panel member address
+    /// @param job_id: Job identifier
+    /// @param decision: true=approve, false=reject
+    pub fn vote_dispute(env: Env, arbitrator: Address, job_id: u32, decision: bool) {
+        arbitrator.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        let mut dispute = Self::load_dispute(&env, job_id);
+        if dispute.arbitrators.len() < 2 || !dispute.arbitrators.contains(&arbitrator) {
+            panic_with_error!(&env, Error::NotArbitrator);
+        }
+        if dispute.pending_ruling.is_some() {
+            panic_with_error!(&env, Error::RulingPending);
+        }
+        if dispute.votes.contains_key(arbitrator.clone()) {
+            panic_with_error!(&env, Error::AlreadyVoted);
+        }
+        if env.ledger().timestamp() > dispute.voting_deadline {
+            panic_with_error!(&env, Error::DeadlinePassed);
+        }
+
+        dispute.votes.set(arbitrator.clone(), decision);
+        Self::emit(&env, DIS_VOTE, job_id, &arbitrator, VoteCast { version: EVENT_VERSION, decision });
+
+        let (approvals, rejections) = Self::tally_votes(&dispute);
+        let majority = dispute.arbitrators.len() / 2 + 1;
+        if approvals >= majority || rejections >= majority {
+            let outcome = approvals >= majority;
+            let voters = dispute.votes.keys();
+            let fee_amount = Self::apply_ruling(
+                &env,
+                job_id,
+                &mut job,
+                &mut dispute,
+                Ruling::from_decision(outcome),
+                voters,
+                &arbitrator,
+            );
+            Self::emit(&env, DIS_RES, job_id, &arbitrator, RulingMade {
+                version: EVENT_VERSION,
+                milestone_idx: dispute.milestone_idx,
+                ruling: Ruling::from_decision(outcome),
+                fee: fee_amount,
+            });
+        } else {
+            Self::save_dispute(&env, job_id, &dispute);
+        }
+    }
+
+    /// Close a panel dispute after the voting window using the votes cast.
+    /// Ties keep the status quo and reject the disputed work.
+    /// @param env: Soroban environment
+    /// @param caller: Any address
+    /// @param job_id: Job identifier
+    pub fn finalize_dispute(env: Env, caller: Address, job_id: u32) {
+        caller.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        let mut dispute = Self::load_dispute(&env, job_id);
+        if dispute.arbitrators.len() < 2 || dispute.votes.is_empty() {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if dispute.pending_ruling.is_some() {
+            panic_with_error!(&env, Error::RulingPending);
+        }
+        if env.ledger().timestamp() <= dispute.voting_deadline {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+
+        let (approvals, rejections) = Self::tally_votes(&dispute);
+        let outcome = approvals > rejections;
+        let voters = dispute.votes.keys();
+        let fee_amount = Self::apply_ruling(
+            &env,
+            job_id,
+            &mut job,
+            &mut dispute,
+            Ruling::from_decision(outcome),
+            voters,
+            &caller,
+        );
+
+        Self::emit(&env, DIS_RES, job_id, &caller, RulingMade {
+            version: EVENT_VERSION,
+            milestone_idx: dispute.milestone_idx,
+            ruling: Ruling::from_decision(outcome),
+            fee: fee_amount,
+        });
+    }
+
+    /// Appeal a first-instance ruling by posting a bond (losing party only).
+    /// The case moves to a larger panel, or to the admin if the registry is
+    /// too small; the bond is forfeited to the treasury if the ruling stands.
+    /// @param env: Soroban environment
+    /// @param party: Losing party
+    /// @param job_id: Job identifier
+    pub fn appeal(env: Env, party: Address, job_id: u32) {
+        party.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let job = Self::get_job(&env, job_id);
+        let mut dispute = Self::load_dispute(&env, job_id);
+        let ruling = dispute.pending_ruling.get()
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidState));
+        if env.ledger().timestamp() > dispute.appeal_deadline || !Self::appeals_allowed(&job) {
+            panic_with_error!(&env, Error::AppealWindowClosed);
+        }
+
+        // Only a party that lost (fully or partly) may appeal
+        let is_client = job.client == party;
+        let is_talent = Self::dispute_talent(&job) == Some(party.clone());
+        let lost = match ruling {
+            Ruling::Approve => is_client,
+            Ruling::Reject => is_talent,
+            Ruling::Split(_) => is_client || is_talent,
+        };
+        if !lost {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+
+        let disputed_amount = match dispute.milestone_idx {
+            Some(idx) => job.milestones.get(idx).unwrap().amount,
+            None => job.total_value - job.amount_paid,
+        };
+        let bond = (disputed_amount * APPEAL_BOND_BPS / BPS_DENOM).max(1);
+        Self::escrow_in(&env, job_id, &job.token, &party, bond, false);
+
+        // Escalate to a larger panel excluding the first-instance arbitrators
+        let panel_size = dispute.arbitrators.len() * 2 + 1;
+        let appeal_panel = Self::try_select_panel(&env, &job, panel_size, &dispute.deciders)
+            .unwrap_or_else(|| Vec::from_array(&env, [Self::get_admin_address(&env)]));
+        Self::reassign_arbitrators(&env, &dispute.arbitrators, &appeal_panel);
+        dispute.arbitrators = appeal_panel;
+        dispute.votes = Map::new(&env);
+        dispute.voting_deadline = env.ledger().timestamp() + VOTE_WINDOW;
+        dispute.ruling_deadline = env.ledger().timestamp() + Self::ruling_window(&job);
+        dispute.pending_ruling = OptRuling::None;
+        dispute.deciders = Vec::new(&env);
+        dispute.appeal = OptAppeal::Some(Appeal {
+            appellant: party.clone(),
+            bond,
+            original_ruling: ruling,
+        });
+        Self::save_dispute(&env, job_id, &dispute);
+
+        Self::emit(&env, APPEAL, job_id, &party, Appealed {
+            version: EVENT_VERSION,
+            bond,
+            arbitrators: dispute.arbitrators,
+        });
+    }
+
+    /// Execute a first-instance ruling once the appeal window has lapsed
+    /// @param env: Soroban environment
+    /// @param caller: Any address
+    /// @param job_id: Job identifier
+    pub fn execute_ruling(env: Env, caller: Address, job_id: u32) {
+        caller.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        let dispute = Self::load_dispute(&env, job_id);
+        let ruling = dispute.pending_ruling.get()
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidState));
+        if env.ledger().timestamp() <= dispute.appeal_deadline {
+            panic_with_error!(&env, Error::RulingPending);
+        }
+
+        let fee_amount = Self::settle_dispute(
+            &env,
+            job_id,
+            &mut job,
+            &dispute,
+            ruling.clone(),
+            &dispute.deciders,
+            &caller,
+        );
+
+        Self::emit(&env, RUL_EXE, job_id, &caller, RulingMade {
+            version: EVENT_VERSION,
+            milestone_idx: dispute.milestone_idx,
+            ruling,
+            fee: fee_amount,
+        });
+    }
+
+    /// Handle an arbitrator who failed to rule before the deadline.
+    /// The case is reassigned to fresh arbitrators up to a limit, after
+    /// which the configured default ruling is applied (no arbitration fee).
+    /// @param env: Soroban environment
+    /// @param caller: Any address
+    /// @param job_id: Job identifier
+    pub fn timeout_dispute(env: Env, caller: Address, job_id: u32) {
+        caller.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        let mut dispute = Self::load_dispute(&env, job_id);
+        if dispute.pending_ruling.is_some() || dispute.awaiting_approval.is_some() {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        // Panels that cast votes are closed with `finalize_dispute` instead
+        if !dispute.votes.is_empty() {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        let now = env.ledger().timestamp();
+        if now <= dispute.ruling_deadline {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+
+        let replacement = if dispute.reassignments < MAX_REASSIGN {
+            Self::try_select_panel(&env, &job, dispute.arbitrators.len(), &dispute.arbitrators)
+        } else {
+            None
+        };
+
+        match replacement {
+            Some(arbitrators) => {
+                Self::reassign_arbitrators(&env, &dispute.arbitrators, &arbitrators);
+                dispute.arbitrators = arbitrators.clone();
+                dispute.reassignments += 1;
+                dispute.voting_deadline = now + VOTE_WINDOW;
+                dispute.ruling_deadline = now + Self::ruling_window(&job);
+                Self::save_dispute(&env, job_id, &dispute);
+
+                Self::emit(&env, DIS_TOUT, job_id, &caller, DisputeTimedOut {
+                    version: EVENT_VERSION,
+                    reassigned_to: Some(arbitrators),
+                    ruling: None.into(),
+                });
+            }
+            None => {
+                let mut ruling = Self::default_ruling(&env);
+                if dispute.milestone_idx.is_none() && matches!(ruling, Ruling::Split(_)) {
+                    // Splits only apply to a single milestone
+                    ruling = Ruling::Reject;
+                }
+                Self::apply_ruling(
+                    &env,
+                    job_id,
+                    &mut job,
+                    &mut dispute,
+                    ruling.clone(),
+                    Vec::new(&env),
+                    &caller,
+                );
+
+                Self::emit(&env, DIS_TOUT, job_id, &caller, DisputeTimedOut {
+                    version: EVENT_VERSION,
+                    reassigned_to: None,
+                    ruling: Some(ruling).into(),
+                });
+            }
+        }
+    }
+
+    /// Add evidence to the open dispute (client or talent)
+    /// @param env: Soroban environment
+    /// @param party: Client or talent
+    /// @param job_id: Job identifier
+    /// @param evidence_hash: Hash of off-chain evidence
+    /// @param note: Short statement (256 bytes max)
+    pub fn submit_evidence(
+        env: Env,
+        party: Address,
+        job_id: u32,
+        evidence_hash: BytesN<32>,
+        note: Bytes,
+    ) {
+        party.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let job = Self::get_job(&env, job_id);
+        if !Self::has_open_dispute(&env, job_id) {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if job.client != party && Self::dispute_talent(&job) != Some(party.clone()) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if note.len() > MAX_NOTE_LEN {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+
+        let mut log = Self::get_evidence(env.clone(), job_id);
+        if log.len() >= MAX_EVIDENCE {
+            panic_with_error!(&env, Error::EvidenceLimit);
+        }
+        log.push_back(Evidence {
+            submitter: party.clone(),
+            evidence_hash: evidence_hash.clone(),
+            note,
+            submitted_at: env.ledger().timestamp(),
+        });
+        env.storage().persistent().set(&JobKey::Evidence(job_id), &log);
+
+        Self::emit(&env, EVID_SUB, job_id, &party, EvidenceAdded {
+            version: EVENT_VERSION,
+            index: log.len() - 1,
+            evidence_hash,
+        });
+    }
+
+    /// Get evidence log for the job's current (or most recent) dispute
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn get_evidence(env: Env, job_id: u32) -> Vec<Evidence> {
+        env.storage().persistent().get(&JobKey::Evidence(job_id))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Get open dispute for a job
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn get_dispute(env: Env, job_id: u32) -> Option<Dispute> {
+        env.storage().persistent().get(&JobKey::Dispute(job_id))
+    }
+
+    // ==============
+    // JOB CANCELLATION
+    // ==============
+    /// Cancel job and refund remaining funds
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    pub fn cancel_job(env: Env, client: Address, job_id: u32) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        Self::require_approved(&env, job_id, &job, JobAction::Cancel);
+        if matches!(job.state, JobState::Completed | JobState::Cancelled | JobState::Expired) {
+            panic_with_error!(&env, Error::JobCompleted);
+        }
+        // Running streams are ended with `stop_stream` so vested funds stay with the talent
+        if job.state == JobState::Active && job.payment.stream.is_some() {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        // Disputed milestone funds stay put until the ruling settles
+        if Self::has_open_dispute(&env, job_id) {
+            panic_with_error!(&env, Error::ArbitrationPending);
+        }
+        // Hired talent is owed the agreed notice first
+        if job.state == JobState::Active && job.cancel_terms.cancellation_notice > 0 {
+            panic_with_error!(&env, Error::NoticeRequired);
+        }
+        // Approved work is owed once it vests; only a dispute can stop it
+        if job.milestones.iter().any(|m| m.state == MilestoneState::Approved) {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+
+        Self::settle_cancellation(&env, job_id, &mut job, &client);
+    }
+
+    /// Cancel only if the job is still at `expected_seq`
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    /// @param expected_seq: Job `action_seq` the caller last saw
+    pub fn cancel_job_checked(env: Env, client: Address, job_id: u32, expected_seq: u64) {
+        Self::check_seq(&env, job_id, expected_seq);
+        Self::cancel_job(env, client, job_id);
+    }
+
+    /// Give notice of cancelling an Active job that has a notice period.
+    /// The talent can still submit outstanding work until the notice runs
+    /// out; it is approved or disputed as usual before the refund.
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    /// @return Time from which `finalize_cancellation` can be called
+    pub fn notice_cancellation(env: Env, client: Address, job_id: u32) -> u64 {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        Self::require_approved(&env, job_id, &job, JobAction::Cancel);
+        if job.state != JobState::Active || job.cancel_terms.cancellation_notice == 0 || job.payment.stream.is_some() {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if job.cancel_terms.cancel_effective_at.is_some() {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+
+        let effective_at = env.ledger().timestamp() + job.cancel_terms.cancellation_notice;
+        job.cancel_terms.cancel_effective_at = Some(effective_at);
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, CAN_NOT, job_id, &client, CancellationNoticed {
+            version: EVENT_VERSION,
+            effective_at,
+        });
+        effective_at
+    }
+
+    /// Complete a noticed cancellation once the notice has run out and
+    /// all submitted work has been settled
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    pub fn finalize_cancellation(env: Env, client: Address, job_id: u32) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if job.state != JobState::Active {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if !Self::notice_served(&env, &job) {
+            panic_with_error!(&env, Error::NoticeRequired);
+        }
+        if Self::has_open_dispute(&env, job_id) {
+            panic_with_error!(&env, Error::ArbitrationPending);
+        }
+        // Work handed in during the notice must be approved or disputed first
+        if job.milestones.iter().any(|m| {
+            matches!(m.state, MilestoneState::Submitted | MilestoneState::Approved)
+        }) {
+            panic_with_error!(&env, Error::MilestonePending);
+        }
+
+        Self::settle_cancellation(&env, job_id, &mut job, &client);
+    }
+
+    /// Cancel only the work that has not started: Pending milestones are
+    /// voided and refunded, while Paid, Approved, Submitted and Disputed
+    /// milestones finish their normal approval or dispute life cycle.
+    /// The job closes once nothing is left in flight.
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    /// @return Amount refunded to the client
+    pub fn cancel_remaining(env: Env, client: Address, job_id: u32) -> i128 {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        Self::require_approved(&env, job_id, &job, JobAction::Cancel);
+        // Streams, timesheets and retainers do not pay per milestone
+        if job.state != JobState::Active
+            || job.payment.stream.is_some()
+            || job.payment.hourly_rate > 0
+            || job.payment.retainer.is_some()
+        {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        // Unsettled subjob funds still belong to the parent escrow
+        if !Self::parent_settled(&env, &job) {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        // Pending work is the talent's to finish until the notice runs out
+        if !Self::notice_served(&env, &job) {
+            panic_with_error!(&env, Error::NoticeRequired);
+        }
+
+        let mut voided: i128 = 0;
+        for i in 0..job.milestones.len() {
+            let mut milestone = job.milestones.get(i).unwrap();
+            if milestone.state == MilestoneState::Pending {
+                Self::void_subjobs(&env, &milestone);
+                voided += milestone.amount;
+                milestone.state = MilestoneState::Void;
+                job.milestones.set(i, milestone);
+            }
+        }
+        if voided == 0 {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+
+        // Kill fee applies to the voided value only, as in `cancel_job`
+        let cancellation_fee = match &job.talent {
+            Some(_) => (voided * Self::kill_fee_bps(&job) as i128 / BPS_DENOM).max(0),
+            None => 0,
+        };
+        let refund_amount = voided - cancellation_fee;
+
+        if cancellation_fee > 0 {
+            Self::pay_talent(&env, job_id, &mut job, cancellation_fee);
+            Self::ledger(&env, &mut job).release(cancellation_fee);
+        }
+        if refund_amount > 0 {
+            Self::refund_escrow(&env, job_id, &job, refund_amount);
+            Self::ledger(&env, &mut job).refund(refund_amount);
+        }
+        job.total_value -= refund_amount;
+
+        // Nothing left in flight: close the job now instead of waiting on
+        // an approval that will never come
+        let in_flight = job.milestones.iter().any(|m| {
+            !matches!(m.state, MilestoneState::Paid | MilestoneState::Void)
+        });
+        if !in_flight {
+            if job.milestones.iter().any(|m| m.state == MilestoneState::Paid) {
+                Self::complete_job(&env, job_id, &mut job, &client);
+            } else {
+                if let Some(talent) = job.talent.clone() {
+                    Self::release_bond(&env, job_id, &mut job, &talent);
+                }
+                Self::set_state(&env, job_id, &mut job, JobState::Cancelled, &client);
+            }
+        }
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, CAN_REM, job_id, &client, Settlement {
+            version: EVENT_VERSION,
+            talent_amount: cancellation_fee,
+            client_amount: refund_amount,
+            bond_forfeited: 0,
+        });
+        refund_amount
+    }
+
+    /// Reclaim a job the talent never worked on: every milestone deadline
+    /// has passed with nothing submitted. The full escrow is refunded with
+    /// no cancellation fee, and any work bond is forfeited to the client.
+    /// @param env: Soroban environment
+    /// @param client: Job creator
+    /// @param job_id: Job identifier
+    pub fn reclaim_abandoned_job(env: Env, client: Address, job_id: u32) {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        // Streamed and hourly work has no milestone submissions to judge by
+        if job.state != JobState::Active || job.payment.stream.is_some() || job.payment.hourly_rate > 0 {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        let now = env.ledger().timestamp();
+        for milestone in job.milestones.iter() {
+            if milestone.state != MilestoneState::Pending {
+                panic_with_error!(&env, Error::InvalidState);
+            }
+            if now <= milestone.deadline {
+                panic_with_error!(&env, Error::TooEarly);
+            }
+        }
+
+        for i in 0..job.milestones.len() {
+            let mut milestone = job.milestones.get(i).unwrap();
+            Self::void_subjobs(&env, &milestone);
+            milestone.state = MilestoneState::Void;
+            job.milestones.set(i, milestone);
+        }
+
+        // Unsettled subjob funds go back to the parent escrow, not the client
+        let remaining = Self::ledger(&env, &mut job).refund_all();
+        let refund_amount = if Self::parent_settled(&env, &job) { remaining } else { 0 };
+        if refund_amount > 0 {
+            Self::refund_escrow(&env, job_id, &job, refund_amount);
+        }
+        let forfeited = Self::release_bond(&env, job_id, &mut job, &client);
+
+        Self::set_state(&env, job_id, &mut job, JobState::Cancelled, &client);
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, JOB_ABND, job_id, &client, Settlement {
+            version: EVENT_VERSION,
+            talent_amount: 0,
+            client_amount: refund_amount,
+            bond_forfeited: forfeited,
+        });
+    }
+
+    /// Propose closing the job with an agreed split of the remaining escrow.
+    /// The amounts must add up to the current escrow balance.
+    /// @param env: Soroban environment
+    /// @param party: Client or talent
+    /// @param job_id: Job identifier
+    /// @param talent_amount: Escrow share for the talent
+    /// @param client_amount: Escrow share for the client
+    pub fn propose_cancellation(
+        env: Env,
+        party: Address,
+        job_id: u32,
+        talent_amount: i128,
+        client_amount: i128,
+    ) {
+        party.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let job = Self::get_job(&env, job_id);
+        Self::counterparty(&env, &job, &party);
+        Self::validate_cancellation(&env, job_id, &job, talent_amount, client_amount);
+        if party == job.client {
+            Self::require_approved(&env, job_id, &job, JobAction::Cancel);
+        }
+
+        env.storage().persistent().set(&JobKey::CancelProposal(job_id), &CancelProposal {
+            proposer: party.clone(),
+            talent_amount,
+            client_amount,
+            proposed_at: env.ledger().timestamp(),
+        });
+
+        Self::emit(&env, CAN_PROP, job_id, &party, Settlement {
+            version: EVENT_VERSION,
+            talent_amount,
+            client_amount,
+            bond_forfeited: 0,
+        });
+    }
+
+    /// Accept the other party's cancellation proposal and settle the job
+    /// @param env: Soroban environment
+    /// @param counterparty: Party that did not propose
+    /// @param job_id: Job identifier
+    pub fn accept_cancellation(env: Env, counterparty: Address, job_id: u32) {
+        counterparty.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        let proposal: CancelProposal = env.storage().persistent().get(&JobKey::CancelProposal(job_id))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidState));
+        if Self::counterparty(&env, &job, &counterparty) != proposal.proposer {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if counterparty == job.client {
+            Self::require_approved(&env, job_id, &job, JobAction::Cancel);
+        }
+        // Escrow may have moved since the proposal was made
+        Self::validate_cancellation(
+            &env,
+            job_id,
+            &job,
+            proposal.talent_amount,
+            proposal.client_amount,
+        );
+
+        // Approved work is owed in full; the split covers the rest
+        Self::release_approved(&env, job_id, &mut job);
+        if proposal.talent_amount > 0 {
+            Self::pay_talent(&env, job_id, &mut job, proposal.talent_amount);
+            Self::ledger(&env, &mut job).release(proposal.talent_amount);
+        }
+        if proposal.client_amount > 0 {
+            Self::refund_escrow(&env, job_id, &job, proposal.client_amount);
+            Self::ledger(&env, &mut job).refund(proposal.client_amount);
+        }
+        for i in 0..job.milestones.len() {
+            let mut milestone = job.milestones.get(i).unwrap();
+            if milestone.state != MilestoneState::Paid {
+                Self::void_subjobs(&env, &milestone);
+                milestone.state = MilestoneState::Void;
+                job.milestones.set(i, milestone);
+            }
+        }
+        if let Some(talent) = job.talent.clone() {
+            Self::release_bond(&env, job_id, &mut job, &talent);
+        }
+
+        Self::set_state(&env, job_id, &mut job, JobState::Cancelled, &counterparty);
+        Self::update_job(&env, job_id, &job);
+        env.storage().persistent().remove(&JobKey::CancelProposal(job_id));
+
+        Self::emit(&env, CAN_ACC, job_id, &counterparty, Settlement {
+            version: EVENT_VERSION,
+            talent_amount: proposal.talent_amount,
+            client_amount: proposal.client_amount,
+            bond_forfeited: 0,
+        });
+    }
+
+    /// Withdraw from an active job. Unpaid milestones are voided and the
+    /// unspent escrow is returned to the client.
+    /// @param env: Soroban environment
+    /// @param talent: Hired freelancer
+    /// @param job_id: Job identifier
+    pub fn resign(env: Env, talent: Address, job_id: u32) {
+        talent.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.talent != Some(talent.clone()) {
+            panic_with_error!(&env, Error::TalentOnly);
+        }
+        if job.state != JobState::Active {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if Self::has_open_dispute(&env, job_id) {
+            panic_with_error!(&env, Error::ArbitrationPending);
+        }
+
+        // Streamed work already vested stays with the talent
+        if let Some(mut stream) = job.payment.stream.get() {
+            let vested = Self::vested_amount(&env, &job, &stream) - stream.withdrawn;
+            if vested > 0 {
+                Self::pay_talent(&env, job_id, &mut job, vested);
+                stream.withdrawn += vested;
+                Self::ledger(&env, &mut job).release(vested);
+            }
+            job.payment.stream = Some(stream).into();
+        }
+        // Approved work is paid out rather than voided with the rest
+        Self::release_approved(&env, job_id, &mut job);
+
+        for i in 0..job.milestones.len() {
+            let mut milestone = job.milestones.get(i).unwrap();
+            if milestone.state != MilestoneState::Paid {
+                Self::void_subjobs(&env, &milestone);
+                milestone.state = MilestoneState::Void;
+                job.milestones.set(i, milestone);
+            }
+        }
+
+        // Unsettled subjob funds go back to the parent escrow, not the client
+        let remaining = Self::ledger(&env, &mut job).refund_all();
+        let refund_amount = if Self::parent_settled(&env, &job) { remaining } else { 0 };
+        if refund_amount > 0 {
+            Self::refund_escrow(&env, job_id, &job, refund_amount);
+        }
+
+        // Abandonment forfeits the work bond to the client
+        let client = job.client.clone();
+        let forfeited = Self::release_bond(&env, job_id, &mut job, &client);
+        if forfeited > 0 {
+            Self::emit(&env, BOND_FFT, job_id, &talent, PaymentMade {
+                version: EVENT_VERSION,
+                index: None,
+                amount: forfeited,
+                fee: 0,
+            });
+        }
+
+        Self::set_state(&env, job_id, &mut job, JobState::Cancelled, &talent);
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, TAL_RES, job_id, &talent, Settlement {
+            version: EVENT_VERSION,
+            talent_amount: 0,
+            client_amount: refund_amount,
+            bond_forfeited: 0,
+        });
+    }
+
+    /// Hand the unfinished milestones of an Active job to a new talent.
+    /// Allowed once every outstanding deadline has been missed, or at any
+    /// time with the old talent's signature. Paid milestones stay with the
+    /// old talent; the rest restart with the time they originally had after
+    /// the previous milestone. A talent who missed every deadline forfeits
+    /// their bond to the client.
+    /// @param env: Soroban environment
     /// @param client: Job creator
     /// @param job_id: Job identifier
-    /// @param milestone_idx: Milestone index
-    pub fn approve_milestone(
+    /// @param new_talent: Talent taking over, who signs and posts any bond
+    pub fn replace_talent(env: Env, client: Address, job_id: u32, new_talent: Address) {
+        client.require_auth();
+        new_talent.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut job = Self::get_job(&env, job_id);
+        if job.client != client {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        Self::require_approved(&env, job_id, &job, JobAction::ReplaceTalent(new_talent.clone()));
+        // Streamed and hourly work has no milestone schedule to hand over
+        if job.state != JobState::Active || job.payment.stream.is_some() || job.payment.hourly_rate > 0 {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if Self::has_open_dispute(&env, job_id) {
+            panic_with_error!(&env, Error::ArbitrationPending);
+        }
+        let old_talent = job.talent.clone()
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidState));
+        if new_talent == old_talent || new_talent == client {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        if let Some(agency) = job.roles.agency.as_ref() {
+            agency.address.require_auth();
+        }
+        Self::check_invited(&env, &job, &new_talent);
+        Self::check_talent_eligibility(&env, &job, &new_talent);
+
+        // Approved work is paid to the old talent now, even if still
+        // vesting; work under review must be settled first
+        Self::release_approved(&env, job_id, &mut job);
+        let now = env.ledger().timestamp();
+        let mut abandoned = true;
+        let mut outstanding = 0;
+        for milestone in job.milestones.iter() {
+            match milestone.state {
+                MilestoneState::Paid | MilestoneState::Void => {}
+                MilestoneState::Pending | MilestoneState::Rejected => {
+                    outstanding += 1;
+                    abandoned = abandoned && now > milestone.deadline;
+                }
+                _ => panic_with_error!(&env, Error::InvalidState),
+            }
+        }
+        if outstanding == 0 {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if !abandoned {
+            old_talent.require_auth();
+        }
+
+        // Each remaining milestone keeps its original lead over the one before
+        let mut previous_deadline = job.created_at;
+        for i in 0..job.milestones.len() {
+            let mut milestone = job.milestones.get_unchecked(i);
+            if matches!(milestone.state, MilestoneState::Pending | MilestoneState::Rejected) {
+                let lead = milestone.deadline.saturating_sub(previous_deadline);
+                previous_deadline = milestone.deadline;
+                milestone.deadline = now + lead;
+                milestone.state = MilestoneState::Pending;
+                job.milestones.set(i, milestone);
+            } else {
+                previous_deadline = milestone.deadline;
+            }
+        }
+
+        let bond_to = if abandoned { client.clone() } else { old_talent.clone() };
+        let released = Self::release_bond(&env, job_id, &mut job, &bond_to);
+        let bond_forfeited = if abandoned { released } else { 0 };
+        if job.talent_bond > 0 {
+            Self::escrow_in(&env, job_id, &job.token, &new_talent, job.talent_bond, false);
+            job.bond_posted = job.talent_bond;
+        }
+
+        // Nothing the old talent arranged carries over
+        env.storage().persistent().remove(&JobKey::CancelProposal(job_id));
+        env.storage().persistent().remove(&JobKey::DeadlineRequests(job_id));
+        Self::lapse_amendment(&env, job_id);
+        job.cancel_terms.cancel_effective_at = None;
+        job.payout_split = Vec::new(&env);
+        job.talent = Some(new_talent.clone());
+        Self::adjust_active_jobs(&env, &old_talent, false);
+        Self::adjust_active_jobs(&env, &new_talent, true);
+        Self::update_job(&env, job_id, &job);
+
+        Self::emit(&env, TAL_RPL, job_id, &client, TalentReplaced {
+            version: EVENT_VERSION,
+            old_talent,
+            new_talent,
+            reassigned: outstanding,
+            bond_forfeited,
+        });
+    }
+
+    // =================
+    // MULTISIG CLIENTS
+    // =================
+    /// Create a job whose approvals, cancellations and dispute raises need
+    /// sign-off from `threshold` of `approvers`. The client still funds the
+    /// job, receives refunds and submits each action once approved.
+    /// @param env: Soroban environment
+    /// @param client: Corporate account funding the job
+    /// @param approvers: Addresses that sign off on client actions
+    /// @param threshold: Approvals needed per action
+    /// @param title: Job title (plain text or hash)
+    /// @param descriptions: Milestone descriptions
+    /// @param amounts: Milestone payments
+    /// @param deadlines: Milestone deadlines (timestamps)
+    /// @return job_id: Created job identifier
+    pub fn create_job_multisig(
+        env: Env,
+        client: Address,
+        approvers: Vec<Address>,
+        threshold: u32,
+        title: Text,
+        descriptions: Vec<Text>,
+        amounts: Vec<i128>,
+        deadlines: Vec<u64>,
+    ) -> u32 {
+        client.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let options = JobOptions {
+            multisig: OptMultisig::Some(Multisig { approvers, threshold }),
+            ..JobOptions::default()
+        };
+        Self::create_job_internal(&env, &client, title, descriptions, amounts, deadlines, options)
+    }
+
+    /// Propose a client action on a multisig job; counts as the proposer's
+    /// approval
+    /// @param env: Soroban environment
+    /// @param approver: One of the job's approvers
+    /// @param job_id: Job identifier
+    /// @param action: Client action to sign off
+    /// @return action_id: Identifier for `approve_action`
+    pub fn propose_job_action(env: Env, approver: Address, job_id: u32, action: JobAction) -> u32 {
+        approver.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let job = Self::get_job(&env, job_id);
+        Self::check_approver(&env, &job, &approver);
+
+        let count_key = JobKey::JobActionCount(job_id);
+        let action_id: u32 = env.storage().persistent().get(&count_key).unwrap_or(0u32) + 1;
+        env.storage().persistent().set(&count_key, &action_id);
+        Self::extend_persistent(&env, &count_key);
+
+        let mut actions = Self::get_job_actions(&env, job_id);
+        actions.set(action_id, PendingAction {
+            action: action.clone(),
+            approvals: Vec::from_array(&env, [approver.clone()]),
+            proposed_at: env.ledger().timestamp(),
+        });
+        Self::save_job_actions(&env, job_id, &actions);
+
+        Self::emit(&env, JAC_PROP, job_id, &approver, ActionApproved {
+            version: EVENT_VERSION,
+            action_id,
+            action,
+            approvals: 1,
+        });
+        action_id
+    }
+
+    /// Sign off on a proposed multisig action. Once the threshold is met
+    /// the client can perform the action through the usual entry point.
+    /// @param env: Soroban environment
+    /// @param approver: One of the job's approvers
+    /// @param job_id: Job identifier
+    /// @param action_id: Proposed action identifier
+    pub fn approve_action(env: Env, approver: Address, job_id: u32, action_id: u32) {
+        approver.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let job = Self::get_job(&env, job_id);
+        Self::check_approver(&env, &job, &approver);
+
+        let mut actions = Self::get_job_actions(&env, job_id);
+        let mut pending = actions.get(action_id)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::ActionNotFound));
+        if pending.approvals.contains(&approver) {
+            panic_with_error!(&env, Error::AlreadyVoted);
+        }
+        pending.approvals.push_back(approver.clone());
+        actions.set(action_id, pending.clone());
+        Self::save_job_actions(&env, job_id, &actions);
+
+        Self::emit(&env, JAC_APPR, job_id, &approver, ActionApproved {
+            version: EVENT_VERSION,
+            action_id,
+            action: pending.action,
+            approvals: pending.approvals.len(),
+        });
+    }
+
+    /// Get a multisig action still awaiting execution
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    /// @param action_id: Proposed action identifier
+    pub fn get_job_action(env: Env, job_id: u32, action_id: u32) -> Option<PendingAction> {
+        Self::get_job_actions(&env, job_id).get(action_id)
+    }
+
+    // =================
+    // ARBITRATOR MANAGEMENT
+    // =================
+    /// Register as arbitrator, locking a token stake in the contract
+    /// @param env: Soroban environment
+    /// @param arbitrator: Arbitrator address
+    /// @param specialization: Area of expertise
+    /// @param stake: Collateral to lock (at least the minimum stake)
+    pub fn register_arbitrator(
+        env: Env,
+        arbitrator: Address,
+        specialization: BytesN<32>,
+        stake: i128,
+    ) {
+        arbitrator.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut arbitrators = Self::get_arbitrators(&env);
+        if arbitrators.contains_key(arbitrator.clone()) {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if stake < Self::min_arbitrator_stake(&env) {
+            panic_with_error!(&env, Error::StakeTooLow);
+        }
+
+        Self::escrow_in(&env, NO_JOB, &Self::get_token_id(&env), &arbitrator, stake, false);
+
+        arbitrators.set(
+            arbitrator.clone(),
+            Arbitrator {
+                address: arbitrator.clone(),
+                fee_percentage: ARB_FEE,
+                reputation: 80, // Initial reputation
+                cases_handled: 0,
+                specialization: specialization.clone(),
+                stake,
+                unstake_requested_at: None,
+                open_disputes: 0,
+                last_active: env.ledger().timestamp(),
+            },
+        );
+
+        Self::save_arbitrators(&env, &arbitrators);
+
+        let mut index = Self::get_specialization_index(&env, &specialization);
+        index.push_back(arbitrator.clone());
+        env.storage().persistent().set(&AccountKey::Specialization(specialization.clone()), &index);
+
+        env.events().publish(
+            (ARB_REG, arbitrator),
+            (specialization, stake)
+        );
+    }
+
+    /// Start unstake cooldown; arbitrator stops receiving new disputes
+    /// @param env: Soroban environment
+    /// @param arbitrator: Arbitrator address
+    pub fn request_unstake(env: Env, arbitrator: Address) {
+        arbitrator.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut arbitrators = Self::get_arbitrators(&env);
+        let mut record = arbitrators.get(arbitrator.clone())
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotArbitrator));
+        if record.unstake_requested_at.is_some() {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+
+        let now = env.ledger().timestamp();
+        record.unstake_requested_at = Some(now);
+        arbitrators.set(arbitrator.clone(), record);
+        Self::save_arbitrators(&env, &arbitrators);
+
+        env.events().publish(
+            (ARB_UNST, arbitrator),
+            (now, now + UNSTAKE_COOLDOWN)
+        );
+    }
+
+    /// Withdraw stake and leave the registry after the cooldown
+    /// @param env: Soroban environment
+    /// @param arbitrator: Arbitrator address
+    pub fn unstake_arbitrator(env: Env, arbitrator: Address) {
+        arbitrator.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut arbitrators = Self::get_arbitrators(&env);
+        let record = arbitrators.get(arbitrator.clone())
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotArbitrator));
+        let requested_at = record.unstake_requested_at
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidState));
+        if env.ledger().timestamp() < requested_at + UNSTAKE_COOLDOWN {
+            panic_with_error!(&env, Error::CooldownActive);
+        }
+        if record.open_disputes > 0 {
+            panic_with_error!(&env, Error::ArbitratorBusy);
+        }
+
+        let stake = Self::remove_arbitrator(&env, &mut arbitrators, &arbitrator);
+        Self::save_arbitrators(&env, &arbitrators);
+
+        env.events().publish(
+            (ARB_UNST, arbitrator),
+            stake
+        );
+    }
+
+    /// Leave the registry. Only allowed without open disputes; arbitrators
+    /// with remaining stake must first complete the unstake cooldown.
+    /// @param env: Soroban environment
+    /// @param arbitrator: Arbitrator address
+    pub fn deregister_arbitrator(env: Env, arbitrator: Address) {
+        arbitrator.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut arbitrators = Self::get_arbitrators(&env);
+        let record = arbitrators.get(arbitrator.clone())
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotArbitrator));
+        if record.open_disputes > 0 {
+            panic_with_error!(&env, Error::ArbitratorBusy);
+        }
+        if record.stake > 0 {
+            let cooled_down = match record.unstake_requested_at {
+                Some(requested_at) => env.ledger().timestamp() >= requested_at + UNSTAKE_COOLDOWN,
+                None => false,
+            };
+            if !cooled_down {
+                panic_with_error!(&env, Error::CooldownActive);
+            }
+        }
+
+        let stake = Self::remove_arbitrator(&env, &mut arbitrators, &arbitrator);
+        Self::save_arbitrators(&env, &arbitrators);
+
+        env.events().publish(
+            (ARB_DREG, arbitrator),
+            stake
+        );
+    }
+
+    /// Remove arbitrators inactive since `cutoff_ts` that hold no disputes,
+    /// returning their stake (admin only)
+    /// @param env: Soroban environment
+    /// @param admin: Contract administrator
+    /// @param cutoff_ts: Prune arbitrators last active before this time
+    /// @return pruned: Number of arbitrators removed
+    pub fn prune_inactive_arbitrators(env: Env, admin: Address, cutoff_ts: u64) -> u32 {
+        Self::require_admin(&env, &admin);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut arbitrators = Self::get_arbitrators(&env);
+        let mut pruned = 0u32;
+        for (address, record) in Self::get_arbitrators(&env).iter() {
+            if record.last_active < cutoff_ts && record.open_disputes == 0 {
+                let stake = Self::remove_arbitrator(&env, &mut arbitrators, &address);
+                env.events().publish(
+                    (ARB_DREG, address),
+                    stake
+                );
+                pruned += 1;
+            }
+        }
+        Self::save_arbitrators(&env, &arbitrators);
+        pruned
+    }
+
+    /// Confiscate arbitrator stake into the treasury (admin only)
+    /// @param env: Soroban environment
+    /// @param admin: Contract administrator
+    /// @param arbitrator: Arbitrator address
+    /// @param amount: Stake to confiscate
+    /// @param reason_hash: Hash of off-chain justification
+    pub fn slash_arbitrator(
+        env: Env,
+        admin: Address,
+        arbitrator: Address,
+        amount: i128,
+        reason_hash: BytesN<32>,
+    ) {
+        Self::require_admin(&env, &admin);
+        if amount <= 0 {
+            panic_with_error!(&env, Error::AmountRequired);
+        }
+
+        let mut arbitrators = Self::get_arbitrators(&env);
+        let mut record = arbitrators.get(arbitrator.clone())
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotArbitrator));
+        if amount > record.stake {
+            panic_with_error!(&env, Error::InsufficientFunds);
+        }
+
+        record.stake -= amount;
+        arbitrators.set(arbitrator.clone(), record);
+        Self::save_arbitrators(&env, &arbitrators);
+        Self::accrue_fees(&env, &Self::get_token_id(&env), amount);
+
+        env.events().publish(
+            (ARB_SLSH, arbitrator),
+            (amount, reason_hash)
+        );
+    }
+
+    /// Change own service fee, bounded by the admin-set cap
+    /// @param env: Soroban environment
+    /// @param arbitrator: Arbitrator address
+    /// @param new_fee: Fee percentage of job value (0-100)
+    pub fn update_arbitrator_fee(env: Env, arbitrator: Address, new_fee: i128) {
+        arbitrator.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        if new_fee < 0 {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        if new_fee > Self::max_arbitrator_fee(&env) {
+            panic_with_error!(&env, Error::FeeTooHigh);
+        }
+
+        let mut arbitrators = Self::get_arbitrators(&env);
+        let mut record = arbitrators.get(arbitrator.clone())
+            .unwrap_or_else(|| panic_with_error!(&env, Error::NotArbitrator));
+        record.fee_percentage = new_fee;
+        record.last_active = env.ledger().timestamp();
+        arbitrators.set(arbitrator.clone(), record);
+        Self::save_arbitrators(&env, &arbitrators);
+
+        env.events().publish(
+            (ARB_FEEU, arbitrator),
+            new_fee
+        );
+    }
+
+    /// Rate the arbitrators of a job's last resolved dispute (once per party)
+    /// @param env: Soroban environment
+    /// @param party: Client or talent
+    /// @param job_id: Job identifier
+    /// @param score: Rating (0-100)
+    pub fn rate_arbitrator(env: Env, party: Address, job_id: u32, score: u32) {
+        party.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        if score > 100 {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        let job = Self::get_job(&env, job_id);
+        if job.client != party && Self::dispute_talent(&job) != Some(party.clone()) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+
+        let mut window: RatingWindow = env.storage().persistent().get(&JobKey::RatingWindow(job_id))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidState));
+        if window.rated_by.contains(&party) {
+            panic_with_error!(&env, Error::AlreadyVoted);
+        }
+        window.rated_by.push_back(party.clone());
+        env.storage().persistent().set(&JobKey::RatingWindow(job_id), &window);
+
+        // Weighted moving average of received ratings
+        let mut arbitrators = Self::get_arbitrators(&env);
+        for arbitrator in window.deciders.iter() {
+            if let Some(mut record) = arbitrators.get(arbitrator.clone()) {
+                record.reputation = (record.reputation * (100 - RATING_WEIGHT)
+                    + score * RATING_WEIGHT) / 100;
+                arbitrators.set(arbitrator, record);
+            }
+        }
+        Self::save_arbitrators(&env, &arbitrators);
+
+        Self::emit(&env, ARB_RATE, job_id, &party, ArbitratorsRated {
+            version: EVENT_VERSION,
+            arbitrators: window.deciders,
+            score,
+        });
+    }
+
+    /// Page through the arbitrator registry
+    /// @param env: Soroban environment
+    /// @param start: Index of first result
+    /// @param limit: Max results (capped at 50)
+    pub fn list_arbitrators(env: Env, start: u32, limit: u32) -> Vec<Arbitrator> {
+        let arbitrators = Self::get_arbitrators(&env).values();
+        let end = start.saturating_add(limit.min(MAX_PAGE)).min(arbitrators.len());
+        let mut page = Vec::new(&env);
+        for i in start..end {
+            page.push_back(arbitrators.get(i).unwrap());
+        }
+        page
+    }
+
+    /// Page through arbitrators registered with a specialization
+    /// @param env: Soroban environment
+    /// @param tag: Specialization
+    /// @param start: Index of first result
+    /// @param limit: Max results (capped at 50)
+    pub fn list_arbitrators_by_spec(
+        env: Env,
+        tag: BytesN<32>,
+        start: u32,
+        limit: u32,
+    ) -> Vec<Arbitrator> {
+        let arbitrators = Self::get_arbitrators(&env);
+        let index = Self::get_specialization_index(&env, &tag);
+        let end = start.saturating_add(limit.min(MAX_PAGE)).min(index.len());
+        let mut page = Vec::new(&env);
+        for i in start..end {
+            if let Some(record) = arbitrators.get(index.get(i).unwrap()) {
+                page.push_back(record);
+            }
+        }
+        page
+    }
+
+    /// Get registered arbitrator details
+    /// @param env: Soroban environment
+    /// @param arbitrator: Arbitrator address
+    pub fn get_arbitrator(env: Env, arbitrator: Address) -> Option<Arbitrator> {
+        Self::get_arbitrators(&env).get(arbitrator)
+    }
+
+    /// Get maximum fee percentage arbitrators may charge
+    /// @param env: Soroban environment
+    pub fn get_max_arbitrator_fee(env: Env) -> i128 {
+        Self::max_arbitrator_fee(&env)
+    }
+
+    /// Get cap on per-job cancellation fees (bps)
+    /// @param env: Soroban environment
+    pub fn get_max_cancellation_fee(env: Env) -> u32 {
+        Self::max_cancellation_fee(&env)
+    }
+
+    /// Get max plain-text title and description lengths (bytes)
+    /// @param env: Soroban environment
+    /// @return (title_max, description_max)
+    pub fn get_text_limits(env: Env) -> (u32, u32) {
+        Self::text_limits(&env)
+    }
+
+    /// Get max milestones per job, open jobs per client and URI length
+    /// @param env: Soroban environment
+    pub fn get_job_limits(env: Env) -> JobLimits {
+        Self::job_limits(&env)
+    }
+
+    /// Get minimum stake required to register as arbitrator
+    /// @param env: Soroban environment
+    pub fn get_min_arbitrator_stake(env: Env) -> i128 {
+        Self::min_arbitrator_stake(&env)
+    }
+
+    // =================
+    // REVIEWS
+    // =================
+    /// Review the other party once a job is completed or cancelled
+    /// @param env: Soroban environment
+    /// @param author: Client or talent
+    /// @param job_id: Job identifier
+    /// @param score: Rating (1-5)
+    /// @param comment_hash: Hash of off-chain comment
+    pub fn leave_review(
+        env: Env,
+        author: Address,
+        job_id: u32,
+        score: u32,
+        comment_hash: BytesN<32>,
+    ) {
+        author.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        if !(1..=5).contains(&score) {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        let job = Self::get_job(&env, job_id);
+        if !matches!(job.state, JobState::Completed | JobState::Cancelled) {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        let reviewee = Self::counterparty(&env, &job, &author);
+        if env.storage().persistent().has(&JobKey::Reviewed(job_id, author.clone())) {
+            panic_with_error!(&env, Error::AlreadyReviewed);
+        }
+        env.storage().persistent().set(&JobKey::Reviewed(job_id, author.clone()), &true);
+
+        // The review count doubles as the page cursor
+        let mut stats = Self::get_rating_stats(&env, &reviewee);
+        let page_key = AccountKey::Reviews(reviewee.clone(), stats.count / INDEX_PAGE);
+        let mut reviews: Vec<Review> = env.storage().persistent().get(&page_key)
+            .unwrap_or_else(|| Vec::new(&env));
+        reviews.push_back(Review {
+            job_id,
+            author: author.clone(),
+            score,
+            comment_hash: comment_hash.clone(),
+            created_at: env.ledger().timestamp(),
+        });
+        env.storage().persistent().set(&page_key, &reviews);
+        Self::extend_persistent(&env, &page_key);
+        Self::extend_persistent(&env, &JobKey::Reviewed(job_id, author.clone()));
+
+        stats.total_score += score;
+        stats.count += 1;
+        env.storage().persistent().set(&AccountKey::Rating(reviewee.clone()), &stats);
+        Self::extend_persistent(&env, &AccountKey::Rating(reviewee.clone()));
+
+        if let Some(mut profile) = Self::talent_profile(&env, &reviewee) {
+            profile.rating = Self::average_rating(&env, &reviewee);
+            Self::save_talent_profile(&env, &reviewee, &profile);
+        }
+
+        Self::emit(&env, REVIEW, job_id, &author, ReviewLeft {
+            version: EVENT_VERSION,
+            reviewee,
+            score,
+            comment_hash,
+        });
+    }
+
+    /// Get average review score scaled by 100 (e.g. 450 = 4.50), 0 if unrated
+    /// @param env: Soroban environment
+    /// @param address: Reviewed party
+    pub fn get_average_rating(env: Env, address: Address) -> u32 {
+        Self::average_rating(&env, &address)
+    }
+
+    /// Page through reviews received by an address
+    /// @param env: Soroban environment
+    /// @param address: Reviewed party
+    /// @param start: Index of first result
+    /// @param limit: Max results (capped at 50)
+    pub fn list_reviews(env: Env, address: Address, start: u32, limit: u32) -> Vec<Review> {
+        let count = Self::get_rating_stats(&env, &address).count;
+        let end = start.saturating_add(limit.min(MAX_PAGE)).min(count);
+        let mut page = Vec::new(&env);
+        let mut bucket: Option<(u32, Vec<Review>)> = None;
+        for i in start..end {
+            let number = i / INDEX_PAGE;
+            if bucket.as_ref().map(|(loaded, _)| *loaded) != Some(number) {
+                bucket = Some((number, Self::get_reviews(&env, &address, number)));
+            }
+            if let Some((_, reviews)) = &bucket {
+                page.push_back(reviews.get(i % INDEX_PAGE).unwrap());
+            }
+        }
+        page
+    }
+
+    // =================
+    // TALENT & CLIENT PROFILES
+    // =================
+    /// Create an on-chain talent profile
+    /// @param env: Soroban environment
+    /// @param talent: Freelancer address
+    /// @param skills: Declared skills (20 max)
+    /// @param profile_hash: Hash of off-chain profile
+    pub fn register_talent(env: Env, talent: Address, skills: Vec<Symbol>, profile_hash: BytesN<32>) {
+        talent.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        if Self::talent_profile(&env, &talent).is_some() {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+        if skills.len() > MAX_SKILLS {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+
+        Self::save_talent_profile(&env, &talent, &TalentProfile {
+            skills: skills.clone(),
+            profile_hash: profile_hash.clone(),
+            jobs_completed: 0,
+            total_earned: 0,
+            rating: Self::average_rating(&env, &talent),
+            registered_at: env.ledger().timestamp(),
+            active_jobs: 0,
+        });
+
+        env.events().publish(
+            (TAL_REG, talent),
+            (skills, profile_hash)
+        );
+    }
+
+    /// Update skills and profile hash of an existing talent profile
+    /// @param env: Soroban environment
+    /// @param talent: Freelancer address
+    /// @param skills: Declared skills (20 max)
+    /// @param profile_hash: Hash of off-chain profile
+    pub fn update_talent_profile(
+        env: Env,
+        talent: Address,
+        skills: Vec<Symbol>,
+        profile_hash: BytesN<32>,
+    ) {
+        talent.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut profile = Self::talent_profile(&env, &talent)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::TalentNotRegistered));
+        if skills.len() > MAX_SKILLS {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+
+        profile.skills = skills.clone();
+        profile.profile_hash = profile_hash.clone();
+        Self::save_talent_profile(&env, &talent, &profile);
+
+        env.events().publish(
+            (TAL_UPD, talent),
+            (skills, profile_hash)
+        );
+    }
+
+    /// Set (or clear) the token a payee wants milestone payouts in. Payouts
+    /// are swapped through the chosen router and fall back to the escrow
+    /// token if the swap fails.
+    /// @param env: Soroban environment
+    /// @param payee: Talent or payout split recipient
+    /// @param preference: Token, whitelisted router and slippage bound (None = escrow token)
+    pub fn set_payout_preference(env: Env, payee: Address, preference: Option<PayoutPreference>) {
+        payee.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let key = AccountKey::PayoutPreference(payee.clone());
+        match &preference {
+            Some(preference) => {
+                if !Self::get_allowed_routers(&env).contains_key(preference.router.clone())
+                    || preference.max_slippage_bps > MAX_SLIPPAGE_BPS
+                {
+                    panic_with_error!(&env, Error::InvalidInput);
+                }
+                env.storage().persistent().set(&key, preference);
+                Self::extend_persistent(&env, &key);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+
+        env.events().publish(
+            (PAY_PREF, payee),
+            preference
+        );
+    }
+
+    /// Get a payee's payout token preference
+    /// @param env: Soroban environment
+    /// @param payee: Payee address
+    pub fn get_payout_preference(env: Env, payee: Address) -> Option<PayoutPreference> {
+        env.storage().persistent().get(&AccountKey::PayoutPreference(payee))
+    }
+
+    /// Get talent profile
+    /// @param env: Soroban environment
+    /// @param talent: Freelancer address
+    pub fn get_talent_profile(env: Env, talent: Address) -> Option<TalentProfile> {
+        Self::talent_profile(&env, &talent)
+    }
+
+    /// Check whether a talent has declared a skill
+    /// @param env: Soroban environment
+    /// @param talent: Freelancer address
+    /// @param skill: Skill to look up
+    pub fn talent_has_skill(env: Env, talent: Address, skill: Symbol) -> bool {
+        match Self::talent_profile(&env, &talent) {
+            Some(profile) => profile.skills.contains(&skill),
+            None => false,
+        }
+    }
+
+    /// Let a secondary "work key" (e.g. a CI bot) submit milestones and ask
+    /// for deadline extensions on some jobs for the talent. The key can never
+    /// approve, withdraw or dispute.
+    /// Granting again replaces the key's scope.
+    /// @param env: Soroban environment
+    /// @param talent: Freelancer address
+    /// @param key: Address of the work key
+    /// @param job_ids: Jobs the key may work on (20 max)
+    /// @param expiry: Timestamp from which the key is unusable
+    pub fn grant_session_key(env: Env, talent: Address, key: Address, job_ids: Vec<u32>, expiry: u64) {
+        talent.require_auth();
+        Self::check_not_paused(&env);
+
+        if key == talent
+            || job_ids.is_empty()
+            || job_ids.len() > MAX_SESSION_JOBS
+            || expiry <= env.ledger().timestamp()
+        {
+            panic_with_error!(&env, Error::InvalidInput);
+        }
+        // A key already working for another talent cannot be taken over
+        let storage_key = AccountKey::SessionKey(key.clone());
+        let existing: Option<SessionKey> = env.storage().persistent().get(&storage_key);
+        if existing.is_some_and(|session| session.talent != talent) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+
+        env.storage().persistent().set(&storage_key, &SessionKey {
+            talent: talent.clone(),
+            job_ids: job_ids.clone(),
+            expires_at: expiry,
+        });
+        Self::extend_persistent(&env, &storage_key);
+
+        env.events().publish(
+            (SES_GRT, talent),
+            (key, job_ids, expiry)
+        );
+    }
+
+    /// Revoke a work key before it expires
+    /// @param env: Soroban environment
+    /// @param talent: Freelancer address
+    /// @param key: Address of the work key
+    pub fn revoke_session_key(env: Env, talent: Address, key: Address) {
+        talent.require_auth();
+
+        let storage_key = AccountKey::SessionKey(key.clone());
+        let session: SessionKey = env.storage().persistent().get(&storage_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidInput));
+        if session.talent != talent {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        env.storage().persistent().remove(&storage_key);
+
+        env.events().publish(
+            (SES_REV, talent),
+            key
+        );
+    }
+
+    /// Get the scope of a work key
+    /// @param env: Soroban environment
+    /// @param key: Address of the work key
+    pub fn get_session_key(env: Env, key: Address) -> Option<SessionKey> {
+        env.storage().persistent().get(&AccountKey::SessionKey(key))
+    }
+
+    /// Get a talent's earnings summary for reporting
+    /// @param env: Soroban environment
+    /// @param talent: Freelancer address
+    /// @return (total_earned, jobs_completed, active_jobs)
+    pub fn get_talent_earnings(env: Env, talent: Address) -> (i128, u32, u32) {
+        match Self::talent_profile(&env, &talent) {
+            Some(profile) => (profile.total_earned, profile.jobs_completed, profile.active_jobs),
+            None => (0, 0, 0),
+        }
+    }
+
+    /// Get a client's spending summary for reporting
+    /// @param env: Soroban environment
+    /// @param client: Client address
+    /// @return (total_funded, total_spent, open_jobs)
+    pub fn get_client_spend(env: Env, client: Address) -> (i128, i128, u32) {
+        let profile = Self::client_profile(&env, &client);
+        (profile.total_funded, profile.total_spent, profile.open_jobs)
+    }
+
+    /// Get client track record (jobs posted, spend, disputes, cancellations)
+    /// @param env: Soroban environment
+    /// @param client: Client address
+    pub fn get_client_profile(env: Env, client: Address) -> ClientProfile {
+        Self::client_profile(&env, &client)
+    }
+
+    /// Standard read interface for other contracts (lending, DAOs) to
+    /// query an address's marketplace history in one call
+    /// @param env: Soroban environment
+    /// @param address: Talent or client address
+    pub fn reputation_of(env: Env, address: Address) -> ReputationSummary {
+        let talent = Self::talent_profile(&env, &address);
+        let client = Self::client_profile(&env, &address);
+        ReputationSummary {
+            version: REPUTATION_VERSION,
+            address: address.clone(),
+            rating: Self::average_rating(&env, &address),
+            reviews: Self::get_rating_stats(&env, &address).count,
+            talent_registered: talent.is_some(),
+            jobs_completed: talent.as_ref().map_or(0, |profile| profile.jobs_completed),
+            total_earned: talent.as_ref().map_or(0, |profile| profile.total_earned),
+            jobs_posted: client.jobs_posted,
+            total_spent: client.total_spent,
+            disputes_raised: client.disputes_raised,
+            cancellations: client.cancellations,
+            as_of: env.ledger().timestamp(),
+        }
+    }
+
+    /// Record a reputation snapshot on the ledger for off-chain
+    /// verification. The digest is published in a REP_SNAP event, so a
+    /// verifier can check a presented summary against ledger history.
+    /// @param env: Soroban environment
+    /// @param address: Talent or client address
+    /// @return Summary with the ledger it was taken at and its digest
+    pub fn reputation_snapshot(env: Env, address: Address) -> ReputationSnapshot {
+        let summary = Self::reputation_of(env.clone(), address.clone());
+        let ledger = env.ledger().sequence();
+        let digest: BytesN<32> = env.crypto()
+            .sha256(&(env.current_contract_address(), ledger, summary.clone()).to_xdr(&env))
+            .into();
+
+        env.events().publish(
+            (REP_SNAP, address),
+            (ledger, digest.clone())
+        );
+        ReputationSnapshot { summary, ledger, digest }
+    }
+
+    // =================
+    // PLATFORM FEES
+    // =================
+    /// Get current platform fee in basis points
+    /// @param env: Soroban environment
+    pub fn get_platform_fee(env: Env) -> u32 {
+        Self::platform_fee_bps(&env)
+    }
+
+    /// Get platform fees accrued in the treasury for a token
+    /// @param env: Soroban environment
+    /// @param token: Payment token contract
+    pub fn get_accrued_fees(env: Env, token: Address) -> i128 {
+        Self::get_treasury(&env).get(token).unwrap_or(0)
+    }
+
+    /// Withdraw accrued platform fees (admin only)
+    /// @param env: Soroban environment
+    /// @param admin: Contract administrator
+    /// @param token: Payment token contract
+    /// @param to: Fee recipient
+    /// @param amount: Amount to withdraw
+    pub fn withdraw_fees(env: Env, admin: Address, token: Address, to: Address, amount: i128) {
+        Self::require_admin(&env, &admin);
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+        if amount <= 0 {
+            panic_with_error!(&env, Error::AmountRequired);
+        }
+
+        let mut treasury = Self::get_treasury(&env);
+        let accrued = treasury.get(token.clone()).unwrap_or(0);
+        if amount > accrued {
+            panic_with_error!(&env, Error::InsufficientFunds);
+        }
+        treasury.set(token.clone(), accrued - amount);
+        env.storage().persistent().set(&AccountKey::Treasury, &treasury);
+
+        Self::escrow_out(&env, NO_JOB, &token, &to, amount);
+
+        env.events().publish(
+            (FEE_WDR, admin),
+            (token, to, amount)
+        );
+    }
+
+    /// Get unclaimed referral rewards per token
+    /// @param env: Soroban environment
+    /// @param referrer: Referrer address
+    pub fn get_referral_earnings(env: Env, referrer: Address) -> Map<Address, i128> {
+        env.storage().persistent().get(&AccountKey::ReferralEarnings(referrer))
+            .unwrap_or_else(|| Map::new(&env))
+    }
+
+    /// Withdraw unclaimed referral rewards in one token
+    /// @param env: Soroban environment
+    /// @param referrer: Referrer address
+    /// @param token: Reward token
+    /// @return Amount paid out
+    pub fn claim_referral_earnings(env: Env, referrer: Address, token: Address) -> i128 {
+        referrer.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let mut earnings = Self::get_referral_earnings(env.clone(), referrer.clone());
+        let amount = earnings.get(token.clone()).unwrap_or(0);
+        if amount <= 0 {
+            panic_with_error!(&env, Error::AmountRequired);
+        }
+        earnings.remove(token.clone());
+        env.storage().persistent().set(&AccountKey::ReferralEarnings(referrer.clone()), &earnings);
+
+        Self::escrow_out(&env, NO_JOB, &token, &referrer, amount);
+
+        env.events().publish(
+            (REF_CLM, referrer),
+            (token, amount)
+        );
+        amount
+    }
+
+    // =================
+    // INSURANCE FUND
+    // =================
+    /// File a claim against the insurance pool for a loss on a job, e.g. a
+    /// failed token payout or a slashing shortfall
+    /// @param env: Soroban environment
+    /// @param party: Client or talent of the job
+    /// @param job_id: Job identifier
+    /// @param amount: Amount claimed in the job's token
+    /// @param evidence_hash: Hash of off-chain evidence
+    /// @return claim_id: Claim identifier
+    pub fn claim_insurance(
+        env: Env,
+        party: Address,
+        job_id: u32,
+        amount: i128,
+        evidence_hash: BytesN<32>,
+    ) -> u32 {
+        party.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let job = Self::get_job(&env, job_id);
+        if party != job.client && job.talent != Some(party.clone()) {
+            panic_with_error!(&env, Error::Unauthorized);
+        }
+        if amount <= 0 {
+            panic_with_error!(&env, Error::AmountRequired);
+        }
+
+        let claim_id = env.storage().instance().get(&ConfigKey::ClaimCount)
+            .unwrap_or(0u32) + 1;
+        env.storage().instance().set(&ConfigKey::ClaimCount, &claim_id);
+
+        let key = AccountKey::InsuranceClaim(claim_id);
+        env.storage().persistent().set(&key, &InsuranceClaim {
+            job_id,
+            claimant: party.clone(),
+            token: job.token.clone(),
+            amount,
+            evidence_hash: evidence_hash.clone(),
+            filed_at: env.ledger().timestamp(),
+            votes: Map::new(&env),
+            status: ClaimStatus::Pending,
+        });
+        Self::extend_persistent(&env, &key);
+
+        Self::emit(&env, INS_CLM, job_id, &party, InsuranceClaimed {
+            version: EVENT_VERSION,
+            claim_id,
+            amount,
+            evidence_hash,
+        });
+        claim_id
+    }
+
+    /// Decide an insurance claim. The admin decides alone; otherwise
+    /// CLAIM_PANEL active arbitrators must vote the same way.
+    /// @param env: Soroban environment
+    /// @param decider: Admin or active arbitrator
+    /// @param claim_id: Claim identifier
+    /// @param approve: Pay the claim (true) or deny it (false)
+    pub fn resolve_claim(env: Env, decider: Address, claim_id: u32, approve: bool) {
+        decider.require_auth();
+        Self::check_not_paused(&env);
+        let _guard = Self::check_reentrancy(&env);
+
+        let key = AccountKey::InsuranceClaim(claim_id);
+        let mut claim: InsuranceClaim = env.storage().persistent().get(&key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::ClaimNotFound));
+        if claim.status != ClaimStatus::Pending {
+            panic_with_error!(&env, Error::InvalidState);
+        }
+
+        if decider != Self::get_admin_address(&env) {
+            if !Self::is_active_arbitrator(&env, &decider) {
+                panic_with_error!(&env, Error::NotArbitrator);
+            }
+            // Parties to the job cannot judge its claims
+            let job = Self::get_job(&env, claim.job_id);
+            if decider == job.client || job.talent == Some(decider.clone()) {
+                panic_with_error!(&env, Error::Unauthorized);
+            }
+            if claim.votes.contains_key(decider.clone()) {
+                panic_with_error!(&env, Error::AlreadyVoted);
+            }
+            claim.votes.set(decider.clone(), approve);
+            let matching = claim.votes.values().iter()
+                .filter(|vote| *vote == approve)
+                .count() as u32;
+            if matching < CLAIM_PANEL {
+                env.storage().persistent().set(&key, &claim);
+                Self::emit(&env, INS_VOTE, claim.job_id, &decider, ClaimDecision {
+                    version: EVENT_VERSION,
+                    claim_id,
+                    approved: approve,
+                    amount: 0,
+                });
+                return;
+            }
+        }
+
+        let mut paid = 0;
+        if approve {
+            let mut pool = Self::get_insurance_pool_map(&env);
+            let balance = pool.get(claim.token.clone()).unwrap_or(0);
+            if claim.amount > balance {
+                panic_with_error!(&env, Error::InsufficientFunds);
+            }
+            pool.set(claim.token.clone(), balance - claim.amount);
+            env.storage().persistent().set(&AccountKey::InsurancePool, &pool);
+            Self::escrow_out(&env, NO_JOB, &claim.token, &claim.claimant, claim.amount);
+            paid = claim.amount;
+            claim.status = ClaimStatus::Paid;
+        } else {
+            claim.status = ClaimStatus::Denied;
+        }
+        env.storage().persistent().set(&key, &claim);
+
+        Self::emit(&env, INS_RES, claim.job_id, &decider, ClaimDecision {
+            version: EVENT_VERSION,
+            claim_id,
+            approved: approve,
+            amount: paid,
+        });
+    }
+
+    /// Get an insurance claim
+    /// @param env: Soroban environment
+    /// @param claim_id: Claim identifier
+    pub fn get_insurance_claim(env: Env, claim_id: u32) -> Option<InsuranceClaim> {
+        env.storage().persistent().get(&AccountKey::InsuranceClaim(claim_id))
+    }
+
+    /// Get the insurance pool balance for a token
+    /// @param env: Soroban environment
+    /// @param token: Payment token contract
+    pub fn get_insurance_pool(env: Env, token: Address) -> i128 {
+        Self::get_insurance_pool_map(&env).get(token).unwrap_or(0)
+    }
+
+    // =================
+    // KEEPERS
+    // =================
+    /// Run whatever time-based maintenance is due on a job and pay the
+    /// keeper a small bounty from the treasury (while fees last)
+    /// @param env: Soroban environment
+    /// @param keeper: Any address
+    /// @param job_id: Job identifier
+    /// @return The task performed
+    pub fn perform_upkeep(env: Env, keeper: Address, job_id: u32) -> KeeperTask {
+        keeper.require_auth();
+        let job = Self::get_job(&env, job_id);
+        let task = Self::keeper_task(&env, job_id, &job)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidState));
+
+        // Each entry point takes the reentrancy lock itself
+        match task {
+            KeeperTask::ExpireUnfunded => Self::expire_unfunded_job(env.clone(), job_id),
+            // The client's standing allowance authorizes each period
+            KeeperTask::AdvancePeriod => {
+                Self::check_not_paused(&env);
+                let _guard = Self::check_reentrancy(&env);
+                Self::advance_retainer(&env, job_id);
+            }
+            KeeperTask::TimeoutDispute => Self::timeout_dispute(env.clone(), keeper.clone(), job_id),
+            KeeperTask::FinalizeDispute => Self::finalize_dispute(env.clone(), keeper.clone(), job_id),
+            KeeperTask::ExecuteRuling => Self::execute_ruling(env.clone(), keeper.clone(), job_id),
+        }
+
+        let _guard = Self::check_reentrancy(&env);
+        let token = Self::get_token_id(&env);
+        let mut treasury = Self::get_treasury(&env);
+        let accrued = treasury.get(token.clone()).unwrap_or(0);
+        let bounty: i128 = env.storage().instance().get(&ConfigKey::KeeperBounty)
+            .unwrap_or(DEFAULT_KEEPER_BOUNTY);
+        let bounty = bounty.min(accrued);
+        if bounty > 0 {
+            treasury.set(token.clone(), accrued - bounty);
+            env.storage().persistent().set(&AccountKey::Treasury, &treasury);
+            Self::escrow_out(&env, NO_JOB, &token, &keeper, bounty);
+        }
+
+        Self::emit(&env, KEEP_PAY, job_id, &keeper, KeeperPaid {
+            version: EVENT_VERSION,
+            task: task.clone(),
+            bounty: bounty.max(0),
+        });
+        task
+    }
+
+    /// Page through jobs with maintenance due, for keeper bots
+    /// @param env: Soroban environment
+    /// @param start: First creation sequence number to scan
+    /// @param limit: Jobs to scan (capped at 50)
+    /// @return (job_id, task) for each job in the window with work due
+    pub fn list_actionable_jobs(env: Env, start: u32, limit: u32) -> Vec<(u32, KeeperTask)> {
+        let count: u32 = env.storage().instance().get(&ConfigKey::JobCount).unwrap_or(0);
+        let first = start.max(1);
+        let end = first.saturating_add(limit.min(MAX_PAGE)).min(count.saturating_add(1));
+        let mut page = Vec::new(&env);
+        for seq in first..end {
+            let job_id = Self::job_id_at(&env, seq);
+            let job: Option<Job> = env.storage().persistent().get(&JobKey::Job(job_id));
+            if let Some(job) = job {
+                if let Some(task) = Self::keeper_task(&env, job_id, &job) {
+                    page.push_back((job_id, task));
+                }
+            }
+        }
+        page
+    }
+
+    // ==================
+    // TIMELOCKED CONFIG
+    // ==================
+    /// Schedule a configuration change (admin only).
+    /// The action becomes executable once the timelock delay has elapsed.
+    /// @param env: Soroban environment
+    /// @param admin: Contract administrator
+    /// @param action: Configuration change to schedule
+    /// @return action_id: Queued action identifier
+    pub fn queue_action(env: Env, admin: Address, action: AdminAction) -> u32 {
+        Self::require_admin(&env, &admin);
+        Self::validate_action(&env, &action);
+
+        let eta = env.ledger().timestamp() + Self::timelock_delay(&env);
+        let action_id = env.storage().instance().get(&ConfigKey::ActionCount)
+            .unwrap_or(0u32) + 1;
+        env.storage().instance().set(&ConfigKey::ActionCount, &action_id);
+
+        let mut queue = Self::get_action_queue(&env);
+        queue.set(action_id, QueuedAction { action: action.clone(), eta });
+        env.storage().persistent().set(&AccountKey::ActionQueue, &queue);
+
+        env.events().publish(
+            (ACT_QUE, admin),
+            (action_id, action, eta)
+        );
+        action_id
+    }
+
+    /// Apply a queued configuration change once its delay has elapsed.
+    /// Callable by anyone; authorization happened when it was queued.
+    /// @param env: Soroban environment
+    /// @param action_id: Queued action identifier
+    pub fn execute_action(env: Env, action_id: u32) {
+        let mut queue = Self::get_action_queue(&env);
+        let queued = queue.get(action_id)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::ActionNotFound));
+        if env.ledger().timestamp() < queued.eta {
+            panic_with_error!(&env, Error::TimelockNotReady);
+        }
+
+        queue.remove(action_id);
+        env.storage().persistent().set(&AccountKey::ActionQueue, &queue);
+
+        match queued.action.clone() {
+            AdminAction::SetPlatformFee(fee_bps) => {
+                env.storage().instance().set(&ConfigKey::PlatformFee, &fee_bps);
+                env.events().publish((FEE_SET,), fee_bps);
+            }
+            AdminAction::SetTokenAllowed(token, allowed) => {
+                let mut tokens = Self::get_allowed_tokens(&env);
+                if allowed {
+                    tokens.set(token, true);
+                } else {
+                    tokens.remove(token);
+                }
+                env.storage().instance().set(&ConfigKey::AllowedTokens, &tokens);
+            }
+            AdminAction::RemoveArbitrator(arbitrator) => {
+                let mut arbitrators = Self::get_arbitrators(&env);
+                if arbitrators.contains_key(arbitrator.clone()) {
+                    Self::remove_arbitrator(&env, &mut arbitrators, &arbitrator);
+                    Self::save_arbitrators(&env, &arbitrators);
+                }
+            }
+            AdminAction::SetTimelockDelay(delay) => {
+                env.storage().instance().set(&ConfigKey::TimelockDelay, &delay);
+            }
+            AdminAction::SetMinArbStake(amount) => {
+                env.storage().instance().set(&ConfigKey::MinArbStake, &amount);
+            }
+            AdminAction::SetDefaultRuling(ruling) => {
+                env.storage().instance().set(&ConfigKey::DefaultRuling, &ruling);
+            }
+            AdminAction::SetMaxArbitratorFee(fee_percentage) => {
+                env.storage().instance().set(&ConfigKey::MaxArbitratorFee, &fee_percentage);
+            }
+            AdminAction::SetMaxCancellationFee(fee_bps) => {
+                env.storage().instance().set(&ConfigKey::MaxCancellationFee, &fee_bps);
+            }
+            AdminAction::SetJobLimits(limits) => {
+                env.storage().instance().set(&ConfigKey::JobLimits, &limits);
+            }
+            AdminAction::SetTextLimits(title_max, description_max) => {
+                env.storage().instance().set(&ConfigKey::TextLimits, &(title_max, description_max));
+            }
+            AdminAction::SetInsuranceShare(share_bps) => {
+                env.storage().instance().set(&ConfigKey::InsuranceShare, &share_bps);
+            }
+            AdminAction::SetReferralReward(reward_bps) => {
+                env.storage().instance().set(&ConfigKey::ReferralReward, &reward_bps);
+            }
+            AdminAction::SetKeeperBounty(bounty) => {
+                env.storage().instance().set(&ConfigKey::KeeperBounty, &bounty);
+            }
+            AdminAction::SetOracleAllowed(oracle, allowed) => {
+                let mut oracles = Self::get_allowed_oracles(&env);
+                if allowed {
+                    oracles.set(oracle, true);
+                } else {
+                    oracles.remove(oracle);
+                }
+                env.storage().instance().set(&ConfigKey::AllowedOracles, &oracles);
+            }
+            AdminAction::SetRouterAllowed(router, allowed) => {
+                let mut routers = Self::get_allowed_routers(&env);
+                if allowed {
+                    routers.set(router, true);
+                } else {
+                    routers.remove(router);
+                }
+                env.storage().instance().set(&ConfigKey::AllowedRouters, &routers);
+            }
+            AdminAction::SetYieldStrategy(config) => {
+                // Existing positions stay with the strategy they were deployed to
+                match config.as_ref() {
+                    Some(config) => env.storage().instance().set(&ConfigKey::YieldStrategy, config),
+                    None => env.storage().instance().remove(&ConfigKey::YieldStrategy),
+                }
+                env.events().publish((YLD_SET,), config);
+            }
+            AdminAction::AddHook(event_kind, target) => {
+                // Slots may have filled up while the action was queued
+                Self::check_hook_slot(&env, &event_kind, &target);
+                let mut hooks = Self::get_hook_map(&env);
+                let mut targets = hooks.get(event_kind.clone()).unwrap_or_else(|| Vec::new(&env));
+                targets.push_back(target.clone());
+                hooks.set(event_kind.clone(), targets);
+                env.storage().instance().set(&ConfigKey::Hooks, &hooks);
+                env.events().publish((HOOK_ADD,), (event_kind, target));
+            }
+        }
+
+        env.events().publish(
+            (ACT_EXE,),
+            (action_id, queued.action)
+        );
+    }
+
+    /// Cancel a queued configuration change (admin only)
+    /// @param env: Soroban environment
+    /// @param admin: Contract administrator
+    /// @param action_id: Queued action identifier
+    pub fn cancel_action(env: Env, admin: Address, action_id: u32) {
+        Self::require_admin(&env, &admin);
+
+        let mut queue = Self::get_action_queue(&env);
+        if !queue.contains_key(action_id) {
+            panic_with_error!(&env, Error::ActionNotFound);
+        }
+        queue.remove(action_id);
+        env.storage().persistent().set(&AccountKey::ActionQueue, &queue);
+
+        env.events().publish(
+            (ACT_CAN, admin),
+            action_id
+        );
+    }
+
+    /// Get a queued configuration change
+    /// @param env: Soroban environment
+    /// @param action_id: Queued action identifier
+    pub fn get_queued_action(env: Env, action_id: u32) -> Option<QueuedAction> {
+        Self::get_action_queue(&env).get(action_id)
+    }
+
+    /// Schedule a yield strategy change (admin only); shorthand for
+    /// `queue_action` with `AdminAction::SetYieldStrategy`.
+    /// Newly funded escrow is deployed to the strategy and unwound before
+    /// the job's first payout.
+    /// @param env: Soroban environment
+    /// @param admin: Contract administrator
+    /// @param strategy: Whitelisted yield contract (None turns yield off)
+    /// @param client_share_bps: Share of earned yield paid to the client
+    /// @return action_id: Queued action identifier
+    pub fn set_yield_strategy(
         env: Env,
-        client: Address,
+        admin: Address,
+        strategy: Option<Address>,
+        client_share_bps: u32,
+    ) -> u32 {
+        let config = strategy.map(|strategy| YieldConfig { strategy, client_share_bps });
+        Self::queue_action(env, admin, AdminAction::SetYieldStrategy(config.into()))
+    }
+
+    /// Get the yield strategy new escrow is deployed to
+    /// @param env: Soroban environment
+    pub fn get_yield_strategy(env: Env) -> Option<YieldConfig> {
+        env.storage().instance().get(&ConfigKey::YieldStrategy)
+    }
+
+    /// Get a job's escrow currently deployed to a yield strategy
+    /// @param env: Soroban environment
+    /// @param job_id: Job identifier
+    pub fn get_yield_position(env: Env, job_id: u32) -> Option<YieldPosition> {
+        env.storage().persistent().get(&JobKey::YieldPosition(job_id))
+    }
+
+    /// Pull a job's escrow out of its yield strategy now (admin only), e.g.
+    /// when the strategy is being retired or looks compromised. A live
+    /// position is unwound as before a payout. A stranded one is redeemed
+    /// into the treasury, which fronted its principal.
+    /// @param env: Soroban environment
+    /// @param admin: Contract administrator
+    /// @param job_id: Job identifier
+    /// @return Amount the strategy paid back
+    pub fn emergency_unwind(env: Env, admin: Address, job_id: u32) -> i128 {
+        Self::require_admin(&env, &admin);
+        let _guard = Self::check_reentrancy(&env);
+
+        let key = JobKey::YieldPosition(job_id);
+        let position: YieldPosition = env.storage().persistent().get(&key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidState));
+        if position.stranded_at.is_none() {
+            return Self::unwind_yield(&env, job_id);
+        }
+
+        let received = YieldStrategyClient::new(&env, &position.strategy)
+            .withdraw(&position.token, &position.shares, &env.current_contract_address());
+        env.storage().persistent().remove(&key);
+        Self::adjust_total(&env, AccountKey::YieldDeployed, &position.token, -position.principal);
+        Self::accrue_fees(&env, &position.token, received);
+
+        Self::emit(&env, YLD_RCV, job_id, &admin, TokenMoved {
+            version: EVENT_VERSION,
+            token: position.token,
+            amount: received,
+        });
+        received
+    }
+
+    /// Get timelock delay in seconds
+    /// @param env: Soroban environment
+    pub fn get_timelock_delay(env: Env) -> u64 {
+        Self::timelock_delay(&env)
+    }
+
+    /// Check whether a token may be used for job escrow
+    /// @param env: Soroban environment
+    /// @param token: Payment token contract
+    pub fn is_token_allowed(env: Env, token: Address) -> bool {
+        token == Self::get_token_id(&env)
+            || Self::get_allowed_tokens(&env).contains_key(token)
+    }
+
+    // ====================
+    // INTERNAL HELPERS
+    // ====================
+    /// Pay a milestone to its payee (the talent unless overridden),
+    /// withholding the platform fee. Returns the fee credited to the treasury.
+    fn approve_milestone_internal(env: &Env, job_id: u32, job: &mut Job, idx: u32) -> i128 {
+        if job.payment.pricing.is_some() {
+            Self::reprice_milestone(env, job_id, job, idx);
+        }
+        let mut milestone = job.milestones.get(idx)
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidIndex));
+        let amount = milestone.amount;
+        // Subjob reservations stay in escrow for the child jobs
+        let reserved = Self::reserved_for_subjobs(env, &milestone);
+        let platform_fee = if amount > reserved {
+            let payee = Self::milestone_payee(env, job, &milestone);
+            Self::pay_worker(env, job_id, job, &payee, amount - reserved)
+        } else {
+            0
+        };
+
+        // Subjob escrow stops being counted in this job and stands alone
+        for child_id in milestone.subjobs.iter() {
+            let child = Self::get_job(env, child_id);
+            if child.state != JobState::Cancelled {
+                Self::adjust_total(env, AccountKey::Liabilities, &child.token, child.escrow_balance);
+            }
+        }
+
+        milestone.state = MilestoneState::Paid;
+        job.milestones.set(idx, milestone);
+        Self::ledger(env, job).release(amount);
+        platform_fee
+    }
+
+    /// Pay out every approved milestone still vesting, ahead of its release
+    /// time. Used when the job ends before the delay runs out.
+    fn release_approved(env: &Env, job_id: u32, job: &mut Job) {
+        for i in 0..job.milestones.len() {
+            if job.milestones.get(i).unwrap().state == MilestoneState::Approved {
+                Self::approve_milestone_internal(env, job_id, job, i);
+            }
+        }
+    }
+
+    /// Escrow held for approved milestones that have not vested yet
+    fn approved_value(job: &Job) -> i128 {
+        job.milestones.iter()
+            .filter(|m| m.state == MilestoneState::Approved)
+            .map(|m| m.amount)
+            .sum()
+    }
+
+    /// Pay an approved milestone, or hold it for the job's release delay
+    fn release_or_vest(env: &Env, job_id: u32, job: &mut Job, idx: u32, actor: &Address) -> i128 {
+        if job.release_delay == 0 {
+            return Self::approve_milestone_internal(env, job_id, job, idx);
+        }
+        let mut milestone = job.milestones.get(idx)
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidIndex));
+        let vests_at = env.ledger().timestamp() + job.release_delay;
+        milestone.state = MilestoneState::Approved;
+        milestone.vests_at = Some(vests_at);
+        job.milestones.set(idx, milestone);
+
+        Self::emit(env, MIL_VST, job_id, actor, MilestoneVesting {
+            version: EVENT_VERSION,
+            milestone_idx: idx,
+            vests_at,
+        });
+        0
+    }
+
+    /// Rate for a priced job's token: the oracle's, or the fallback rate
+    /// when the oracle call fails, is stale or the oracle was delisted
+    fn current_rate(env: &Env, job: &Job, terms: &PriceTerms) -> i128 {
+        if !Self::get_allowed_oracles(env).contains_key(terms.oracle.clone()) {
+            return terms.fallback_rate;
+        }
+        match PriceOracleClient::new(env, &terms.oracle).try_price(&job.token) {
+            Ok(Ok((rate, updated_at)))
+                if rate > 0 && env.ledger().timestamp() <= updated_at.saturating_add(MAX_PRICE_AGE) =>
+            {
+                rate
+            }
+            _ => terms.fallback_rate,
+        }
+    }
+
+    /// Convert a priced job's reference amounts to token amounts at funding.
+    /// Each milestone escrows enough to pay at the top of the slippage band.
+    fn price_job(env: &Env, job: &mut Job) {
+        let mut pricing = job.payment.pricing.get().unwrap();
+        pricing.funded_rate = Self::current_rate(env, job, &pricing.terms);
+        let max_rate = pricing.funded_rate
+            + pricing.funded_rate * pricing.terms.max_slippage_bps as i128 / BPS_DENOM;
+
+        let mut total_value = 0;
+        for i in 0..job.milestones.len() {
+            let mut milestone = job.milestones.get(i).unwrap();
+            milestone.amount = pricing.reference_amounts.get(i).unwrap() * max_rate / RATE_SCALE;
+            if milestone.amount <= 0 {
+                panic_with_error!(env, Error::AmountRequired);
+            }
+            total_value += milestone.amount;
+            job.milestones.set(i, milestone);
+        }
+        job.total_value = total_value;
+        job.payment.pricing = OptPricing::Some(pricing);
+    }
+
+    /// Settle a priced milestone at the current rate, kept within the
+    /// slippage band around the funding rate. Escrow held beyond the
+    /// converted amount is refunded to the client.
+    fn reprice_milestone(env: &Env, job_id: u32, job: &mut Job, idx: u32) {
+        let pricing = job.payment.pricing.get().unwrap();
+        let mut milestone = job.milestones.get(idx)
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidIndex));
+        let band = pricing.funded_rate * pricing.terms.max_slippage_bps as i128 / BPS_DENOM;
+        let rate = Self::current_rate(env, job, &pricing.terms)
+            .clamp(pricing.funded_rate - band, pricing.funded_rate + band);
+        let reference_amount = pricing.reference_amounts.get(idx).unwrap();
+        let amount = reference_amount * rate / RATE_SCALE;
+
+        let excess = milestone.amount - amount;
+        if excess > 0 {
+            milestone.amount = amount;
+            job.milestones.set(idx, milestone);
+            job.total_value -= excess;
+            Self::refund_escrow(env, job_id, job, excess);
+            Self::ledger(env, job).refund(excess);
+        }
+
+        Self::emit(env, REPRICE, job_id, &job.client, MilestonePriced {
+            version: EVENT_VERSION,
+            milestone_idx: idx,
+            reference_amount,
+            rate,
+            amount,
+        });
+    }
+
+    /// Validate job terms, store the job and record it on the client profile
+    fn create_job_internal(
+        env: &Env,
+        client: &Address,
+        title: Text,
+        descriptions: Vec<Text>,
+        amounts: Vec<i128>,
+        deadlines: Vec<u64>,
+        options: JobOptions,
+    ) -> u32 {
+        // Validate inputs
+        if descriptions.len() != amounts.len() || amounts.len() != deadlines.len() {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+        let limits = Self::job_limits(env);
+        if amounts.len() > limits.max_milestones {
+            panic_with_error!(env, Error::TooManyMilestones);
+        }
+        if Self::client_profile(env, client).open_jobs >= limits.max_open_jobs {
+            panic_with_error!(env, Error::TooManyOpenJobs);
+        }
+
+        let total_value: i128 = amounts.iter().sum();
+        if total_value <= 0 {
+            panic_with_error!(env, Error::AmountRequired);
+        }
+        let (title_max, description_max) = Self::text_limits(env);
+        Self::check_text(env, &title, title_max);
+
+        let token = match options.token {
+            Some(token) => {
+                if !Self::is_token_allowed(env.clone(), token.clone()) {
+                    panic_with_error!(env, Error::TokenNotAllowed);
+                }
+                token
+            }
+            None => Self::get_token_id(env),
+        };
+        // Ratings are stored x100 on a 1-5 scale
+        if options.min_talent_rating.unwrap_or(0) > 500 {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+        if let Some(terms) = options.retainer.as_ref() {
+            if terms.period == 0 || terms.payment_per_period <= 0 || terms.max_periods == 0 {
+                panic_with_error!(env, Error::InvalidInput);
+            }
+        }
+        // Streams vest the fixed total; retainers keep adding to it
+        if options.stream_duration == Some(0)
+            || (options.stream_duration.is_some() && options.retainer.is_some())
+        {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+        // Contributions are fixed token amounts, and retainer periods are
+        // pulled from the client alone
+        if options.crowdfunded && (options.retainer.is_some() || options.pricing.is_some()) {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+        // Contest prizes are paid once, in full, to entrants nobody hired
+        if options.contest
+            && (options.retainer.is_some()
+                || options.stream_duration.is_some()
+                || options.hourly_rate.is_some()
+                || options.talent_bond.unwrap_or(0) > 0
+                || options.release_delay.unwrap_or(0) > 0
+                || options.multisig.is_some()
+                || options.agency.is_some()
+                || options.pricing.is_some())
+        {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+        let cancellation_fee_bps = options.cancellation_fee_bps.unwrap_or(DEFAULT_CANCEL_FEE_BPS);
+        if cancellation_fee_bps > Self::max_cancellation_fee(env) {
+            panic_with_error!(env, Error::FeeTooHigh);
+        }
+        // Tiers are listed by strictly increasing progress
+        let kill_fee_tiers = options.kill_fee_tiers.unwrap_or_else(|| Vec::new(env));
+        if kill_fee_tiers.len() > MAX_KILL_TIERS {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+        let mut last_progress: Option<u32> = None;
+        for tier in kill_fee_tiers.iter() {
+            if tier.min_progress_bps as i128 > BPS_DENOM
+                || last_progress.is_some_and(|last| tier.min_progress_bps <= last)
+            {
+                panic_with_error!(env, Error::InvalidInput);
+            }
+            if tier.fee_bps > Self::max_cancellation_fee(env) {
+                panic_with_error!(env, Error::FeeTooHigh);
+            }
+            last_progress = Some(tier.min_progress_bps);
+        }
+        if options.talent_bond.unwrap_or(0) < 0 {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+        let expires_at = env.ledger().timestamp()
+            .checked_add(options.expiry.unwrap_or(DEFAULT_FUND_WINDOW))
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidInput));
+        if let Some(policy) = options.dispute_policy.as_ref() {
+            Self::check_dispute_policy(env, policy);
+        }
+        if let Some(multisig) = options.multisig.as_ref() {
+            Self::check_multisig(env, multisig);
+        }
+        if options.referrer.as_ref() == Some(client) {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+        // Priced jobs settle per milestone; their oracle must be whitelisted
+        if let Some(terms) = options.pricing.as_ref() {
+            if !Self::get_allowed_oracles(env).contains_key(terms.oracle.clone())
+                || terms.fallback_rate <= 0
+                || terms.max_slippage_bps > MAX_SLIPPAGE_BPS
+                || options.retainer.is_some()
+                || options.stream_duration.is_some()
+                || options.hourly_rate.is_some()
+            {
+                panic_with_error!(env, Error::InvalidInput);
+            }
+        }
+        // Vesting is for milestone approvals only
+        if let Some(delay) = options.release_delay {
+            if delay > MAX_RELEASE_DELAY
+                || options.stream_duration.is_some()
+                || options.hourly_rate.is_some()
+            {
+                panic_with_error!(env, Error::InvalidInput);
+            }
+        }
+        // Talent, agency and the highest possible platform fee must fit in
+        // every payout
+        if let Some(agency) = options.agency.as_ref() {
+            if agency.address == *client
+                || agency.cut_bps == 0
+                || agency.cut_bps as i128 + MAX_PLAT_FEE_BPS as i128 > BPS_DENOM
+            {
+                panic_with_error!(env, Error::InvalidInput);
+            }
+        }
+        let tags = options.tags.unwrap_or_else(|| Vec::new(env));
+        if tags.len() > MAX_TAGS {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+        for (i, tag) in tags.iter().enumerate() {
+            if tags.first_index_of(&tag) != Some(i as u32) {
+                panic_with_error!(env, Error::InvalidInput);
+            }
+        }
+        // Hourly jobs use the milestone total as their budget
+        if let Some(rate) = options.hourly_rate {
+            if rate <= 0 || options.stream_duration.is_some() || options.retainer.is_some() {
+                panic_with_error!(env, Error::InvalidInput);
+            }
+        }
+
+        // Per-milestone payees line up with the milestones; streamed,
+        // hourly and contest pay do not go through milestone approval
+        let payees = options.milestone_payees.unwrap_or_else(|| Vec::new(env));
+        if !payees.is_empty()
+            && (payees.len() != amounts.len()
+                || options.stream_duration.is_some()
+                || options.hourly_rate.is_some()
+                || options.contest
+                || payees.iter().any(|payee| payee.as_ref() == Some(client)))
+        {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+
+        // Create milestones
+        let mut milestones = Vec::new(env);
+        for i in 0..descriptions.len() {
+            let amount = amounts.get(i).unwrap();
+            if amount <= 0 {
+                panic_with_error!(env, Error::AmountRequired);
+            }
+            let description = descriptions.get(i).unwrap();
+            Self::check_text(env, &description, description_max);
+
+            milestones.push_back(Milestone {
+                description,
+                amount,
+                state: MilestoneState::Pending,
+                submission_data: BytesN::from_array(env, &[0; 32]),
+                submission_uri: None,
+                deadline: deadlines.get(i).unwrap(),
+                submitted_at: None,
+                vests_at: None,
+                subjobs: Vec::new(env),
+                payee: payees.get(i).flatten(),
+            });
+        }
+
+        // Create job
+        let job = Job {
+            client: client.clone(),
+            funder: None,
+            crowdfunded: options.crowdfunded,
+            contest: options.contest,
+            talent: None,
+            offer: None.into(),
+            title: title.clone(),
+            token,
+            specialization: options.specialization,
+            min_talent_rating: options.min_talent_rating.unwrap_or(0),
+            min_jobs_completed: options.min_jobs_completed.unwrap_or(0),
+            payment: PaymentModel {
+                retainer: options.retainer,
+                next_period_at: 0,
+                periods_advanced: 0,
+                stream: options.stream_duration.map(|duration| Stream {
+                    duration,
+                    started_at: None,
+                    stopped_at: None,
+                    withdrawn: 0,
+                }).into(),
+                hourly_rate: options.hourly_rate.unwrap_or(0),
+                pricing: options.pricing.get().map(|terms| Pricing {
+                    terms,
+                    reference_amounts: amounts.clone(),
+                    funded_rate: 0,
+                }).into(),
+            },
+            payout_split: Vec::new(env),
+            parent: None.into(),
+            talent_bond: options.talent_bond.unwrap_or(0),
+            bond_posted: 0,
+            total_value,
+            amount_paid: 0,
+            state: JobState::Created,
+            action_seq: 0,
+            milestones,
+            escrow_balance: 0,
+            deposited: 0,
+            fees_charged: 0,
+            platform_fees: 0,
+            refunded: 0,
+            created_at: env.ledger().timestamp(),
+            expires_at,
+            staffing_window: options.staffing_window.unwrap_or(DEFAULT_STAFF_WINDOW),
+            staffing_deadline: 0,
+            cancel_terms: CancelTerms {
+                cancellation_fee_bps,
+                kill_fee_tiers,
+                cancellation_notice: options.cancellation_notice_period.unwrap_or(0),
+                cancel_effective_at: None,
+            },
+            release_delay: options.release_delay.unwrap_or(0),
+            metadata: None.into(),
+            dispute_policy: options.dispute_policy,
+            invited: None,
+            category: options.category.unwrap_or(GENERAL),
+            tags,
+            roles: JobRoles {
+                multisig: options.multisig,
+                managers: Map::new(env),
+                referrer: options.referrer,
+                agency: options.agency,
+            },
+        };
+
+        let derived_id = options.client_nonce.map(|nonce| Self::derive_job_id(env, client, nonce));
+        let job_id = Self::save_job(env, &job, derived_id);
+        Self::log_transition(env, job_id, None.into(), JobState::Created, client);
+        Self::index_category(env, &job.category, job_id);
+
+        let mut profile = Self::client_profile(env, client);
+        if profile.jobs_posted == 0 {
+            profile.first_job_at = env.ledger().timestamp();
+        }
+        profile.jobs_posted += 1;
+        profile.open_jobs += 1;
+        Self::save_client_profile(env, client, &profile);
+        Self::update_stats(env, |stats| {
+            stats.total_jobs += 1;
+            stats.active_jobs += 1;
+        });
+
+        Self::emit(env, JOB_CRT, job_id, client, JobCreated {
+            version: EVENT_VERSION,
+            title,
+            total_value,
+            token: job.token.clone(),
+            parent: None.into(),
+        });
+        job_id
+    }
+
+    /// Pay a submitted milestone from escrow and emit the approval event
+    fn approve_submitted(env: &Env, client: &Address, job_id: u32, milestone_idx: u32) -> Job {
+        let mut job = Self::get_job(env, job_id);
+        Self::check_client_or_manager(env, &job, client, Permission::ApproveMilestones);
+        Self::require_approved(env, job_id, &job, JobAction::ApproveMilestone(milestone_idx));
+        if job.state != JobState::Active || !Self::parent_settled(env, &job) {
+            panic_with_error!(env, Error::InvalidState);
+        }
+
+        let milestone = job.milestones.get(milestone_idx)
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidIndex));
+
+        if milestone.state != MilestoneState::Submitted {
+            panic_with_error!(env, Error::NotSubmitted);
+        }
+
+        // Transfer payment (net of platform fee), or start it vesting
+        let platform_fee = Self::release_or_vest(env, job_id, &mut job, milestone_idx, client);
+
+        // Check completion
+        Self::check_completion(env, job_id, &mut job, client);
+
+        Self::update_job(env, job_id, &job);
+        Self::emit(env, MIL_APR, job_id, client, PaymentMade {
+            version: EVENT_VERSION,
+            index: Some(milestone_idx),
+            amount: milestone.amount,
+            fee: platform_fee,
+        });
+        job
+    }
+
+    /// Transfer a bonus or tip from the client's wallet to the talent,
+    /// bypassing escrow and platform fees
+    fn pay_direct(env: &Env, job: &Job, amount: i128) {
+        let talent = job.talent.clone()
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidState));
+        token::Client::new(env, &job.token).transfer(&job.client, &talent, &amount);
+
+        if let Some(mut profile) = Self::talent_profile(env, &talent) {
+            profile.total_earned += amount;
+            Self::save_talent_profile(env, &talent, &profile);
+        }
+        let mut client_profile = Self::client_profile(env, &job.client);
+        client_profile.total_spent += amount;
+        Self::save_client_profile(env, &job.client, &client_profile);
+    }
+
+    /// Move the job value into escrow from the payer (the client or a
+    /// sponsor), either as a direct transfer or via `transfer_from` against
+    /// a prior allowance
+    fn fund_escrow(env: &Env, client: &Address, payer: &Address, job_id: u32, use_allowance: bool) {
+        let mut job = Self::get_job(env, job_id);
+        if job.client != *client {
+            panic_with_error!(env, Error::Unauthorized);
+        }
+        // Crowdfunded jobs fill up through `contribute`
+        if job.state != JobState::Created || job.crowdfunded {
+            panic_with_error!(env, Error::InvalidState);
+        }
+        if env.ledger().timestamp() >= job.expires_at {
+            panic_with_error!(env, Error::DeadlinePassed);
+        }
+        if job.payment.pricing.is_some() {
+            Self::price_job(env, &mut job);
+        }
+
+        if use_allowance {
+            let allowance = token::Client::new(env, &job.token)
+                .allowance(payer, &env.current_contract_address());
+            if allowance < job.total_value {
+                panic_with_error!(env, Error::InsufficientFunds);
+            }
+        }
+        Self::escrow_in(env, job_id, &job.token, payer, job.total_value, use_allowance);
+        if payer != client {
+            job.funder = Some(payer.clone());
+        }
+
+        let total_value = job.total_value;
+        Self::ledger(env, &mut job).deposit(total_value);
+        job.staffing_deadline = env.ledger().timestamp() + job.staffing_window;
+        Self::set_state(env, job_id, &mut job, JobState::Funded, client);
+        Self::update_job(env, job_id, &job);
+        Self::deploy_yield(env, job_id, &job);
+
+        Self::emit(env, JOB_FUND, job_id, client, EscrowChanged {
+            version: EVENT_VERSION,
+            amount: job.total_value,
+            escrow_balance: job.escrow_balance,
+        });
+    }
+
+    /// Release `gross` from escrow to the talent (or the team, if a payout
+    /// split is set), withholding the platform fee and paying any agency
+    /// cut. Returns the platform fee.
+    fn pay_talent(env: &Env, job_id: u32, job: &mut Job, gross: i128) -> i128 {
+        let talent = job.talent.clone()
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidState));
+        Self::pay_worker(env, job_id, job, &talent, gross)
+    }
+
+    /// Who a milestone pays: its payee override, or the hired talent
+    fn milestone_payee(env: &Env, job: &Job, milestone: &Milestone) -> Address {
+        milestone.payee.clone()
+            .or_else(|| job.talent.clone())
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidState))
+    }
+
+    /// Release `gross` from escrow to a given worker, as `pay_talent` does
+    /// for the hired talent. Contest prizes and milestones with a payee
+    /// override go to other workers; the talent's payout split only applies
+    /// to the talent.
+    fn pay_worker(env: &Env, job_id: u32, job: &mut Job, talent: &Address, gross: i128) -> i128 {
+        let platform_fee = gross * Self::platform_fee_bps(env) as i128 / BPS_DENOM;
+        let agency_cut = match job.roles.agency.as_ref() {
+            Some(agency) => {
+                let cut = gross * agency.cut_bps as i128 / BPS_DENOM;
+                if cut > 0 {
+                    Self::escrow_out(env, job_id, &job.token, &agency.address, cut);
+                }
+                cut
+            }
+            None => 0,
+        };
+        let net = gross - platform_fee - agency_cut;
+
+        let recipients = if job.payout_split.is_empty() || job.talent.as_ref() != Some(talent) {
+            Vec::from_array(env, [(talent.clone(), BPS_DENOM as u32)])
+        } else {
+            job.payout_split.clone()
+        };
+        let mut remaining = net;
+        for (i, (recipient, bps)) in recipients.iter().enumerate() {
+            // Rounding dust goes to the last recipient
+            let share = if i as u32 == recipients.len() - 1 {
+                remaining
+            } else {
+                net * bps as i128 / BPS_DENOM
+            };
+            remaining -= share;
+            if share > 0 {
+                Self::pay_recipient(env, job_id, &job.token, &recipient, share);
+            }
+            if let Some(mut profile) = Self::talent_profile(env, &recipient) {
+                profile.total_earned += share;
+                Self::save_talent_profile(env, &recipient, &profile);
+            }
+        }
+        if platform_fee > 0 {
+            // A slice of every platform fee backs the insurance pool
+            let insured = platform_fee * Self::insurance_share_bps(env) as i128 / BPS_DENOM;
+            Self::fund_insurance(env, &job.token, insured);
+            Self::accrue_fees(env, &job.token, platform_fee - insured);
+            job.platform_fees += platform_fee;
+        }
+
+        let mut client_profile = Self::client_profile(env, &job.client);
+        client_profile.total_spent += gross;
+        Self::save_client_profile(env, &job.client, &client_profile);
+        Self::update_stats(env, |stats| {
+            let total = stats.total_paid_out.get(job.token.clone()).unwrap_or(0);
+            stats.total_paid_out.set(job.token.clone(), total + gross);
+        });
+        platform_fee
+    }
+
+    /// Pay a payee from escrow in their preferred token when they set one.
+    /// Any swap failure falls back to paying in the escrow token.
+    fn pay_recipient(env: &Env, job_id: u32, token: &Address, to: &Address, amount: i128) {
+        let preference: Option<PayoutPreference> =
+            env.storage().persistent().get(&AccountKey::PayoutPreference(to.clone()));
+        if let Some(preference) = preference {
+            if preference.token != *token
+                && Self::get_allowed_routers(env).contains_key(preference.router.clone())
+                && Self::swap_payout(env, job_id, token, to, amount, &preference)
+            {
+                return;
+            }
+        }
+        Self::escrow_out(env, job_id, token, to, amount);
+    }
+
+    /// Swap escrow tokens through the payee's router, accepting no less
+    /// than the quote minus their slippage. Returns false if the router
+    /// fails, in which case nothing has left escrow.
+    fn swap_payout(
+        env: &Env,
+        job_id: u32,
+        token: &Address,
+        to: &Address,
+        amount: i128,
+        preference: &PayoutPreference,
+    ) -> bool {
+        Self::unwind_yield(env, job_id);
+        let router = SwapRouterClient::new(env, &preference.router);
+        let quote = match router.try_quote(token, &preference.token, &amount) {
+            Ok(Ok(quote)) if quote > 0 => quote,
+            _ => return false,
+        };
+        let min_out = quote - quote * preference.max_slippage_bps as i128 / BPS_DENOM;
+
+        // The router pulls exactly `amount` of the escrow token
+        let contract = env.current_contract_address();
+        env.authorize_as_current_contract(Vec::from_array(env, [
+            InvokerContractAuthEntry::Contract(SubContractInvocation {
+                context: ContractContext {
+                    contract: token.clone(),
+                    fn_name: Symbol::new(env, "transfer"),
+                    args: (contract.clone(), preference.router.clone(), amount).into_val(env),
+                },
+                sub_invocations: Vec::new(env),
+            }),
+        ]));
+        let token_out = token::Client::new(env, &preference.token);
+        let before = token_out.balance(to);
+        match router.try_swap(&contract, token, &preference.token, &amount, &min_out, to) {
+            Ok(Ok(_)) => {}
+            _ => return false,
+        }
+        let amount_out = token_out.balance(to) - before;
+        if amount_out < min_out {
+            panic_with_error!(env, Error::InsufficientFunds);
+        }
+
+        Self::emit(env, ESC_OUT, job_id, &preference.router, TokenMoved {
+            version: EVENT_VERSION,
+            token: token.clone(),
+            amount,
+        });
+        Self::emit(env, PAY_SWP, job_id, to, PayoutSwapped {
+            version: EVENT_VERSION,
+            token_in: token.clone(),
+            amount_in: amount,
+            token_out: preference.token.clone(),
+            amount_out,
+        });
+        true
+    }
+
+    /// Escrow still owed on milestones that can pay out
+    fn unpaid_obligation(job: &Job) -> i128 {
+        job.milestones.iter()
+            .filter(|m| matches!(
+                m.state,
+                MilestoneState::Pending
+                    | MilestoneState::Submitted
+                    | MilestoneState::Approved
+                    | MilestoneState::Rejected
+                    | MilestoneState::Disputed
+            ))
+            .map(|m| m.amount)
+            .sum()
+    }
+
+    /// Escrow not needed for anything the talent can still be paid
+    fn escrow_surplus(env: &Env, job_id: u32, job: &Job) -> i128 {
+        // A running stream owes its whole unvested remainder, and an hourly
+        // budget stays committed until the job is completed or cancelled
+        if job.payment.stream.is_some()
+            || (job.payment.hourly_rate > 0 && job.state != JobState::Completed)
+        {
+            return 0;
+        }
+        let owed = if job.payment.hourly_rate > 0 {
+            Self::load_timesheets(env, job_id).iter()
+                .filter(|log| !log.approved)
+                .map(|log| log.amount)
+                .sum()
+        } else {
+            Self::unpaid_obligation(job)
+        };
+        job.escrow_balance - owed - Self::reserved_for_dispute(env, job_id)
+    }
+
+    /// Invariant: a funded job's escrow covers every milestone still owed
+    fn check_escrow_covers(env: &Env, job: &Job) {
+        if job.payment.stream.is_none()
+            && job.payment.hourly_rate == 0
+            && job.escrow_balance < Self::unpaid_obligation(job)
+        {
+            panic_with_error!(env, Error::InsufficientFunds);
+        }
+    }
+
+    /// Escrow held back from a milestone payout for live subjobs
+    fn reserved_for_subjobs(env: &Env, milestone: &Milestone) -> i128 {
+        let mut reserved = 0;
+        for child_id in milestone.subjobs.iter() {
+            let child = Self::get_job(env, child_id);
+            if child.state != JobState::Cancelled {
+                reserved += child.total_value;
+            }
+        }
+        reserved
+    }
+
+    /// Whether a subjob's funding has been released by its parent milestone
+    fn parent_settled(env: &Env, job: &Job) -> bool {
+        match job.parent.get() {
+            Some((parent_id, idx)) => {
+                let parent = Self::get_job(env, parent_id);
+                matches!(parent.milestones.get(idx).map(|m| m.state), Some(MilestoneState::Paid))
+            }
+            None => true,
+        }
+    }
+
+    /// Cancel the subjobs funded by a milestone that will not pay out
+    fn void_subjobs(env: &Env, milestone: &Milestone) {
+        for child_id in milestone.subjobs.iter() {
+            let mut child = Self::get_job(env, child_id);
+            if child.state != JobState::Cancelled {
+                Self::void_job(env, child_id, &mut child, &env.current_contract_address());
+                Self::update_job(env, child_id, &child);
+            }
+        }
+    }
+
+    /// Cancel an unsettled subjob (and its own subjobs) without transfers
+    fn void_job(env: &Env, job_id: u32, job: &mut Job, actor: &Address) {
+        for milestone in job.milestones.iter() {
+            Self::void_subjobs(env, &milestone);
+        }
+        // Not the talent's fault: their bond comes back
+        if let Some(talent) = job.talent.clone() {
+            Self::release_bond(env, job_id, job, &talent);
+        }
+        Self::set_state(env, job_id, job, JobState::Cancelled, actor);
+        Self::ledger(env, job).refund_all();
+    }
+
+    /// Pay out the talent's work bond: to the talent when released, to the
+    /// client when forfeited. Returns the amount paid.
+    fn release_bond(env: &Env, job_id: u32, job: &mut Job, to: &Address) -> i128 {
+        let bond = job.bond_posted;
+        if bond > 0 {
+            Self::escrow_out(env, job_id, &job.token, to, bond);
+            job.bond_posted = 0;
+        }
+        bond
+    }
+
+    /// Portion of the job value vested by now (or by the stop time)
+    fn vested_amount(env: &Env, job: &Job, stream: &Stream) -> i128 {
+        let Some(started_at) = stream.started_at else {
+            return 0;
+        };
+        let end = started_at + stream.duration;
+        let cutoff = stream.stopped_at.unwrap_or(end)
+            .min(end)
+            .min(env.ledger().timestamp());
+        let elapsed = cutoff.saturating_sub(started_at);
+        job.total_value * elapsed as i128 / stream.duration as i128
+    }
+
+    /// Checks for a negotiated split of the remaining escrow
+    fn validate_cancellation(
+        env: &Env,
+        job_id: u32,
+        job: &Job,
+        talent_amount: i128,
+        client_amount: i128,
+    ) {
+        if job.state != JobState::Active || !Self::parent_settled(env, job) {
+            panic_with_error!(env, Error::InvalidState);
+        }
+        if Self::has_open_dispute(env, job_id) {
+            panic_with_error!(env, Error::ArbitrationPending);
+        }
+        if talent_amount < 0
+            || client_amount < 0
+            || talent_amount + client_amount != job.escrow_balance - Self::approved_value(job)
+        {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+    }
+
+    /// Offer made to `talent` on this job
+    fn pending_offer(env: &Env, job: &Job, talent: &Address) -> Offer {
+        match job.offer.get() {
+            Some(offer) if offer.talent == *talent => offer,
+            _ => panic_with_error!(env, Error::Unauthorized),
+        }
+    }
+
+    /// Client's job that has not hired anyone yet
+    fn get_hiring_job(env: &Env, client: &Address, job_id: u32) -> Job {
+        let job = Self::get_job(env, job_id);
+        Self::check_client_or_manager(env, &job, client, Permission::SelectTalent);
+        if !matches!(job.state, JobState::Created | JobState::Funded) || job.talent.is_some() {
+            panic_with_error!(env, Error::InvalidState);
+        }
+        job
+    }
+
+    /// Caller is the client, or a manager granted `permission`
+    /// Talent-only work actions also accept an unexpired session key the
+    /// hired talent granted for this job
+    fn check_talent_or_session(env: &Env, job: &Job, job_id: u32, caller: &Address) {
+        if job.talent.as_ref() == Some(caller) {
+            return;
+        }
+        let session: Option<SessionKey> = env.storage().persistent()
+            .get(&AccountKey::SessionKey(caller.clone()));
+        let valid = session.is_some_and(|session| {
+            job.talent == Some(session.talent)
+                && session.job_ids.contains(job_id)
+                && env.ledger().timestamp() < session.expires_at
+        });
+        if !valid {
+            panic_with_error!(env, Error::Unauthorized);
+        }
+    }
+
+    fn check_client_or_manager(env: &Env, job: &Job, caller: &Address, permission: Permission) {
+        if job.client == *caller {
+            return;
+        }
+        let granted = job.roles.managers.get(caller.clone())
+            .is_some_and(|permissions| permissions.contains(&permission));
+        if !granted {
+            panic_with_error!(env, Error::Unauthorized);
+        }
+    }
+
+    fn check_multisig(env: &Env, multisig: &Multisig) {
+        let count = multisig.approvers.len();
+        if count == 0 || count > MAX_APPROVERS || multisig.threshold == 0 || multisig.threshold > count {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+        for (i, approver) in multisig.approvers.iter().enumerate() {
+            if multisig.approvers.first_index_of(&approver) != Some(i as u32) {
+                panic_with_error!(env, Error::InvalidInput);
+            }
+        }
+    }
+
+    fn check_approver(env: &Env, job: &Job, approver: &Address) {
+        let is_approver = job.roles.multisig.as_ref()
+            .is_some_and(|multisig| multisig.approvers.contains(approver));
+        if !is_approver {
+            panic_with_error!(env, Error::Unauthorized);
+        }
+    }
+
+    fn get_job_actions(env: &Env, job_id: u32) -> Map<u32, PendingAction> {
+        env.storage().persistent().get(&JobKey::JobActions(job_id))
+            .unwrap_or_else(|| Map::new(env))
+    }
+
+    fn save_job_actions(env: &Env, job_id: u32, actions: &Map<u32, PendingAction>) {
+        env.storage().persistent().set(&JobKey::JobActions(job_id), actions);
+        Self::extend_persistent(env, &JobKey::JobActions(job_id));
+    }
+
+    /// On multisig jobs, consume an approved proposal for `action`;
+    /// a no-op for jobs the client controls alone
+    fn require_approved(env: &Env, job_id: u32, job: &Job, action: JobAction) {
+        let multisig = match job.roles.multisig.as_ref() {
+            Some(multisig) => multisig,
+            None => return,
+        };
+        let mut actions = Self::get_job_actions(env, job_id);
+        let approved = actions.iter().find(|(_, pending)| {
+            pending.action == action && pending.approvals.len() >= multisig.threshold
+        });
+        match approved {
+            Some((action_id, _)) => {
+                actions.remove(action_id);
+                Self::save_job_actions(env, job_id, &actions);
+            }
+            None => panic_with_error!(env, Error::ApprovalsPending),
+        }
+    }
+
+    /// Private jobs can only be offered to invited talents
+    fn check_invited(env: &Env, job: &Job, talent: &Address) {
+        if let Some(invited) = &job.invited {
+            if !invited.contains(talent) {
+                panic_with_error!(env, Error::Unauthorized);
+            }
+        }
+    }
+
+    /// Reject hires that do not meet the job's reputation thresholds.
+    /// Talents without a registered profile only qualify for jobs with none.
+    fn check_talent_eligibility(env: &Env, job: &Job, talent: &Address) {
+        if job.min_talent_rating == 0 && job.min_jobs_completed == 0 {
+            return;
+        }
+        let profile = Self::talent_profile(env, talent)
+            .unwrap_or_else(|| panic_with_error!(env, Error::TalentNotEligible));
+        if profile.rating < job.min_talent_rating
+            || profile.jobs_completed < job.min_jobs_completed
+        {
+            panic_with_error!(env, Error::TalentNotEligible);
+        }
+    }
+
+    /// Start the clocks that run from the hire: stream vesting and
+    /// retainer periods only begin once someone is doing the work
+    fn start_work(env: &Env, job: &mut Job) {
+        let now = env.ledger().timestamp();
+        if let Some(stream) = job.payment.stream.as_mut() {
+            stream.started_at = Some(now);
+        }
+        if let Some(terms) = job.payment.retainer.get() {
+            job.payment.next_period_at = now + terms.period;
+        }
+    }
+
+    /// Whether `advance_period` can still add milestones to the job
+    fn retainer_running(job: &Job) -> bool {
+        job.payment.retainer.as_ref().is_some_and(|terms| job.payment.periods_advanced < terms.max_periods)
+    }
+
+    /// Mark the job completed once every milestone is paid.
+    /// Retainers keep running between periods until their last one.
+    fn check_completion(env: &Env, job_id: u32, job: &mut Job, actor: &Address) {
+        if job.state == JobState::Completed
+            || Self::retainer_running(job)
+            || !job.milestones.iter().all(|m| matches!(m.state, MilestoneState::Paid | MilestoneState::Void))
+        {
+            return;
+        }
+        Self::complete_job(env, job_id, job, actor);
+    }
+
+    /// Publish a job event under topics (name, job_id, actor) and notify
+    /// any hooks registered for it
+    fn emit<D: IntoVal<Env, Val>>(env: &Env, name: Symbol, job_id: u32, actor: &Address, payload: D) {
+        env.events().publish((name.clone(), job_id, actor.clone()), payload);
+        Self::run_hooks(env, &name, job_id, actor);
+    }
+
+    /// A target can be added to an event kind once, up to MAX_HOOKS targets
+    fn check_hook_slot(env: &Env, event_kind: &Symbol, target: &Address) {
+        let targets = Self::get_hook_map(env).get(event_kind.clone()).unwrap_or_else(|| Vec::new(env));
+        if targets.contains(target) || targets.len() >= MAX_HOOKS {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+    }
+
+    /// Notify hook targets registered for a job event. Failures are
+    /// swallowed so a broken target cannot block the marketplace; the
+    /// reentrancy lock is still held, so targets cannot call back into
+    /// state-changing entry points.
+    fn run_hooks(env: &Env, name: &Symbol, job_id: u32, actor: &Address) {
+        let targets = match Self::get_hook_map(env).get(name.clone()) {
+            Some(targets) => targets,
+            None => return,
+        };
+        for target in targets.iter() {
+            let args: Vec<Val> = Vec::from_array(env, [
+                name.into_val(env),
+                job_id.into_val(env),
+                actor.into_val(env),
+            ]);
+            let _ = env.try_invoke_contract::<Val, soroban_sdk::Error>(&target, &HOOK_FN, args);
+        }
+    }
+
+    /// Pull tokens into the contract for a job (NO_JOB for arbitrator
+    /// stakes) and publish ESC_IN with the payer as counterparty
+    fn escrow_in(env: &Env, job_id: u32, token: &Address, from: &Address, amount: i128, use_allowance: bool) {
+        let token_client = token::Client::new(env, token);
+        let contract = env.current_contract_address();
+        if use_allowance {
+            token_client.transfer_from(&contract, from, &contract, &amount);
+        } else {
+            token_client.transfer(from, &contract, &amount);
+        }
+        Self::emit(env, ESC_IN, job_id, from, TokenMoved { version: EVENT_VERSION, token: token.clone(), amount });
+    }
+
+    /// Pay tokens out of the contract for a job (NO_JOB for stakes and
+    /// platform fees) and publish ESC_OUT with the payee as counterparty
+    fn escrow_out(env: &Env, job_id: u32, token: &Address, to: &Address, amount: i128) {
+        if job_id != NO_JOB {
+            Self::unwind_yield(env, job_id);
+        }
+        token::Client::new(env, token).transfer(&env.current_contract_address(), to, &amount);
+        Self::emit(env, ESC_OUT, job_id, to, TokenMoved { version: EVENT_VERSION, token: token.clone(), amount });
+    }
+
+    /// Return escrow to whoever funded the job: the sponsor or the client,
+    /// or the contributors of a crowdfunded job in proportion to their
+    /// contributions (the last one takes the rounding remainder)
+    fn refund_escrow(env: &Env, job_id: u32, job: &Job, amount: i128) {
+        if !job.crowdfunded {
+            let to = job.funder.clone().unwrap_or_else(|| job.client.clone());
+            Self::escrow_out(env, job_id, &job.token, &to, amount);
+            return;
+        }
+        let contributions = Self::load_contributions(env, job_id);
+        let total: i128 = contributions.values().iter().sum();
+        let mut remaining = amount;
+        for (i, (contributor, contributed)) in contributions.iter().enumerate() {
+            let share = if i as u32 + 1 == contributions.len() {
+                remaining
+            } else {
+                amount * contributed / total
+            };
+            if share > 0 {
+                Self::escrow_out(env, job_id, &job.token, &contributor, share);
+                remaining -= share;
+            }
+        }
+    }
+
+    /// Checks that a milestone can still take the given amendment terms
+    fn check_amendment(env: &Env, job_id: u32, job: &Job, milestone_idx: u32, terms: &AmendmentTerms) {
+        // Streamed, hourly and priced jobs do not pay fixed token milestones
+        if job.state != JobState::Active
+            || job.payment.stream.is_some()
+            || job.payment.hourly_rate > 0
+            || job.payment.pricing.is_some()
+        {
+            panic_with_error!(env, Error::InvalidState);
+        }
+        if Self::has_open_dispute(env, job_id) {
+            panic_with_error!(env, Error::ArbitrationPending);
+        }
+        let milestone = job.milestones.get(milestone_idx)
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidIndex));
+        // Only work not yet delivered is renegotiated; subjob reservations
+        // are sized to the current amount
+        if !matches!(milestone.state, MilestoneState::Pending | MilestoneState::Rejected)
+            || !milestone.subjobs.is_empty()
+        {
+            panic_with_error!(env, Error::InvalidState);
+        }
+        if terms.amount <= 0 {
+            panic_with_error!(env, Error::AmountRequired);
+        }
+        if terms.deadline <= env.ledger().timestamp() {
+            panic_with_error!(env, Error::DeadlinePassed);
+        }
+        // Extra escrow comes from the client, never a sponsor or contributors
+        if terms.amount > milestone.amount && (job.funder.is_some() || job.crowdfunded) {
+            panic_with_error!(env, Error::InvalidState);
+        }
+    }
+
+    /// Append an open amendment to the history and publish AMD_PROP.
+    /// A negotiation is a proposal and its chain of counters; once it has
+    /// run MAX_AMENDMENTS rounds the open terms can only be accepted or declined.
+    fn push_amendment(
+        env: &Env,
+        job_id: u32,
+        amendments: &mut Vec<Amendment>,
+        proposer: &Address,
+        milestone_idx: u32,
+        terms: AmendmentTerms,
+    ) -> u32 {
+        let rounds = amendments.iter().rev()
+            .take_while(|amendment| amendment.status == AmendmentStatus::Countered)
+            .count() as u32 + 1;
+        if rounds > MAX_AMENDMENTS {
+            panic_with_error!(env, Error::SubmissionLimit);
+        }
+        amendments.push_back(Amendment {
+            proposer: proposer.clone(),
+            milestone_idx,
+            terms: terms.clone(),
+            proposed_at: env.ledger().timestamp(),
+            status: AmendmentStatus::Open,
+        });
+        Self::save_amendments(env, job_id, amendments);
+
+        let amendment_idx = amendments.len() - 1;
+        Self::emit(env, AMD_PROP, job_id, proposer, AmendmentMade {
+            version: EVENT_VERSION,
+            amendment_idx,
+            milestone_idx,
+            amount: terms.amount,
+            deadline: terms.deadline,
+        });
+        amendment_idx
+    }
+
+    /// The amendment awaiting a response, if any (always the latest)
+    fn open_amendment(amendments: &Vec<Amendment>) -> Option<(u32, Amendment)> {
+        amendments.last()
+            .filter(|amendment| amendment.status == AmendmentStatus::Open)
+            .map(|amendment| (amendments.len() - 1, amendment))
+    }
+
+    /// Decline an open amendment when one of its parties leaves the job,
+    /// since nobody could answer it any more
+    fn lapse_amendment(env: &Env, job_id: u32) {
+        let mut amendments = Self::load_amendments(env, job_id);
+        if let Some((amendment_idx, mut amendment)) = Self::open_amendment(&amendments) {
+            amendment.status = AmendmentStatus::Declined;
+            amendments.set(amendment_idx, amendment);
+            Self::save_amendments(env, job_id, &amendments);
+        }
+    }
+
+    fn load_amendments(env: &Env, job_id: u32) -> Vec<Amendment> {
+        env.storage().persistent().get(&JobKey::Amendments(job_id))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    fn save_amendments(env: &Env, job_id: u32, amendments: &Vec<Amendment>) {
+        env.storage().persistent().set(&JobKey::Amendments(job_id), amendments);
+        Self::extend_persistent(env, &JobKey::Amendments(job_id));
+    }
+
+    fn load_entries(env: &Env, job_id: u32) -> Vec<ContestEntry> {
+        env.storage().persistent().get(&JobKey::Entries(job_id))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    fn load_contributions(env: &Env, job_id: u32) -> Map<Address, i128> {
+        env.storage().persistent().get(&JobKey::Contributions(job_id))
+            .unwrap_or_else(|| Map::new(env))
+    }
+
+    /// Deploy a newly funded job's escrow to the yield strategy, if one is set
+    fn deploy_yield(env: &Env, job_id: u32, job: &Job) {
+        let config: YieldConfig = match env.storage().instance().get(&ConfigKey::YieldStrategy) {
+            Some(config) => config,
+            None => return,
+        };
+        let amount = job.escrow_balance;
+        token::Client::new(env, &job.token)
+            .transfer(&env.current_contract_address(), &config.strategy, &amount);
+        Self::emit(env, ESC_OUT, job_id, &config.strategy, TokenMoved {
+            version: EVENT_VERSION,
+            token: job.token.clone(),
+            amount,
+        });
+        let shares = YieldStrategyClient::new(env, &config.strategy).deposit(&job.token, &amount);
+        Self::adjust_total(env, AccountKey::YieldDeployed, &job.token, amount);
+
+        let key = JobKey::YieldPosition(job_id);
+        env.storage().persistent().set(&key, &YieldPosition {
+            strategy: config.strategy,
+            token: job.token.clone(),
+            shares,
+            principal: amount,
+            client_share_bps: config.client_share_bps,
+            stranded_at: None,
+        });
+        Self::extend_persistent(env, &key);
+    }
+
+    /// Bring a job's deployed escrow back before it pays anything out.
+    /// Yield is split between the client and the treasury; a loss is
+    /// covered from accrued platform fees so escrow stays whole. A strategy
+    /// that fails to pay back is treated as a total loss for now: the
+    /// treasury fronts the principal and the position is kept, stranded,
+    /// for `emergency_unwind`. Returns the amount the strategy paid back.
+    fn unwind_yield(env: &Env, job_id: u32) -> i128 {
+        let key = JobKey::YieldPosition(job_id);
+        let mut position = match env.storage().persistent().get::<_, YieldPosition>(&key) {
+            Some(position) if position.stranded_at.is_none() => position,
+            _ => return 0,
+        };
+
+        let result = YieldStrategyClient::new(env, &position.strategy)
+            .try_withdraw(&position.token, &position.shares, &env.current_contract_address());
+        let Ok(Ok(received)) = result else {
+            Self::cover_from_treasury(env, &position.token, position.principal);
+            position.stranded_at = Some(env.ledger().timestamp());
+            env.storage().persistent().set(&key, &position);
+            Self::extend_persistent(env, &key);
+            Self::emit(env, YLD_FAIL, job_id, &position.strategy, TokenMoved {
+                version: EVENT_VERSION,
+                token: position.token.clone(),
+                amount: position.principal,
+            });
+            return 0;
+        };
+        env.storage().persistent().remove(&key);
+        Self::adjust_total(env, AccountKey::YieldDeployed, &position.token, -position.principal);
+        Self::emit(env, ESC_IN, job_id, &position.strategy, TokenMoved {
+            version: EVENT_VERSION,
+            token: position.token.clone(),
+            amount: received,
+        });
+
+        let earned = received - position.principal;
+        if earned < 0 {
+            Self::cover_from_treasury(env, &position.token, -earned);
+        }
+        let client_amount = earned.max(0) * position.client_share_bps as i128 / BPS_DENOM;
+        if earned > 0 {
+            Self::accrue_fees(env, &position.token, earned - client_amount);
+        }
+
+        let job = Self::get_job(env, job_id);
+        let client = job.client.clone();
+        if client_amount > 0 {
+            Self::refund_escrow(env, job_id, &job, client_amount);
+        }
+        Self::emit(env, YLD_HRV, job_id, &client, YieldHarvested {
+            version: EVENT_VERSION,
+            principal: position.principal,
+            earned,
+            client_amount,
+        });
+        received
+    }
+
+    /// Move a job to a new state, recording the change in its history.
+    /// The caller still persists the job.
+    fn set_state(env: &Env, job_id: u32, job: &mut Job, to: JobState, actor: &Address) {
+        if job.state == to {
+            return;
+        }
+        Self::log_transition(env, job_id, Some(job.state.clone()).into(), to.clone(), actor);
+        // Finished jobs stop counting as open, for the client's limit and the stats
+        if matches!(to, JobState::Completed | JobState::Cancelled | JobState::Expired) {
+            let mut profile = Self::client_profile(env, &job.client);
+            profile.open_jobs = profile.open_jobs.saturating_sub(1);
+            Self::save_client_profile(env, &job.client, &profile);
+            Self::update_stats(env, |stats| stats.active_jobs = stats.active_jobs.saturating_sub(1));
+            if let Some(talent) = &job.talent {
+                Self::adjust_active_jobs(env, talent, false);
+            }
+        }
+        job.state = to;
+    }
+
+    /// Append a job to its category index, INDEX_PAGE ids per entry so no
+    /// single entry grows without bound
+    fn index_category(env: &Env, category: &Symbol, job_id: u32) {
+        let size_key = JobKey::CategorySize(category.clone());
+        let size: u32 = env.storage().persistent().get(&size_key).unwrap_or(0);
+        let page_key = JobKey::CategoryIndex(category.clone(), size / INDEX_PAGE);
+        let mut ids: Vec<u32> = env.storage().persistent().get(&page_key)
+            .unwrap_or_else(|| Vec::new(env));
+        ids.push_back(job_id);
+        env.storage().persistent().set(&page_key, &ids);
+        env.storage().persistent().set(&size_key, &(size + 1));
+        Self::extend_persistent(env, &page_key);
+        Self::extend_persistent(env, &size_key);
+    }
+
+    fn log_transition(env: &Env, job_id: u32, from: OptJobState, to: JobState, actor: &Address) {
+        let key = JobKey::History(job_id);
+        let mut history: Vec<Transition> = env.storage().persistent().get(&key)
+            .unwrap_or_else(|| Vec::new(env));
+        history.push_back(Transition {
+            from,
+            to,
+            actor: actor.clone(),
+            at: env.ledger().timestamp(),
+        });
+        env.storage().persistent().set(&key, &history);
+        Self::extend_persistent(env, &key);
+    }
+
+    /// Whether a job's cancellation notice (if it has one) has run out
+    fn notice_served(env: &Env, job: &Job) -> bool {
+        if job.cancel_terms.cancellation_notice == 0 {
+            return true;
+        }
+        job.cancel_terms.cancel_effective_at.is_some_and(|at| env.ledger().timestamp() >= at)
+    }
+
+    /// Kill fee rate for the job's current progress: the last tier whose
+    /// threshold the share of worked-on milestones has reached, or the flat
+    /// rate when the job has no schedule
+    fn kill_fee_bps(job: &Job) -> u32 {
+        if job.cancel_terms.kill_fee_tiers.is_empty() {
+            return job.cancel_terms.cancellation_fee_bps;
+        }
+        let worked = job.milestones.iter()
+            .filter(|m| !matches!(m.state, MilestoneState::Pending | MilestoneState::Void))
+            .count() as i128;
+        let progress = worked * BPS_DENOM / job.milestones.len().max(1) as i128;
+        let mut fee_bps = 0;
+        for tier in job.cancel_terms.kill_fee_tiers.iter() {
+            if progress >= tier.min_progress_bps as i128 {
+                fee_bps = tier.fee_bps;
+            }
+        }
+        fee_bps
+    }
+
+    /// Pay the kill fee, refund the rest of the escrow and close the job
+    fn settle_cancellation(env: &Env, job_id: u32, job: &mut Job, client: &Address) {
+        // Unsettled subjob funds still belong to the parent escrow
+        if !Self::parent_settled(env, job) {
+            Self::void_job(env, job_id, job, client);
+            Self::update_job(env, job_id, job);
+            Self::emit(env, JOB_CANC, job_id, client, Settlement {
+                version: EVENT_VERSION,
+                talent_amount: 0,
+                client_amount: 0,
+                bond_forfeited: 0,
+            });
+            return;
+        }
+        // Reservations for subjobs are refunded with the rest of the escrow
+        for milestone in job.milestones.iter() {
+            if milestone.state != MilestoneState::Paid {
+                Self::void_subjobs(env, &milestone);
+            }
+        }
+
+        // Kill fee is owed only to a hired talent, on the value still unpaid,
+        // and never more than what is left in escrow
+        let cancellation_fee = match &job.talent {
+            Some(_) => ((job.total_value - job.amount_paid) * Self::kill_fee_bps(job) as i128
+                / BPS_DENOM)
+                .min(job.escrow_balance)
+                .max(0),
+            None => 0,
+        };
+        let refund_amount = job.escrow_balance - cancellation_fee;
+
+        if cancellation_fee > 0 {
+            Self::pay_talent(env, job_id, job, cancellation_fee);
+            Self::ledger(env, job).release(cancellation_fee);
+        }
+
+        // Refund remaining to client
+        if refund_amount > 0 {
+            Self::refund_escrow(env, job_id, job, refund_amount);
+            Self::ledger(env, job).refund(refund_amount);
+        }
+        // Client walked away: the talent gets their bond back
+        if let Some(talent) = job.talent.clone() {
+            Self::release_bond(env, job_id, job, &talent);
+        }
+
+        Self::set_state(env, job_id, job, JobState::Cancelled, client);
+        Self::update_job(env, job_id, job);
+
+        let mut profile = Self::client_profile(env, client);
+        profile.cancellations += 1;
+        Self::save_client_profile(env, client, &profile);
+
+        Self::emit(env, JOB_CANC, job_id, client, Settlement {
+            version: EVENT_VERSION,
+            talent_amount: cancellation_fee,
+            client_amount: refund_amount,
+            bond_forfeited: 0,
+        });
+    }
+
+    /// Close the job and credit the completion to the talent's profile
+    fn complete_job(env: &Env, job_id: u32, job: &mut Job, actor: &Address) {
+        Self::set_state(env, job_id, job, JobState::Completed, actor);
+        Self::reward_referrer(env, job_id, job);
+
+        if let Some(talent) = job.talent.clone() {
+            Self::release_bond(env, job_id, job, &talent);
+            if let Some(mut profile) = Self::talent_profile(env, &talent) {
+                profile.jobs_completed += 1;
+                Self::save_talent_profile(env, &talent, &profile);
+            }
+        }
+    }
+
+    /// Common checks before a party can open a dispute.
+    /// One dispute may be open per job at a time.
+    fn validate_dispute(
+        env: &Env,
+        job_id: u32,
+        job: &Job,
+        caller: &Address,
+        milestone_idx: Option<u32>,
+        panel_size: Option<u32>,
+    ) {
+        if job.state == JobState::Disputed || Self::has_open_dispute(env, job_id) {
+            panic_with_error!(env, Error::ArbitrationPending);
+        }
+        // A pre-agreed panel size rules out sole-arbitrator disputes
+        if let Some(policy) = job.dispute_policy.as_ref() {
+            if policy.panel_size.is_some() && policy.panel_size != panel_size {
+                panic_with_error!(env, Error::InvalidInput);
+            }
+        }
+        // Subjob funds are not at stake until the parent milestone pays out
+        if !Self::parent_settled(env, job) {
+            panic_with_error!(env, Error::InvalidState);
+        }
+        match job.state {
+            JobState::Active => {}
+            // Before hiring, only the offered talent can claim the job was
+            // agreed (the client can simply cancel), and only job-wide
+            JobState::Funded => {
+                if job.offer.is_none() || job.client == *caller || milestone_idx.is_some() {
+                    panic_with_error!(env, Error::InvalidState);
+                }
+            }
+            _ => panic_with_error!(env, Error::InvalidState),
+        }
+
+        // Verify caller is client or talent
+        let is_client = job.client == *caller;
+        let is_talent = Self::dispute_talent(job) == Some(caller.clone());
+        if !is_client && !is_talent {
+            panic_with_error!(env, Error::Unauthorized);
+        }
+        if is_client {
+            Self::require_approved(env, job_id, job, JobAction::RaiseDispute(milestone_idx));
+        }
+
+        // If milestone specified, validate it
+        if let Some(idx) = milestone_idx {
+            let milestone = job.milestones.get(idx)
+                .unwrap_or_else(|| panic_with_error!(env, Error::InvalidIndex));
+            // Rejections can be disputed so an arbitrator may overturn them
+            if !matches!(milestone.state, MilestoneState::Submitted | MilestoneState::Rejected) {
+                panic_with_error!(env, Error::NotSubmitted);
+            }
+        }
+    }
+
+    fn open_dispute(
+        env: &Env,
         job_id: u32,
-        milestone_idx: u32,
+        job: &mut Job,
+        caller: &Address,
+        milestone_idx: Option<u32>,
+        arbitrators: Vec<Address>,
+        awaiting_approval: Option<Address>,
     ) {
-        client.require_auth();
-        Self::check_reentrancy(&env);
+        let now = env.ledger().timestamp();
 
-        let mut job = Self::get_job(&env, job_id);
-        if job.client != client {
-            panic_with_error!(&env, Error::Unauthorized);
+        // Initiator deposits the maximum possible arbitration fee up front
+        let fee_deposit = job.total_value * Self::arbitrator_fee_cap(env, job) / 100;
+        if fee_deposit > 0 {
+            Self::escrow_in(env, job_id, &job.token, caller, fee_deposit, false);
         }
-        if job.state != JobState::Active {
-            panic_with_error!(&env, Error::InvalidState);
+        // The client pays if a talent-initiated dispute goes the talent's
+        // way; set that share aside from escrow surplus now so the fee is
+        // never taken out of milestone funds
+        let fee_reserved = if job.client != *caller {
+            Self::escrow_surplus(env, job_id, job).clamp(0, fee_deposit)
+        } else {
+            0
+        };
+        // A Funded-stage claimant posts the work bond up front so that a
+        // force-continue ruling can hire them outright
+        if job.talent.is_none() && job.talent_bond > 0 {
+            Self::escrow_in(env, job_id, &job.token, caller, job.talent_bond, false);
+            job.bond_posted = job.talent_bond;
+        }
+
+        Self::reassign_arbitrators(env, &Vec::new(env), &arbitrators);
+        Self::save_dispute(env, job_id, &Dispute {
+            raised_by: caller.clone(),
+            milestone_idx,
+            arbitrators,
+            votes: Map::new(env),
+            raised_at: now,
+            voting_deadline: now + VOTE_WINDOW,
+            awaiting_approval,
+            response_deadline: now + APPROVAL_WINDOW,
+            pending_ruling: OptRuling::None,
+            deciders: Vec::new(env),
+            appeal_deadline: 0,
+            appeal: OptAppeal::None,
+            ruling_deadline: now + Self::ruling_window(job),
+            reassignments: 0,
+            fee_deposit,
+            fee_reserved,
+            submission: milestone_idx
+                .and_then(|idx| Self::get_submissions(env.clone(), job_id, idx).last())
+                .into(),
+        });
+        // Each dispute starts with a fresh evidence log
+        env.storage().persistent().remove(&JobKey::Evidence(job_id));
+        Self::update_stats(env, |stats| stats.disputes_raised += 1);
+
+        if *caller == job.client {
+            let mut profile = Self::client_profile(env, caller);
+            profile.disputes_raised += 1;
+            Self::save_client_profile(env, caller, &profile);
+        }
+
+        // Milestone disputes only freeze that milestone; other milestones
+        // keep moving. Job-wide disputes freeze the whole job.
+        match milestone_idx {
+            Some(idx) => {
+                let mut milestone = job.milestones.get(idx).unwrap();
+                milestone.state = MilestoneState::Disputed;
+                job.milestones.set(idx, milestone);
+            }
+            None => Self::set_state(env, job_id, job, JobState::Disputed, caller),
+        }
+        Self::update_job(env, job_id, job);
+    }
+
+    /// The other party to a job (caller must be client or talent)
+    fn counterparty(env: &Env, job: &Job, party: &Address) -> Address {
+        let talent = Self::dispute_talent(job);
+        if job.client == *party {
+            talent.unwrap_or_else(|| panic_with_error!(env, Error::InvalidState))
+        } else if talent == Some(party.clone()) {
+            job.client.clone()
+        } else {
+            panic_with_error!(env, Error::Unauthorized)
+        }
+    }
+
+    /// Talent party to a dispute: the hire, or before hiring the talent
+    /// holding the job's offer
+    fn dispute_talent(job: &Job) -> Option<Address> {
+        job.talent.clone().or_else(|| job.offer.as_ref().map(|offer| offer.talent.clone()))
+    }
+
+    /// Draw distinct active arbitrators, excluding the job's parties
+    fn select_panel(env: &Env, job: &Job, panel_size: u32) -> Vec<Address> {
+        Self::try_select_panel(env, job, panel_size, &Vec::new(env))
+            .unwrap_or_else(|| panic_with_error!(env, Error::NotEnoughArbitrators))
+    }
+
+    fn try_select_panel(
+        env: &Env,
+        job: &Job,
+        panel_size: u32,
+        exclude: &Vec<Address>,
+    ) -> Option<Vec<Address>> {
+        let mut candidates = Vec::new(env);
+        for (address, record) in Self::get_arbitrators(env).iter() {
+            let is_party = address == job.client || Self::dispute_talent(job) == Some(address.clone());
+            if !is_party
+                && !exclude.contains(&address)
+                && Self::policy_allows(job, &address)
+                && Self::is_active_record(env, &record)
+            {
+                candidates.push_back(address);
+            }
+        }
+        if candidates.len() < panel_size {
+            return None;
+        }
+
+        let mut panel = Vec::new(env);
+        for _ in 0..panel_size {
+            let pick = env.prng().gen_range::<u64>(0..candidates.len() as u64) as u32;
+            panel.push_back(candidates.get(pick).unwrap());
+            candidates.remove(pick);
+        }
+        Some(panel)
+    }
+
+    /// Reputation-weighted draw among active arbitrators matching the job's
+    /// specialization, excluding the job's parties
+    fn select_weighted_arbitrator(env: &Env, job: &Job) -> Address {
+        let mut candidates = Vec::new(env);
+        let mut total_weight: u64 = 0;
+        for (address, record) in Self::get_arbitrators(env).iter() {
+            let is_party = address == job.client || Self::dispute_talent(job) == Some(address.clone());
+            let matches = match job.specialization.as_ref() {
+                Some(tag) => record.specialization == *tag,
+                None => true,
+            };
+            if !is_party
+                && matches
+                && Self::policy_allows(job, &address)
+                && Self::is_active_record(env, &record)
+            {
+                // Zero-reputation arbitrators keep a minimal chance
+                let weight = record.reputation.max(1) as u64;
+                total_weight += weight;
+                candidates.push_back((address, weight));
+            }
+        }
+        if candidates.is_empty() {
+            panic_with_error!(env, Error::NotEnoughArbitrators);
+        }
+
+        let mut target = env.prng().gen_range::<u64>(0..total_weight);
+        for (address, weight) in candidates.iter() {
+            if target < weight {
+                return address;
+            }
+            target -= weight;
+        }
+        candidates.last().unwrap().0
+    }
+
+    fn default_ruling(env: &Env) -> Ruling {
+        env.storage().instance().get(&ConfigKey::DefaultRuling)
+            .unwrap_or(Ruling::Reject)
+    }
+
+    fn tally_votes(dispute: &Dispute) -> (u32, u32) {
+        let approvals = dispute.votes.values().iter().filter(|vote| *vote).count() as u32;
+        (approvals, dispute.votes.len() - approvals)
+    }
+
+    /// Record a ruling. First-instance rulings wait out the appeal window;
+    /// appeal rulings are final and settle immediately, releasing or
+    /// forfeiting the appeal bond. Returns fee paid (0 while provisional).
+    fn apply_ruling(
+        env: &Env,
+        job_id: u32,
+        job: &mut Job,
+        dispute: &mut Dispute,
+        ruling: Ruling,
+        deciders: Vec<Address>,
+        actor: &Address,
+    ) -> i128 {
+        Self::touch_arbitrators(env, &deciders);
+        match dispute.appeal.get() {
+            // Parties waived appeals: the ruling is final
+            None if !Self::appeals_allowed(job) => {
+                Self::settle_dispute(env, job_id, job, dispute, ruling, &deciders, actor)
+            }
+            None => {
+                dispute.pending_ruling = OptRuling::Some(ruling);
+                dispute.deciders = deciders;
+                dispute.appeal_deadline = env.ledger().timestamp() + APPEAL_WINDOW;
+                Self::save_dispute(env, job_id, dispute);
+                0
+            }
+            Some(appeal) => {
+                if appeal.original_ruling == ruling {
+                    Self::accrue_fees(env, &job.token, appeal.bond);
+                } else {
+                    Self::escrow_out(env, job_id, &job.token, &appeal.appellant, appeal.bond);
+                }
+                Self::settle_dispute(env, job_id, job, dispute, ruling, &deciders, actor)
+            }
+        }
+    }
+
+    /// Pay arbitration fee split across `paid_arbitrators`, apply the ruling
+    /// and close the dispute. Returns total fee paid out of escrow.
+    fn settle_dispute(
+        env: &Env,
+        job_id: u32,
+        job: &mut Job,
+        dispute: &Dispute,
+        ruling: Ruling,
+        paid_arbitrators: &Vec<Address>,
+        actor: &Address,
+    ) -> i128 {
+        // Pay arbitrators their own fee rate, shared across the panel
+        // (none for default rulings), up to what was set aside when the
+        // dispute was raised
+        let mut budget = dispute.fee_deposit + dispute.fee_reserved;
+        let mut fee_paid: i128 = 0;
+        for arbitrator in paid_arbitrators.iter() {
+            let fee_percentage = Self::arbitrator_fee_percentage(env, job, &arbitrator);
+            let share = (job.total_value * fee_percentage / 100 / paid_arbitrators.len() as i128)
+                .min(budget);
+            if share > 0 {
+                Self::escrow_out(env, job_id, &job.token, &arbitrator, share);
+                fee_paid += share;
+                budget -= share;
+            }
+        }
+
+        // Loser pays. The initiator's deposit covers the fee unless:
+        // - the talent initiated and won: the client pays out of the escrow
+        //   reserved at dispute time;
+        // - the client initiated and won: the talent pays out of their share
+        //   of a split ruling.
+        // Any shortfall stays with the deposit, so milestone funds are never
+        // touched.
+        let talent_won = match ruling {
+            Ruling::Approve => true,
+            Ruling::Reject => false,
+            Ruling::Split(talent_bps) => talent_bps as i128 * 2 >= BPS_DENOM,
+        };
+        let initiator_is_talent = dispute.raised_by != job.client;
+        let fee_from_escrow = if initiator_is_talent && talent_won {
+            fee_paid.min(dispute.fee_reserved)
+        } else {
+            (fee_paid - dispute.fee_deposit).max(0)
+        };
+        let talent_charge = if !initiator_is_talent && !talent_won {
+            fee_paid - fee_from_escrow
+        } else {
+            0
+        };
+        let mut fee_from_talent = 0;
+
+        Self::ledger(env, job).charge_fee(fee_from_escrow);
+
+        // Process decision
+        match (ruling, dispute.milestone_idx) {
+            // Funded stage: nobody hired yet, so the only remedies are
+            // hiring the claimant or refunding the client
+            (Ruling::Approve, None) if job.talent.is_none() => {
+                Self::settle_hiring(env, job_id, job, &dispute.raised_by, true, actor);
+            }
+            (Ruling::Reject, None) if job.talent.is_none() => {
+                Self::settle_hiring(env, job_id, job, &dispute.raised_by, false, actor);
+            }
+            (Ruling::Approve, Some(idx)) => {
+                Self::approve_milestone_internal(env, job_id, job, idx);
+            }
+            (Ruling::Approve, None) => Self::approve_all_milestones(env, job_id, job),
+            (Ruling::Reject, Some(idx)) => Self::reject_milestone(env, job, idx),
+            (Ruling::Reject, None) => Self::reject_all_milestones(env, job),
+            (Ruling::Split(talent_bps), Some(idx)) => {
+                fee_from_talent = Self::split_milestone(env, job_id, job, idx, talent_bps, talent_charge);
+            }
+            (Ruling::Split(_), None) => panic_with_error!(env, Error::InvalidIndex),
+        }
+
+        let deposit_refund = dispute.fee_deposit - (fee_paid - fee_from_escrow - fee_from_talent);
+        if deposit_refund > 0 {
+            Self::escrow_out(env, job_id, &job.token, &dispute.raised_by, deposit_refund);
+        }
+
+        // A lost dispute forfeits the talent's work bond to the client
+        if !talent_won && job.state != JobState::Cancelled {
+            let client = job.client.clone();
+            let forfeited = Self::release_bond(env, job_id, job, &client);
+            if forfeited > 0 {
+                let talent = job.talent.clone().unwrap();
+                Self::emit(env, BOND_FFT, job_id, &talent, PaymentMade {
+                    version: EVENT_VERSION,
+                    index: None,
+                    amount: forfeited,
+                    fee: 0,
+                });
+            }
+        }
+
+        // Update job state
+        if job.state != JobState::Cancelled {
+            Self::set_state(env, job_id, job, JobState::Active, actor);
+            Self::check_completion(env, job_id, job, actor);
+        }
+        Self::update_job(env, job_id, job);
+        env.storage().persistent().remove(&JobKey::Dispute(job_id));
+        let elapsed = env.ledger().timestamp().saturating_sub(dispute.raised_at);
+        Self::update_stats(env, |stats| {
+            stats.disputes_resolved += 1;
+            stats.resolution_time_total += elapsed;
+            stats.avg_resolution_time = stats.resolution_time_total / stats.disputes_resolved as u64;
+        });
+        Self::reassign_arbitrators(env, &dispute.arbitrators, &Vec::new(env));
+
+        // Credit deciders and open a rating window for the parties
+        let mut arbitrators = Self::get_arbitrators(env);
+        for arbitrator in paid_arbitrators.iter() {
+            if let Some(mut record) = arbitrators.get(arbitrator.clone()) {
+                record.cases_handled += 1;
+                arbitrators.set(arbitrator, record);
+            }
+        }
+        Self::save_arbitrators(env, &arbitrators);
+        env.storage().persistent().set(&JobKey::RatingWindow(job_id), &RatingWindow {
+            deciders: paid_arbitrators.clone(),
+            rated_by: Vec::new(env),
+        });
+
+        fee_paid
+    }
+
+    /// Settle a Funded-stage dispute: either hire the claimant on the job's
+    /// posted terms, or cancel and refund the client (and the claimant's bond)
+    fn settle_hiring(
+        env: &Env,
+        job_id: u32,
+        job: &mut Job,
+        claimant: &Address,
+        hire: bool,
+        actor: &Address,
+    ) {
+        if hire {
+            job.talent = Some(claimant.clone());
+            job.offer = None.into();
+            Self::adjust_active_jobs(env, claimant, true);
+            Self::set_state(env, job_id, job, JobState::Active, actor);
+            Self::start_work(env, job);
+            Self::emit(env, TAL_SEL, job_id, claimant, TalentHired {
+                version: EVENT_VERSION,
+                client: job.client.clone(),
+                bond: job.bond_posted,
+            });
+        } else {
+            let refund_amount = Self::ledger(env, job).refund_all();
+            if refund_amount > 0 {
+                Self::refund_escrow(env, job_id, job, refund_amount);
+            }
+            Self::release_bond(env, job_id, job, claimant);
+            Self::set_state(env, job_id, job, JobState::Cancelled, actor);
+        }
+    }
+
+    /// Load a dispute decided by a single, approved arbitrator
+    fn load_sole_arbitrator_dispute(env: &Env, job_id: u32, arbitrator: &Address) -> Dispute {
+        let dispute = Self::load_dispute(env, job_id);
+        if dispute.arbitrators != Vec::from_array(env, [arbitrator.clone()]) {
+            panic_with_error!(env, Error::NotArbitrator);
+        }
+        if dispute.awaiting_approval.is_some() {
+            panic_with_error!(env, Error::ArbitratorNotApproved);
+        }
+        if dispute.pending_ruling.is_some() {
+            panic_with_error!(env, Error::RulingPending);
+        }
+        dispute
+    }
+
+    fn has_open_dispute(env: &Env, job_id: u32) -> bool {
+        env.storage().persistent().has(&JobKey::Dispute(job_id))
+    }
+
+    /// Client escrow set aside for the open dispute's arbitration fee
+    fn reserved_for_dispute(env: &Env, job_id: u32) -> i128 {
+        env.storage().persistent().get::<_, Dispute>(&JobKey::Dispute(job_id))
+            .map(|dispute| dispute.fee_reserved)
+            .unwrap_or(0)
+    }
+
+    fn load_dispute(env: &Env, job_id: u32) -> Dispute {
+        env.storage().persistent().get(&JobKey::Dispute(job_id))
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidState))
+    }
+
+    fn save_dispute(env: &Env, job_id: u32, dispute: &Dispute) {
+        env.storage().persistent().set(&JobKey::Dispute(job_id), dispute);
+        Self::extend_persistent(env, &JobKey::Dispute(job_id));
+    }
+
+    fn approve_all_milestones(env: &Env, job_id: u32, job: &mut Job) {
+        for i in 0..job.milestones.len() {
+            let milestone = job.milestones.get(i).unwrap();
+            if matches!(milestone.state, MilestoneState::Submitted) {
+                Self::approve_milestone_internal(env, job_id, job, i);
+            }
+        }
+    }
+
+    /// Settle a milestone partially: talent gets `talent_bps` of the amount
+    /// (net of platform fee), the client is refunded the rest. Up to
+    /// `talent_charge` of the talent's share is withheld for an arbitration
+    /// fee the talent owes; returns the amount withheld.
+    fn split_milestone(
+        env: &Env,
+        job_id: u32,
+        job: &mut Job,
+        idx: u32,
+        talent_bps: u32,
+        talent_charge: i128,
+    ) -> i128 {
+        let mut milestone = job.milestones.get(idx)
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidIndex));
+        // Partial payouts do not fund subcontracts
+        Self::void_subjobs(env, &milestone);
+        let amount = milestone.amount;
+        let talent_share = amount * talent_bps as i128 / BPS_DENOM;
+        let withheld = talent_charge.clamp(0, talent_share);
+        let talent_amount = talent_share - withheld;
+        let client_amount = amount - talent_share;
+
+        if talent_amount > 0 {
+            let payee = Self::milestone_payee(env, job, &milestone);
+            Self::pay_worker(env, job_id, job, &payee, talent_amount);
+        }
+        if client_amount > 0 {
+            Self::refund_escrow(env, job_id, job, client_amount);
+        }
+
+        milestone.state = MilestoneState::Paid;
+        job.milestones.set(idx, milestone);
+        let mut ledger = Self::ledger(env, job);
+        ledger.release(talent_amount);
+        ledger.refund(client_amount);
+        ledger.charge_fee(withheld);
+        withheld
+    }
+
+    fn reject_milestone(env: &Env, job: &mut Job, idx: u32) {
+        let mut milestone = job.milestones.get(idx)
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidIndex));
+
+        Self::void_subjobs(env, &milestone);
+        milestone.state = MilestoneState::Rejected;
+        job.milestones.set(idx, milestone);
+    }
+
+    fn reject_all_milestones(env: &Env, job: &mut Job) {
+        for i in 0..job.milestones.len() {
+            let mut milestone = job.milestones.get(i).unwrap();
+            if matches!(milestone.state, MilestoneState::Submitted) {
+                Self::void_subjobs(env, &milestone);
+                milestone.state = MilestoneState::Rejected;
+                job.milestones.set(i, milestone);
+            }
+        }
+    }
+
+    fn validate_action(env: &Env, action: &AdminAction) {
+        match action {
+            AdminAction::SetPlatformFee(fee_bps) => {
+                if *fee_bps > MAX_PLAT_FEE_BPS {
+                    panic_with_error!(env, Error::FeeTooHigh);
+                }
+            }
+            AdminAction::RemoveArbitrator(arbitrator) => {
+                if !Self::is_arbitrator(env, arbitrator) {
+                    panic_with_error!(env, Error::NotArbitrator);
+                }
+            }
+            // A shorter delay would let the admin outrun users' exit window
+            AdminAction::SetTimelockDelay(delay) => {
+                if *delay < MIN_TL_DELAY {
+                    panic_with_error!(env, Error::InvalidInput);
+                }
+            }
+            AdminAction::SetMinArbStake(amount) | AdminAction::SetKeeperBounty(amount) => {
+                if *amount < 0 {
+                    panic_with_error!(env, Error::InvalidInput);
+                }
+            }
+            AdminAction::SetDefaultRuling(Ruling::Split(talent_bps)) => {
+                if *talent_bps as i128 > BPS_DENOM {
+                    panic_with_error!(env, Error::InvalidInput);
+                }
+            }
+            AdminAction::SetMaxArbitratorFee(fee_percentage) => {
+                if *fee_percentage < 0 || *fee_percentage > 100 {
+                    panic_with_error!(env, Error::InvalidInput);
+                }
+            }
+            AdminAction::SetMaxCancellationFee(fee_bps) => {
+                if *fee_bps as i128 > BPS_DENOM {
+                    panic_with_error!(env, Error::InvalidInput);
+                }
+            }
+            AdminAction::SetJobLimits(limits) => {
+                if limits.max_milestones == 0 || limits.max_open_jobs == 0 || limits.max_uri_len == 0
+                    || limits.max_milestones > MAX_MILESTONES_CAP || limits.max_uri_len > MAX_TEXT_LEN
+                {
+                    panic_with_error!(env, Error::InvalidInput);
+                }
+            }
+            AdminAction::SetTextLimits(title_max, description_max) => {
+                if *title_max == 0 || *description_max == 0
+                    || *title_max > MAX_TEXT_LEN || *description_max > MAX_TEXT_LEN
+                {
+                    panic_with_error!(env, Error::InvalidInput);
+                }
+            }
+            AdminAction::SetInsuranceShare(share_bps) | AdminAction::SetReferralReward(share_bps) => {
+                if *share_bps as i128 > BPS_DENOM {
+                    panic_with_error!(env, Error::InvalidInput);
+                }
+            }
+            AdminAction::SetYieldStrategy(OptYieldConfig::Some(config)) => {
+                if config.client_share_bps as i128 > BPS_DENOM {
+                    panic_with_error!(env, Error::InvalidInput);
+                }
+            }
+            AdminAction::AddHook(event_kind, target) => {
+                Self::check_hook_slot(env, event_kind, target);
+            }
+            AdminAction::SetTokenAllowed(_, _)
+            | AdminAction::SetOracleAllowed(_, _)
+            | AdminAction::SetRouterAllowed(_, _)
+            | AdminAction::SetDefaultRuling(_)
+            | AdminAction::SetYieldStrategy(OptYieldConfig::None) => {}
+        }
+    }
+
+    fn timelock_delay(env: &Env) -> u64 {
+        env.storage().instance().get(&ConfigKey::TimelockDelay)
+            .unwrap_or(DEFAULT_TL_DELAY)
+    }
+
+    fn get_action_queue(env: &Env) -> Map<u32, QueuedAction> {
+        env.storage().persistent().get(&AccountKey::ActionQueue)
+            .unwrap_or_else(|| Map::new(env))
+    }
+
+    fn get_allowed_tokens(env: &Env) -> Map<Address, bool> {
+        env.storage().instance().get(&ConfigKey::AllowedTokens)
+            .unwrap_or_else(|| Map::new(env))
+    }
+
+    /// Add the next retainer milestone, funded from the client's allowance.
+    /// Shared by `advance_period` and keepers.
+    fn advance_retainer(env: &Env, job_id: u32) -> u32 {
+        let mut job = Self::get_job(env, job_id);
+        let terms = job.payment.retainer.get()
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidState));
+        if job.state != JobState::Active || job.payment.periods_advanced >= terms.max_periods {
+            panic_with_error!(env, Error::InvalidState);
+        }
+        let now = env.ledger().timestamp();
+        if now < job.payment.next_period_at {
+            panic_with_error!(env, Error::TooEarly);
+        }
+
+        let allowance = token::Client::new(env, &job.token)
+            .allowance(&job.client, &env.current_contract_address());
+        if allowance < terms.payment_per_period {
+            panic_with_error!(env, Error::InsufficientFunds);
+        }
+        Self::escrow_in(env, job_id, &job.token, &job.client, terms.payment_per_period, true);
+
+        // Work for the new period is due when the following one starts
+        job.payment.next_period_at += terms.period;
+        job.payment.periods_advanced += 1;
+        job.milestones.push_back(Milestone {
+            description: job.title.clone(),
+            amount: terms.payment_per_period,
+            state: MilestoneState::Pending,
+            submission_data: BytesN::from_array(env, &[0; 32]),
+            submission_uri: None,
+            deadline: job.payment.next_period_at,
+            submitted_at: None,
+            vests_at: None,
+            subjobs: Vec::new(env),
+            payee: None,
+        });
+        job.total_value += terms.payment_per_period;
+        Self::ledger(env, &mut job).deposit(terms.payment_per_period);
+
+        let milestone_idx = job.milestones.len() - 1;
+        Self::update_job(env, job_id, &job);
+
+        Self::emit(env, RET_ADV, job_id, &job.client, PaymentMade {
+            version: EVENT_VERSION,
+            index: Some(milestone_idx),
+            amount: terms.payment_per_period,
+            fee: 0,
+        });
+        milestone_idx
+    }
+
+    /// Time-based maintenance due on a job, if any
+    fn keeper_task(env: &Env, job_id: u32, job: &Job) -> Option<KeeperTask> {
+        let now = env.ledger().timestamp();
+        if job.state == JobState::Created && now >= job.expires_at {
+            return Some(KeeperTask::ExpireUnfunded);
+        }
+        if Self::has_open_dispute(env, job_id) {
+            let dispute = Self::load_dispute(env, job_id);
+            let (task, due) = if dispute.pending_ruling.is_some() {
+                (KeeperTask::ExecuteRuling, now > dispute.appeal_deadline)
+            } else if !dispute.votes.is_empty() {
+                (
+                    KeeperTask::FinalizeDispute,
+                    dispute.arbitrators.len() >= 2 && now > dispute.voting_deadline,
+                )
+            } else {
+                (
+                    KeeperTask::TimeoutDispute,
+                    dispute.awaiting_approval.is_none() && now > dispute.ruling_deadline,
+                )
+            };
+            return if due { Some(task) } else { None };
+        }
+        if let Some(terms) = job.payment.retainer.as_ref() {
+            if job.state == JobState::Active
+                && job.payment.periods_advanced < terms.max_periods
+                && now >= job.payment.next_period_at
+            {
+                return Some(KeeperTask::AdvancePeriod);
+            }
+        }
+        None
+    }
+
+    fn get_draft_record(env: &Env, draft_id: u32) -> Draft {
+        env.storage().persistent().get(&JobKey::Draft(draft_id))
+            .unwrap_or_else(|| panic_with_error!(env, Error::JobNotFound))
+    }
+
+    fn save_draft(env: &Env, draft_id: u32, draft: &Draft) {
+        let key = JobKey::Draft(draft_id);
+        env.storage().persistent().set(&key, draft);
+        Self::extend_persistent(env, &key);
+    }
+
+    /// Milestone terms must cover every milestone with positive amounts
+    fn check_terms(env: &Env, milestones: u32, amounts: &Vec<i128>, deadlines: &Vec<u64>) {
+        if milestones == 0 || amounts.len() != milestones || deadlines.len() != milestones {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+        if milestones > Self::job_limits(env).max_milestones {
+            panic_with_error!(env, Error::TooManyMilestones);
+        }
+        if amounts.iter().any(|amount| amount <= 0) {
+            panic_with_error!(env, Error::AmountRequired);
+        }
+    }
+
+    fn get_hook_map(env: &Env) -> Map<Symbol, Vec<Address>> {
+        env.storage().instance().get(&ConfigKey::Hooks)
+            .unwrap_or_else(|| Map::new(env))
+    }
+
+    fn get_allowed_oracles(env: &Env) -> Map<Address, bool> {
+        env.storage().instance().get(&ConfigKey::AllowedOracles)
+            .unwrap_or_else(|| Map::new(env))
+    }
+
+    fn get_allowed_routers(env: &Env) -> Map<Address, bool> {
+        env.storage().instance().get(&ConfigKey::AllowedRouters)
+            .unwrap_or_else(|| Map::new(env))
+    }
+
+    fn state_version(env: &Env) -> u32 {
+        env.storage().instance().get(&ConfigKey::StateVersion)
+            .unwrap_or(0u32)
+    }
+
+    fn paused(env: &Env) -> bool {
+        env.storage().instance().get(&ConfigKey::Paused)
+            .unwrap_or(false)
+    }
+
+    /// Every state-changing entrypoint passes through here, so config in
+    /// instance storage is kept alive as a side effect
+    fn check_not_paused(env: &Env) {
+        if Self::paused(env) {
+            panic_with_error!(env, Error::ContractPaused);
+        }
+        Self::extend_instance(env);
+    }
+
+    fn extend_instance(env: &Env) {
+        env.storage().instance().extend_ttl(INSTANCE_TTL_THRESHOLD, INSTANCE_TTL);
+    }
+
+    /// Extend a persistent entry's TTL if it exists
+    fn extend_persistent<K: IntoVal<Env, Val>>(env: &Env, key: &K) {
+        if env.storage().persistent().has(key) {
+            env.storage().persistent().extend_ttl(key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL);
+        }
+    }
+
+    fn check_reentrancy(env: &Env) -> ReentrancyGuard<'_> {
+        if env.storage().temporary().has(&ConfigKey::ReentrancyLock) {
+            panic_with_error!(env, Error::Reentrancy);
+        }
+        env.storage().temporary().set(&ConfigKey::ReentrancyLock, &true);
+        ReentrancyGuard { env }
+    }
+
+    fn ledger<'a>(env: &'a Env, job: &'a mut Job) -> EscrowLedger<'a> {
+        EscrowLedger { env, job }
+    }
+
+    /// Escrow matches its ledger counters and no counter is negative
+    fn ledger_balanced(job: &Job) -> bool {
+        let counters = [job.escrow_balance, job.deposited, job.amount_paid, job.fees_charged, job.refunded];
+        counters.iter().all(|c| *c >= 0)
+            && job.deposited.checked_sub(job.amount_paid)
+                .and_then(|v| v.checked_sub(job.fees_charged))
+                .and_then(|v| v.checked_sub(job.refunded))
+                == Some(job.escrow_balance)
+    }
+
+    fn load_timesheets(env: &Env, job_id: u32) -> Vec<TimeLog> {
+        env.storage().persistent().get(&JobKey::Timesheets(job_id))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    fn get_template_record(env: &Env, template_id: u32) -> JobTemplate {
+        env.storage().persistent().get(&JobKey::Template(template_id))
+            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidInput))
+    }
+
+    /// Store a new job under the next counter id, or under `derived_id`.
+    /// Derived jobs still take a sequence number so paging by sequence
+    /// (keepers, migrations) reaches them through `JobKey::JobAt`.
+    fn save_job(env: &Env, job: &Job, derived_id: Option<u32>) -> u32 {
+        let mut count = env.storage().instance().get(&ConfigKey::JobCount)
+            .unwrap_or(0u32);
+        count += 1;
+        env.storage().instance().set(&ConfigKey::JobCount, &count);
+        let job_id = match derived_id {
+            Some(job_id) => {
+                let key = JobKey::JobAt(count);
+                env.storage().persistent().set(&key, &job_id);
+                Self::extend_persistent(env, &key);
+                job_id
+            }
+            None => count,
+        };
+        Self::update_job(env, job_id, job);
+        job_id
+    }
+
+    /// Job id for a creation sequence number
+    fn job_id_at(env: &Env, seq: u32) -> u32 {
+        env.storage().persistent().get(&JobKey::JobAt(seq)).unwrap_or(seq)
+    }
+
+    /// Consume the client's next nonce and derive the job id from
+    /// sha256(client, nonce). An id already in use moves on to the next
+    /// nonce, so a collision never leaves the client's sequence stuck.
+    fn derive_job_id(env: &Env, client: &Address, nonce: u64) -> u32 {
+        let key = AccountKey::ClientNonce(client.clone());
+        let expected: u64 = env.storage().persistent().get(&key).unwrap_or(0);
+        if nonce != expected {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+
+        let mut next = nonce;
+        let mut job_id = Self::job_id_for(env, client, next);
+        while Self::job_id_taken(env, job_id) {
+            next += 1;
+            job_id = Self::job_id_for(env, client, next);
+        }
+        env.storage().persistent().set(&key, &(next + 1));
+        Self::extend_persistent(env, &key);
+
+        // Looked up by the nonce the client asked for, whoever owns the job later
+        let index = AccountKey::NonceJob(client.clone(), nonce);
+        env.storage().persistent().set(&index, &job_id);
+        Self::extend_persistent(env, &index);
+        job_id
+    }
+
+    /// Live or archived jobs keep their id for good
+    fn job_id_taken(env: &Env, job_id: u32) -> bool {
+        env.storage().persistent().has(&JobKey::Job(job_id))
+            || env.storage().persistent().has(&JobKey::Archived(job_id))
+    }
+
+    fn job_id_for(env: &Env, client: &Address, nonce: u64) -> u32 {
+        let digest = env.crypto().sha256(&(client.clone(), nonce).to_xdr(env)).to_array();
+        u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]) | DERIVED_ID_FLAG
+    }
+
+    fn update_job(env: &Env, job_id: u32, job: &Job) {
+        if !Self::ledger_balanced(job) {
+            panic_with_error!(env, Error::EscrowMismatch);
+        }
+        // Every write moves the job to a new sequence number
+        let mut job = job.clone();
+        job.action_seq += 1;
+        let key = JobKey::Job(job_id);
+        env.storage().persistent().set(&key, &job);
+        env.storage().persistent().extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL);
+    }
+
+    /// Fail if the job moved on since the caller read `expected_seq`
+    fn check_seq(env: &Env, job_id: u32, expected_seq: u64) {
+        if Self::get_job(env, job_id).action_seq != expected_seq {
+            panic_with_error!(env, Error::StaleState);
+        }
+    }
+
+    fn job_terms(env: &Env, job_id: u32, job: &Job) -> JobTerms {
+        let mut descriptions = Vec::new(env);
+        let mut amounts = Vec::new(env);
+        let mut deadlines = Vec::new(env);
+        let mut payees = Vec::new(env);
+        for milestone in job.milestones.iter() {
+            descriptions.push_back(milestone.description);
+            amounts.push_back(milestone.amount);
+            deadlines.push_back(milestone.deadline);
+            payees.push_back(milestone.payee);
+        }
+        // Priced amounts are converted at funding; the agreed figures are
+        // the reference amounts
+        if let Some(pricing) = job.payment.pricing.as_ref() {
+            amounts = pricing.reference_amounts.clone();
         }
+        JobTerms {
+            version: TERMS_VERSION,
+            job_id,
+            client: job.client.clone(),
+            talent: job.talent.clone(),
+            agency: job.roles.agency.clone(),
+            token: job.token.clone(),
+            title: job.title.clone(),
+            descriptions,
+            amounts,
+            deadlines,
+            payees,
+            pricing: job.payment.pricing.as_ref().map(|pricing| pricing.terms.clone()).into(),
+            talent_bond: job.talent_bond,
+            cancellation_fee_bps: job.cancel_terms.cancellation_fee_bps,
+            kill_fee_tiers: job.cancel_terms.kill_fee_tiers.clone(),
+            cancellation_notice: job.cancel_terms.cancellation_notice,
+            release_delay: job.release_delay,
+            dispute_policy: job.dispute_policy.clone(),
+            spec_hash: job.metadata.as_ref().map(|metadata| metadata.hash.clone()).into(),
+        }
+    }
+
+    /// Look up a job without trapping, for read-only entry points
+    fn find_job(env: &Env, job_id: u32) -> Option<Job> {
+        env.storage().persistent().get(&JobKey::Job(job_id))
+    }
+
+    fn get_job(env: &Env, job_id: u32) -> Job {
+        let key = JobKey::Job(job_id);
+        let job = env.storage().persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic_with_error!(env, Error::JobNotFound));
+        env.storage().persistent().extend_ttl(&key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL);
+        job
+    }
+
+    fn get_token_id(env: &Env) -> Address {
+        env.storage().instance().get(&ConfigKey::Token)
+            .unwrap_or_else(|| panic_with_error!(env, Error::TokenNotSet))
+    }
 
-        let mut milestone = job.milestones.get(milestone_idx)
-            .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidIndex));
-            
-        if milestone.state != MilestoneState::Submitted {
-            panic_with_error!(&env, Error::NotSubmitted);
+    fn talent_profile(env: &Env, talent: &Address) -> Option<TalentProfile> {
+        env.storage().persistent().get(&AccountKey::Talent(talent.clone()))
+    }
+
+    fn save_talent_profile(env: &Env, talent: &Address, profile: &TalentProfile) {
+        env.storage().persistent().set(&AccountKey::Talent(talent.clone()), profile);
+    }
+
+    /// Count a job the talent was hired on (or one that finished)
+    fn adjust_active_jobs(env: &Env, talent: &Address, hired: bool) {
+        if let Some(mut profile) = Self::talent_profile(env, talent) {
+            profile.active_jobs = if hired {
+                profile.active_jobs + 1
+            } else {
+                profile.active_jobs.saturating_sub(1)
+            };
+            Self::save_talent_profile(env, talent, &profile);
         }
+    }
 
-        // Transfer payment
-        let token_id = Self::get_token_id(&env);
-        token::Client::new(&env, &token_id).transfer(
-            &env.current_contract_address(),
-            &job.talent.unwrap(),
-            &milestone.amount
-        );
+    fn client_profile(env: &Env, client: &Address) -> ClientProfile {
+        env.storage().persistent().get(&AccountKey::Client(client.clone()))
+            .unwrap_or_default()
+    }
 
-        // Update state
-        milestone.state = MilestoneState::Paid;
-        job.milestones.set(milestone_idx, milestone);
-        job.amount_paid += milestone.amount;
-        job.escrow_balance -= milestone.amount;
+    fn save_client_profile(env: &Env, client: &Address, profile: &ClientProfile) {
+        env.storage().persistent().set(&AccountKey::Client(client.clone()), profile);
+    }
 
-        // Check completion
-        if job.milestones.iter().all(|m| matches!(m.state, MilestoneState::Paid)) {
-            job.state = JobState::Completed;
-        }
+    fn get_reviews(env: &Env, address: &Address, number: u32) -> Vec<Review> {
+        env.storage().persistent().get(&AccountKey::Reviews(address.clone(), number))
+            .unwrap_or_else(|| Vec::new(env))
+    }
 
-        Self::update_job(&env, job_id, &job);
-        env.events().publish(
-            (MIL_APR, client),
-            (job_id, milestone_idx, milestone.amount)
-        );
+    fn get_rating_stats(env: &Env, address: &Address) -> RatingStats {
+        env.storage().persistent().get(&AccountKey::Rating(address.clone()))
+            .unwrap_or_default()
     }
 
-    // =================
-    // DISPUTE RESOLUTION
-    // =================
-    /// Raise dispute for job/milestone
-    /// @param env: Soroban environment
-    /// @param caller: Dispute initiator
-    /// @param job_id: Job identifier
-    /// @param milestone_idx: Optional milestone index
-    /// @param arbitrator: Chosen arbitrator address
-    pub fn raise_dispute(
-        env: Env,
-        caller: Address,
-        job_id: u32,
-        milestone_idx: Option<u32>,
-        arbitrator: Address,
-    ) {
-        caller.require_auth();
-        Self::check_reentrancy(&env);
+    fn average_rating(env: &Env, address: &Address) -> u32 {
+        let stats = Self::get_rating_stats(env, address);
+        (stats.total_score * 100).checked_div(stats.count).unwrap_or(0)
+    }
 
-        let mut job = Self::get_job(&env, job_id);
-        if job.state == JobState::Disputed {
-            panic_with_error!(&env, Error::ArbitrationPending);
-        }
-        if job.state != JobState::Active {
-            panic_with_error!(&env, Error::InvalidState);
+    /// Gate for configuration endpoints: caller must be the stored admin
+    fn require_admin(env: &Env, admin: &Address) {
+        admin.require_auth();
+        if Self::get_admin_address(env) != *admin {
+            panic_with_error!(env, Error::Unauthorized);
         }
+    }
 
-        // Verify caller is client or talent
-        let is_client = job.client == caller;
-        let is_talent = job.talent == Some(caller.clone());
-        if !is_client && !is_talent {
-            panic_with_error!(&env, Error::Unauthorized);
+    /// Validate and store the initial configuration (once)
+    fn configure(env: &Env, config: &InitConfig) {
+        if env.storage().instance().has(&ConfigKey::Token) {
+            panic_with_error!(env, Error::InvalidState);
         }
+        // Same bounds as the timelocked setters
+        Self::validate_action(env, &AdminAction::SetPlatformFee(config.platform_fee_bps));
+        Self::validate_action(env, &AdminAction::SetMaxArbitratorFee(config.max_arbitrator_fee));
+        Self::validate_action(env, &AdminAction::SetMaxCancellationFee(config.max_cancellation_fee_bps));
+        Self::validate_action(env, &AdminAction::SetMinArbStake(config.min_arbitrator_stake));
+        Self::validate_action(env, &AdminAction::SetTimelockDelay(config.timelock_delay));
+        Self::validate_action(env, &AdminAction::SetTextLimits(config.text_limits.0, config.text_limits.1));
+        Self::validate_action(env, &AdminAction::SetJobLimits(config.job_limits.clone()));
 
-        // Verify arbitrator exists
-        if !Self::is_arbitrator(&env, &arbitrator) {
-            panic_with_error!(&env, Error::NotArbitrator);
+        let storage = env.storage().instance();
+        storage.set(&ConfigKey::Token, &config.token);
+        storage.set(&ConfigKey::Admin, &config.admin);
+        storage.set(&ConfigKey::PlatformFee, &config.platform_fee_bps);
+        storage.set(&ConfigKey::StateVersion, &STATE_VERSION);
+        storage.set(&ConfigKey::TimelockDelay, &config.timelock_delay);
+        storage.set(&ConfigKey::MinArbStake, &config.min_arbitrator_stake);
+        storage.set(&ConfigKey::MaxArbitratorFee, &config.max_arbitrator_fee);
+        storage.set(&ConfigKey::MaxCancellationFee, &config.max_cancellation_fee_bps);
+        storage.set(&ConfigKey::TextLimits, &config.text_limits);
+        storage.set(&ConfigKey::JobLimits, &config.job_limits);
+        Self::extend_instance(env);
+    }
+
+    fn get_admin_address(env: &Env) -> Address {
+        env.storage().instance().get(&ConfigKey::Admin)
+            .unwrap_or_else(|| panic_with_error!(env, Error::AdminNotSet))
+    }
+
+    fn platform_fee_bps(env: &Env) -> u32 {
+        env.storage().instance().get(&ConfigKey::PlatformFee)
+            .unwrap_or(0)
+    }
+
+    fn get_treasury(env: &Env) -> Map<Address, i128> {
+        env.storage().persistent().get(&AccountKey::Treasury)
+            .unwrap_or_else(|| Map::new(env))
+    }
+
+    /// Take a yield loss out of accrued platform fees
+    fn cover_from_treasury(env: &Env, token: &Address, amount: i128) {
+        if Self::get_treasury(env).get(token.clone()).unwrap_or(0) < amount {
+            panic_with_error!(env, Error::InsufficientFunds);
         }
+        Self::accrue_fees(env, token, -amount);
+    }
 
-        // If milestone specified, validate it
-        if let Some(idx) = milestone_idx {
-            let milestone = job.milestones.get(idx)
-                .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidIndex));
-            if milestone.state != MilestoneState::Submitted {
-                panic_with_error!(&env, Error::NotSubmitted);
-            }
+    /// Credit a completed job's referrer with a share of the platform fees
+    /// actually collected on it, taken from the treasury. Talents cannot
+    /// refer themselves.
+    fn reward_referrer(env: &Env, job_id: u32, job: &Job) {
+        let referrer = match &job.roles.referrer {
+            Some(referrer) if job.talent.as_ref() != Some(referrer) => referrer.clone(),
+            _ => return,
+        };
+        let reward_bps: u32 = env.storage().instance().get(&ConfigKey::ReferralReward)
+            .unwrap_or(DEFAULT_REFERRAL_BPS);
+        let mut treasury = Self::get_treasury(env);
+        let accrued = treasury.get(job.token.clone()).unwrap_or(0);
+        let reward = (job.platform_fees * reward_bps as i128 / BPS_DENOM).min(accrued);
+        if reward <= 0 {
+            return;
         }
+        treasury.set(job.token.clone(), accrued - reward);
+        env.storage().persistent().set(&AccountKey::Treasury, &treasury);
 
-        // Update job state
-        job.state = JobState::Disputed;
-        job.dispute_raised_by = Some(caller.clone());
-        job.selected_arbitrator = Some(arbitrator.clone());
-        Self::update_job(&env, job_id, &job);
+        let key = AccountKey::ReferralEarnings(referrer.clone());
+        let mut earnings = Self::get_referral_earnings(env.clone(), referrer.clone());
+        earnings.set(job.token.clone(), earnings.get(job.token.clone()).unwrap_or(0) + reward);
+        env.storage().persistent().set(&key, &earnings);
+        Self::extend_persistent(env, &key);
 
-        env.events().publish(
-            (DIS_RIS, caller),
-            (job_id, milestone_idx, arbitrator)
-        );
+        Self::emit(env, REF_PAY, job_id, &referrer, ReferralRewarded {
+            version: EVENT_VERSION,
+            referrer: referrer.clone(),
+            token: job.token.clone(),
+            amount: reward,
+        });
     }
 
-    /// Resolve dispute (arbitrator only)
-    /// @param env: Soroban environment
-    /// @param arbitrator: Arbitrator address
-    /// @param job_id: Job identifier
-    /// @param milestone_idx: Optional milestone index
-    /// @param decision: true=approve, false=reject
-    pub fn resolve_dispute(
-        env: Env,
-        arbitrator: Address,
-        job_id: u32,
-        milestone_idx: Option<u32>,
-        decision: bool,
-    ) {
-        arbitrator.require_auth();
-        Self::check_reentrancy(&env);
+    fn insurance_share_bps(env: &Env) -> u32 {
+        env.storage().instance().get(&ConfigKey::InsuranceShare)
+            .unwrap_or(DEFAULT_INS_SHARE_BPS)
+    }
 
-        let mut job = Self::get_job(&env, job_id);
-        if job.state != JobState::Disputed {
-            panic_with_error!(&env, Error::InvalidState);
-        }
-        if job.selected_arbitrator != Some(arbitrator.clone()) {
-            panic_with_error!(&env, Error::NotArbitrator);
+    fn get_insurance_pool_map(env: &Env) -> Map<Address, i128> {
+        env.storage().persistent().get(&AccountKey::InsurancePool)
+            .unwrap_or_else(|| Map::new(env))
+    }
+
+    fn fund_insurance(env: &Env, token: &Address, amount: i128) {
+        if amount <= 0 {
+            return;
         }
+        let mut pool = Self::get_insurance_pool_map(env);
+        let balance = pool.get(token.clone()).unwrap_or(0);
+        pool.set(token.clone(), balance + amount);
+        env.storage().persistent().set(&AccountKey::InsurancePool, &pool);
+        Self::extend_persistent(env, &AccountKey::InsurancePool);
+    }
 
-        // Calculate arbitrator fee
-        let token_id = Self::get_token_id(&env);
-        let fee_amount = job.total_value * ARB_FEE / 100;
-        
-        // Pay arbitrator
-        token::Client::new(&env, &token_id).transfer(
-            &env.current_contract_address(),
-            &arbitrator,
-            &fee_amount
-        );
+    /// Add `delta` to a per-token running total stored under `key`
+    fn adjust_total(env: &Env, key: AccountKey, token: &Address, delta: i128) {
+        let mut totals: Map<Address, i128> = env.storage().persistent().get(&key)
+            .unwrap_or_else(|| Map::new(env));
+        let total = totals.get(token.clone()).unwrap_or(0);
+        totals.set(token.clone(), total + delta);
+        env.storage().persistent().set(&key, &totals);
+        Self::extend_persistent(env, &key);
+    }
 
-        // Process decision
-        if decision {
-            if let Some(idx) = milestone_idx {
-                Self::approve_milestone_internal(&env, &mut job, idx);
-            } else {
-                Self::approve_all_milestones(&env, &mut job);
-            }
-        } else {
-            if let Some(idx) = milestone_idx {
-                Self::reject_milestone(&env, &mut job, idx);
-            } else {
-                Self::reject_all_milestones(&env, &mut job);
-            }
-        }
+    fn load_stats(env: &Env) -> MarketplaceStats {
+        env.storage().instance().get(&ConfigKey::Stats)
+            .unwrap_or_else(|| MarketplaceStats {
+                total_jobs: 0,
+                active_jobs: 0,
+                total_escrowed: Map::new(env),
+                total_paid_out: Map::new(env),
+                disputes_raised: 0,
+                disputes_resolved: 0,
+                resolution_time_total: 0,
+                avg_resolution_time: 0,
+            })
+    }
 
-        // Update job state
-        job.escrow_balance -= fee_amount;
-        job.state = if job.milestones.iter().all(|m| matches!(m.state, MilestoneState::Paid)) {
-            JobState::Completed
-        } else {
-            JobState::Active
-        };
-        job.dispute_raised_by = None;
-        job.selected_arbitrator = None;
-        Self::update_job(&env, job_id, &job);
+    fn update_stats(env: &Env, update: impl FnOnce(&mut MarketplaceStats)) {
+        let mut stats = Self::load_stats(env);
+        update(&mut stats);
+        env.storage().instance().set(&ConfigKey::Stats, &stats);
+    }
 
-        env.events().publish(
-            (DIS_RES, arbitrator),
-            (job_id, milestone_idx, decision, fee_amount)
-        );
+    fn token_total(env: &Env, key: AccountKey, token: &Address) -> i128 {
+        env.storage().persistent().get::<AccountKey, Map<Address, i128>>(&key)
+            .and_then(|totals| totals.get(token.clone()))
+            .unwrap_or(0)
     }
 
-    // ==============
-    // JOB CANCELLATION
-    // ==============
-    /// Cancel job and refund remaining funds
-    /// @param env: Soroban environment
-    /// @param client: Job creator
-    /// @param job_id: Job identifier
-    pub fn cancel_job(env: Env, client: Address, job_id: u32) {
-        client.require_auth();
-        Self::check_reentrancy(&env);
+    fn accrue_fees(env: &Env, token: &Address, amount: i128) {
+        let mut treasury = Self::get_treasury(env);
+        let accrued = treasury.get(token.clone()).unwrap_or(0);
+        treasury.set(token.clone(), accrued + amount);
+        env.storage().persistent().set(&AccountKey::Treasury, &treasury);
+        Self::extend_persistent(env, &AccountKey::Treasury);
+    }
 
-        let mut job = Self::get_job(&env, job_id);
-        if job.client != client {
-            panic_with_error!(&env, Error::Unauthorized);
-        }
-        if matches!(job.state, JobState::Completed | JobState::Cancelled) {
-            panic_with_error!(&env, Error::JobCompleted);
+    fn get_arbitrators(env: &Env) -> Map<Address, Arbitrator> {
+        env.storage().persistent().get(&AccountKey::Arbitrators)
+            .unwrap_or_else(|| Map::new(env))
+    }
+
+    fn save_arbitrators(env: &Env, arbitrators: &Map<Address, Arbitrator>) {
+        env.storage().persistent().set(&AccountKey::Arbitrators, arbitrators);
+        Self::extend_persistent(env, &AccountKey::Arbitrators);
+    }
+
+    fn is_arbitrator(env: &Env, address: &Address) -> bool {
+        Self::get_arbitrators(env).contains_key(address.clone())
+    }
+
+    fn is_active_arbitrator(env: &Env, address: &Address) -> bool {
+        match Self::get_arbitrators(env).get(address.clone()) {
+            Some(record) => Self::is_active_record(env, &record),
+            None => false,
         }
+    }
 
-        let token_id = Self::get_token_id(&env);
-        let refund_amount = job.escrow_balance - job.cancellation_fee;
+    /// Registered, fully staked and not winding down
+    fn is_active_record(env: &Env, record: &Arbitrator) -> bool {
+        record.unstake_requested_at.is_none()
+            && record.stake >= Self::min_arbitrator_stake(env)
+    }
 
-        // Pay cancellation fee to talent if hired
-        if let Some(talent) = &job.talent {
-            token::Client::new(&env, &token_id).transfer(
-                &env.current_contract_address(),
-                talent,
-                &job.cancellation_fee
-            );
-        } else {
-            // If no talent, fee goes back to client
-            refund_amount += job.cancellation_fee;
+    /// Drop an arbitrator from the registry map (caller persists it),
+    /// returning any unslashed stake. Returns the stake refunded.
+    fn remove_arbitrator(
+        env: &Env,
+        arbitrators: &mut Map<Address, Arbitrator>,
+        arbitrator: &Address,
+    ) -> i128 {
+        let record = arbitrators.get(arbitrator.clone())
+            .unwrap_or_else(|| panic_with_error!(env, Error::NotArbitrator));
+        arbitrators.remove(arbitrator.clone());
+
+        let mut index = Self::get_specialization_index(env, &record.specialization);
+        if let Some(pos) = index.first_index_of(arbitrator) {
+            index.remove(pos);
         }
+        env.storage().persistent().set(&AccountKey::Specialization(record.specialization.clone()), &index);
 
-        // Refund remaining to client
-        if refund_amount > 0 {
-            token::Client::new(&env, &token_id).transfer(
-                &env.current_contract_address(),
-                &client,
-                &refund_amount
-            );
+        if record.stake > 0 {
+            Self::escrow_out(env, NO_JOB, &Self::get_token_id(env), arbitrator, record.stake);
         }
+        record.stake
+    }
 
-        job.state = JobState::Cancelled;
-        job.escrow_balance = 0;
-        Self::update_job(&env, job_id, &job);
+    fn get_specialization_index(env: &Env, tag: &BytesN<32>) -> Vec<Address> {
+        env.storage().persistent().get(&AccountKey::Specialization(tag.clone()))
+            .unwrap_or_else(|| Vec::new(env))
+    }
 
-        env.events().publish(
-            (JOB_CANC, client),
-            (job_id, refund_amount, job.cancellation_fee)
-        );
+    /// Move open-dispute counts from `previous` to `next` assignees
+    fn reassign_arbitrators(env: &Env, previous: &Vec<Address>, next: &Vec<Address>) {
+        let mut arbitrators = Self::get_arbitrators(env);
+        for address in previous.iter() {
+            if let Some(mut record) = arbitrators.get(address.clone()) {
+                record.open_disputes = record.open_disputes.saturating_sub(1);
+                arbitrators.set(address, record);
+            }
+        }
+        for address in next.iter() {
+            if let Some(mut record) = arbitrators.get(address.clone()) {
+                record.open_disputes += 1;
+                arbitrators.set(address, record);
+            }
+        }
+        Self::save_arbitrators(env, &arbitrators);
     }
 
-    // =================
-    // ARBITRATOR MANAGEMENT
-    // =================
-    /// Register as arbitrator
-    /// @param env: Soroban environment
-    /// @param arbitrator: Arbitrator address
-    /// @param specialization: Area of expertise
-    pub fn register_arbitrator(
-        env: Env,
-        arbitrator: Address,
-        specialization: BytesN<32>,
-    ) {
-        arbitrator.require_auth();
-        Self::check_reentrancy(&env);
+    /// Record ruling activity for inactivity pruning
+    fn touch_arbitrators(env: &Env, deciders: &Vec<Address>) {
+        let now = env.ledger().timestamp();
+        let mut arbitrators = Self::get_arbitrators(env);
+        for address in deciders.iter() {
+            if let Some(mut record) = arbitrators.get(address.clone()) {
+                record.last_active = now;
+                arbitrators.set(address, record);
+            }
+        }
+        Self::save_arbitrators(env, &arbitrators);
+    }
 
-        let mut arbitrators = Self::get_arbitrators(&env);
-        if arbitrators.contains_key(arbitrator.clone()) {
-            panic_with_error!(&env, Error::InvalidState);
+    fn max_cancellation_fee(env: &Env) -> u32 {
+        env.storage().instance().get(&ConfigKey::MaxCancellationFee)
+            .unwrap_or(DEFAULT_CANCEL_FEE_MAX)
+    }
+
+    fn text_limits(env: &Env) -> (u32, u32) {
+        env.storage().instance().get(&ConfigKey::TextLimits)
+            .unwrap_or((DEFAULT_TITLE_MAX, DEFAULT_DESC_MAX))
+    }
+
+    /// Reject plain text longer than `max` bytes; hashes are fixed size
+    fn check_text(env: &Env, text: &Text, max: u32) {
+        if let Text::Plain(value) = text {
+            if value.is_empty() {
+                panic_with_error!(env, Error::InvalidInput);
+            }
+            if value.len() > max {
+                panic_with_error!(env, Error::InputTooLong);
+            }
         }
+    }
 
-        arbitrators.set(
-            arbitrator.clone(),
-            Arbitrator {
-                address: arbitrator.clone(),
-                fee_percentage: ARB_FEE,
-                reputation: 80, // Initial reputation
-                cases_handled: 0,
-                specialization,
-            },
-        );
+    fn job_limits(env: &Env) -> JobLimits {
+        env.storage().instance().get(&ConfigKey::JobLimits)
+            .unwrap_or(JobLimits {
+                max_milestones: DEFAULT_MAX_MILESTONES,
+                max_open_jobs: DEFAULT_MAX_OPEN_JOBS,
+                max_uri_len: DEFAULT_URI_MAX,
+            })
+    }
 
-        env.storage().set(&ARB_REG, &arbitrators);
-        env.events().publish(
-            (ARB_REG, arbitrator),
-            specialization
-        );
+    fn max_arbitrator_fee(env: &Env) -> i128 {
+        env.storage().instance().get(&ConfigKey::MaxArbitratorFee)
+            .unwrap_or(DEFAULT_ARB_FEE_MAX)
     }
 
-    // ====================
-    // INTERNAL HELPERS
-    // ====================
-    fn approve_milestone_internal(env: &Env, job: &mut Job, idx: u32) {
-        let mut milestone = job.milestones.get(idx)
-            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidIndex));
-            
-        let token_id = Self::get_token_id(env);
-        token::Client::new(env, &token_id).transfer(
-            &env.current_contract_address(),
-            &job.talent.unwrap(),
-            &milestone.amount
-        );
+    /// Registered fee bounded by the job's cap; unregistered deciders
+    /// (e.g. the admin on appeal) use the default rate
+    fn arbitrator_fee_percentage(env: &Env, job: &Job, arbitrator: &Address) -> i128 {
+        let fee_percentage = Self::get_arbitrators(env).get(arbitrator.clone())
+            .map(|record| record.fee_percentage)
+            .unwrap_or(ARB_FEE);
+        fee_percentage.min(Self::arbitrator_fee_cap(env, job))
+    }
 
-        milestone.state = MilestoneState::Paid;
-        job.milestones.set(idx, milestone);
-        job.amount_paid += milestone.amount;
-        job.escrow_balance -= milestone.amount;
+    /// Global fee cap, lowered by the job's dispute policy if it sets one
+    fn arbitrator_fee_cap(env: &Env, job: &Job) -> i128 {
+        let cap = Self::max_arbitrator_fee(env);
+        match job.dispute_policy.as_ref().and_then(|policy| policy.max_fee) {
+            Some(max_fee) => cap.min(max_fee),
+            None => cap,
+        }
     }
 
-    fn approve_all_milestones(env: &Env, job: &mut Job) {
-        for i in 0..job.milestones.len() {
-            let mut milestone = job.milestones.get(i).unwrap();
-            if matches!(milestone.state, MilestoneState::Submitted) {
-                Self::approve_milestone_internal(env, job, i);
+    fn ruling_window(job: &Job) -> u64 {
+        job.dispute_policy.as_ref()
+            .and_then(|policy| policy.ruling_window)
+            .unwrap_or(RULING_WINDOW)
+    }
+
+    fn appeals_allowed(job: &Job) -> bool {
+        job.dispute_policy.as_ref().is_none_or(|policy| policy.appeals)
+    }
+
+    /// Arbitrator is on the job's pre-agreed list (or the job has none)
+    fn policy_allows(job: &Job, arbitrator: &Address) -> bool {
+        match job.dispute_policy.as_ref() {
+            Some(policy) if !policy.arbitrators.is_empty() => policy.arbitrators.contains(arbitrator),
+            _ => true,
+        }
+    }
+
+    fn check_dispute_policy(env: &Env, policy: &DisputePolicy) {
+        if policy.arbitrators.len() > MAX_POLICY_ARBS || policy.ruling_window == Some(0) {
+            panic_with_error!(env, Error::InvalidInput);
+        }
+        if let Some(size) = policy.panel_size {
+            let too_few = !policy.arbitrators.is_empty() && size > policy.arbitrators.len();
+            if size < 3 || size.is_multiple_of(2) || too_few {
+                panic_with_error!(env, Error::InvalidInput);
+            }
+        }
+        if let Some(max_fee) = policy.max_fee {
+            if max_fee < 0 {
+                panic_with_error!(env, Error::InvalidInput);
+            }
+            if max_fee > Self::max_arbitrator_fee(env) {
+                panic_with_error!(env, Error::FeeTooHigh);
             }
         }
     }
 
-    fn reject_milestone(env: &Env, job: &mut Job, idx: u32) {
-        let mut milestone = job.milestones.get(idx)
-            .unwrap_or_else(|| panic_with_error!(env, Error::InvalidIndex));
-            
-        milestone.state = MilestoneState::Rejected;
-        milestone.submission_data = BytesN::from_array(env, &[0; 32]);
-        job.milestones.set(idx, milestone);
+    fn min_arbitrator_stake(env: &Env) -> i128 {
+        env.storage().instance().get(&ConfigKey::MinArbStake)
+            .unwrap_or(DEFAULT_MIN_STAKE)
     }
 
-    fn reject_all_milestones(env: &Env, job: &mut Job) {
-        for i in 0..job.milestones.len() {
-            let mut milestone = job.milestones.get(i).unwrap();
-            if matches!(milestone.state, MilestoneState::Submitted) {
-                milestone.state = MilestoneState::Rejected;
-                milestone.submission_data = BytesN::from_array(env, &[0; 32]);
-                job.milestones.set(i, milestone);
+    /// Strkey (`C...`) address for a raw contract id, used to upgrade
+    /// token ids stored as `BytesN<32>` by earlier versions
+    fn contract_address_from_id(env: &Env, contract_id: &BytesN<32>) -> Address {
+        const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+        let mut payload = [0u8; 35];
+        payload[0] = 2 << 3; // Contract strkey version byte
+        payload[1..33].copy_from_slice(&contract_id.to_array());
+
+        // CRC16-XModem checksum over version byte and id, little endian
+        let mut crc: u16 = 0;
+        for byte in payload[..33].iter() {
+            crc ^= (*byte as u16) << 8;
+            for _ in 0..8 {
+                crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
             }
         }
+        payload[33..].copy_from_slice(&crc.to_le_bytes());
+
+        // Base32 without padding: 35 bytes -> 56 characters
+        let mut encoded = [0u8; 56];
+        for (i, chunk) in payload.chunks(5).enumerate() {
+            let bits = chunk.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+            for j in 0..8 {
+                encoded[i * 8 + j] = ALPHABET[((bits >> (35 - 5 * j)) & 0x1f) as usize];
+            }
+        }
+        Address::from_string_bytes(&Bytes::from_slice(env, &encoded))
     }
 
-    fn check_reentrancy(env: &Env) {
-        if env.storage().has(&RE_ENTRY) {
-            panic_with_error!(env, Error::Reentrancy);
+    /// Re-key a map of raw contract ids by token Address (pre-v2 layout)
+    fn key_by_address<V: IntoVal<Env, Val> + TryFromVal<Env, Val> + Clone>(env: &Env, legacy: Map<BytesN<32>, V>) -> Map<Address, V> {
+        let mut map = Map::new(env);
+        for (id, value) in legacy.iter() {
+            map.set(Self::contract_address_from_id(env, &id), value);
         }
-        env.storage().set(&RE_ENTRY, &true);
+        map
     }
 
-    fn save_job(env: &Env, job: &Job) -> u32 {
-        let mut count = env.storage().get(&symbol_short!("JOB_CNT"))
-            .unwrap_or(Ok(0u32))
-            .unwrap();
-        count += 1;
-        env.storage().set(&symbol_short!("JOB_CNT"), &count);
-        env.storage().set(&Self::job_key(count), job);
-        count
+    /// Job key used before v3: the big-endian id zero-padded to 32 bytes
+    fn legacy_job_key(env: &Env, job_id: u32) -> BytesN<32> {
+        let mut arr = [0u8; 32];
+        arr[..4].copy_from_slice(&job_id.to_be_bytes());
+        BytesN::from_array(env, &arr)
     }
 
-    fn update_job(env: &Env, job_id: u32, job: &Job) {
-        env.storage().set(&Self::job_key(job_id), job);
+    /// Current layout of a v1 job: the inline dispute fields are dropped
+    /// (disputes live under `JobKey::Dispute`)
+    fn upgrade_job_v1(env: &Env, legacy: JobV1) -> Job {
+        Self::upgrade_job_v2(env, JobV2 {
+            client: legacy.client,
+            talent: legacy.talent,
+            title: legacy.title,
+            token: legacy.token,
+            specialization: None.into(),
+            min_talent_rating: 0,
+            min_jobs_completed: 0,
+            total_value: legacy.total_value,
+            amount_paid: legacy.amount_paid,
+            state: legacy.state,
+            milestones: legacy.milestones,
+            escrow_balance: legacy.escrow_balance,
+            created_at: legacy.created_at,
+            cancellation_fee: legacy.cancellation_fee,
+        })
     }
 
-    fn get_job(env: &Env, job_id: u32) -> Job {
-        env.storage()
-            .get(&Self::job_key(job_id))
-            .unwrap_or_else(|| panic_with_error!(env, Error::JobNotFound))
-            .unwrap()
+    /// Current layout of a v2 job. Everything added since takes the value a
+    /// new fixed-milestone job would have.
+    fn upgrade_job_v2(env: &Env, legacy: JobV2) -> Job {
+        let mut milestones = Vec::new(env);
+        for milestone in legacy.milestones.iter() {
+            milestones.push_back(Milestone {
+                description: Text::Hash(milestone.description),
+                amount: milestone.amount,
+                state: milestone.state,
+                submission_data: milestone.submission_data,
+                submission_uri: None,
+                deadline: milestone.deadline,
+                submitted_at: milestone.submitted_at,
+                vests_at: None,
+                subjobs: Vec::new(env),
+                payee: None,
+            });
+        }
+        // The flat cancellation fee becomes its share of the job value
+        let cancellation_fee_bps = if legacy.total_value > 0 {
+            (legacy.cancellation_fee * BPS_DENOM / legacy.total_value) as u32
+        } else {
+            0
+        };
+        Job {
+            client: legacy.client,
+            funder: None,
+            crowdfunded: false,
+            contest: false,
+            talent: legacy.talent,
+            offer: None.into(),
+            title: Text::Hash(legacy.title),
+            token: legacy.token,
+            specialization: legacy.specialization,
+            min_talent_rating: legacy.min_talent_rating,
+            min_jobs_completed: legacy.min_jobs_completed,
+            payment: PaymentModel {
+                retainer: None.into(),
+                next_period_at: 0,
+                periods_advanced: 0,
+                stream: None.into(),
+                hourly_rate: 0,
+                pricing: None.into(),
+            },
+            payout_split: Vec::new(env),
+            parent: None.into(),
+            talent_bond: 0,
+            bond_posted: 0,
+            total_value: legacy.total_value,
+            amount_paid: legacy.amount_paid,
+            state: legacy.state,
+            action_seq: 0,
+            milestones,
+            escrow_balance: legacy.escrow_balance,
+            // Older layouts kept no ledger: treat what is held plus what was
+            // paid as deposited
+            deposited: legacy.escrow_balance + legacy.amount_paid,
+            fees_charged: 0,
+            platform_fees: 0,
+            refunded: 0,
+            created_at: legacy.created_at,
+            expires_at: legacy.created_at.saturating_add(DEFAULT_FUND_WINDOW),
+            staffing_window: DEFAULT_STAFF_WINDOW,
+            staffing_deadline: legacy.created_at.saturating_add(DEFAULT_STAFF_WINDOW),
+            cancel_terms: CancelTerms {
+                cancellation_fee_bps,
+                kill_fee_tiers: Vec::new(env),
+                cancellation_notice: 0,
+                cancel_effective_at: None,
+            },
+            release_delay: 0,
+            metadata: None.into(),
+            dispute_policy: None.into(),
+            invited: None,
+            category: GENERAL,
+            tags: Vec::new(env),
+            roles: JobRoles {
+                multisig: None.into(),
+                managers: Map::new(env),
+                referrer: None,
+                agency: None.into(),
+            },
+        }
     }
 
-    fn get_token_id(env: &Env) -> BytesN<32> {
-        env.storage().get(&TOKEN_ID))
-            .unwrap_or_else(|| panic_with_error!(env, Error::TokenNotSet))
-            .unwrap()
+    /// Move an instance entry to a new key, keeping its value untouched
+    fn move_instance<K: IntoVal<Env, Val>, T: IntoVal<Env, Val>>(env: &Env, from: &K, to: &T) {
+        let value: Option<Val> = env.storage().instance().get(from);
+        if let Some(value) = value {
+            env.storage().instance().set(to, &value);
+            env.storage().instance().remove(from);
+        }
     }
 
-    fn get_arbitrators(env: &Env) -> Map<Address, Arbitrator> {
-        env.storage().get(&ARB_REG))
-            .unwrap_or_else(|| Ok(Map::new(env)))
-            .unwrap()
+    /// Move a persistent entry to a new key, keeping its value untouched
+    fn move_persistent<K: IntoVal<Env, Val>, T: IntoVal<Env, Val>>(env: &Env, from: &K, to: &T) {
+        let value: Option<Val> = env.storage().persistent().get(from);
+        if let Some(value) = value {
+            env.storage().persistent().set(to, &value);
+            env.storage().persistent().remove(from);
+            env.storage().persistent().extend_ttl(to, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL);
+        }
     }
 
-    fn is_arbitrator(env: &Env, address: &Address) -> bool {
-        Self::get_arbitrators(env).contains_key(address.clone())
+    /// Re-key pre-v3 instance config under `ConfigKey`
+    fn migrate_config_keys(env: &Env) {
+        Self::move_instance(env, &symbol_short!("TOKEN_ID"), &ConfigKey::Token);
+        Self::move_instance(env, &symbol_short!("ADMIN"), &ConfigKey::Admin);
+        Self::move_instance(env, &symbol_short!("PEND_ADM"), &ConfigKey::PendingAdmin);
+        Self::move_instance(env, &symbol_short!("PLAT_FEE"), &ConfigKey::PlatformFee);
+        Self::move_instance(env, &symbol_short!("PAUSED"), &ConfigKey::Paused);
+        Self::move_instance(env, &symbol_short!("STATE_VER"), &ConfigKey::StateVersion);
+        Self::move_instance(env, &symbol_short!("TOKENS"), &ConfigKey::AllowedTokens);
+        Self::move_instance(env, &symbol_short!("TL_DELAY"), &ConfigKey::TimelockDelay);
+        Self::move_instance(env, &symbol_short!("TL_CNT"), &ConfigKey::ActionCount);
+        Self::move_instance(env, &symbol_short!("MIN_STAKE"), &ConfigKey::MinArbStake);
+        Self::move_instance(env, &symbol_short!("ARB_FMAX"), &ConfigKey::MaxArbitratorFee);
+        Self::move_instance(env, &symbol_short!("CANC_MAX"), &ConfigKey::MaxCancellationFee);
+        Self::move_instance(env, &symbol_short!("DEF_RULE"), &ConfigKey::DefaultRuling);
+        Self::move_instance(env, &symbol_short!("TPL_CNT"), &ConfigKey::TemplateCount);
+        Self::move_instance(env, &symbol_short!("JOB_CNT"), &ConfigKey::JobCount);
     }
 
-    fn job_key(job_id: u32) -> BytesN<32> {
-        BytesN::from_array(&Env::default(), &{
-            let mut arr = [0u8; 32];
-            arr[..4].copy_from_slice(&job_id.to_be_bytes());
-            arr
-        })
+    /// Move an account's profile, review pages and rating to v3 keys
+    fn migrate_account(env: &Env, account: &Address) {
+        Self::move_persistent(env, &(symbol_short!("TALENT"), account.clone()), &AccountKey::Talent(account.clone()));
+        Self::move_persistent(env, &(symbol_short!("CLIENT"), account.clone()), &AccountKey::Client(account.clone()));
+        // The review count tells how many pages were written
+        let legacy_rating = (symbol_short!("RATING"), account.clone());
+        let stats: Option<RatingStats> = env.storage().persistent().get(&legacy_rating);
+        if let Some(stats) = stats {
+            for page in 0..stats.count.div_ceil(INDEX_PAGE) {
+                Self::move_persistent(
+                    env,
+                    &(symbol_short!("REVIEWS"), account.clone(), page),
+                    &AccountKey::Reviews(account.clone(), page)
+                );
+            }
+            Self::move_persistent(env, &legacy_rating, &AccountKey::Rating(account.clone()));
+        }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test;
@@ -1,359 +1,4812 @@
-// #![cfg(test)]
-
-// use super::*;
-// use soroban_sdk::{vec, Env, String};
-
-// #[test]
-// fn test() {
-//     let env = Env::default();
-//     let contract_id = env.register(Contract, ());
-//     let client = ContractClient::new(&env, &contract_id);
-
-//     let words = client.hello(&String::from_str(&env, "Dev"));
-//     assert_eq!(
-//         words,
-//         vec![
-//             &env,
-//             String::from_str(&env, "Hello"),
-//             String::from_str(&env, "Dev"),
-//         ]
-//     );
-// }
 #![cfg(test)]
 
 use soroban_sdk::{
-    testutils::{Address as _, Events}, 
-    vec, 
-    Address, 
-    BytesN, 
-    Env, 
-    IntoVal, 
-    Symbol
+    map,
+    symbol_short,
+    testutils::{storage::{Instance as _, Persistent as _}, Address as _, Events, Ledger},
+    token::{Client as TokenClient, StellarAssetClient},
+    vec,
+    Address,
+    Bytes,
+    BytesN,
+    Env,
+    IntoVal,
+    Map,
+    String,
+    Symbol,
+    Vec,
 };
 
 use crate::{
-    JobMarketplaceContract, 
-    JobMarketplaceContractClient, 
-    Error, 
-    JobStatus, 
-    MilestoneStatus
+    AdminAction,
+    Agency,
+    AmendmentStatus,
+    AmendmentTerms,
+    BatchApproved,
+    ClaimStatus,
+    APPEAL_WINDOW,
+    APPROVAL_WINDOW,
+    AccountKey,
+    ConfigKey,
+    JobKey,
+    DecentralizedJobMarket,
+    DecentralizedJobMarketClient,
+    DEFAULT_ARB_FEE_MAX,
+    DEFAULT_CANCEL_FEE_MAX,
+    DEFAULT_DESC_MAX,
+    DEFAULT_FUND_WINDOW,
+    DEFAULT_MAX_MILESTONES,
+    DEFAULT_MAX_OPEN_JOBS,
+    DEFAULT_MIN_STAKE,
+    DEFAULT_TITLE_MAX,
+    DEFAULT_TL_DELAY,
+    DEFAULT_URI_MAX,
+    DERIVED_ID_FLAG,
+    DisputeOpened,
+    DisputePolicy,
+    Error,
+    EVENT_VERSION,
+    INDEX_PAGE,
+    InitConfig,
+    Job,
+    JobAction,
+    JobLimits,
+    JobCreated,
+    JobOptions,
+    KillFeeTier,
+    JobSpec,
+    JobState,
+    JobV1,
+    KeeperTask,
+    JobV2,
+    MilestoneSpec,
+    MilestoneState,
+    MilestoneV1,
+    OptAgency,
+    OptDisputePolicy,
+    OptHash,
+    OptJobState,
+    OptParent,
+    OptPriceTerms,
+    OptRetainer,
+    MAX_AMENDMENTS,
+    MAX_EVIDENCE,
+    MAX_NOTE_LEN,
+    MAX_REASSIGN,
+    MIN_TL_DELAY,
+    PaymentMade,
+    PayoutPreference,
+    PriceTerms,
+    Permission,
+    RATE_SCALE,
+    RetainerTerms,
+    RULING_WINDOW,
+    Ruling,
+    Settlement,
+    UNSTAKE_COOLDOWN,
+    STATE_VERSION,
+    TalentHired,
+    Text,
+    TokenMoved,
+    WorkSubmitted,
 };
 
+/// Deployment configuration with every parameter at its default
+fn init_config(admin: &Address, token: &Address) -> InitConfig {
+    InitConfig {
+        admin: admin.clone(),
+        token: token.clone(),
+        platform_fee_bps: 0,
+        max_arbitrator_fee: DEFAULT_ARB_FEE_MAX,
+        max_cancellation_fee_bps: DEFAULT_CANCEL_FEE_MAX,
+        min_arbitrator_stake: DEFAULT_MIN_STAKE,
+        timelock_delay: DEFAULT_TL_DELAY,
+        text_limits: (DEFAULT_TITLE_MAX, DEFAULT_DESC_MAX),
+        job_limits: JobLimits {
+            max_milestones: DEFAULT_MAX_MILESTONES,
+            max_open_jobs: DEFAULT_MAX_OPEN_JOBS,
+            max_uri_len: DEFAULT_URI_MAX,
+        },
+    }
+}
+
+fn setup_market(env: &Env) -> (DecentralizedJobMarketClient<'_>, TokenClient<'_>, StellarAssetClient<'_>, Address) {
+    let admin = Address::generate(env);
+    let sac = env.register_stellar_asset_contract_v2(admin.clone());
+    let contract_id = env.register(DecentralizedJobMarket, (init_config(&admin, &sac.address()),));
+    let market = DecentralizedJobMarketClient::new(env, &contract_id);
+
+    (
+        market,
+        TokenClient::new(env, &sac.address()),
+        StellarAssetClient::new(env, &sac.address()),
+        admin,
+    )
+}
+
+fn read_job(env: &Env, market: &DecentralizedJobMarketClient, job_id: u32) -> Job {
+    env.as_contract(&market.address, || DecentralizedJobMarket::get_job(env, job_id))
+}
+
+/// Arbitration fee deposit for a `hire_talent` job (10% fee cap of 300)
+const DISPUTE_DEPOSIT: i128 = 30;
+
+/// Create a two-milestone job (100 + 200), fund it and hire the talent
+fn hire_talent(
+    env: &Env,
+    market: &DecentralizedJobMarketClient,
+    token_admin: &StellarAssetClient,
+    client: &Address,
+    talent: &Address,
+) -> u32 {
+    token_admin.mint(client, &300);
+    let now = env.ledger().timestamp();
+    let job_id = market.create_job(
+        client,
+        &Text::Hash(BytesN::from_array(env, &[1; 32])),
+        &vec![env, Text::Hash(BytesN::from_array(env, &[2; 32])), Text::Hash(BytesN::from_array(env, &[3; 32]))],
+        &vec![env, 100, 200],
+        &vec![env, now + 1_000, now + 2_000],
+    );
+    market.fund_job(client, &job_id);
+    market.offer_job(client, &job_id, talent);
+    market.accept_offer(talent, &job_id);
+    job_id
+}
+
+/// Register an arbitrator with the minimum stake
+fn register_arbitrator(market: &DecentralizedJobMarketClient, token_admin: &StellarAssetClient, arbitrator: &Address) {
+    token_admin.mint(arbitrator, &DEFAULT_MIN_STAKE);
+    market.register_arbitrator(arbitrator, &BytesN::from_array(&market.env, &[7; 32]), &DEFAULT_MIN_STAKE);
+}
+
+/// Let the appeal window lapse and execute the pending ruling
+fn execute_ruling(env: &Env, market: &DecentralizedJobMarketClient, job_id: u32) {
+    env.ledger().with_mut(|l| l.timestamp += APPEAL_WINDOW + 1);
+    market.execute_ruling(&Address::generate(env), &job_id);
+}
+
+fn set_platform_fee(env: &Env, market: &DecentralizedJobMarketClient, admin: &Address, fee_bps: u32) {
+    let action_id = market.queue_action(admin, &AdminAction::SetPlatformFee(fee_bps));
+    env.ledger().with_mut(|l| l.timestamp += DEFAULT_TL_DELAY);
+    market.execute_action(&action_id);
+}
+
 #[test]
 fn test_create_job() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, JobMarketplaceContract);
-    let client = JobMarketplaceContractClient::new(&env, &contract_id);
+    env.mock_all_auths();
+    let (market, token, _, _) = setup_market(&env);
 
-    let client_address = Address::random(&env);
-    env.mock_all_auths(); // This will mock all auth requirements
-    
-    let title = BytesN::from_array(&env, &[1; 32]);
+    let client_address = Address::generate(&env);
+    let title = Text::Hash(BytesN::from_array(&env, &[1; 32]));
     let descriptions = vec![
         &env,
-        BytesN::from_array(&env, &[2; 32]),
-        BytesN::from_array(&env, &[3; 32]),
+        Text::Hash(BytesN::from_array(&env, &[2; 32])),
+        Text::Hash(BytesN::from_array(&env, &[3; 32])),
     ];
     let amounts = vec![&env, 100, 200];
+    let deadlines = vec![&env, 1_000u64, 2_000u64];
 
     // Test successful job creation
-    let job_id = client.create_job(
-        &client_address,
-        &title,
-        &descriptions,
-        &amounts,
-    );
+    let job_id = market.create_job(&client_address, &title, &descriptions, &amounts, &deadlines);
     assert_eq!(job_id, 1);
 
-    // Verify job count increased
-    let job_count = env.storage().get(&Symbol::short("JOB_COUNT")).unwrap().unwrap();
-    assert_eq!(job_count, 1u32);
-
     // Verify event was emitted
-    let events = env.events().all();
-    assert_eq!(events.len(), 1);
-    assert!(events.contains((
-        contract_id.clone(),
-        (Symbol::short("JOB_CREATED"), client_address.clone()).into_val(&env),
-        (job_id, title, 300).into_val(&env)
+    assert!(env.events().all().contains((
+        market.address.clone(),
+        (symbol_short!("JOB_CRT"), job_id, client_address.clone()).into_val(&env),
+        JobCreated {
+            version: EVENT_VERSION,
+            title: title.clone(),
+            total_value: 300,
+            token: token.address.clone(),
+            parent: OptParent::None,
+        }.into_val(&env),
     )));
 
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.client, client_address);
+    assert_eq!(job.state, JobState::Created);
+    assert_eq!(job.total_value, 300);
+    assert_eq!(job.milestones.len(), 2);
+
     // Test invalid input (length mismatch)
     let bad_amounts = vec![&env, 100];
-    let result = client.try_create_job(
-        &client_address,
-        &title,
-        &descriptions,
-        &bad_amounts,
-    );
+    let result = market.try_create_job(&client_address, &title, &descriptions, &bad_amounts, &deadlines);
     assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
 
     // Test invalid input (zero amount)
     let zero_amounts = vec![&env, 0, 0];
-    let result = client.try_create_job(
+    let result = market.try_create_job(&client_address, &title, &descriptions, &zero_amounts, &deadlines);
+    assert_eq!(result, Err(Ok(Error::AmountRequired.into())));
+}
+
+#[test]
+fn test_plain_text_titles_are_length_capped() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, _, admin) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let descriptions = vec![&env, Text::Plain(String::from_str(&env, "Wireframes for the landing page"))];
+    let amounts = vec![&env, 100];
+    let deadlines = vec![&env, 1_000u64];
+    assert_eq!(market.get_text_limits(), (128, 1_024));
+
+    let title = Text::Plain(String::from_str(&env, "Landing page redesign"));
+    let job_id = market.create_job(&client_address, &title, &descriptions, &amounts, &deadlines);
+    assert_eq!(read_job(&env, &market, job_id).title, title);
+
+    // Empty or over-long text is rejected; hashes are always accepted
+    let empty = Text::Plain(String::from_str(&env, ""));
+    let result = market.try_create_job(&client_address, &empty, &descriptions, &amounts, &deadlines);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+
+    let result = market.try_queue_action(&admin, &AdminAction::SetTextLimits(0, 16));
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    let action_id = market.queue_action(&admin, &AdminAction::SetTextLimits(16, 16));
+    env.ledger().with_mut(|l| l.timestamp += DEFAULT_TL_DELAY);
+    market.execute_action(&action_id);
+    assert_eq!(market.get_text_limits(), (16, 16));
+
+    let result = market.try_create_job(&client_address, &title, &descriptions, &amounts, &deadlines);
+    assert_eq!(result, Err(Ok(Error::InputTooLong.into())));
+    let hashed = vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))];
+    let short = Text::Plain(String::from_str(&env, "Landing page"));
+    market.create_job(&client_address, &short, &hashed, &amounts, &deadlines);
+}
+
+#[test]
+fn test_job_limits_bound_milestones_and_open_jobs() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, admin) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let limits = |max_milestones, max_open_jobs, max_uri_len| JobLimits { max_milestones, max_open_jobs, max_uri_len };
+    assert_eq!(market.get_job_limits(), limits(50, 100, 256));
+    let result = market.try_queue_action(&admin, &AdminAction::SetJobLimits(limits(0, 1, 256)));
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    let action_id = market.queue_action(&admin, &AdminAction::SetJobLimits(limits(2, 1, 16)));
+    env.ledger().with_mut(|l| l.timestamp += DEFAULT_TL_DELAY);
+    market.execute_action(&action_id);
+
+    let hash = |byte: u8| Text::Hash(BytesN::from_array(&env, &[byte; 32]));
+    let now = env.ledger().timestamp();
+    let result = market.try_create_job(
+        &client_address,
+        &hash(1),
+        &vec![&env, hash(2), hash(3), hash(4)],
+        &vec![&env, 100, 100, 100],
+        &vec![&env, now + 1_000, now + 2_000, now + 3_000],
+    );
+    assert_eq!(result, Err(Ok(Error::TooManyMilestones.into())));
+
+    // One open job at a time; finishing it frees the slot
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    assert_eq!(market.get_client_profile(&client_address).open_jobs, 1);
+    let result = market.try_create_job(&client_address, &hash(1), &vec![&env, hash(2)], &vec![&env, 100], &vec![&env, now + 1_000]);
+    assert_eq!(result, Err(Ok(Error::TooManyOpenJobs.into())));
+    let long_uri = String::from_str(&env, "ipfs://a-very-long-deliverable");
+    let result = market.try_submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &long_uri);
+    assert_eq!(result, Err(Ok(Error::InputTooLong.into())));
+
+    market.cancel_job(&client_address, &job_id);
+    assert_eq!(token.balance(&market.address), 0);
+    assert_eq!(market.get_client_profile(&client_address).open_jobs, 0);
+    market.create_job(&client_address, &hash(1), &vec![&env, hash(2)], &vec![&env, 100], &vec![&env, now + 1_000]);
+}
+
+#[test]
+fn test_create_jobs_batch_is_atomic() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, _, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let spec = |amount: i128| JobSpec {
+        title: Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        descriptions: vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        amounts: vec![&env, amount],
+        deadlines: vec![&env, 1_000u64],
+        options: JobOptions::default(),
+    };
+
+    // One bad spec rejects the whole batch
+    let result = market.try_create_jobs_batch(&client_address, &vec![&env, spec(100), spec(0)]);
+    assert_eq!(result, Err(Ok(Error::AmountRequired.into())));
+    let result = market.try_create_jobs_batch(&client_address, &Vec::new(&env));
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    let mut too_many = Vec::new(&env);
+    for _ in 0..11 {
+        too_many.push_back(spec(100));
+    }
+    let result = market.try_create_jobs_batch(&client_address, &too_many);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+
+    let job_ids = market.create_jobs_batch(&client_address, &vec![&env, spec(100), spec(250)]);
+    assert_eq!(job_ids, vec![&env, 1, 2]);
+    assert_eq!(read_job(&env, &market, 2).total_value, 250);
+    assert_eq!(market.get_client_profile(&client_address).jobs_posted, 2);
+}
+
+#[test]
+fn test_jobs_listed_by_category() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, _, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let design = symbol_short!("design");
+    let create = |options: &JobOptions| market.try_create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000u64],
+        options,
+    );
+
+    // Tags are capped and must be distinct
+    let logo = symbol_short!("logo");
+    let options = JobOptions { tags: Some(vec![&env, logo.clone(), logo.clone()]), ..Default::default() };
+    assert_eq!(create(&options), Err(Ok(Error::InvalidInput.into())));
+    let too_many = vec![&env, symbol_short!("a"), symbol_short!("b"), symbol_short!("c"), symbol_short!("d"), symbol_short!("e"), symbol_short!("f")];
+    let options = JobOptions { tags: Some(too_many), ..Default::default() };
+    assert_eq!(create(&options), Err(Ok(Error::InvalidInput.into())));
+
+    let options = JobOptions { category: Some(design.clone()), tags: Some(vec![&env, logo.clone()]), ..Default::default() };
+    let first = create(&options).unwrap().unwrap();
+    let untagged = create(&JobOptions::default()).unwrap().unwrap();
+    let second = create(&options).unwrap().unwrap();
+
+    assert_eq!(market.get_job_tags(&first).unwrap(), (design.clone(), vec![&env, logo]));
+    assert_eq!(market.get_job_tags(&untagged).unwrap(), (symbol_short!("general"), vec![&env]));
+    assert_eq!(market.list_jobs_by_category(&design, &0, &10), vec![&env, first, second]);
+    assert_eq!(market.list_jobs_by_category(&design, &1, &10), vec![&env, second]);
+    assert_eq!(market.list_jobs_by_category(&symbol_short!("general"), &0, &10), vec![&env, untagged]);
+    assert!(market.list_jobs_by_category(&symbol_short!("writing"), &0, &10).is_empty());
+}
+
+#[test]
+fn test_create_job_from_template() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, _, _) = setup_market(&env);
+
+    let owner = Address::generate(&env);
+    let client_address = Address::generate(&env);
+    let title = Text::Hash(BytesN::from_array(&env, &[1; 32]));
+    let specs = vec![
+        &env,
+        MilestoneSpec { description: Text::Hash(BytesN::from_array(&env, &[2; 32])), amount: 100 },
+        MilestoneSpec { description: Text::Hash(BytesN::from_array(&env, &[3; 32])), amount: 200 },
+    ];
+    let result = market.try_create_template(&owner, &title, &Vec::new(&env));
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    let template_id = market.create_template(&owner, &title, &specs);
+    assert_eq!(market.get_template(&template_id).unwrap().milestones, specs);
+
+    // One offset per milestone, and deadlines must not overflow
+    let result = market.try_create_job_from_template(&client_address, &template_id, &vec![&env, 10]);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    env.ledger().with_mut(|l| l.timestamp = 1_000);
+    let result = market.try_create_job_from_template(&client_address, &template_id, &vec![&env, 10, u64::MAX]);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+
+    let job_id = market.create_job_from_template(&client_address, &template_id, &vec![&env, 10, 20]);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.client, client_address);
+    assert_eq!(job.total_value, 300);
+    assert_eq!(job.milestones.get(1).unwrap().deadline, 1_020);
+}
+
+#[test]
+fn test_fund_job_with_allowance() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    token_admin.mint(&client_address, &100);
+    let job_id = market.create_job(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000u64],
+    );
+
+    // The allowance must cover the whole job value
+    token.approve(&client_address, &market.address, &99, &1_000);
+    let result = market.try_fund_job_with_allowance(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::InsufficientFunds.into())));
+
+    token.approve(&client_address, &market.address, &100, &1_000);
+    market.fund_job_with_allowance(&client_address, &job_id);
+    assert_eq!(token.balance(&market.address), 100);
+    assert_eq!(token.allowance(&client_address, &market.address), 0);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.state, JobState::Funded);
+    assert_eq!(job.escrow_balance, 100);
+}
+
+#[test]
+fn test_sponsor_funds_job_and_takes_refunds() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let sponsor = Address::generate(&env);
+    token_admin.mint(&sponsor, &100);
+    token_admin.mint(&client_address, &10);
+    let job_id = market.create_job(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000u64],
+    );
+
+    market.fund_job_for(&sponsor, &client_address, &job_id);
+    assert_eq!(token.balance(&sponsor), 0);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.state, JobState::Funded);
+    assert_eq!(job.funder, Some(sponsor.clone()));
+
+    // The client cannot mix their own tokens into sponsored escrow
+    let result = market.try_top_up_escrow(&client_address, &job_id, &10);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+
+    // The refund follows the money, not the job's creator
+    market.cancel_job(&client_address, &job_id);
+    assert_eq!(token.balance(&sponsor), 100);
+    assert_eq!(token.balance(&client_address), 10);
+    assert_eq!(token.balance(&market.address), 0);
+}
+
+#[test]
+fn test_crowdfunded_job_refunds_contributors_pro_rata() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let backer_a = Address::generate(&env);
+    let backer_b = Address::generate(&env);
+    token_admin.mint(&client_address, &300);
+    token_admin.mint(&backer_a, &200);
+    token_admin.mint(&backer_b, &200);
+    let options = JobOptions { crowdfunded: true, ..Default::default() };
+    let create = || market.create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32])), Text::Hash(BytesN::from_array(&env, &[3; 32]))],
+        &vec![&env, 100, 200],
+        &vec![&env, 1_000u64, 2_000u64],
+        &options,
+    );
+
+    // Funded by contributions only, never more than the job still lacks
+    let job_id = create();
+    let result = market.try_fund_job(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+    market.contribute(&backer_a, &job_id, &200);
+    let result = market.try_contribute(&backer_b, &job_id, &150);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    market.contribute(&backer_b, &job_id, &100);
+    assert_eq!(read_job(&env, &market, job_id).state, JobState::Funded);
+    assert_eq!(market.get_contributions(&job_id).unwrap().get(backer_a.clone()), Some(200));
+
+    // Cancelling returns each contribution to its backer
+    market.cancel_job(&client_address, &job_id);
+    assert_eq!(token.balance(&backer_a), 200);
+    assert_eq!(token.balance(&backer_b), 200);
+    assert_eq!(token.balance(&client_address), 300);
+
+    // A job that never fills up refunds what it collected on expiry
+    let job_id = create();
+    market.contribute(&backer_a, &job_id, &50);
+    market.contribute(&backer_b, &job_id, &25);
+    env.ledger().with_mut(|l| l.timestamp += DEFAULT_FUND_WINDOW);
+    market.expire_unfunded_job(&job_id);
+    assert_eq!(read_job(&env, &market, job_id).state, JobState::Expired);
+    assert_eq!(token.balance(&backer_a), 200);
+    assert_eq!(token.balance(&backer_b), 200);
+    assert_eq!(token.balance(&market.address), 0);
+}
+
+#[test]
+fn test_contest_pays_ranked_prizes_and_refunds_the_rest() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    token_admin.mint(&client_address, &300);
+    let options = JobOptions { contest: true, ..Default::default() };
+    let job_id = market.create_job_with_options(
         &client_address,
-        &title,
-        &descriptions,
-        &zero_amounts,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32])), Text::Hash(BytesN::from_array(&env, &[3; 32]))],
+        &vec![&env, 200, 100],
+        &vec![&env, 1_000u64, 1_000u64],
+        &options,
     );
-    assert_eq!(result, Err(Ok(Error::AmountMustBePositive.into())));
+    market.fund_job(&client_address, &job_id);
+
+    // Contest winners are picked from entries, never hired
+    let result = market.try_offer_job(&client_address, &job_id, &first);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+
+    let uri = String::from_str(&env, "ipfs://entry");
+    market.enter_contest(&first, &job_id, &BytesN::from_array(&env, &[7; 32]), &uri);
+    assert_eq!(market.enter_contest(&second, &job_id, &BytesN::from_array(&env, &[8; 32]), &uri), 1);
+    // Someone else's work is not a new entry
+    let result = market.try_enter_contest(&second, &job_id, &BytesN::from_array(&env, &[7; 32]), &uri);
+    assert_eq!(result, Err(Ok(Error::DuplicateSubmission.into())));
+
+    // Entries are still open
+    let result = market.try_pick_winners(&client_address, &job_id, &vec![&env, 1u32]);
+    assert_eq!(result, Err(Ok(Error::TooEarly.into())));
+
+    env.ledger().with_mut(|l| l.timestamp = 1_001);
+    market.pick_winners(&client_address, &job_id, &vec![&env, 1u32]);
+
+    // Only first place was awarded; the runner-up prize goes back
+    assert_eq!(token.balance(&second), 200);
+    assert_eq!(token.balance(&first), 0);
+    assert_eq!(token.balance(&client_address), 100);
+    assert_eq!(token.balance(&market.address), 0);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.state, JobState::Completed);
+    assert_eq!(job.talent, Some(second));
+    let entries = market.get_contest_entries(&job_id).unwrap();
+    assert_eq!(entries.get(0).unwrap().prize, 0);
+    assert_eq!(entries.get(1).unwrap().prize, 200);
 }
 
 #[test]
 fn test_select_talent() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, JobMarketplaceContract);
-    let client = JobMarketplaceContractClient::new(&env, &contract_id);
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
 
-    let client_address = Address::random(&env);
-    let talent_address = Address::random(&env);
-    env.mock_all_auths(); // Mock all auth requirements
-    
-    let title = BytesN::from_array(&env, &[1; 32]);
-    let descriptions = vec![&env, BytesN::from_array(&env, &[2; 32])];
-    let amounts = vec![&env, 100];
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    token_admin.mint(&client_address, &100);
 
-    // Create job first
-    let job_id = client.create_job(
+    let job_id = market.create_job(
         &client_address,
-        &title,
-        &descriptions,
-        &amounts,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000u64],
     );
 
-    // Test successful talent selection
-    client.select_talent(&client_address, &job_id, &talent_address);
+    // Talent can only be offered the job once it is funded
+    let result = market.try_offer_job(&client_address, &job_id, &talent_address);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+    market.fund_job(&client_address, &job_id);
 
-    // Verify job state
-    let job = client.get_job(&job_id);
-    assert_eq!(job.talent, Some(talent_address.clone()));
-    assert_eq!(job.status, JobStatus::InProgress);
+    // Test unauthorized access
+    let other_address = Address::generate(&env);
+    let result = market.try_offer_job(&other_address, &job_id, &talent_address);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+    // Offers lapse, and only the offered talent can answer one
+    market.offer_job(&client_address, &job_id, &other_address);
+    market.decline_offer(&other_address, &job_id);
+    market.offer_job(&client_address, &job_id, &talent_address);
+    let result = market.try_accept_offer(&other_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    env.ledger().with_mut(|l| l.timestamp = 604_801);
+    let result = market.try_accept_offer(&talent_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::DeadlinePassed.into())));
+    assert_eq!(read_job(&env, &market, job_id).state, JobState::Funded);
+
+    // Test successful talent selection
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
 
     // Verify event was emitted
-    let events = env.events().all();
-    assert_eq!(events.len(), 2);
-    assert!(events.contains((
-        contract_id.clone(),
-        (Symbol::short("TALENT_SEL"), client_address.clone()).into_val(&env),
-        (job_id, talent_address).into_val(&env)
+    assert!(env.events().all().contains((
+        market.address.clone(),
+        (symbol_short!("TAL_SEL"), job_id, talent_address.clone()).into_val(&env),
+        TalentHired { version: EVENT_VERSION, client: client_address.clone(), bond: 0 }.into_val(&env),
     )));
 
-    // Test unauthorized access
-    let other_address = Address::random(&env);
-    env.mock_all_auths(); // Reset auth mocks
-    let result = client.try_select_talent(&other_address, &job_id, &talent_address);
-    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.talent, Some(talent_address.clone()));
+    assert_eq!(job.state, JobState::Active);
 
     // Test invalid state (already has talent)
-    env.mock_all_auths(); // Reset auth mocks
-    let result = client.try_select_talent(&client_address, &job_id, &talent_address);
-    assert_eq!(result, Err(Ok(Error::TalentAlreadySelected.into())));
+    let result = market.try_offer_job(&client_address, &job_id, &other_address);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
 }
 
 #[test]
-fn test_submit_milestone() {
+fn test_invite_only_job_hires_invited_talent() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, JobMarketplaceContract);
-    let client = JobMarketplaceContractClient::new(&env, &contract_id);
-
-    let client_address = Address::random(&env);
-    let talent_address = Address::random(&env);
-    env.mock_all_auths(); // Mock all auth requirements
-    
-    let title = BytesN::from_array(&env, &[1; 32]);
-    let descriptions = vec![&env, BytesN::from_array(&env, &[2; 32])];
-    let amounts = vec![&env, 100];
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
 
-    // Create job and select talent
-    let job_id = client.create_job(
+    let client_address = Address::generate(&env);
+    let invited = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    token_admin.mint(&client_address, &100);
+    let job_id = market.create_job(
         &client_address,
-        &title,
-        &descriptions,
-        &amounts,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000u64],
     );
-    client.select_talent(&client_address, &job_id, &talent_address);
+    assert_eq!(market.get_invited(&job_id), None);
+
+    // The first invitation makes the job private
+    market.invite_talent(&client_address, &job_id, &invited);
+    let result = market.try_invite_talent(&client_address, &job_id, &invited);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+    assert_eq!(market.get_invited(&job_id), Some(vec![&env, invited.clone()]));
+    market.fund_job(&client_address, &job_id);
+    let result = market.try_offer_job(&client_address, &job_id, &stranger);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+    // A revoked invitation voids the pending offer
+    market.offer_job(&client_address, &job_id, &invited);
+    market.revoke_invite(&client_address, &job_id, &invited);
+    let result = market.try_accept_offer(&invited, &job_id);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    assert_eq!(market.get_invited(&job_id), Some(vec![&env]));
+
+    market.invite_talent(&client_address, &job_id, &invited);
+    market.accept_offer(&invited, &job_id);
+    assert_eq!(read_job(&env, &market, job_id).talent, Some(invited.clone()));
+
+    // The invitation list is frozen once someone is hired
+    let result = market.try_invite_talent(&client_address, &job_id, &stranger);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+}
+
+#[test]
+fn test_submit_milestone() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
 
     // Test successful milestone submission
     let submission_data = BytesN::from_array(&env, &[3; 32]);
-    client.submit_milestone(&talent_address, &job_id, &0, &submission_data);
-
-    // Verify milestone state
-    let job = client.get_job(&job_id);
-    let milestone = job.milestones.get(0).unwrap();
-    assert_eq!(milestone.status, MilestoneStatus::Submitted);
-    assert_eq!(milestone.submission_data, submission_data);
+    let uri = String::from_str(&env, "ipfs://deliverable");
+    market.submit_milestone(&talent_address, &job_id, &0, &submission_data, &uri);
 
     // Verify event was emitted
-    let events = env.events().all();
-    assert_eq!(events.len(), 3);
-    assert!(events.contains((
-        contract_id.clone(),
-        (Symbol::short("WORK_SUB"), talent_address.clone()).into_val(&env),
-        (job_id, 0u32, submission_data).into_val(&env)
+    assert!(env.events().all().contains((
+        market.address.clone(),
+        (symbol_short!("WRK_SUB"), job_id, talent_address.clone()).into_val(&env),
+        WorkSubmitted {
+            version: EVENT_VERSION,
+            milestone_idx: 0,
+            data: submission_data.clone(),
+            uri: uri.clone(),
+        }.into_val(&env),
     )));
 
+    let milestone = read_job(&env, &market, job_id).milestones.get(0).unwrap();
+    assert_eq!(milestone.state, MilestoneState::Submitted);
+    assert_eq!(milestone.submission_data, submission_data);
+    assert_eq!(milestone.submission_uri, Some(uri.clone()));
+
     // Test unauthorized access
-    let other_address = Address::random(&env);
-    env.mock_all_auths(); // Reset auth mocks
-    let result = client.try_submit_milestone(&other_address, &job_id, &0, &submission_data);
+    let other_address = Address::generate(&env);
+    let result = market.try_submit_milestone(&other_address, &job_id, &1, &submission_data, &uri);
     assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
 
     // Test invalid milestone index
-    env.mock_all_auths(); // Reset auth mocks
-    let result = client.try_submit_milestone(&talent_address, &job_id, &1, &submission_data);
-    assert_eq!(result, Err(Ok(Error::InvalidMilestoneIndex.into())));
+    let result = market.try_submit_milestone(&talent_address, &job_id, &2, &submission_data, &uri);
+    assert_eq!(result, Err(Ok(Error::InvalidIndex.into())));
+
+    // Test milestone already under review
+    let result = market.try_submit_milestone(&talent_address, &job_id, &0, &submission_data, &uri);
+    assert_eq!(result, Err(Ok(Error::AlreadySubmitted.into())));
+}
+
+#[test]
+fn test_job_views_return_none_for_missing_ids() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    assert_eq!(market.try_get_job(&job_id), Some(read_job(&env, &market, job_id)));
+    assert_eq!(market.get_escrow_surplus(&job_id), Some(0));
+
+    // Probing past the last id reads as empty rather than failing
+    let missing = job_id + 1;
+    assert_eq!(market.try_get_job(&missing), None);
+    assert_eq!(market.get_job_tags(&missing), None);
+    assert_eq!(market.get_managers(&missing), None);
+    assert_eq!(market.get_withdrawable(&missing), None);
+    assert_eq!(market.verify_job_invariants(&missing), None);
+    assert_eq!(market.get_action_seq(&missing), None);
+    assert_eq!(market.get_draft(&1), None);
+
+    // State changes still fail loudly
+    let result = market.try_cancel_job(&client_address, &missing);
+    assert_eq!(result, Err(Ok(Error::JobNotFound.into())));
+}
+
+#[test]
+fn test_marketplace_stats_follow_job_lifecycle() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let arbitrator_address = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &arbitrator_address);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    let stats = market.get_marketplace_stats();
+    assert_eq!((stats.total_jobs, stats.active_jobs), (1, 1));
+    assert_eq!(stats.total_escrowed.get(token.address.clone()), Some(300));
+
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    token_admin.mint(&client_address, &DISPUTE_DEPOSIT);
+    market.raise_dispute(&client_address, &job_id, &Some(1), &arbitrator_address);
+    assert_eq!(market.get_marketplace_stats().disputes_raised, 1);
+
+    // Ruled for the talent, which completes the job
+    env.ledger().with_mut(|l| l.timestamp += 100);
+    market.resolve_dispute(&arbitrator_address, &job_id, &Some(1), &true);
+    execute_ruling(&env, &market, job_id);
+    let stats = market.get_marketplace_stats();
+    assert_eq!((stats.total_jobs, stats.active_jobs), (1, 0));
+    assert_eq!(stats.total_paid_out.get(token.address.clone()), Some(300));
+    assert_eq!(stats.disputes_resolved, 1);
+    assert_eq!(stats.avg_resolution_time, 100 + APPEAL_WINDOW + 1);
+}
+
+#[test]
+fn test_earnings_and_spend_summaries() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    market.register_talent(&talent_address, &vec![&env, symbol_short!("rust")], &BytesN::from_array(&env, &[5; 32]));
+    assert_eq!(market.get_talent_earnings(&Address::generate(&env)), (0, 0, 0));
+
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    assert_eq!(market.get_talent_earnings(&talent_address), (0, 0, 1));
+    assert_eq!(market.get_client_spend(&client_address), (300, 0, 1));
+
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+    assert_eq!(market.get_talent_earnings(&talent_address), (100, 0, 1));
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &1);
+    assert_eq!(market.get_talent_earnings(&talent_address), (300, 1, 0));
+    assert_eq!(market.get_client_spend(&client_address), (300, 300, 0));
+}
+
+#[test]
+fn test_job_commitment_changes_with_terms_not_progress() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    let commitment = market.job_commitment(&job_id).unwrap();
+    assert_eq!(market.job_commitment(&(job_id + 1)), None);
+
+    // Delivering work is progress, not a change of terms
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    assert!(market.verify_job_commitment(&job_id, &commitment));
+
+    // A moved deadline is
+    let deadline = read_job(&env, &market, job_id).milestones.get(1).unwrap().deadline;
+    market.request_deadline_extension(&talent_address, &job_id, &1, &(deadline + 500));
+    market.approve_deadline_extension(&client_address, &job_id, &1);
+    assert!(!market.verify_job_commitment(&job_id, &commitment));
+    assert_ne!(market.job_commitment(&job_id).unwrap(), commitment);
+}
+
+#[test]
+fn test_derived_job_ids_skip_collisions() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, _, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let create = |nonce: u64| {
+        let options = JobOptions { client_nonce: Some(nonce), ..Default::default() };
+        market.try_create_job_with_options(
+            &client_address,
+            &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+            &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+            &vec![&env, 100],
+            &vec![&env, 1_000u64],
+            &options,
+        )
+    };
+    let derived = |nonce: u64| env.as_contract(&market.address, || DecentralizedJobMarket::job_id_for(&env, &client_address, nonce));
+
+    assert_eq!(create(1), Err(Ok(Error::InvalidInput.into())));
+    let first = create(0).unwrap().unwrap();
+    assert_eq!(first, derived(0));
+    assert!(first & DERIVED_ID_FLAG != 0);
+    assert_eq!(market.get_job_id_by_nonce(&client_address, &0), Some(first));
+    assert_eq!(market.get_job_id_at(&1), Some(first));
+    assert_eq!(market.get_client_nonce(&client_address), 1);
+
+    // The id for nonce 1 is taken: nonce 2 is used and both are consumed
+    env.as_contract(&market.address, || {
+        let job: Job = env.storage().persistent().get(&JobKey::Job(first)).unwrap();
+        env.storage().persistent().set(&JobKey::Job(derived(1)), &job);
+    });
+    let second = create(1).unwrap().unwrap();
+    assert_eq!(second, derived(2));
+    assert_eq!(market.get_job_id_by_nonce(&client_address, &1), Some(second));
+    assert_eq!(market.get_job_id_by_nonce(&client_address, &2), None);
+    assert_eq!(market.get_client_nonce(&client_address), 3);
+    assert_eq!(market.get_job_id_at(&2), Some(second));
+}
 
-    // Test milestone not pending
-    env.mock_all_auths(); // Reset auth mocks
-    let result = client.try_submit_milestone(&talent_address, &job_id, &0, &submission_data);
-    assert_eq!(result, Err(Ok(Error::MilestoneNotPending.into())));
+#[test]
+fn test_archive_job_after_retention_keeps_verifiable_summary() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    let result = market.try_archive_job(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+    market.cancel_job(&client_address, &job_id);
+    let final_job = read_job(&env, &market, job_id);
+
+    // Finished jobs stay whole for the retention period
+    let result = market.try_archive_job(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::TooEarly.into())));
+    env.ledger().with_mut(|l| l.timestamp += 7_776_000);
+    let result = market.try_archive_job(&Address::generate(&env), &job_id);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    let final_hash = market.archive_job(&talent_address, &job_id);
+
+    assert_eq!(market.try_get_job(&job_id), None);
+    let archived = market.get_archived_job(&job_id).unwrap();
+    assert_eq!(archived.outcome, JobState::Cancelled);
+    assert_eq!(archived.final_hash, final_hash);
+    assert_eq!(archived.refunded, final_job.refunded);
+    assert!(market.verify_archived_job(&job_id, &final_job));
+    let mut forged = final_job.clone();
+    forged.amount_paid += 1;
+    assert!(!market.verify_archived_job(&job_id, &forged));
+}
+
+#[test]
+fn test_job_metadata_set_by_client_until_job_ends() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    assert_eq!(market.get_job_metadata(&job_id), None);
+
+    let uri = String::from_str(&env, "ipfs://spec-v1");
+    let hash = BytesN::from_array(&env, &[7; 32]);
+    let result = market.try_set_job_metadata(&talent_address, &job_id, &uri, &hash);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    let result = market.try_set_job_metadata(&client_address, &job_id, &String::from_str(&env, ""), &hash);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+
+    market.set_job_metadata(&client_address, &job_id, &uri, &hash);
+    let metadata = market.get_job_metadata(&job_id).unwrap();
+    assert_eq!((metadata.uri, metadata.hash), (uri.clone(), hash.clone()));
+
+    market.cancel_job(&client_address, &job_id);
+    let result = market.try_set_job_metadata(&client_address, &job_id, &uri, &hash);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
 }
 
 #[test]
 fn test_approve_milestone() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, JobMarketplaceContract);
-    let client = JobMarketplaceContractClient::new(&env, &contract_id);
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
 
-    let client_address = Address::random(&env);
-    let talent_address = Address::random(&env);
-    env.mock_all_auths(); // Mock all auth requirements
-    
-    let title = BytesN::from_array(&env, &[1; 32]);
-    let descriptions = vec![&env, BytesN::from_array(&env, &[2; 32])];
-    let amounts = vec![&env, 100];
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
 
-    // Create job, select talent, and submit milestone
-    let job_id = client.create_job(
-        &client_address,
-        &title,
-        &descriptions,
-        &amounts,
-    );
-    client.select_talent(&client_address, &job_id, &talent_address);
-    let submission_data = BytesN::from_array(&env, &[3; 32]);
-    client.submit_milestone(&talent_address, &job_id, &0, &submission_data);
+    // Test invalid milestone state
+    let result = market.try_approve_milestone(&client_address, &job_id, &0);
+    assert_eq!(result, Err(Ok(Error::NotSubmitted.into())));
 
-    // Test successful milestone approval
-    client.approve_milestone(&client_address, &job_id, &0);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
 
-    // Verify milestone and job state
-    let job = client.get_job(&job_id);
-    let milestone = job.milestones.get(0).unwrap();
-    assert_eq!(milestone.status, MilestoneStatus::Approved);
-    assert_eq!(job.amount_paid, 100);
-    assert_eq!(job.status, JobStatus::Completed);
+    // Test unauthorized access
+    let other_address = Address::generate(&env);
+    let result = market.try_approve_milestone(&other_address, &job_id, &0);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+    // Test successful milestone approval
+    market.approve_milestone(&client_address, &job_id, &0);
 
     // Verify event was emitted
-    let events = env.events().all();
-    assert_eq!(events.len(), 4);
-    assert!(events.contains((
-        contract_id.clone(),
-        (Symbol::short("MILEST_APPR"), client_address.clone()).into_val(&env),
-        (job_id, 0u32, 100, talent_address).into_val(&env)
+    assert!(env.events().all().contains((
+        market.address.clone(),
+        (symbol_short!("MIL_APR"), job_id, client_address.clone()).into_val(&env),
+        PaymentMade { version: EVENT_VERSION, index: Some(0), amount: 100, fee: 0 }.into_val(&env),
     )));
 
-    // Test unauthorized access
-    let other_address = Address::random(&env);
-    env.mock_all_auths(); // Reset auth mocks
-    let result = client.try_approve_milestone(&other_address, &job_id, &0);
-    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.milestones.get(0).unwrap().state, MilestoneState::Paid);
+    assert_eq!(job.amount_paid, 100);
+    assert_eq!(job.state, JobState::Active);
 
-    // Test invalid milestone state
-    env.mock_all_auths(); // Reset auth mocks
-    let result = client.try_approve_milestone(&client_address, &job_id, &0);
-    assert_eq!(result, Err(Ok(Error::MilestoneNotSubmitted.into())));
+    // Paying the last milestone completes the job
+    market.approve_milestone(&client_address, &job_id, &1);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.state, JobState::Completed);
+    assert_eq!(job.escrow_balance, 0);
+    assert_eq!(token.balance(&talent_address), 300);
 }
 
 #[test]
-fn test_dispute_workflow() {
+fn test_job_history_records_each_state_transition() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, JobMarketplaceContract);
-    let client = JobMarketplaceContractClient::new(&env, &contract_id);
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
 
-    let client_address = Address::random(&env);
-    let talent_address = Address::random(&env);
-    let arbitrator_address = Address::random(&env);
-    env.mock_all_auths(); // Mock all auth requirements
-    
-    let title = BytesN::from_array(&env, &[1; 32]);
-    let descriptions = vec![&env, BytesN::from_array(&env, &[2; 32])];
-    let amounts = vec![&env, 100];
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    let data = BytesN::from_array(&env, &[3; 32]);
+    let uri = String::from_str(&env, "ipfs://deliverable");
+    market.submit_milestone(&talent_address, &job_id, &0, &data, &uri);
+    market.approve_milestone(&client_address, &job_id, &0);
+    market.submit_milestone(&talent_address, &job_id, &1, &data, &uri);
+    env.ledger().with_mut(|l| l.timestamp += 100);
+    market.approve_milestone(&client_address, &job_id, &1);
 
-    // Create job, select talent, and submit milestone
-    let job_id = client.create_job(
-        &client_address,
-        &title,
-        &descriptions,
-        &amounts,
+    let history = market.get_job_history(&job_id, &0, &10);
+    let mut states = Vec::new(&env);
+    for transition in history.iter() {
+        states.push_back(transition.to);
+    }
+    assert_eq!(
+        states,
+        vec![&env, JobState::Created, JobState::Funded, JobState::Active, JobState::Completed]
     );
-    client.select_talent(&client_address, &job_id, &talent_address);
-    let submission_data = BytesN::from_array(&env, &[3; 32]);
-    client.submit_milestone(&talent_address, &job_id, &0, &submission_data);
+    let first = history.get(0).unwrap();
+    assert_eq!((first.from, first.actor), (OptJobState::None, client_address.clone()));
+    let accepted = history.get(2).unwrap();
+    assert_eq!((accepted.from, accepted.actor), (OptJobState::Some(JobState::Funded), talent_address));
+    assert_eq!(history.get(3).unwrap().at, env.ledger().timestamp());
 
-    // Test raising a dispute (by client)
-    client.raise_dispute(&client_address, &job_id, &Some(0));
+    // Paged reads
+    let page = market.get_job_history(&job_id, &3, &10);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().to, JobState::Completed);
+    assert!(market.get_job_history(&job_id, &4, &10).is_empty());
+}
 
-    // Verify job state
-    let job = client.get_job(&job_id);
-    assert_eq!(job.status, JobStatus::Disputed);
-    assert_eq!(job.dispute_raised, Some(client_address.clone()));
+#[test]
+fn test_escrow_events_track_every_token_movement() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
 
-    // Verify event was emitted
-    let events = env.events().all();
-    assert_eq!(events.len(), 4);
-    assert!(events.contains((
-        contract_id.clone(),
-        (Symbol::short("DISPUTE_RAISED"), client_address.clone()).into_val(&env),
-        (job_id, Some(0u32)).into_val(&env)
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    token_admin.mint(&client_address, &300);
+    let job_id = market.create_job(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32])), Text::Hash(BytesN::from_array(&env, &[3; 32]))],
+        &vec![&env, 100, 200],
+        &vec![&env, 1_000u64, 2_000u64],
+    );
+    market.fund_job(&client_address, &job_id);
+    assert!(env.events().all().contains((
+        market.address.clone(),
+        (symbol_short!("ESC_IN"), job_id, client_address.clone()).into_val(&env),
+        TokenMoved { version: EVENT_VERSION, token: token.address.clone(), amount: 300 }.into_val(&env),
     )));
 
-    // Test resolving dispute (approve)
-    env.mock_all_auths(); // Reset auth mocks
-    client.resolve_dispute(&arbitrator_address, &job_id, &Some(0), &true);
-
-    // Verify job state
-    let job = client.get_job(&job_id);
-    assert_eq!(job.status, JobStatus::Completed);
-    assert_eq!(job.amount_paid, 100);
-    assert_eq!(job.milestones.get(0).unwrap().status, MilestoneStatus::Approved);
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, ""));
+    market.approve_milestone(&client_address, &job_id, &0);
+    assert!(env.events().all().contains((
+        market.address.clone(),
+        (symbol_short!("ESC_OUT"), job_id, talent_address.clone()).into_val(&env),
+        TokenMoved { version: EVENT_VERSION, token: token.address.clone(), amount: 100 }.into_val(&env),
+    )));
 
-    // Verify event was emitted
+    // Refunds are reported against the job too
+    market.cancel_job(&client_address, &job_id);
     let events = env.events().all();
-    assert_eq!(events.len(), 5);
     assert!(events.contains((
-        contract_id.clone(),
-        (Symbol::short("DISPUTE_RES"), arbitrator_address.clone()).into_val(&env),
-        (job_id, Some(0u32), true).into_val(&env)
+        market.address.clone(),
+        (symbol_short!("ESC_OUT"), job_id, client_address.clone()).into_val(&env),
+        TokenMoved { version: EVENT_VERSION, token: token.address.clone(), amount: 180 }.into_val(&env),
     )));
-
-    // Test raising dispute on non-existent job
-    env.mock_all_auths(); // Reset auth mocks
-    let result = client.try_raise_dispute(&client_address, &999, &Some(0));
-    assert_eq!(result, Err(Ok(Error::JobNotFound.into())));
+    assert_eq!(token.balance(&market.address), 0);
 }
 
 #[test]
-fn test_reentrancy_guard() {
+fn test_multisig_client_actions_need_threshold_approvals() {
     let env = Env::default();
-    let contract_id = env.register_contract(None, JobMarketplaceContract);
-    let client = JobMarketplaceContractClient::new(&env, &contract_id);
-
-    let client_address = Address::random(&env);
-    env.mock_all_auths(); // Mock all auth requirements
-    
-    let title = BytesN::from_array(&env, &[1; 32]);
-    let descriptions = vec![&env, BytesN::from_array(&env, &[2; 32])];
-    let amounts = vec![&env, 100];
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
 
-    // This should succeed
-    let job_id = client.create_job(
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let cfo = Address::generate(&env);
+    let cto = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let approvers = vec![&env, cfo.clone(), cto.clone()];
+    let create = |threshold: u32| market.try_create_job_multisig(
         &client_address,
-        &title,
-        &descriptions,
-        &amounts,
+        &approvers,
+        &threshold,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32])), Text::Hash(BytesN::from_array(&env, &[3; 32]))],
+        &vec![&env, 100, 200],
+        &vec![&env, 1_000u64, 2_000],
     );
+    assert_eq!(create(3), Err(Ok(Error::InvalidInput.into())));
+    let job_id = create(2).unwrap().unwrap();
+    token_admin.mint(&client_address, &300);
+    market.fund_job(&client_address, &job_id);
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+
+    // The client alone cannot release funds
+    let result = market.try_approve_milestone(&client_address, &job_id, &0);
+    assert_eq!(result, Err(Ok(Error::ApprovalsPending.into())));
+    let result = market.try_propose_job_action(&outsider, &job_id, &JobAction::ApproveMilestone(0));
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    let action_id = market.propose_job_action(&cfo, &job_id, &JobAction::ApproveMilestone(0));
+    let result = market.try_approve_action(&cfo, &job_id, &action_id);
+    assert_eq!(result, Err(Ok(Error::AlreadyVoted.into())));
+    let result = market.try_approve_milestone(&client_address, &job_id, &0);
+    assert_eq!(result, Err(Ok(Error::ApprovalsPending.into())));
 
-    // Verify guard was cleared
-    assert!(!env.storage().has(&Symbol::short("REENTRANCY")));
-}
\ No newline at end of file
+    // Once the threshold is met the approval goes through and is consumed
+    market.approve_action(&cto, &job_id, &action_id);
+    market.approve_milestone(&client_address, &job_id, &0);
+    assert_eq!(token.balance(&talent_address), 100);
+    assert_eq!(market.get_job_action(&job_id, &action_id), None);
+
+    // Cancelling needs its own sign-off
+    let result = market.try_cancel_job(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::ApprovalsPending.into())));
+    let action_id = market.propose_job_action(&cto, &job_id, &JobAction::Cancel);
+    market.approve_action(&cfo, &job_id, &action_id);
+    market.cancel_job(&client_address, &job_id);
+    assert_eq!(read_job(&env, &market, job_id).state, JobState::Cancelled);
+}
+
+#[test]
+fn test_job_ownership_transfers_to_new_client() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let new_client = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let manager = Address::generate(&env);
+    token_admin.mint(&client_address, &300);
+    let options = JobOptions { client_nonce: Some(0), ..Default::default() };
+    let job_id = market.create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32])), Text::Hash(BytesN::from_array(&env, &[3; 32]))],
+        &vec![&env, 100, 200],
+        &vec![&env, 1_000u64, 2_000u64],
+        &options,
+    );
+    market.fund_job(&client_address, &job_id);
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+    market.add_manager(&client_address, &job_id, &manager, &vec![&env, Permission::ApproveMilestones]);
+
+    // The hired talent cannot become their own client
+    let result = market.try_transfer_job_ownership(&client_address, &job_id, &talent_address);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    market.transfer_job_ownership(&client_address, &job_id, &new_client);
+
+    // Authority moves to the new client; escrow and the nonce lookup stay put
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.client, new_client);
+    assert_eq!(job.escrow_balance, 300);
+    assert!(job.roles.managers.is_empty());
+    assert_eq!(market.get_job_id_by_nonce(&client_address, &0), Some(job_id));
+
+    let uri = String::from_str(&env, "ipfs://deliverable");
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[4; 32]), &uri);
+    let result = market.try_approve_milestone(&client_address, &job_id, &0);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    let result = market.try_approve_milestone(&manager, &job_id, &0);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    market.approve_milestone(&new_client, &job_id, &0);
+    assert_eq!(token.balance(&talent_address), 100);
+
+    // Unspent escrow is refunded to the new client
+    market.cancel_job(&new_client, &job_id);
+    assert_eq!(token.balance(&client_address), 0);
+    assert_eq!(token.balance(&new_client) + token.balance(&talent_address), 300);
+    assert_eq!(market.get_client_profile(&client_address).open_jobs, 0);
+}
+
+#[test]
+fn test_job_manager_acts_within_granted_permissions() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let manager = Address::generate(&env);
+    token_admin.mint(&client_address, &300);
+    let job_id = market.create_job(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32])), Text::Hash(BytesN::from_array(&env, &[3; 32]))],
+        &vec![&env, 100, 200],
+        &vec![&env, 1_000u64, 2_000],
+    );
+    market.fund_job(&client_address, &job_id);
+
+    // Only the client grants permissions, and never to themselves
+    let hiring = vec![&env, Permission::SelectTalent];
+    let result = market.try_add_manager(&manager, &job_id, &manager, &hiring);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    let result = market.try_add_manager(&client_address, &job_id, &client_address, &hiring);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    market.add_manager(&client_address, &job_id, &manager, &hiring);
+
+    // A hiring manager can offer the job but not approve work
+    market.offer_job(&manager, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    let result = market.try_approve_milestone(&manager, &job_id, &0);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+    // Re-adding replaces the scope
+    market.add_manager(&client_address, &job_id, &manager, &vec![&env, Permission::ApproveMilestones]);
+    assert_eq!(market.get_managers(&job_id).unwrap().get(manager.clone()), Some(vec![&env, Permission::ApproveMilestones]));
+    market.approve_milestone(&manager, &job_id, &0);
+    assert_eq!(token.balance(&talent_address), 100);
+
+    // Managers never cancel, and lose their powers once removed
+    let result = market.try_cancel_job(&manager, &job_id);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    market.remove_manager(&client_address, &job_id, &manager);
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[5; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    let result = market.try_approve_milestone(&manager, &job_id, &1);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+}
+
+#[test]
+fn test_bonus_and_tip_bypass_escrow() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+
+    // Bonuses and tips come from the client's wallet, not the escrow
+    token_admin.mint(&client_address, &30);
+    let result = market.try_approve_with_bonus(&client_address, &job_id, &0, &0);
+    assert_eq!(result, Err(Ok(Error::AmountRequired.into())));
+    market.approve_with_bonus(&client_address, &job_id, &0, &20);
+    assert_eq!(token.balance(&talent_address), 120);
+    assert_eq!(read_job(&env, &market, job_id).escrow_balance, 200);
+
+    let result = market.try_tip_talent(&client_address, &job_id, &10);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &1);
+    market.tip_talent(&client_address, &job_id, &10);
+
+    assert_eq!(token.balance(&talent_address), 330);
+    assert_eq!(token.balance(&client_address), 0);
+    assert_eq!(market.get_client_profile(&client_address).total_spent, 330);
+}
+
+#[test]
+fn test_payout_split_divides_milestone_payments() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let designer = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+
+    // Shares must add up to 100% and only the lead sets them
+    let result = market.try_set_payout_split(&talent_address, &job_id, &vec![&env, (designer.clone(), 5_000u32)]);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    let split = vec![&env, (talent_address.clone(), 6_000u32), (designer.clone(), 4_000u32)];
+    let result = market.try_set_payout_split(&designer, &job_id, &split);
+    assert_eq!(result, Err(Ok(Error::TalentOnly.into())));
+    market.set_payout_split(&talent_address, &job_id, &split);
+
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+    assert_eq!(token.balance(&talent_address), 60);
+    assert_eq!(token.balance(&designer), 40);
+}
+
+#[test]
+fn test_milestone_payees_deliver_and_get_paid() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let vendor = Address::generate(&env);
+    token_admin.mint(&client_address, &300);
+    let create = |payees: Vec<Option<Address>>| market.try_create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32])), Text::Hash(BytesN::from_array(&env, &[3; 32]))],
+        &vec![&env, 100, 200],
+        &vec![&env, 1_000u64, 2_000u64],
+        &JobOptions { milestone_payees: Some(payees), ..Default::default() },
+    );
+
+    // One payee per milestone, and never the client
+    let result = create(vec![&env, Some(vendor.clone())]);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    let result = create(vec![&env, None, Some(client_address.clone())]);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+
+    let job_id = create(vec![&env, None, Some(vendor.clone())]).unwrap().unwrap();
+    market.fund_job(&client_address, &job_id);
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+
+    // The vendor delivers their own milestone, but not the talent's
+    let uri = String::from_str(&env, "ipfs://deliverable");
+    let result = market.try_submit_milestone(&vendor, &job_id, &0, &BytesN::from_array(&env, &[4; 32]), &uri);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[4; 32]), &uri);
+    market.submit_milestone(&vendor, &job_id, &1, &BytesN::from_array(&env, &[5; 32]), &uri);
+
+    market.approve_milestone(&client_address, &job_id, &0);
+    market.approve_milestone(&client_address, &job_id, &1);
+    assert_eq!(token.balance(&talent_address), 100);
+    assert_eq!(token.balance(&vendor), 200);
+    assert_eq!(read_job(&env, &market, job_id).state, JobState::Completed);
+}
+
+#[test]
+fn test_subjob_settles_from_parent_payout() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let subcontractor = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+
+    let child_id = market.create_subjob(
+        &talent_address,
+        &job_id,
+        &1,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 80],
+        &vec![&env, 2_000u64],
+    );
+    // A milestone cannot fund more than its own payout
+    let result = market.try_create_subjob(
+        &talent_address,
+        &job_id,
+        &1,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 150],
+        &vec![&env, 2_000u64],
+    );
+    assert_eq!(result, Err(Ok(Error::InsufficientFunds.into())));
+
+    market.offer_job(&talent_address, &child_id, &subcontractor);
+    market.accept_offer(&subcontractor, &child_id);
+    market.submit_milestone(&subcontractor, &child_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    let result = market.try_approve_milestone(&talent_address, &child_id, &0);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+
+    // The parent payout releases the reservation to the child
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &1);
+    assert_eq!(token.balance(&talent_address), 120);
+    market.approve_milestone(&talent_address, &child_id, &0);
+    assert_eq!(token.balance(&subcontractor), 80);
+    assert_eq!(read_job(&env, &market, child_id).state, JobState::Completed);
+}
+
+#[test]
+fn test_approve_milestones_in_batch() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+
+    // All or nothing: milestone 1 is not submitted yet
+    let result = market.try_approve_milestones(&client_address, &job_id, &vec![&env, 0, 1]);
+    assert_eq!(result, Err(Ok(Error::NotSubmitted.into())));
+    assert_eq!(token.balance(&talent_address), 0);
+
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    let result = market.try_approve_milestones(&client_address, &job_id, &vec![&env, 0, 0]);
+    assert_eq!(result, Err(Ok(Error::NotSubmitted.into())));
+    market.approve_milestones(&client_address, &job_id, &vec![&env, 0, 1]);
+
+    assert!(env.events().all().contains((
+        market.address.clone(),
+        (symbol_short!("MIL_BAT"), job_id, client_address.clone()).into_val(&env),
+        BatchApproved { version: EVENT_VERSION, indices: vec![&env, 0, 1], amount: 300, fee: 0 }.into_val(&env),
+    )));
+    assert_eq!(token.balance(&talent_address), 300);
+    assert_eq!(read_job(&env, &market, job_id).state, JobState::Completed);
+}
+
+#[test]
+fn test_relayed_approval_is_bound_to_reviewed_submission() {
+    use soroban_sdk::testutils::{AuthorizedFunction, AuthorizedInvocation};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    let reviewed = BytesN::from_array(&env, &[3; 32]);
+    market.submit_milestone(&talent_address, &job_id, &0, &reviewed, &String::from_str(&env, "ipfs://deliverable"));
+
+    // An approval signed for other work does not apply
+    let result = market.try_approve_milestone_signed(&client_address, &job_id, &0, &BytesN::from_array(&env, &[8; 32]));
+    assert_eq!(result, Err(Ok(Error::NotSubmitted.into())));
+
+    market.approve_milestone_signed(&client_address, &job_id, &0, &reviewed);
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(
+        auths[0],
+        (
+            client_address.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    market.address.clone(),
+                    Symbol::new(&env, "approve_milestone_signed"),
+                    (job_id, 0u32, reviewed.clone()).into_val(&env),
+                )),
+                sub_invocations: Default::default(),
+            },
+        )
+    );
+    assert_eq!(token.balance(&talent_address), 100);
+}
+
+#[test]
+fn test_session_key_submits_and_requests_extensions() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let bot = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    let other_job = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    let now = env.ledger().timestamp();
+    market.grant_session_key(&talent_address, &bot, &vec![&env, job_id], &(now + 500));
+
+    // The key works only on the jobs it was granted
+    market.submit_milestone(&bot, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    let result = market.try_submit_milestone(&bot, &other_job, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    let result = market.try_request_deadline_extension(&bot, &job_id, &0, &(now + 5_000));
+    assert_eq!(result, Err(Ok(Error::MilestonePending.into())));
+    let result = market.try_request_deadline_extension(&bot, &job_id, &1, &(now + 2_000));
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    market.request_deadline_extension(&bot, &job_id, &1, &(now + 5_000));
+    assert_eq!(market.get_deadline_requests(&job_id), map![&env, (1, now + 5_000)]);
+
+    let result = market.try_approve_deadline_extension(&talent_address, &job_id, &1);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    market.approve_deadline_extension(&client_address, &job_id, &1);
+    assert_eq!(read_job(&env, &market, job_id).milestones.get(1).unwrap().deadline, now + 5_000);
+    assert_eq!(market.get_deadline_requests(&job_id).len(), 0);
+
+    // Expired or revoked keys are refused
+    env.ledger().with_mut(|l| l.timestamp = now + 500);
+    let result = market.try_request_deadline_extension(&bot, &job_id, &1, &(now + 6_000));
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    market.revoke_session_key(&talent_address, &bot);
+    assert_eq!(market.get_session_key(&bot), None);
+}
+
+#[test]
+fn test_retainer_periods_run_from_hire_until_cap() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    token_admin.mint(&client_address, &120);
+    let terms = RetainerTerms { period: 5_000, payment_per_period: 10, max_periods: 2 };
+    let options = JobOptions { retainer: OptRetainer::Some(terms), ..Default::default() };
+    let job_id = market.create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000u64],
+        &options,
+    );
+    market.fund_job(&client_address, &job_id);
+    token.approve(&client_address, &market.address, &20, &1_000);
+
+    // Hired a while after posting: the first period starts now
+    env.ledger().with_mut(|l| l.timestamp = 500);
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+    assert_eq!(read_job(&env, &market, job_id).payment.next_period_at, 5_500);
+    let result = market.try_advance_period(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::TooEarly.into())));
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+    assert_eq!(read_job(&env, &market, job_id).state, JobState::Active);
+
+    // Only the client may pull from its allowance
+    env.ledger().with_mut(|l| l.timestamp = 5_500);
+    let result = market.try_advance_period(&talent_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    assert_eq!(market.advance_period(&client_address, &job_id), 1);
+
+    // The retainer ends after its last period
+    env.ledger().with_mut(|l| l.timestamp = 10_500);
+    assert_eq!(market.advance_period(&client_address, &job_id), 2);
+    let result = market.try_advance_period(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+    assert_eq!(token.balance(&client_address), 0);
+
+    for idx in 1..3u32 {
+        market.submit_milestone(&talent_address, &job_id, &idx, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+        market.approve_milestone(&client_address, &job_id, &idx);
+    }
+    assert_eq!(token.balance(&talent_address), 120);
+    assert_eq!(read_job(&env, &market, job_id).state, JobState::Completed);
+}
+
+#[test]
+fn test_stream_vests_from_hire_and_settles_on_stop() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    market.register_talent(&talent_address, &vec![&env, symbol_short!("rust")], &BytesN::from_array(&env, &[5; 32]));
+    token_admin.mint(&client_address, &100);
+    let options = JobOptions { stream_duration: Some(1_000), ..Default::default() };
+    let job_id = market.create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000u64],
+        &options,
+    );
+    market.fund_job(&client_address, &job_id);
+
+    // Hired at ledger time 0: vesting still starts
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+    let result = market.try_submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+    let result = market.try_cancel_job(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+
+    env.ledger().with_mut(|l| l.timestamp = 250);
+    assert_eq!(market.get_withdrawable(&job_id).unwrap(), 25);
+    assert_eq!(market.withdraw_streamed(&talent_address, &job_id), 25);
+    let result = market.try_withdraw_streamed(&talent_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::AmountRequired.into())));
+
+    // Nothing vested is left unclaimed, so stopping settles the job
+    market.stop_stream(&client_address, &job_id);
+    assert_eq!(token.balance(&client_address), 75);
+    assert_eq!(token.balance(&talent_address), 25);
+    assert_eq!(read_job(&env, &market, job_id).state, JobState::Completed);
+    assert_eq!(market.get_talent_profile(&talent_address).unwrap().jobs_completed, 1);
+}
+
+#[test]
+fn test_hourly_timesheets_settle_from_budget() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    token_admin.mint(&client_address, &100);
+    let options = JobOptions { hourly_rate: Some(10), ..Default::default() };
+    let job_id = market.create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000u64],
+        &options,
+    );
+    market.fund_job(&client_address, &job_id);
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+
+    let period = BytesN::from_array(&env, &[6; 32]);
+    let result = market.try_submit_milestone(&talent_address, &job_id, &0, &period, &String::from_str(&env, "ipfs://deliverable"));
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+    assert_eq!(market.log_hours(&talent_address, &job_id, &period, &3), 0);
+    market.approve_timesheet(&client_address, &job_id, &0);
+    let result = market.try_approve_timesheet(&client_address, &job_id, &0);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+    assert_eq!(token.balance(&talent_address), 30);
+
+    // Timesheets cannot outrun the escrowed budget
+    assert_eq!(market.log_hours(&talent_address, &job_id, &period, &8), 1);
+    let result = market.try_approve_timesheet(&client_address, &job_id, &1);
+    assert_eq!(result, Err(Ok(Error::InsufficientFunds.into())));
+    assert_eq!(market.log_hours(&talent_address, &job_id, &period, &7), 2);
+    market.approve_timesheet(&client_address, &job_id, &2);
+
+    assert_eq!(token.balance(&talent_address), 100);
+    assert!(market.get_timesheets(&job_id).get(2).unwrap().approved);
+    assert_eq!(read_job(&env, &market, job_id).state, JobState::Completed);
+}
+
+#[test]
+fn test_replace_talent_after_missed_deadlines() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let old_talent = Address::generate(&env);
+    let new_talent = Address::generate(&env);
+    token_admin.mint(&client_address, &300);
+    token_admin.mint(&old_talent, &50);
+    token_admin.mint(&new_talent, &50);
+    let options = JobOptions { talent_bond: Some(50), ..Default::default() };
+    let job_id = market.create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32])), Text::Hash(BytesN::from_array(&env, &[3; 32]))],
+        &vec![&env, 100, 200],
+        &vec![&env, 1_000u64, 2_000u64],
+        &options,
+    );
+    market.fund_job(&client_address, &job_id);
+    market.offer_job(&client_address, &job_id, &old_talent);
+    market.accept_offer(&old_talent, &job_id);
+    let uri = String::from_str(&env, "ipfs://deliverable");
+    market.submit_milestone(&old_talent, &job_id, &0, &BytesN::from_array(&env, &[4; 32]), &uri);
+    market.approve_milestone(&client_address, &job_id, &0);
+
+    // Milestone 1 goes unsubmitted past its deadline: the old bond is forfeited
+    env.ledger().with_mut(|l| l.timestamp = 2_001);
+    market.replace_talent(&client_address, &job_id, &new_talent);
+    assert_eq!(token.balance(&client_address), 50);
+    assert_eq!(token.balance(&new_talent), 0);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.talent, Some(new_talent.clone()));
+    // The remaining milestone gets the 1_000s it originally had after the first
+    assert_eq!(job.milestones.get(1).unwrap().deadline, 3_001);
+
+    // The old talent keeps what they were paid; the new one finishes the job
+    let result = market.try_submit_milestone(&old_talent, &job_id, &1, &BytesN::from_array(&env, &[5; 32]), &uri);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    market.submit_milestone(&new_talent, &job_id, &1, &BytesN::from_array(&env, &[5; 32]), &uri);
+    market.approve_milestone(&client_address, &job_id, &1);
+    assert_eq!(token.balance(&old_talent), 100);
+    assert_eq!(token.balance(&new_talent), 250);
+    assert_eq!(token.balance(&market.address), 0);
+    assert_eq!(read_job(&env, &market, job_id).state, JobState::Completed);
+}
+
+#[test]
+fn test_amendment_counter_offers_settle_the_difference() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+
+    // The talent declines with a counter; the client accepts a lower amount
+    market.propose_amendment(&client_address, &job_id, &0, &AmendmentTerms { amount: 60, deadline: 1_500 });
+    let counter = AmendmentTerms { amount: 80, deadline: 1_500 };
+    assert_eq!(market.decline_amendment(&talent_address, &job_id, &Some(counter)), Some(1));
+    // Only the other party answers
+    let result = market.try_accept_amendment(&talent_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    market.accept_amendment(&client_address, &job_id);
+    assert_eq!(token.balance(&client_address), 20);
+
+    // A higher amount accepted by the talent is pulled against the allowance
+    market.propose_amendment(&client_address, &job_id, &1, &AmendmentTerms { amount: 210, deadline: 3_000 });
+    let result = market.try_accept_amendment(&talent_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::InsufficientFunds.into())));
+    token.approve(&client_address, &market.address, &10, &1_000);
+    market.accept_amendment(&talent_address, &job_id);
+
+    assert_eq!(token.balance(&client_address), 10);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.total_value, 290);
+    assert_eq!(job.milestones.get(0).unwrap().deadline, 1_500);
+    assert_eq!(job.milestones.get(1).unwrap().amount, 210);
+    assert_eq!(market.verify_job_invariants(&job_id), Some(true));
+
+    // Every round stays on record
+    let amendments = market.get_amendments(&job_id).unwrap();
+    assert_eq!(amendments.len(), 3);
+    assert_eq!(amendments.get(0).unwrap().status, AmendmentStatus::Countered);
+    assert_eq!(amendments.get(1).unwrap().status, AmendmentStatus::Accepted);
+    assert_eq!(amendments.get(2).unwrap().status, AmendmentStatus::Accepted);
+}
+
+#[test]
+fn test_amendment_rounds_are_capped_per_negotiation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+
+    let terms = |round: u32| AmendmentTerms { amount: 20 + round as i128, deadline: 1_000 };
+    market.propose_amendment(&client_address, &job_id, &0, &terms(0));
+    for round in 1..MAX_AMENDMENTS {
+        let party = if round % 2 == 1 { &talent_address } else { &client_address };
+        market.decline_amendment(party, &job_id, &Some(terms(round)));
+    }
+    let result = market.try_decline_amendment(&client_address, &job_id, &Some(terms(99)));
+    assert_eq!(result, Err(Ok(Error::SubmissionLimit.into())));
+
+    // Closing the negotiation lets the client open a fresh one
+    market.decline_amendment(&client_address, &job_id, &None);
+    let amendment_idx = market.propose_amendment(&client_address, &job_id, &0, &terms(0));
+    assert_eq!(amendment_idx, MAX_AMENDMENTS);
+    market.decline_amendment(&talent_address, &job_id, &Some(terms(1)));
+    assert_eq!(market.get_amendments(&job_id).unwrap().len(), MAX_AMENDMENTS + 2);
+}
+
+#[test]
+fn test_talent_resigns_and_client_gets_unspent_escrow() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+
+    let result = market.try_resign(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::TalentOnly.into())));
+    market.resign(&talent_address, &job_id);
+
+    assert!(env.events().all().contains((
+        market.address.clone(),
+        (symbol_short!("TAL_RES"), job_id, talent_address.clone()).into_val(&env),
+        Settlement { version: EVENT_VERSION, talent_amount: 0, client_amount: 200, bond_forfeited: 0 }.into_val(&env),
+    )));
+    assert_eq!(token.balance(&client_address), 200);
+    assert_eq!(token.balance(&talent_address), 100);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.state, JobState::Cancelled);
+    assert_eq!(job.milestones.get(0).unwrap().state, MilestoneState::Paid);
+    assert_eq!(job.milestones.get(1).unwrap().state, MilestoneState::Void);
+}
+
+/// Hire `talent` on a 100+200 job whose approvals vest after 1_000 seconds
+fn hire_with_release_delay(
+    env: &Env,
+    market: &DecentralizedJobMarketClient,
+    token_admin: &StellarAssetClient,
+    client: &Address,
+    talent: &Address,
+) -> u32 {
+    token_admin.mint(client, &300);
+    let now = env.ledger().timestamp();
+    let options = JobOptions { release_delay: Some(1_000), ..Default::default() };
+    let job_id = market.create_job_with_options(
+        client,
+        &Text::Hash(BytesN::from_array(env, &[1; 32])),
+        &vec![env, Text::Hash(BytesN::from_array(env, &[2; 32])), Text::Hash(BytesN::from_array(env, &[3; 32]))],
+        &vec![env, 100, 200],
+        &vec![env, now + 5_000, now + 6_000],
+        &options,
+    );
+    market.fund_job(client, &job_id);
+    market.offer_job(client, &job_id, talent);
+    market.accept_offer(talent, &job_id);
+    job_id
+}
+
+#[test]
+fn test_release_delay_vests_approvals_until_claimed_or_frozen() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let arbitrator = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &arbitrator);
+    let job_id = hire_with_release_delay(&env, &market, &token_admin, &client_address, &talent_address);
+
+    // Nothing to freeze before the client approves
+    let result = market.try_freeze_vested(&client_address, &job_id, &0, &arbitrator);
+    assert_eq!(result, Err(Ok(Error::TooEarly.into())));
+
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+    assert_eq!(read_job(&env, &market, job_id).milestones.get(0).unwrap().state, MilestoneState::Approved);
+    assert_eq!(token.balance(&talent_address), 0);
+    let result = market.try_claim_vested(&talent_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::NothingVested.into())));
+    let result = market.try_cancel_job(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+
+    // Once vested the client can no longer stop it
+    env.ledger().with_mut(|l| l.timestamp += 1_000);
+    let result = market.try_freeze_vested(&client_address, &job_id, &0, &arbitrator);
+    assert_eq!(result, Err(Ok(Error::DeadlinePassed.into())));
+    assert_eq!(market.claim_vested(&talent_address, &job_id), 100);
+    assert_eq!(token.balance(&talent_address), 100);
+
+    // A freeze inside the window turns the approval into a dispute
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &1);
+    token_admin.mint(&client_address, &30);
+    market.freeze_vested(&client_address, &job_id, &1, &arbitrator);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.milestones.get(1).unwrap().state, MilestoneState::Disputed);
+    assert_eq!(job.milestones.get(1).unwrap().vests_at, None);
+}
+
+#[test]
+fn test_resign_while_vesting_pays_approved_work() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_with_release_delay(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+
+    // The approved 100 goes to the talent; only the unworked 200 is refunded
+    market.resign(&talent_address, &job_id);
+    assert_eq!(token.balance(&talent_address), 100);
+    assert_eq!(token.balance(&client_address), 200);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.state, JobState::Cancelled);
+    assert_eq!(job.milestones.get(0).unwrap().state, MilestoneState::Paid);
+    assert_eq!(job.milestones.get(1).unwrap().state, MilestoneState::Void);
+
+    // A mutual cancellation splits only what is left after approved work
+    let job_id = hire_with_release_delay(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+    let result = market.try_propose_cancellation(&client_address, &job_id, &100, &200);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    market.propose_cancellation(&client_address, &job_id, &50, &150);
+    market.accept_cancellation(&talent_address, &job_id);
+    assert_eq!(token.balance(&talent_address), 250);
+    assert_eq!(token.balance(&client_address), 350);
+    assert_eq!(token.balance(&market.address), 0);
+}
+
+#[test]
+fn test_talent_bond_returned_on_completion_and_forfeited_on_resign() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    token_admin.mint(&client_address, &200);
+    token_admin.mint(&talent_address, &50);
+    let options = JobOptions { talent_bond: Some(50), ..Default::default() };
+    let mut job_ids = Vec::new(&env);
+    for _ in 0..2 {
+        let job_id = market.create_job_with_options(
+            &client_address,
+            &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+            &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+            &vec![&env, 100],
+            &vec![&env, 1_000u64],
+            &options,
+        );
+        market.fund_job(&client_address, &job_id);
+        market.offer_job(&client_address, &job_id, &talent_address);
+        job_ids.push_back(job_id);
+    }
+
+    // Finishing the work hands the bond back with the payout
+    let first = job_ids.get(0).unwrap();
+    market.accept_offer(&talent_address, &first);
+    assert_eq!(token.balance(&talent_address), 0);
+    market.submit_milestone(&talent_address, &first, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &first, &0);
+    assert_eq!(token.balance(&talent_address), 150);
+
+    // Walking away from the second job forfeits it to the client
+    let second = job_ids.get(1).unwrap();
+    market.accept_offer(&talent_address, &second);
+    market.resign(&talent_address, &second);
+    assert_eq!(token.balance(&talent_address), 100);
+    assert_eq!(token.balance(&client_address), 150);
+    assert_eq!(token.balance(&market.address), 0);
+}
+
+#[test]
+fn test_mutual_cancellation_splits_remaining_escrow() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+
+    // The split must account for the whole escrow
+    let result = market.try_propose_cancellation(&talent_address, &job_id, &100, &100);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    market.propose_cancellation(&talent_address, &job_id, &120, &180);
+
+    // The proposer cannot accept their own proposal
+    let result = market.try_accept_cancellation(&talent_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    market.accept_cancellation(&client_address, &job_id);
+
+    assert_eq!(token.balance(&talent_address), 120);
+    assert_eq!(token.balance(&client_address), 180);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.state, JobState::Cancelled);
+    assert_eq!(job.escrow_balance, 0);
+    let result = market.try_accept_cancellation(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+}
+
+#[test]
+fn test_cancellation_fee_charged_on_unpaid_value_for_staffed_jobs() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+
+    // Nobody hired: the whole escrow goes back
+    token_admin.mint(&client_address, &100);
+    let unstaffed = market.create_job(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000u64],
+    );
+    market.fund_job(&client_address, &unstaffed);
+    market.cancel_job(&client_address, &unstaffed);
+    assert_eq!(token.balance(&client_address), 100);
+
+    // Per-job rates are bounded by the admin cap
+    let options = JobOptions { cancellation_fee_bps: Some(2_500), ..Default::default() };
+    let result = market.try_create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000u64],
+        &options,
+    );
+    assert_eq!(result, Err(Ok(Error::FeeTooHigh.into())));
+    assert_eq!(market.get_max_cancellation_fee(), 2_000);
+
+    // 10% of the 200 still unpaid once the first milestone is paid
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+    market.cancel_job(&client_address, &job_id);
+    assert_eq!(token.balance(&talent_address), 120);
+    assert_eq!(token.balance(&client_address), 280);
+}
+
+#[test]
+fn test_cancel_remaining_keeps_submitted_milestones() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+
+    // Only the Pending milestone is voided; the kill fee applies to its 200
+    assert_eq!(market.cancel_remaining(&client_address, &job_id), 180);
+    assert_eq!(token.balance(&client_address), 180);
+    assert_eq!(token.balance(&talent_address), 20);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.state, JobState::Active);
+    assert_eq!(job.milestones.get(1).unwrap().state, MilestoneState::Void);
+    let result = market.try_cancel_remaining(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+
+    // The submitted work still settles, closing the job
+    market.approve_milestone(&client_address, &job_id, &0);
+    assert_eq!(token.balance(&talent_address), 120);
+    assert_eq!(read_job(&env, &market, job_id).state, JobState::Completed);
+    assert!(market.verify_job_invariants(&job_id).unwrap());
+}
+
+#[test]
+fn test_kill_fee_tiers_follow_progress() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let tier = |min_progress_bps, fee_bps| KillFeeTier { min_progress_bps, fee_bps };
+    let hire = |tiers: Vec<KillFeeTier>| {
+        token_admin.mint(&client_address, &300);
+        let now = env.ledger().timestamp();
+        let options = JobOptions { kill_fee_tiers: Some(tiers), ..Default::default() };
+        let job_id = market.create_job_with_options(
+            &client_address,
+            &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+            &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32])), Text::Hash(BytesN::from_array(&env, &[3; 32]))],
+            &vec![&env, 100, 200],
+            &vec![&env, now + 1_000, now + 2_000],
+            &options,
+        );
+        market.fund_job(&client_address, &job_id);
+        market.offer_job(&client_address, &job_id, &talent_address);
+        market.accept_offer(&talent_address, &job_id);
+        job_id
+    };
+
+    // Thresholds must increase and every tier stays under the admin cap
+    let options = JobOptions { kill_fee_tiers: Some(vec![&env, tier(5_000, 500), tier(5_000, 1_000)]), ..Default::default() };
+    let result = market.try_create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000u64],
+        &options,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    let options = JobOptions { kill_fee_tiers: Some(vec![&env, tier(0, 2_500)]), ..Default::default() };
+    let result = market.try_create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000u64],
+        &options,
+    );
+    assert_eq!(result, Err(Ok(Error::FeeTooHigh.into())));
+
+    // Nothing handed in yet: 5% of the 300 voided
+    let job_id = hire(vec![&env, tier(0, 500), tier(5_000, 2_000)]);
+    market.cancel_job(&client_address, &job_id);
+    assert_eq!(token.balance(&talent_address), 15);
+    assert_eq!(token.balance(&client_address), 285);
+
+    // Half the milestones submitted: 20% of the 200 still pending
+    let job_id = hire(vec![&env, tier(0, 500), tier(5_000, 2_000)]);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    assert_eq!(market.cancel_remaining(&client_address, &job_id), 160);
+    assert_eq!(token.balance(&talent_address), 55);
+}
+
+#[test]
+fn test_cancellation_notice_lets_talent_hand_in_work() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    token_admin.mint(&client_address, &300);
+    let options = JobOptions { cancellation_notice_period: Some(500), ..Default::default() };
+    let job_id = market.create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32])), Text::Hash(BytesN::from_array(&env, &[3; 32]))],
+        &vec![&env, 100, 200],
+        &vec![&env, 1_000u64, 2_000],
+        &options,
+    );
+    market.fund_job(&client_address, &job_id);
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+
+    // Staffed work cannot be cancelled outright
+    let result = market.try_cancel_job(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::NoticeRequired.into())));
+    let result = market.try_finalize_cancellation(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::NoticeRequired.into())));
+    assert_eq!(market.notice_cancellation(&client_address, &job_id), 500);
+
+    // Work handed in during the notice is settled before the refund
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    let result = market.try_finalize_cancellation(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::NoticeRequired.into())));
+    env.ledger().with_mut(|l| l.timestamp = 500);
+    let result = market.try_submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[5; 32]), &String::from_str(&env, ""));
+    assert_eq!(result, Err(Ok(Error::DeadlinePassed.into())));
+    let result = market.try_finalize_cancellation(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::MilestonePending.into())));
+    market.approve_milestone(&client_address, &job_id, &0);
+
+    // The kill fee covers the 200 left unpaid
+    market.finalize_cancellation(&client_address, &job_id);
+    assert_eq!(read_job(&env, &market, job_id).state, JobState::Cancelled);
+    assert_eq!(token.balance(&talent_address), 120);
+    assert_eq!(token.balance(&client_address), 180);
+}
+
+#[test]
+fn test_reclaim_abandoned_job_after_missed_deadlines() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+
+    // The last milestone is not yet overdue
+    env.ledger().with_mut(|l| l.timestamp = 1_500);
+    let result = market.try_reclaim_abandoned_job(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::TooEarly.into())));
+
+    env.ledger().with_mut(|l| l.timestamp = 2_001);
+    let result = market.try_reclaim_abandoned_job(&talent_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    market.reclaim_abandoned_job(&client_address, &job_id);
+
+    // Full refund, no kill fee for the absent talent
+    assert_eq!(token.balance(&client_address), 300);
+    assert_eq!(token.balance(&talent_address), 0);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.state, JobState::Cancelled);
+    assert_eq!(job.milestones.get(0).unwrap().state, MilestoneState::Void);
+}
+
+#[test]
+fn test_reclaim_rejected_once_work_was_submitted() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+
+    env.ledger().with_mut(|l| l.timestamp = 2_001);
+    let result = market.try_reclaim_abandoned_job(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+}
+
+#[test]
+fn test_unfunded_job_expires_after_funding_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    token_admin.mint(&client_address, &100);
+    let options = JobOptions { expiry: Some(500), ..Default::default() };
+    let job_id = market.create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000u64],
+        &options,
+    );
+
+    let result = market.try_expire_unfunded_job(&job_id);
+    assert_eq!(result, Err(Ok(Error::TooEarly.into())));
+
+    env.ledger().with_mut(|l| l.timestamp = 500);
+    let result = market.try_fund_job(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::DeadlinePassed.into())));
+    market.expire_unfunded_job(&job_id);
+    assert_eq!(read_job(&env, &market, job_id).state, JobState::Expired);
+
+    let result = market.try_expire_unfunded_job(&job_id);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+    let result = market.try_cancel_job(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::JobCompleted.into())));
+}
+
+#[test]
+fn test_top_up_escrow_grows_job_value() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+
+    token_admin.mint(&client_address, &50);
+    let result = market.try_top_up_escrow(&client_address, &job_id, &0);
+    assert_eq!(result, Err(Ok(Error::AmountRequired.into())));
+    let result = market.try_top_up_escrow(&talent_address, &job_id, &50);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    market.top_up_escrow(&client_address, &job_id, &50);
+
+    assert_eq!(token.balance(&market.address), 350);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.escrow_balance, 350);
+    assert_eq!(job.total_value, 350);
+}
+
+#[test]
+fn test_escrow_ledger_balances_through_job_lifecycle() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    token_admin.mint(&client_address, &50);
+    market.top_up_escrow(&client_address, &job_id, &50);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, ""));
+    market.approve_milestone(&client_address, &job_id, &0);
+    market.withdraw_surplus(&client_address, &job_id);
+    market.cancel_job(&client_address, &job_id);
+
+    // 350 in: 100 paid for work, 20 cancellation fee, the rest refunded
+    let job = read_job(&env, &market, job_id);
+    assert_eq!((job.deposited, job.amount_paid, job.fees_charged, job.refunded), (350, 120, 0, 230));
+    assert_eq!(job.escrow_balance, 0);
+    assert!(market.verify_job_invariants(&job_id).unwrap());
+
+    // A record that no longer adds up is reported
+    env.as_contract(&market.address, || {
+        let mut job = DecentralizedJobMarket::get_job(&env, job_id);
+        job.refunded -= 1;
+        env.storage().persistent().set(&JobKey::Job(job_id), &job);
+    });
+    assert!(!market.verify_job_invariants(&job_id).unwrap());
+}
+
+#[test]
+fn test_withdraw_surplus_keeps_unpaid_milestones_covered() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+
+    let result = market.try_withdraw_surplus(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::AmountRequired.into())));
+
+    token_admin.mint(&client_address, &50);
+    market.top_up_escrow(&client_address, &job_id, &50);
+    assert_eq!(market.get_escrow_surplus(&job_id).unwrap(), 50);
+    let result = market.try_withdraw_surplus(&talent_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    assert_eq!(market.withdraw_surplus(&client_address, &job_id), 50);
+
+    assert_eq!(token.balance(&client_address), 50);
+    assert_eq!(token.balance(&market.address), 300);
+    assert_eq!(market.get_escrow_surplus(&job_id).unwrap(), 0);
+}
+
+#[test]
+fn test_hourly_budget_not_withdrawable_while_active() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    token_admin.mint(&client_address, &100);
+    let options = JobOptions { hourly_rate: Some(10), ..Default::default() };
+    let job_id = market.create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000u64],
+        &options,
+    );
+    market.fund_job(&client_address, &job_id);
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+    market.log_hours(&talent_address, &job_id, &BytesN::from_array(&env, &[6; 32]), &1);
+    market.approve_timesheet(&client_address, &job_id, &0);
+
+    // The unspent budget is what future hours are paid from
+    let result = market.try_withdraw_surplus(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::AmountRequired.into())));
+    assert_eq!(market.get_escrow_surplus(&job_id).unwrap(), 0);
+
+    // Ending the engagement returns it
+    market.resign(&talent_address, &job_id);
+    assert_eq!(token.balance(&talent_address), 10);
+    assert_eq!(token.balance(&client_address), 90);
+    assert_eq!(token.balance(&market.address), 0);
+}
+
+#[test]
+fn test_dispute_workflow() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let arbitrator_address = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &arbitrator_address);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+
+    // Only registered arbitrators can be chosen
+    let stranger = Address::generate(&env);
+    let result = market.try_raise_dispute(&client_address, &job_id, &Some(0), &stranger);
+    assert_eq!(result, Err(Ok(Error::NotArbitrator.into())));
+
+    // Test raising a dispute (by client)
+    token_admin.mint(&client_address, &DISPUTE_DEPOSIT);
+    market.raise_dispute(&client_address, &job_id, &Some(0), &arbitrator_address);
+
+    // Verify event was emitted
+    assert!(env.events().all().contains((
+        market.address.clone(),
+        (symbol_short!("DIS_RIS"), job_id, client_address.clone()).into_val(&env),
+        DisputeOpened {
+            version: EVENT_VERSION,
+            milestone_idx: Some(0),
+            arbitrators: vec![&env, arbitrator_address.clone()],
+        }.into_val(&env),
+    )));
+
+    // Only the disputed milestone is frozen
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.state, JobState::Active);
+    assert_eq!(job.milestones.get(0).unwrap().state, MilestoneState::Disputed);
+    assert_eq!(market.get_dispute(&job_id).unwrap().raised_by, client_address);
+
+    // Only the selected arbitrator rules
+    let result = market.try_resolve_dispute(&stranger, &job_id, &Some(0), &true);
+    assert_eq!(result, Err(Ok(Error::NotArbitrator.into())));
+
+    // Test resolving dispute (approve)
+    market.resolve_dispute(&arbitrator_address, &job_id, &Some(0), &true);
+    execute_ruling(&env, &market, job_id);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.state, JobState::Active);
+    assert_eq!(job.amount_paid, 100);
+    assert_eq!(job.milestones.get(0).unwrap().state, MilestoneState::Paid);
+    assert_eq!(token.balance(&talent_address), 100);
+    assert_eq!(token.balance(&arbitrator_address), 15);
+
+    // Test raising dispute on non-existent job
+    let result = market.try_raise_dispute(&client_address, &999, &Some(0), &arbitrator_address);
+    assert_eq!(result, Err(Ok(Error::JobNotFound.into())));
+}
+
+#[test]
+fn test_milestone_dispute_leaves_other_milestones_open() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let arbitrator_address = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &arbitrator_address);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    token_admin.mint(&client_address, &DISPUTE_DEPOSIT);
+    market.raise_dispute(&client_address, &job_id, &Some(0), &arbitrator_address);
+
+    // Milestone 1 still moves through submission and approval
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &1);
+    assert_eq!(token.balance(&talent_address), 200);
+    assert_eq!(read_job(&env, &market, job_id).milestones.get(0).unwrap().state, MilestoneState::Disputed);
+
+    // The disputed escrow cannot be pulled or disputed twice
+    let result = market.try_cancel_job(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::ArbitrationPending.into())));
+    token_admin.mint(&talent_address, &DISPUTE_DEPOSIT);
+    let result = market.try_raise_dispute(&talent_address, &job_id, &None, &arbitrator_address);
+    assert_eq!(result, Err(Ok(Error::ArbitrationPending.into())));
+}
+
+#[test]
+fn test_funded_job_dispute_hires_or_refunds() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let arbitrator_address = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &arbitrator_address);
+    let now = env.ledger().timestamp();
+    let post_job = || {
+        token_admin.mint(&client_address, &300);
+        let job_id = market.create_job(
+            &client_address,
+            &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+            &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32])), Text::Hash(BytesN::from_array(&env, &[3; 32]))],
+            &vec![&env, 100, 200],
+            &vec![&env, now + APPEAL_WINDOW * 2, now + APPEAL_WINDOW * 3],
+        );
+        market.fund_job(&client_address, &job_id);
+        market.offer_job(&client_address, &job_id, &talent_address);
+        job_id
+    };
+
+    // Only the offered talent can take a Funded job to arbitration, job-wide
+    let job_id = post_job();
+    token_admin.mint(&client_address, &DISPUTE_DEPOSIT);
+    let result = market.try_raise_dispute(&client_address, &job_id, &None, &arbitrator_address);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+    token_admin.mint(&talent_address, &DISPUTE_DEPOSIT);
+    let result = market.try_raise_dispute(&talent_address, &job_id, &Some(0), &arbitrator_address);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+
+    // Approval hires the claimant on the posted terms
+    market.raise_dispute(&talent_address, &job_id, &None, &arbitrator_address);
+    market.resolve_dispute(&arbitrator_address, &job_id, &None, &true);
+    execute_ruling(&env, &market, job_id);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.state, JobState::Active);
+    assert_eq!(job.talent, Some(talent_address.clone()));
+    assert!(job.offer.is_none());
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+    // No escrow surplus was set aside, so the 15 fee came out of the deposit
+    assert_eq!(token.balance(&talent_address), 100 + DISPUTE_DEPOSIT - 15);
+
+    // Rejection cancels the job and refunds the client in full
+    let job_id = post_job();
+    let client_before = token.balance(&client_address);
+    market.raise_dispute(&talent_address, &job_id, &None, &arbitrator_address);
+    market.resolve_dispute(&arbitrator_address, &job_id, &None, &false);
+    execute_ruling(&env, &market, job_id);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.state, JobState::Cancelled);
+    assert_eq!(job.escrow_balance, 0);
+    assert_eq!(token.balance(&client_address), client_before + 300);
+    assert_eq!(token.balance(&talent_address), 100 + DISPUTE_DEPOSIT - 30);
+}
+
+#[test]
+fn test_talent_disputes_rejected_submission() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let arbitrator_address = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &arbitrator_address);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    let data = BytesN::from_array(&env, &[3; 32]);
+    let uri = String::from_str(&env, "ipfs://deliverable");
+
+    // Only submitted work can be rejected
+    let result = market.try_reject_submission(&client_address, &job_id, &0);
+    assert_eq!(result, Err(Ok(Error::NotSubmitted.into())));
+    market.submit_milestone(&talent_address, &job_id, &0, &data, &uri);
+    let result = market.try_reject_submission(&talent_address, &job_id, &0);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    market.reject_submission(&client_address, &job_id, &0);
+    assert_eq!(read_job(&env, &market, job_id).milestones.get(0).unwrap().state, MilestoneState::Rejected);
+
+    // The dispute carries the rejected submission for the arbitrator
+    token_admin.mint(&talent_address, &DISPUTE_DEPOSIT);
+    market.raise_dispute(&talent_address, &job_id, &Some(0), &arbitrator_address);
+    let submission = market.get_dispute(&job_id).unwrap().submission.get().unwrap();
+    assert_eq!(submission.data, data);
+    assert_eq!(submission.uri, Some(uri));
+
+    // Overturning the rejection releases the payment
+    market.resolve_dispute(&arbitrator_address, &job_id, &Some(0), &true);
+    execute_ruling(&env, &market, job_id);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.milestones.get(0).unwrap().state, MilestoneState::Paid);
+    assert_eq!(token.balance(&talent_address), 100 + DISPUTE_DEPOSIT - 15);
+}
+
+#[test]
+fn test_dispute_policy_binds_arbitration_terms() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let agreed = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &agreed);
+    register_arbitrator(&market, &token_admin, &outsider);
+
+    let policy = DisputePolicy {
+        arbitrators: vec![&env, agreed.clone()],
+        panel_size: None,
+        max_fee: Some(2),
+        ruling_window: Some(1_000),
+        appeals: false,
+    };
+    let mut options = JobOptions { dispute_policy: OptDisputePolicy::Some(policy.clone()), ..Default::default() };
+    token_admin.mint(&client_address, &300);
+    let create = |options: &JobOptions| market.try_create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32])), Text::Hash(BytesN::from_array(&env, &[3; 32]))],
+        &vec![&env, 100, 200],
+        &vec![&env, 1_000u64, 2_000],
+        options,
+    );
+    // Policies cannot exceed the global fee cap or ask for an even panel
+    options.dispute_policy = OptDisputePolicy::Some(DisputePolicy { max_fee: Some(50), ..policy.clone() });
+    assert_eq!(create(&options), Err(Ok(Error::FeeTooHigh.into())));
+    options.dispute_policy = OptDisputePolicy::Some(DisputePolicy { panel_size: Some(2), ..policy.clone() });
+    assert_eq!(create(&options), Err(Ok(Error::InvalidInput.into())));
+    options.dispute_policy = OptDisputePolicy::Some(policy);
+    let job_id = create(&options).unwrap().unwrap();
+    market.fund_job(&client_address, &job_id);
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+
+    // Only the pre-agreed arbitrator can hear it, within the agreed window
+    token_admin.mint(&client_address, &6);
+    let result = market.try_raise_dispute(&client_address, &job_id, &Some(0), &outsider);
+    assert_eq!(result, Err(Ok(Error::NotArbitrator.into())));
+    market.raise_dispute(&client_address, &job_id, &Some(0), &agreed);
+    let dispute = market.get_dispute(&job_id).unwrap();
+    assert_eq!(dispute.fee_deposit, 6);
+    assert_eq!(dispute.ruling_deadline, env.ledger().timestamp() + 1_000);
+
+    // Appeals were waived, so the ruling settles at once at the 2% cap
+    market.resolve_dispute(&agreed, &job_id, &Some(0), &true);
+    assert!(market.get_dispute(&job_id).is_none());
+    assert_eq!(token.balance(&agreed), 6);
+    assert_eq!(token.balance(&talent_address), 100);
+}
+
+#[test]
+fn test_rejected_milestone_keeps_submission_history() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let arbitrator_address = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &arbitrator_address);
+    // Deadline far enough out to outlast the appeal window
+    token_admin.mint(&client_address, &100);
+    let job_id = market.create_job(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, APPEAL_WINDOW * 2],
+    );
+    market.fund_job(&client_address, &job_id);
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+    let first = BytesN::from_array(&env, &[3; 32]);
+    market.submit_milestone(&talent_address, &job_id, &0, &first, &String::from_str(&env, "ipfs://draft"));
+
+    token_admin.mint(&client_address, &DISPUTE_DEPOSIT);
+    market.raise_dispute(&client_address, &job_id, &Some(0), &arbitrator_address);
+    market.resolve_dispute(&arbitrator_address, &job_id, &Some(0), &false);
+    execute_ruling(&env, &market, job_id);
+    assert_eq!(read_job(&env, &market, job_id).milestones.get(0).unwrap().state, MilestoneState::Rejected);
+
+    // The rejected round stays on record next to the resubmission
+    let second = BytesN::from_array(&env, &[4; 32]);
+    market.submit_milestone(&talent_address, &job_id, &0, &second, &String::from_str(&env, ""));
+    let history = market.get_submissions(&job_id, &0);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.get(0).unwrap().data, first);
+    assert_eq!(history.get(0).unwrap().uri, Some(String::from_str(&env, "ipfs://draft")));
+    assert_eq!(history.get(1).unwrap().data, second);
+    assert_eq!(history.get(1).unwrap().round, 1);
+    assert_eq!(history.get(1).unwrap().uri, None);
+    assert!(market.get_submissions(&job_id, &1).is_empty());
+
+    market.approve_milestone(&client_address, &job_id, &0);
+    assert_eq!(token.balance(&talent_address), 100);
+}
+
+#[test]
+fn test_resubmission_errors_and_duplicate_deliverables() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    let uri = String::from_str(&env, "ipfs://deliverable");
+    let first = BytesN::from_array(&env, &[3; 32]);
+    market.submit_milestone(&talent_address, &job_id, &0, &first, &uri);
+    let result = market.try_submit_milestone(&talent_address, &job_id, &0, &first, &uri);
+    assert_eq!(result, Err(Ok(Error::AlreadySubmitted.into())));
+
+    // After a rejection only a changed deliverable is accepted
+    market.reject_submission(&client_address, &job_id, &0);
+    let result = market.try_submit_milestone(&talent_address, &job_id, &0, &first, &uri);
+    assert_eq!(result, Err(Ok(Error::DuplicateSubmission.into())));
+    let second = BytesN::from_array(&env, &[4; 32]);
+    market.submit_milestone(&talent_address, &job_id, &0, &second, &uri);
+    market.reject_submission(&client_address, &job_id, &0);
+    let result = market.try_submit_milestone(&talent_address, &job_id, &0, &first, &uri);
+    assert_eq!(result, Err(Ok(Error::DuplicateSubmission.into())));
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[5; 32]), &uri);
+
+    market.approve_milestone(&client_address, &job_id, &0);
+    assert_eq!(token.balance(&talent_address), 100);
+    let result = market.try_submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[6; 32]), &uri);
+    assert_eq!(result, Err(Ok(Error::AlreadyApproved.into())));
+}
+
+#[test]
+fn test_checked_actions_fail_on_stale_sequence() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let arbitrator_address = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &arbitrator_address);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+
+    // The client's page was loaded before the talent disputed milestone 1
+    let seen = market.get_action_seq(&job_id).unwrap();
+    token_admin.mint(&talent_address, &DISPUTE_DEPOSIT);
+    market.raise_dispute_checked(&talent_address, &job_id, &Some(1), &arbitrator_address, &seen);
+    assert!(market.get_action_seq(&job_id).unwrap() > seen);
+    let result = market.try_approve_milestone_checked(&client_address, &job_id, &0, &seen);
+    assert_eq!(result, Err(Ok(Error::StaleState.into())));
+    let result = market.try_cancel_job_checked(&client_address, &job_id, &seen);
+    assert_eq!(result, Err(Ok(Error::StaleState.into())));
+
+    // A fresh read goes through
+    market.approve_milestone_checked(&client_address, &job_id, &0, &market.get_action_seq(&job_id).unwrap());
+    assert_eq!(token.balance(&talent_address), 100);
+}
+
+#[test]
+fn test_winning_client_fee_comes_out_of_talent_split_share() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let arbitrator_address = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &arbitrator_address);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    token_admin.mint(&client_address, &DISPUTE_DEPOSIT);
+    market.raise_dispute(&client_address, &job_id, &Some(0), &arbitrator_address);
+
+    market.resolve_dispute_split(&arbitrator_address, &job_id, &0, &4_000);
+    execute_ruling(&env, &market, job_id);
+    // The 15 fee is withheld from the talent's 40; the client's deposit comes back
+    assert_eq!(token.balance(&arbitrator_address), 15);
+    assert_eq!(token.balance(&talent_address), 25);
+    assert_eq!(token.balance(&client_address), 60 + DISPUTE_DEPOSIT);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.escrow_balance, 200);
+}
+
+#[test]
+fn test_appeal_escalates_and_refunds_bond_when_overturned() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, admin) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let arbitrator_address = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &arbitrator_address);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    token_admin.mint(&client_address, &DISPUTE_DEPOSIT);
+    market.raise_dispute(&client_address, &job_id, &Some(0), &arbitrator_address);
+    market.resolve_dispute(&arbitrator_address, &job_id, &Some(0), &true);
+
+    let keeper = Address::generate(&env);
+    let result = market.try_execute_ruling(&keeper, &job_id);
+    assert_eq!(result, Err(Ok(Error::RulingPending.into())));
+    // Only the losing side may appeal
+    let result = market.try_appeal(&talent_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+    // 10% bond; no other arbitrators, so the admin hears the appeal
+    token_admin.mint(&client_address, &10);
+    market.appeal(&client_address, &job_id);
+    assert_eq!(token.balance(&client_address), 0);
+    let dispute = market.get_dispute(&job_id).unwrap();
+    assert_eq!(dispute.arbitrators, vec![&env, admin.clone()]);
+
+    // Client won: bond back, and the deposit too less the admin's fee
+    market.resolve_dispute(&admin, &job_id, &Some(0), &false);
+    assert_eq!(token.balance(&client_address), 25);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.milestones.get(0).unwrap().state, MilestoneState::Rejected);
+    assert_eq!(token.balance(&talent_address), 0);
+}
+
+#[test]
+fn test_appeal_window_closes() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let arbitrator_address = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &arbitrator_address);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    token_admin.mint(&client_address, &DISPUTE_DEPOSIT);
+    market.raise_dispute(&client_address, &job_id, &Some(0), &arbitrator_address);
+    market.resolve_dispute(&arbitrator_address, &job_id, &Some(0), &false);
+
+    env.ledger().with_mut(|l| l.timestamp += APPEAL_WINDOW + 1);
+    let result = market.try_appeal(&talent_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::AppealWindowClosed.into())));
+}
+
+#[test]
+fn test_dispute_timeout_reassigns_then_applies_default_ruling() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &first);
+    register_arbitrator(&market, &token_admin, &second);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    token_admin.mint(&client_address, &DISPUTE_DEPOSIT);
+    market.raise_dispute(&client_address, &job_id, &Some(0), &first);
+
+    let keeper = Address::generate(&env);
+    let result = market.try_timeout_dispute(&keeper, &job_id);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+
+    env.ledger().with_mut(|l| l.timestamp += RULING_WINDOW + 1);
+    market.timeout_dispute(&keeper, &job_id);
+    let dispute = market.get_dispute(&job_id).unwrap();
+    assert_eq!(dispute.arbitrators, vec![&env, second.clone()]);
+    assert_eq!(dispute.reassignments, 1);
+
+    for _ in 0..MAX_REASSIGN {
+        env.ledger().with_mut(|l| l.timestamp += RULING_WINDOW + 1);
+        market.timeout_dispute(&keeper, &job_id);
+    }
+    // Out of reassignments: the default (reject) ruling is pending, unpaid
+    let dispute = market.get_dispute(&job_id).unwrap();
+    assert_eq!(dispute.pending_ruling.get(), Some(Ruling::Reject));
+    execute_ruling(&env, &market, job_id);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.milestones.get(0).unwrap().state, MilestoneState::Rejected);
+    assert_eq!(job.escrow_balance, 300);
+    assert_eq!(token.balance(&first), 0);
+    assert_eq!(token.balance(&second), 0);
+}
+
+#[test]
+fn test_dispute_split_ruling_pays_both_parties() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let arbitrator_address = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &arbitrator_address);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    token_admin.mint(&client_address, &DISPUTE_DEPOSIT);
+    market.raise_dispute(&client_address, &job_id, &Some(0), &arbitrator_address);
+
+    let result = market.try_resolve_dispute_split(&arbitrator_address, &job_id, &0, &10_001);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    let result = market.try_resolve_dispute_split(&arbitrator_address, &job_id, &1, &6_000);
+    assert_eq!(result, Err(Ok(Error::InvalidIndex.into())));
+
+    market.resolve_dispute_split(&arbitrator_address, &job_id, &0, &6_000);
+    execute_ruling(&env, &market, job_id);
+    // Talent won the larger share, so the client's deposit pays the fee
+    assert_eq!(token.balance(&talent_address), 60);
+    assert_eq!(token.balance(&client_address), 40 + DISPUTE_DEPOSIT - 15);
+    assert_eq!(token.balance(&arbitrator_address), 15);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.state, JobState::Active);
+    assert_eq!(job.amount_paid, 60);
+    assert_eq!(job.escrow_balance, 200);
+}
+
+#[test]
+fn test_evidence_log_is_capped_per_dispute() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let arbitrator_address = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &arbitrator_address);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+
+    let evidence_hash = BytesN::from_array(&env, &[5; 32]);
+    let note = Bytes::from_slice(&env, b"late delivery");
+    let result = market.try_submit_evidence(&client_address, &job_id, &evidence_hash, &note);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+
+    token_admin.mint(&client_address, &DISPUTE_DEPOSIT);
+
+    market.raise_dispute(&client_address, &job_id, &Some(0), &arbitrator_address);
+    let result = market.try_submit_evidence(&arbitrator_address, &job_id, &evidence_hash, &note);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    let long_note = Bytes::from_slice(&env, &[0; MAX_NOTE_LEN as usize + 1]);
+    let result = market.try_submit_evidence(&client_address, &job_id, &evidence_hash, &long_note);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+
+    for _ in 0..MAX_EVIDENCE {
+        market.submit_evidence(&talent_address, &job_id, &evidence_hash, &note);
+    }
+    let result = market.try_submit_evidence(&client_address, &job_id, &evidence_hash, &note);
+    assert_eq!(result, Err(Ok(Error::EvidenceLimit.into())));
+    let log = market.get_evidence(&job_id);
+    assert_eq!(log.len(), MAX_EVIDENCE);
+    assert_eq!(log.get(0).unwrap().submitter, talent_address);
+}
+
+#[test]
+fn test_dispute_arbitrator_needs_counterparty_approval() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let proposed = Address::generate(&env);
+    let countered = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &proposed);
+    register_arbitrator(&market, &token_admin, &countered);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+
+    token_admin.mint(&client_address, &DISPUTE_DEPOSIT);
+
+    market.raise_dispute_with_approval(&client_address, &job_id, &Some(0), &proposed);
+    let result = market.try_resolve_dispute(&proposed, &job_id, &Some(0), &true);
+    assert_eq!(result, Err(Ok(Error::ArbitratorNotApproved.into())));
+    let result = market.try_approve_arbitrator(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+    // Talent counters; the client now has to approve
+    market.counter_propose_arbitrator(&talent_address, &job_id, &countered);
+    let result = market.try_approve_arbitrator(&talent_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    market.approve_arbitrator(&client_address, &job_id);
+
+    market.resolve_dispute(&countered, &job_id, &Some(0), &true);
+    execute_ruling(&env, &market, job_id);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.milestones.get(0).unwrap().state, MilestoneState::Paid);
+}
+
+#[test]
+fn test_fallback_arbitrator_after_approval_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let proposed = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &proposed);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    token_admin.mint(&client_address, &DISPUTE_DEPOSIT);
+    market.raise_dispute_with_approval(&client_address, &job_id, &Some(0), &proposed);
+
+    let keeper = Address::generate(&env);
+    let result = market.try_assign_fallback_arbitrator(&keeper, &job_id);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+
+    env.ledger().with_mut(|l| l.timestamp += APPROVAL_WINDOW + 1);
+    market.assign_fallback_arbitrator(&keeper, &job_id);
+    let dispute = market.get_dispute(&job_id).unwrap();
+    assert_eq!(dispute.awaiting_approval, None);
+    assert_eq!(dispute.arbitrators, vec![&env, proposed.clone()]);
+    market.resolve_dispute(&proposed, &job_id, &Some(0), &false);
+}
+
+#[test]
+fn test_auto_dispute_draws_matching_specialist() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let generalist = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &generalist);
+    let specialist = Address::generate(&env);
+    let tag = BytesN::from_array(&env, &[8; 32]);
+    token_admin.mint(&specialist, &DEFAULT_MIN_STAKE);
+    market.register_arbitrator(&specialist, &tag, &DEFAULT_MIN_STAKE);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    token_admin.mint(&client_address, &100);
+    let options = JobOptions { specialization: Some(tag).into(), ..Default::default() };
+    let job_id = market.create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000],
+        &options,
+    );
+    market.fund_job(&client_address, &job_id);
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+
+    token_admin.mint(&talent_address, &10);
+    market.raise_dispute_auto(&talent_address, &job_id, &None);
+    assert_eq!(market.get_dispute(&job_id).unwrap().arbitrators, vec![&env, specialist.clone()]);
+    market.resolve_dispute(&specialist, &job_id, &None, &true);
+    execute_ruling(&env, &market, job_id);
+    // No escrow surplus was set aside, so the fee comes out of the deposit
+    assert_eq!(token.balance(&talent_address), 105);
+    assert_eq!(token.balance(&market.address), DEFAULT_MIN_STAKE * 2);
+    assert!(market.verify_job_invariants(&job_id).unwrap());
+}
+
+#[test]
+fn test_panel_dispute_settles_on_majority() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+
+    let result = market.try_raise_dispute_panel(&talent_address, &job_id, &Some(0), &3);
+    assert_eq!(result, Err(Ok(Error::NotEnoughArbitrators.into())));
+    for _ in 0..3 {
+        register_arbitrator(&market, &token_admin, &Address::generate(&env));
+    }
+    let result = market.try_raise_dispute_panel(&talent_address, &job_id, &Some(0), &2);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    token_admin.mint(&talent_address, &DISPUTE_DEPOSIT);
+    market.raise_dispute_panel(&talent_address, &job_id, &Some(0), &3);
+
+    let panel = market.get_dispute(&job_id).unwrap().arbitrators;
+    assert_eq!(panel.len(), 3);
+    let first = panel.get(0).unwrap();
+    let second = panel.get(1).unwrap();
+    market.vote_dispute(&first, &job_id, &false);
+    let result = market.try_vote_dispute(&first, &job_id, &false);
+    assert_eq!(result, Err(Ok(Error::AlreadyVoted.into())));
+
+    // Second matching vote is a majority and decides the case
+    market.vote_dispute(&second, &job_id, &false);
+    assert!(market.get_dispute(&job_id).unwrap().pending_ruling.is_some());
+    execute_ruling(&env, &market, job_id);
+    assert_eq!(market.get_dispute(&job_id), None);
+    assert_eq!(token.balance(&first), 7);
+    assert_eq!(token.balance(&second), 7);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.state, JobState::Active);
+    assert_eq!(job.milestones.get(0).unwrap().state, MilestoneState::Rejected);
+}
+
+#[test]
+fn test_reentrancy_guard() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, _, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let title = Text::Hash(BytesN::from_array(&env, &[1; 32]));
+    let descriptions = vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))];
+    let amounts = vec![&env, 100];
+    let deadlines = vec![&env, 1_000u64];
+
+    // This should succeed
+    market.create_job(&client_address, &title, &descriptions, &amounts, &deadlines);
+
+    // Verify guard was cleared, so the next call goes through too
+    let locked = env.as_contract(&market.address, || env.storage().temporary().has(&ConfigKey::ReentrancyLock));
+    assert!(!locked);
+    assert_eq!(market.create_job(&client_address, &title, &descriptions, &amounts, &deadlines), 2);
+}
+
+// ==========================
+// ARBITRATORS
+// ==========================
+#[test]
+fn test_arbitrator_stake_slash_and_unstake() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, admin) = setup_market(&env);
+    let specialization = BytesN::from_array(&env, &[7; 32]);
+
+    let arbitrator_address = Address::generate(&env);
+    token_admin.mint(&arbitrator_address, &DEFAULT_MIN_STAKE);
+    let result = market.try_register_arbitrator(&arbitrator_address, &specialization, &(DEFAULT_MIN_STAKE - 1));
+    assert_eq!(result, Err(Ok(Error::StakeTooLow.into())));
+    market.register_arbitrator(&arbitrator_address, &specialization, &DEFAULT_MIN_STAKE);
+    assert_eq!(token.balance(&arbitrator_address), 0);
+    assert_eq!(token.balance(&market.address), DEFAULT_MIN_STAKE);
+
+    // Slashed stake moves to the treasury
+    market.slash_arbitrator(&admin, &arbitrator_address, &100, &BytesN::from_array(&env, &[9; 32]));
+    assert_eq!(market.get_accrued_fees(&token.address), 100);
+
+    let result = market.try_unstake_arbitrator(&arbitrator_address);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+    market.request_unstake(&arbitrator_address);
+    let result = market.try_unstake_arbitrator(&arbitrator_address);
+    assert_eq!(result, Err(Ok(Error::CooldownActive.into())));
+
+    env.ledger().with_mut(|l| l.timestamp += UNSTAKE_COOLDOWN);
+    market.unstake_arbitrator(&arbitrator_address);
+    assert_eq!(token.balance(&arbitrator_address), DEFAULT_MIN_STAKE - 100);
+    let result = market.try_request_unstake(&arbitrator_address);
+    assert_eq!(result, Err(Ok(Error::NotArbitrator.into())));
+}
+
+#[test]
+fn test_arbitrators_are_listed_by_specialization() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, admin) = setup_market(&env);
+
+    let tag = BytesN::from_array(&env, &[7; 32]);
+    let other_tag = BytesN::from_array(&env, &[8; 32]);
+    let first = Address::generate(&env);
+    let second = Address::generate(&env);
+    let other = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &first);
+    register_arbitrator(&market, &token_admin, &second);
+    token_admin.mint(&other, &DEFAULT_MIN_STAKE);
+    market.register_arbitrator(&other, &other_tag, &DEFAULT_MIN_STAKE);
+
+    assert_eq!(market.list_arbitrators(&0, &10).len(), 3);
+    assert_eq!(market.list_arbitrators(&2, &10).len(), 1);
+    let page = market.list_arbitrators_by_spec(&tag, &0, &1);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().address, first);
+    assert_eq!(market.list_arbitrators_by_spec(&tag, &1, &10).get(0).unwrap().address, second);
+
+    // Leaving the registry also drops the index entry
+    assert_eq!(market.prune_inactive_arbitrators(&admin, &1), 3);
+    assert_eq!(market.list_arbitrators_by_spec(&tag, &0, &10).len(), 0);
+    assert_eq!(market.list_arbitrators_by_spec(&other_tag, &0, &10).len(), 0);
+}
+
+#[test]
+fn test_busy_arbitrator_cannot_deregister() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let arbitrator_address = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &arbitrator_address);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    token_admin.mint(&client_address, &DISPUTE_DEPOSIT);
+    market.raise_dispute(&client_address, &job_id, &Some(0), &arbitrator_address);
+    assert_eq!(market.get_arbitrator(&arbitrator_address).unwrap().open_disputes, 1);
+
+    let result = market.try_deregister_arbitrator(&arbitrator_address);
+    assert_eq!(result, Err(Ok(Error::ArbitratorBusy.into())));
+    market.resolve_dispute(&arbitrator_address, &job_id, &Some(0), &true);
+    execute_ruling(&env, &market, job_id);
+    assert_eq!(market.get_arbitrator(&arbitrator_address).unwrap().open_disputes, 0);
+
+    // Staked arbitrators still go through the cooldown
+    let result = market.try_deregister_arbitrator(&arbitrator_address);
+    assert_eq!(result, Err(Ok(Error::CooldownActive.into())));
+    market.request_unstake(&arbitrator_address);
+    env.ledger().with_mut(|l| l.timestamp += UNSTAKE_COOLDOWN);
+    market.deregister_arbitrator(&arbitrator_address);
+    assert_eq!(market.get_arbitrator(&arbitrator_address), None);
+    assert_eq!(token.balance(&arbitrator_address), DEFAULT_MIN_STAKE + 15);
+}
+
+#[test]
+fn test_admin_prunes_inactive_arbitrators() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, admin) = setup_market(&env);
+
+    let idle = Address::generate(&env);
+    let active = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &idle);
+    register_arbitrator(&market, &token_admin, &active);
+    env.ledger().with_mut(|l| l.timestamp += 1_000);
+    market.update_arbitrator_fee(&active, &6);
+
+    let result = market.try_prune_inactive_arbitrators(&active, &500);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    assert_eq!(market.prune_inactive_arbitrators(&admin, &500), 1);
+    assert_eq!(market.get_arbitrator(&idle), None);
+    assert_eq!(token.balance(&idle), DEFAULT_MIN_STAKE);
+    assert!(market.get_arbitrator(&active).is_some());
+}
+
+#[test]
+fn test_arbitrator_fee_is_bounded_by_cap() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, admin) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let arbitrator_address = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &arbitrator_address);
+
+    let result = market.try_update_arbitrator_fee(&client_address, &8);
+    assert_eq!(result, Err(Ok(Error::NotArbitrator.into())));
+    let result = market.try_update_arbitrator_fee(&arbitrator_address, &(market.get_max_arbitrator_fee() + 1));
+    assert_eq!(result, Err(Ok(Error::FeeTooHigh.into())));
+    market.update_arbitrator_fee(&arbitrator_address, &8);
+
+    // Lowering the cap also bounds fees already registered
+    let action_id = market.queue_action(&admin, &AdminAction::SetMaxArbitratorFee(6));
+    env.ledger().with_mut(|l| l.timestamp += DEFAULT_TL_DELAY);
+    market.execute_action(&action_id);
+    assert_eq!(market.get_max_arbitrator_fee(), 6);
+
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    token_admin.mint(&client_address, &DISPUTE_DEPOSIT);
+    market.raise_dispute(&client_address, &job_id, &Some(0), &arbitrator_address);
+    market.resolve_dispute(&arbitrator_address, &job_id, &Some(0), &true);
+    execute_ruling(&env, &market, job_id);
+    assert_eq!(token.balance(&arbitrator_address), 18);
+}
+
+#[test]
+fn test_parties_rate_arbitrator_after_ruling() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let arbitrator_address = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &arbitrator_address);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+
+    // Nothing to rate before a ruling executes
+    let result = market.try_rate_arbitrator(&client_address, &job_id, &50);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+
+    token_admin.mint(&client_address, &DISPUTE_DEPOSIT);
+
+    market.raise_dispute(&client_address, &job_id, &Some(0), &arbitrator_address);
+    market.resolve_dispute(&arbitrator_address, &job_id, &Some(0), &true);
+    execute_ruling(&env, &market, job_id);
+    let record = market.get_arbitrator(&arbitrator_address).unwrap();
+    assert_eq!(record.cases_handled, 1);
+    assert_eq!(record.reputation, 80);
+
+    let result = market.try_rate_arbitrator(&client_address, &job_id, &101);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    let result = market.try_rate_arbitrator(&arbitrator_address, &job_id, &100);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+    market.rate_arbitrator(&client_address, &job_id, &0);
+    assert_eq!(market.get_arbitrator(&arbitrator_address).unwrap().reputation, 64);
+    let result = market.try_rate_arbitrator(&client_address, &job_id, &0);
+    assert_eq!(result, Err(Ok(Error::AlreadyVoted.into())));
+    market.rate_arbitrator(&talent_address, &job_id, &100);
+    assert_eq!(market.get_arbitrator(&arbitrator_address).unwrap().reputation, 71);
+}
+
+// ==========================
+// REVIEWS
+// ==========================
+#[test]
+fn test_parties_review_each_other_once_job_ends() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let comment = BytesN::from_array(&env, &[9; 32]);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+
+    // Reviews open only once the job is over
+    let result = market.try_leave_review(&client_address, &job_id, &5, &comment);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+    market.cancel_job(&client_address, &job_id);
+
+    let result = market.try_leave_review(&client_address, &job_id, &6, &comment);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    let stranger = Address::generate(&env);
+    let result = market.try_leave_review(&stranger, &job_id, &3, &comment);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+    market.leave_review(&client_address, &job_id, &4, &comment);
+    market.leave_review(&talent_address, &job_id, &5, &comment);
+    let result = market.try_leave_review(&client_address, &job_id, &5, &comment);
+    assert_eq!(result, Err(Ok(Error::AlreadyReviewed.into())));
+
+    assert_eq!(market.get_average_rating(&talent_address), 400);
+    assert_eq!(market.get_average_rating(&client_address), 500);
+    assert_eq!(market.get_average_rating(&stranger), 0);
+    let reviews = market.list_reviews(&talent_address, &0, &10);
+    assert_eq!(reviews.len(), 1);
+    assert_eq!(reviews.get(0).unwrap().author, client_address);
+}
+
+#[test]
+fn test_reviews_are_paged_across_storage_entries() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let talent_address = Address::generate(&env);
+    let comment = BytesN::from_array(&env, &[9; 32]);
+    for i in 0..=INDEX_PAGE {
+        let client_address = Address::generate(&env);
+        let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+        market.cancel_job(&client_address, &job_id);
+        market.leave_review(&client_address, &job_id, &(1 + i % 5), &comment);
+    }
+
+    assert_eq!(market.get_average_rating(&talent_address), 298);
+    let reviews = market.list_reviews(&talent_address, &(INDEX_PAGE - 1), &10);
+    assert_eq!(reviews.len(), 2);
+    assert_eq!(reviews.get(0).unwrap().score, 5);
+    assert_eq!(reviews.get(1).unwrap().score, 1);
+}
+
+// ==========================
+// TALENT PROFILES
+// ==========================
+#[test]
+fn test_talent_profile_tracks_payouts_and_reviews() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let profile_hash = BytesN::from_array(&env, &[5; 32]);
+    let skills = vec![&env, symbol_short!("rust")];
+
+    let result = market.try_update_talent_profile(&talent_address, &skills, &profile_hash);
+    assert_eq!(result, Err(Ok(Error::TalentNotRegistered.into())));
+    let mut too_many = Vec::new(&env);
+    for _ in 0..21 {
+        too_many.push_back(symbol_short!("rust"));
+    }
+    let result = market.try_register_talent(&talent_address, &too_many, &profile_hash);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+
+    market.register_talent(&talent_address, &skills, &profile_hash);
+    let result = market.try_register_talent(&talent_address, &skills, &profile_hash);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+    market.update_talent_profile(&talent_address, &vec![&env, symbol_short!("design")], &profile_hash);
+    assert!(market.talent_has_skill(&talent_address, &symbol_short!("design")));
+    assert!(!market.talent_has_skill(&talent_address, &symbol_short!("rust")));
+
+    // Payouts and completion feed the profile
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &1);
+    market.leave_review(&client_address, &job_id, &4, &BytesN::from_array(&env, &[9; 32]));
+
+    let profile = market.get_talent_profile(&talent_address).unwrap();
+    assert_eq!(profile.total_earned, 300);
+    assert_eq!(profile.jobs_completed, 1);
+    assert_eq!(profile.rating, 400);
+}
+
+#[test]
+fn test_hire_enforces_talent_thresholds() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let veteran = Address::generate(&env);
+    let newcomer = Address::generate(&env);
+    let profile_hash = BytesN::from_array(&env, &[5; 32]);
+    market.register_talent(&veteran, &vec![&env, symbol_short!("rust")], &profile_hash);
+    market.register_talent(&newcomer, &vec![&env, symbol_short!("rust")], &profile_hash);
+
+    // Ratings are x100 on a 1-5 scale
+    let too_strict = JobOptions { min_talent_rating: Some(501), ..Default::default() };
+    let result = market.try_create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000],
+        &too_strict,
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+
+    let first_job = hire_talent(&env, &market, &token_admin, &client_address, &veteran);
+    market.submit_milestone(&veteran, &first_job, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &first_job, &0);
+    market.submit_milestone(&veteran, &first_job, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &first_job, &1);
+
+    token_admin.mint(&client_address, &100);
+    let options = JobOptions { min_jobs_completed: Some(1), ..Default::default() };
+    let job_id = market.create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000],
+        &options,
+    );
+    market.fund_job(&client_address, &job_id);
+
+    let result = market.try_offer_job(&client_address, &job_id, &newcomer);
+    assert_eq!(result, Err(Ok(Error::TalentNotEligible.into())));
+    let unregistered = Address::generate(&env);
+    let result = market.try_offer_job(&client_address, &job_id, &unregistered);
+    assert_eq!(result, Err(Ok(Error::TalentNotEligible.into())));
+    market.offer_job(&client_address, &job_id, &veteran);
+    market.accept_offer(&veteran, &job_id);
+}
+
+#[test]
+fn test_client_profile_tracks_lifecycle() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let arbitrator_address = Address::generate(&env);
+    register_arbitrator(&market, &token_admin, &arbitrator_address);
+
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+    token_admin.mint(&client_address, &DISPUTE_DEPOSIT);
+    market.raise_dispute(&client_address, &job_id, &None, &arbitrator_address);
+
+    let other_job = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.cancel_job(&client_address, &other_job);
+
+    let profile = market.get_client_profile(&client_address);
+    assert_eq!(profile.jobs_posted, 2);
+    // Milestone payout plus the kill fee on the cancelled job's unpaid 300
+    assert_eq!(profile.total_spent, 130);
+    assert_eq!(profile.disputes_raised, 1);
+    assert_eq!(profile.cancellations, 1);
+}
+
+#[test]
+fn test_reputation_of_summarizes_history() {
+    use soroban_sdk::xdr::ToXdr;
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    market.register_talent(&talent_address, &vec![&env, symbol_short!("rust")], &BytesN::from_array(&env, &[5; 32]));
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &1);
+    market.leave_review(&client_address, &job_id, &5, &BytesN::from_array(&env, &[9; 32]));
+
+    let talent = market.reputation_of(&talent_address);
+    assert_eq!(talent.version, 1);
+    assert!(talent.talent_registered);
+    assert_eq!((talent.jobs_completed, talent.total_earned), (1, 300));
+    assert_eq!((talent.rating, talent.reviews), (500, 1));
+    let client = market.reputation_of(&client_address);
+    assert!(!client.talent_registered);
+    assert_eq!((client.jobs_posted, client.total_spent), (1, 300));
+
+    // The snapshot digest binds the summary to this contract and ledger
+    let snapshot = market.reputation_snapshot(&talent_address);
+    assert_eq!(snapshot.summary, talent);
+    assert_eq!(snapshot.ledger, env.ledger().sequence());
+    let digest: BytesN<32> = env.crypto()
+        .sha256(&(market.address.clone(), snapshot.ledger, talent).to_xdr(&env))
+        .into();
+    assert_eq!(snapshot.digest, digest);
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("REP_SNAP"), talent_address).into_val(&env));
+    let published: (u32, BytesN<32>) = soroban_sdk::FromVal::from_val(&env, &data);
+    assert_eq!(published, (snapshot.ledger, digest));
+}
+
+// ==========================
+// PLATFORM FEES
+// ==========================
+#[test]
+fn test_platform_fee_accrues_and_withdraws() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, admin) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+
+    // Capped at 10% and reserved to the admin
+    let result = market.try_queue_action(&admin, &AdminAction::SetPlatformFee(1_001));
+    assert_eq!(result, Err(Ok(Error::FeeTooHigh.into())));
+    let result = market.try_queue_action(&client_address, &AdminAction::SetPlatformFee(500));
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    set_platform_fee(&env, &market, &admin, 500);
+    assert_eq!(market.get_platform_fee(), 500);
+
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &1);
+
+    // 5% of the 200 payout is kept; a tenth of it backs the insurance pool
+    assert_eq!(token.balance(&talent_address), 190);
+    assert_eq!(market.get_accrued_fees(&token.address), 9);
+    assert_eq!(market.get_insurance_pool(&token.address), 1);
+
+    let result = market.try_withdraw_fees(&admin, &token.address, &admin, &10);
+    assert_eq!(result, Err(Ok(Error::InsufficientFunds.into())));
+    let operator = Address::generate(&env);
+    market.withdraw_fees(&admin, &token.address, &operator, &9);
+    assert_eq!(token.balance(&operator), 9);
+    assert_eq!(market.get_accrued_fees(&token.address), 0);
+}
+
+#[test]
+fn test_insurance_claims_paid_from_fee_pool() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, admin) = setup_market(&env);
+    set_platform_fee(&env, &market, &admin, 1_000);
+    let action_id = market.queue_action(&admin, &AdminAction::SetInsuranceShare(5_000));
+    env.ledger().with_mut(|l| l.timestamp += DEFAULT_TL_DELAY);
+    market.execute_action(&action_id);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &1);
+    // Half of the 20 fee goes to the pool
+    assert_eq!(market.get_insurance_pool(&token.address), 10);
+
+    // Only the job's parties can claim
+    let evidence = BytesN::from_array(&env, &[9; 32]);
+    let result = market.try_claim_insurance(&Address::generate(&env), &job_id, &5, &evidence);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    let claim_id = market.claim_insurance(&talent_address, &job_id, &5, &evidence);
+
+    // Three matching arbitrator votes decide it
+    let arbitrators = [Address::generate(&env), Address::generate(&env), Address::generate(&env)];
+    for arbitrator in arbitrators.iter() {
+        register_arbitrator(&market, &token_admin, arbitrator);
+    }
+    market.resolve_claim(&arbitrators[0], &claim_id, &true);
+    let result = market.try_resolve_claim(&arbitrators[0], &claim_id, &true);
+    assert_eq!(result, Err(Ok(Error::AlreadyVoted.into())));
+    market.resolve_claim(&arbitrators[1], &claim_id, &true);
+    assert_eq!(market.get_insurance_claim(&claim_id).unwrap().status, ClaimStatus::Pending);
+    market.resolve_claim(&arbitrators[2], &claim_id, &true);
+    assert_eq!(market.get_insurance_claim(&claim_id).unwrap().status, ClaimStatus::Paid);
+    assert_eq!(token.balance(&talent_address), 180 + 5);
+    assert_eq!(market.get_insurance_pool(&token.address), 5);
+
+    // The admin decides alone, and cannot pay out more than the pool holds
+    let claim_id = market.claim_insurance(&client_address, &job_id, &6, &evidence);
+    let result = market.try_resolve_claim(&admin, &claim_id, &true);
+    assert_eq!(result, Err(Ok(Error::InsufficientFunds.into())));
+    market.resolve_claim(&admin, &claim_id, &false);
+    assert_eq!(market.get_insurance_claim(&claim_id).unwrap().status, ClaimStatus::Denied);
+    let result = market.try_resolve_claim(&admin, &99, &true);
+    assert_eq!(result, Err(Ok(Error::ClaimNotFound.into())));
+}
+
+#[test]
+fn test_referrer_rewarded_from_collected_fees() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, admin) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let referrer = Address::generate(&env);
+    token_admin.mint(&client_address, &300);
+    let now = env.ledger().timestamp();
+    let descriptions = vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32])), Text::Hash(BytesN::from_array(&env, &[3; 32]))];
+    let title = Text::Hash(BytesN::from_array(&env, &[1; 32]));
+    // Far enough out to outlast the fee change's timelock
+    let deadlines = vec![&env, now + 2 * DEFAULT_TL_DELAY, now + 3 * DEFAULT_TL_DELAY];
+
+    // Clients cannot refer themselves
+    let result = market.try_create_job_with_referrer(&client_address, &title, &descriptions, &vec![&env, 100, 200], &deadlines, &client_address);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+
+    let job_id = market.create_job_with_referrer(&client_address, &title, &descriptions, &vec![&env, 100, 200], &deadlines, &referrer);
+    market.fund_job(&client_address, &job_id);
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+
+    // The first milestone pays out fee-free; the fee applies to the second
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+    set_platform_fee(&env, &market, &admin, 1_000);
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[5; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &1);
+
+    // 10% of the 20 collected, not of 10% of the full 300
+    assert_eq!(market.get_referral_earnings(&referrer).get(token.address.clone()), Some(2));
+    assert_eq!(market.get_accrued_fees(&token.address), 18 - 2);
+
+    assert_eq!(market.claim_referral_earnings(&referrer, &token.address), 2);
+    assert_eq!(token.balance(&referrer), 2);
+    assert_eq!(market.get_referral_earnings(&referrer).get(token.address.clone()), None);
+}
+
+#[test]
+fn test_agency_cosigns_selection_and_takes_cut() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let agency = Address::generate(&env);
+    token_admin.mint(&client_address, &300);
+    let create = |options: &JobOptions| market.try_create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32])), Text::Hash(BytesN::from_array(&env, &[3; 32]))],
+        &vec![&env, 100, 200],
+        &vec![&env, 1_000u64, 2_000],
+        options,
+    );
+
+    // The cut plus the platform fee cap must fit in a payout
+    let options = JobOptions { agency: OptAgency::Some(Agency { address: agency.clone(), cut_bps: 9_500 }), ..Default::default() };
+    assert_eq!(create(&options), Err(Ok(Error::InvalidInput.into())));
+    let options = JobOptions { agency: OptAgency::Some(Agency { address: client_address.clone(), cut_bps: 1_000 }), ..Default::default() };
+    assert_eq!(create(&options), Err(Ok(Error::InvalidInput.into())));
+
+    let options = JobOptions { agency: OptAgency::Some(Agency { address: agency.clone(), cut_bps: 1_000 }), ..Default::default() };
+    let job_id = create(&options).unwrap().unwrap();
+    market.fund_job(&client_address, &job_id);
+    market.offer_job(&client_address, &job_id, &talent_address);
+    assert!(env.auths().iter().any(|(address, _)| *address == agency));
+    market.accept_offer(&talent_address, &job_id);
+
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &1);
+    assert_eq!(token.balance(&agency), 20);
+    assert_eq!(token.balance(&talent_address), 180);
+    assert!(market.verify_job_invariants(&job_id).unwrap());
+}
+
+#[test]
+fn test_solvency_tracks_escrow_per_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    assert_eq!(market.get_liabilities(&token.address), 300);
+    assert!(market.check_solvency(&token.address));
+
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &1);
+    assert_eq!(market.get_liabilities(&token.address), 100);
+    assert!(market.check_solvency(&token.address));
+
+    // Tokens leaving outside the ledger raise an alert
+    let drain = Address::generate(&env);
+    env.as_contract(&market.address, || token.transfer(&market.address, &drain, &40));
+    assert!(!market.check_solvency(&token.address));
+    let (_, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(topics, (symbol_short!("INSOLV"), token.address.clone()).into_val(&env));
+    let amounts: (i128, i128) = soroban_sdk::FromVal::from_val(&env, &data);
+    assert_eq!(amounts, (100, 60));
+}
+
+// ==========================
+// NATIVE XLM (SAC) ESCROW
+// ==========================
+const XLM: i128 = 10_000_000; // 1 XLM in stroops
+
+fn create_xlm_job(env: &Env, market: &DecentralizedJobMarketClient, client: &Address) -> u32 {
+    market.create_job(
+        client,
+        &Text::Plain(String::from_str(env, "Landing page")),
+        &vec![
+            env,
+            Text::Plain(String::from_str(env, "Wireframes")),
+            Text::Hash(BytesN::from_array(env, &[3; 32])),
+        ],
+        &vec![env, 30 * XLM, 70 * XLM],
+        &vec![env, 1_000u64, 2_000u64],
+    )
+}
+
+#[test]
+fn test_xlm_fund_and_payout() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, xlm, xlm_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    xlm_admin.mint(&client_address, &(100 * XLM));
+
+    let job_id = create_xlm_job(&env, &market, &client_address);
+    market.fund_job(&client_address, &job_id);
+    assert_eq!(xlm.balance(&client_address), 0);
+    assert_eq!(xlm.balance(&market.address), 100 * XLM);
+
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[9; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+
+    assert_eq!(xlm.balance(&talent_address), 30 * XLM);
+    assert_eq!(xlm.balance(&market.address), 70 * XLM);
+}
+
+#[test]
+fn test_xlm_refund_on_cancel() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, xlm, xlm_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    xlm_admin.mint(&client_address, &(100 * XLM));
+
+    // No talent hired: full refund
+    let job_id = create_xlm_job(&env, &market, &client_address);
+    market.fund_job(&client_address, &job_id);
+    market.cancel_job(&client_address, &job_id);
+
+    assert_eq!(xlm.balance(&client_address), 100 * XLM);
+    assert_eq!(xlm.balance(&market.address), 0);
+}
+
+#[test]
+fn test_xlm_refund_unstaffed() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, xlm, xlm_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    xlm_admin.mint(&client_address, &(100 * XLM));
+
+    let job_id = create_xlm_job(&env, &market, &client_address);
+    market.fund_job(&client_address, &job_id);
+
+    // Too early: the hiring window is still open
+    let result = market.try_refund_unstaffed(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::TooEarly.into())));
+
+    // A live offer may still be accepted
+    env.ledger().with_mut(|l| l.timestamp += 30 * 24 * 60 * 60 - 100);
+    market.offer_job(&client_address, &job_id, &talent_address);
+    env.ledger().with_mut(|l| l.timestamp += 101);
+    let result = market.try_refund_unstaffed(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+
+    env.ledger().with_mut(|l| l.timestamp += 7 * 24 * 60 * 60);
+    market.refund_unstaffed(&client_address, &job_id);
+
+    assert_eq!(xlm.balance(&client_address), 100 * XLM);
+    assert_eq!(xlm.balance(&market.address), 0);
+    assert_eq!(read_job(&env, &market, job_id).state, JobState::Expired);
+}
+
+#[test]
+fn test_xlm_top_up_surplus_refunded() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, xlm, xlm_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    xlm_admin.mint(&client_address, &(125 * XLM));
+
+    let job_id = create_xlm_job(&env, &market, &client_address);
+    market.fund_job(&client_address, &job_id);
+    market.top_up_escrow(&client_address, &job_id, &(25 * XLM));
+    assert_eq!(xlm.balance(&market.address), 125 * XLM);
+
+    // Only the part above the milestone total comes back
+    assert_eq!(market.withdraw_surplus(&client_address, &job_id), 25 * XLM);
+    assert_eq!(xlm.balance(&client_address), 25 * XLM);
+    assert_eq!(xlm.balance(&market.address), 100 * XLM);
+}
+
+#[test]
+fn test_xlm_cancel_fee_paid_to_hired_talent() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, xlm, xlm_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    xlm_admin.mint(&client_address, &(100 * XLM));
+
+    let job_id = create_xlm_job(&env, &market, &client_address);
+    market.fund_job(&client_address, &job_id);
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+    market.cancel_job(&client_address, &job_id);
+
+    // 10% cancellation fee goes to the talent, the rest back to the client
+    assert_eq!(xlm.balance(&talent_address), 10 * XLM);
+    assert_eq!(xlm.balance(&client_address), 90 * XLM);
+    assert_eq!(xlm.balance(&market.address), 0);
+}
+
+// ==========================
+// ADMINISTRATION
+// ==========================
+#[test]
+fn test_admin_transfer_requires_acceptance() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, _, admin) = setup_market(&env);
+    assert_eq!(market.get_admin(), admin);
+
+    let new_admin = Address::generate(&env);
+    let result = market.try_propose_admin(&new_admin, &new_admin);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+    market.propose_admin(&admin, &new_admin);
+    assert_eq!(market.get_pending_admin(), Some(new_admin.clone()));
+    // Proposal alone moves no rights
+    assert_eq!(market.get_admin(), admin);
+    let result = market.try_accept_admin(&Address::generate(&env));
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+
+    market.accept_admin(&new_admin);
+    assert_eq!(market.get_admin(), new_admin);
+    assert_eq!(market.get_pending_admin(), None);
+    let result = market.try_queue_action(&admin, &AdminAction::SetPlatformFee(100));
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    set_platform_fee(&env, &market, &new_admin, 100);
+    assert_eq!(market.get_platform_fee(), 100);
+}
+
+#[test]
+fn test_timelocked_actions_wait_for_delay() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, _, admin) = setup_market(&env);
+    assert_eq!(market.get_timelock_delay(), DEFAULT_TL_DELAY);
+
+    let other_token = env.register_stellar_asset_contract_v2(admin.clone()).address();
+    assert!(!market.is_token_allowed(&other_token));
+    let action_id = market.queue_action(&admin, &AdminAction::SetTokenAllowed(other_token.clone(), true));
+    assert!(market.get_queued_action(&action_id).is_some());
+
+    let result = market.try_execute_action(&action_id);
+    assert_eq!(result, Err(Ok(Error::TimelockNotReady.into())));
+    env.ledger().with_mut(|l| l.timestamp += DEFAULT_TL_DELAY);
+    market.execute_action(&action_id);
+    assert!(market.is_token_allowed(&other_token));
+    assert_eq!(market.get_queued_action(&action_id), None);
+
+    // Jobs may now escrow the allowlisted token
+    let client_address = Address::generate(&env);
+    let options = JobOptions { token: Some(other_token.clone()), ..Default::default() };
+    let job_id = market.create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, 1_000],
+        &options,
+    );
+    assert_eq!(read_job(&env, &market, job_id).token, other_token);
+
+    // Cancelled actions can no longer run
+    let action_id = market.queue_action(&admin, &AdminAction::SetPlatformFee(100));
+    market.cancel_action(&admin, &action_id);
+    let result = market.try_execute_action(&action_id);
+    assert_eq!(result, Err(Ok(Error::ActionNotFound.into())));
+}
+
+#[test]
+fn test_timelock_delay_has_a_floor() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, _, admin) = setup_market(&env);
+
+    let result = market.try_queue_action(&admin, &AdminAction::SetTimelockDelay(MIN_TL_DELAY - 1));
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+
+    let action_id = market.queue_action(&admin, &AdminAction::SetTimelockDelay(MIN_TL_DELAY));
+    env.ledger().with_mut(|l| l.timestamp += DEFAULT_TL_DELAY);
+    market.execute_action(&action_id);
+    assert_eq!(market.get_timelock_delay(), MIN_TL_DELAY);
+}
+
+#[test]
+fn test_pause_blocks_mutations_until_unpaused() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, admin) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+
+    let result = market.try_pause(&client_address);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    market.pause(&admin);
+    assert!(market.is_paused());
+
+    let data = BytesN::from_array(&env, &[3; 32]);
+    let result = market.try_submit_milestone(&talent_address, &job_id, &0, &data, &String::from_str(&env, "ipfs://deliverable"));
+    assert_eq!(result, Err(Ok(Error::ContractPaused.into())));
+    let result = market.try_cancel_job(&client_address, &job_id);
+    assert_eq!(result, Err(Ok(Error::ContractPaused.into())));
+    // Reads keep working
+    assert_eq!(market.get_admin(), admin);
+
+    market.unpause(&admin);
+    market.submit_milestone(&talent_address, &job_id, &0, &data, &String::from_str(&env, "ipfs://deliverable"));
+}
+
+#[test]
+fn test_get_config_reads_all_parameters() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, _, admin) = setup_market(&env);
+
+    let config = market.get_config();
+    assert_eq!(config.admin, admin);
+    assert_eq!(config.token, token.address);
+    assert_eq!(config.platform_fee_bps, 0);
+    assert_eq!(config.max_cancellation_fee_bps, market.get_max_cancellation_fee());
+    assert_eq!(config.min_arbitrator_stake, DEFAULT_MIN_STAKE);
+    assert_eq!((config.ruling_window, config.review_period), (RULING_WINDOW, APPEAL_WINDOW));
+    assert_eq!(config.timelock_delay, DEFAULT_TL_DELAY);
+    assert_eq!(config.text_limits, market.get_text_limits());
+    assert_eq!(config.job_limits, market.get_job_limits());
+    assert_eq!(config.state_version, STATE_VERSION);
+    assert!(!config.paused);
+
+    // Changes show up in the same read
+    set_platform_fee(&env, &market, &admin, 250);
+    market.pause(&admin);
+    let config = market.get_config();
+    assert_eq!(config.platform_fee_bps, 250);
+    assert!(config.paused);
+}
+
+#[test]
+fn test_constructor_applies_init_config() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let sac = env.register_stellar_asset_contract_v2(admin.clone());
+    let mut config = init_config(&admin, &sac.address());
+    config.platform_fee_bps = 250;
+    config.timelock_delay = MIN_TL_DELAY;
+    config.job_limits.max_milestones = 10;
+    let market = DecentralizedJobMarketClient::new(&env, &env.register(DecentralizedJobMarket, (config.clone(),)));
+
+    let read = market.get_config();
+    assert_eq!(read.admin, admin);
+    assert_eq!(read.platform_fee_bps, 250);
+    assert_eq!(read.timelock_delay, MIN_TL_DELAY);
+    assert_eq!(read.job_limits.max_milestones, 10);
+    assert_eq!(read.state_version, STATE_VERSION);
+
+    // Configured in the deploying call: no window for anyone else to step in
+    assert!(market.is_initialized());
+}
+
+#[test]
+#[should_panic]
+fn test_deploy_without_config_fails() {
+    let env = Env::default();
+    env.register(DecentralizedJobMarket, ());
+}
+
+#[test]
+fn test_migrate_bumps_state_version() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, _, admin) = setup_market(&env);
+    assert_eq!(market.get_state_version(), STATE_VERSION);
+
+    // Nothing to do on a fresh deployment
+    let result = market.try_migrate(&admin);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+
+    // Simulate state written by code that predates version tracking
+    env.as_contract(&market.address, || {
+        env.storage().instance().remove(&ConfigKey::StateVersion);
+    });
+    assert_eq!(market.get_state_version(), 0);
+
+    let result = market.try_migrate(&Address::generate(&env));
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    market.migrate(&admin);
+    assert_eq!(market.get_state_version(), STATE_VERSION);
+}
+
+#[test]
+fn test_migrate_rekeys_legacy_token_id_by_address() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, _, admin) = setup_market(&env);
+
+    // v1 stored the token, fees and allowlist under the raw contract id,
+    // with config under symbol keys
+    let token_id = BytesN::from_array(&env, &[4; 32]);
+    env.as_contract(&market.address, || {
+        env.storage().instance().remove(&ConfigKey::StateVersion);
+        env.storage().instance().remove(&ConfigKey::Token);
+        env.storage().instance().remove(&ConfigKey::AllowedTokens);
+        env.storage().instance().set(&symbol_short!("STATE_VER"), &1u32);
+        env.storage().instance().set(&symbol_short!("TOKEN_ID"), &token_id);
+        let treasury: Map<BytesN<32>, i128> = map![&env, (token_id.clone(), 50)];
+        env.storage().persistent().set(&symbol_short!("TREASURY"), &treasury);
+        let tokens: Map<BytesN<32>, bool> = map![&env, (token_id.clone(), true)];
+        env.storage().instance().set(&symbol_short!("TOKENS"), &tokens);
+    });
+
+    market.migrate(&admin);
+    let token = DecentralizedJobMarket::contract_address_from_id(&env, &token_id);
+    assert_eq!(token.to_string().len(), 56);
+    assert_eq!(market.get_accrued_fees(&token), 50);
+    assert!(market.is_token_allowed(&token));
+    let stored: Address = env.as_contract(&market.address, || {
+        env.storage().instance().get(&ConfigKey::Token).unwrap()
+    });
+    assert_eq!(stored, token);
+    env.as_contract(&market.address, || {
+        assert!(!env.storage().instance().has(&symbol_short!("TOKEN_ID")));
+        assert!(!env.storage().persistent().has(&symbol_short!("TREASURY")));
+    });
+}
+
+fn legacy_milestones(env: &Env) -> Vec<MilestoneV1> {
+    vec![
+        env,
+        MilestoneV1 {
+            description: BytesN::from_array(env, &[1; 32]),
+            amount: 30,
+            state: MilestoneState::Paid,
+            submission_data: BytesN::from_array(env, &[9; 32]),
+            deadline: 1_000,
+            submitted_at: Some(500),
+        },
+        MilestoneV1 {
+            description: BytesN::from_array(env, &[2; 32]),
+            amount: 70,
+            state: MilestoneState::Submitted,
+            submission_data: BytesN::from_array(env, &[8; 32]),
+            deadline: 2_000,
+            submitted_at: Some(900),
+        },
+    ]
+}
+
+/// Rewinds the market to `version` with one job stored under its pre-v3 key
+fn store_legacy_job<T: IntoVal<Env, soroban_sdk::Val>>(env: &Env, market: &DecentralizedJobMarketClient, version: u32, job: &T) {
+    env.as_contract(&market.address, || {
+        env.storage().instance().set(&ConfigKey::StateVersion, &version);
+        env.storage().instance().set(&ConfigKey::JobCount, &1u32);
+        env.storage().persistent().set(&DecentralizedJobMarket::legacy_job_key(env, 1), job);
+    });
+}
+
+#[test]
+fn test_migrate_jobs_from_v1_layout() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, _, admin) = setup_market(&env);
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+
+    store_legacy_job(&env, &market, 1, &JobV1 {
+        client: client_address.clone(),
+        talent: Some(talent_address.clone()),
+        title: BytesN::from_array(&env, &[5; 32]),
+        token: token.address.clone(),
+        total_value: 100,
+        amount_paid: 30,
+        state: JobState::Active,
+        milestones: legacy_milestones(&env),
+        escrow_balance: 70,
+        created_at: 100,
+        dispute_raised_by: None,
+        selected_arbitrator: None,
+        cancellation_fee: 10,
+    });
+    env.as_contract(&market.address, || {
+        env.storage().persistent().set(&(symbol_short!("REVIEWED"), 1u32, client_address.clone()), &true);
+    });
+
+    // Jobs wait for the layout migration
+    let result = market.try_migrate_jobs(&admin, &1, &10);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    market.migrate(&admin);
+    assert_eq!(market.get_state_version(), STATE_VERSION);
+    let result = market.try_migrate_jobs(&Address::generate(&env), &1, &10);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    assert_eq!(market.migrate_jobs(&admin, &1, &10), 0);
+
+    let job = read_job(&env, &market, 1);
+    assert_eq!(job.client, client_address);
+    assert_eq!(job.talent, Some(talent_address));
+    assert_eq!(job.specialization, OptHash::None);
+    assert_eq!(job.cancel_terms.cancellation_fee_bps, 1_000);
+    assert_eq!(job.milestones.get(1).unwrap().state, MilestoneState::Submitted);
+    assert!(job.milestones.get(1).unwrap().subjobs.is_empty());
+    assert_eq!(market.list_jobs_by_category(&symbol_short!("general"), &0, &10), vec![&env, 1]);
+    env.as_contract(&market.address, || {
+        let legacy_key = DecentralizedJobMarket::legacy_job_key(&env, 1);
+        assert!(!env.storage().persistent().has(&legacy_key));
+        assert!(env.storage().persistent().has(&JobKey::Reviewed(1, client_address.clone())));
+    });
+}
+
+#[test]
+fn test_migrated_v2_job_pays_out() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, admin) = setup_market(&env);
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+
+    store_legacy_job(&env, &market, 2, &JobV2 {
+        client: client_address.clone(),
+        talent: Some(talent_address.clone()),
+        title: BytesN::from_array(&env, &[5; 32]),
+        token: token.address.clone(),
+        specialization: OptHash::Some(BytesN::from_array(&env, &[7; 32])),
+        min_talent_rating: 0,
+        min_jobs_completed: 0,
+        total_value: 100,
+        amount_paid: 30,
+        state: JobState::Active,
+        milestones: legacy_milestones(&env),
+        escrow_balance: 70,
+        created_at: 100,
+        cancellation_fee: 0,
+    });
+    token_admin.mint(&market.address, &70);
+
+    market.migrate(&admin);
+    assert_eq!(market.migrate_jobs(&admin, &1, &10), 0);
+    let job = read_job(&env, &market, 1);
+    assert_eq!(job.specialization, OptHash::Some(BytesN::from_array(&env, &[7; 32])));
+
+    // The migrated job carries on where it left off
+    market.approve_milestone(&client_address, &1, &1);
+    assert_eq!(token.balance(&talent_address), 70);
+    assert_eq!(read_job(&env, &market, 1).state, JobState::Completed);
+}
+
+// ==========================
+// STORAGE MAINTENANCE
+// ==========================
+#[test]
+fn test_bump_job_extends_storage_lifetime() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    let job_ttl = || env.as_contract(&market.address, || {
+        env.storage().persistent().get_ttl(&JobKey::Job(job_id))
+    });
+    let full_ttl = job_ttl();
+
+    // Twenty idle days eat into both the job and the config lifetimes
+    let idle = 20 * 17_280;
+    env.ledger().with_mut(|l| l.sequence_number += idle);
+    assert_eq!(job_ttl(), full_ttl - idle);
+
+    market.bump_job(&job_id);
+    assert_eq!(job_ttl(), full_ttl);
+    let instance_ttl = env.as_contract(&market.address, || env.storage().instance().get_ttl());
+    assert_eq!(instance_ttl, 30 * 17_280);
+
+    let result = market.try_bump_job(&(job_id + 1));
+    assert_eq!(result, Err(Ok(Error::JobNotFound.into())));
+}
+
+// ==========================
+// REENTRANCY GUARD
+// ==========================
+#[test]
+fn test_sequential_calls_release_guard() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, xlm, xlm_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    xlm_admin.mint(&client_address, &(200 * XLM));
+
+    // Back-to-back calls from the same and different users must not trip the guard
+    let first = create_xlm_job(&env, &market, &client_address);
+    let second = create_xlm_job(&env, &market, &client_address);
+    assert_ne!(first, second);
+
+    market.fund_job(&client_address, &first);
+    market.fund_job(&client_address, &second);
+    market.offer_job(&client_address, &first, &talent_address);
+    market.accept_offer(&talent_address, &first);
+    market.submit_milestone(&talent_address, &first, &0, &BytesN::from_array(&env, &[9; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &first, &0);
+    market.cancel_job(&client_address, &second);
+
+    assert_eq!(xlm.balance(&talent_address), 30 * XLM);
+    assert_eq!(xlm.balance(&market.address), 70 * XLM);
+}
+
+#[test]
+fn test_failed_call_does_not_lock_contract() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, xlm_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    xlm_admin.mint(&client_address, &(100 * XLM));
+
+    let job_id = create_xlm_job(&env, &market, &client_address);
+    assert!(market.try_fund_job(&stranger, &job_id).is_err());
+
+    // The next legitimate call still goes through
+    market.fund_job(&client_address, &job_id);
+}
+
+// ==========================
+// ARBITRATION FEES
+// ==========================
+/// Funded job (30 + 70, plus `top_up`) with a hired talent who has
+/// submitted both milestones; `initiator` disputes the first one with the
+/// maximum fee deposit (10% of the job value). Returns the job id and the
+/// arbitrator.
+fn dispute_first_milestone(
+    env: &Env,
+    market: &DecentralizedJobMarketClient,
+    token_admin: &StellarAssetClient,
+    client: &Address,
+    talent: &Address,
+    initiator: &Address,
+    top_up: i128,
+) -> (u32, Address) {
+    let arbitrator = Address::generate(env);
+    register_arbitrator(market, token_admin, &arbitrator);
+
+    // Deadlines outlast the appeal window so rejected work can be redone
+    token_admin.mint(client, &(100 + top_up));
+    let job_id = market.create_job(
+        client,
+        &Text::Hash(BytesN::from_array(env, &[1; 32])),
+        &vec![env, Text::Hash(BytesN::from_array(env, &[2; 32])), Text::Hash(BytesN::from_array(env, &[3; 32]))],
+        &vec![env, 30, 70],
+        &vec![env, APPEAL_WINDOW * 2, APPEAL_WINDOW * 2],
+    );
+    market.fund_job(client, &job_id);
+    if top_up > 0 {
+        market.top_up_escrow(client, &job_id, &top_up);
+    }
+    market.offer_job(client, &job_id, talent);
+    market.accept_offer(talent, &job_id);
+    let uri = String::from_str(env, "ipfs://deliverable");
+    market.submit_milestone(talent, &job_id, &0, &BytesN::from_array(env, &[9; 32]), &uri);
+    market.submit_milestone(talent, &job_id, &1, &BytesN::from_array(env, &[8; 32]), &uri);
+
+    token_admin.mint(initiator, &((100 + top_up) / 10));
+    market.raise_dispute(initiator, &job_id, &Some(0), &arbitrator);
+    (job_id, arbitrator)
+}
+
+/// Resubmit milestones sent back by the ruling, then approve everything
+/// still awaiting approval
+fn approve_remaining(env: &Env, market: &DecentralizedJobMarketClient, client: &Address, talent: &Address, job_id: u32) {
+    let uri = String::from_str(env, "ipfs://revised");
+    for idx in 0..2u32 {
+        let milestone = read_job(env, market, job_id).milestones.get(idx).unwrap();
+        if milestone.state == MilestoneState::Rejected {
+            market.submit_milestone(talent, &job_id, &idx, &BytesN::from_array(env, &[20; 32]), &uri);
+        }
+        if read_job(env, market, job_id).milestones.get(idx).unwrap().state == MilestoneState::Submitted {
+            market.approve_milestone(client, &job_id, &idx);
+        }
+    }
+}
+
+#[test]
+fn test_won_dispute_fee_does_not_underfund_milestones() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let (job_id, arbitrator) = dispute_first_milestone(
+        &env, &market, &token_admin, &client_address, &talent_address, &talent_address, 0,
+    );
+    market.resolve_dispute(&arbitrator, &job_id, &Some(0), &true);
+    execute_ruling(&env, &market, job_id);
+
+    // Escrow held no surplus, so the 5% fee came out of the deposit and
+    // the second milestone is still fully funded
+    assert!(market.verify_job_invariants(&job_id).unwrap());
+    market.approve_milestone(&client_address, &job_id, &1);
+
+    assert_eq!(token.balance(&talent_address), 100 + 5);
+    assert_eq!(token.balance(&arbitrator), 5);
+    assert_eq!(token.balance(&market.address), DEFAULT_MIN_STAKE);
+    assert_eq!(read_job(&env, &market, job_id).fees_charged, 0);
+}
+
+#[test]
+fn test_won_dispute_fee_paid_from_reserved_surplus() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let (job_id, arbitrator) = dispute_first_milestone(
+        &env, &market, &token_admin, &client_address, &talent_address, &talent_address, 10,
+    );
+
+    // The surplus reserved for the fee cannot be withdrawn mid-dispute
+    assert_eq!(market.get_escrow_surplus(&job_id).unwrap(), 0);
+    assert!(market.try_withdraw_surplus(&client_address, &job_id).is_err());
+
+    market.resolve_dispute(&arbitrator, &job_id, &Some(0), &true);
+    execute_ruling(&env, &market, job_id);
+    market.approve_milestone(&client_address, &job_id, &1);
+
+    // The client's reserve paid the fee and the talent's deposit came back
+    assert_eq!(token.balance(&talent_address), 100 + 11);
+    assert_eq!(token.balance(&arbitrator), 5);
+    assert_eq!(market.withdraw_surplus(&client_address, &job_id), 5);
+    assert_eq!(token.balance(&market.address), DEFAULT_MIN_STAKE);
+    assert!(market.verify_job_invariants(&job_id).unwrap());
+}
+
+#[test]
+fn test_milestones_payable_after_rejection() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let (job_id, arbitrator) = dispute_first_milestone(
+        &env, &market, &token_admin, &client_address, &talent_address, &talent_address, 0,
+    );
+    market.resolve_dispute(&arbitrator, &job_id, &Some(0), &false);
+    execute_ruling(&env, &market, job_id);
+    approve_remaining(&env, &market, &client_address, &talent_address, job_id);
+
+    // Both milestones paid in full; the talent's deposit covered the fee
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.state, JobState::Completed);
+    assert_eq!(job.amount_paid, 100);
+    assert_eq!(job.escrow_balance, 0);
+    assert_eq!(token.balance(&talent_address), 100 + 5);
+    assert!(market.verify_job_invariants(&job_id).unwrap());
+}
+
+#[test]
+fn test_milestones_payable_after_split() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let (job_id, arbitrator) = dispute_first_milestone(
+        &env, &market, &token_admin, &client_address, &talent_address, &talent_address, 0,
+    );
+    market.resolve_dispute_split(&arbitrator, &job_id, &0, &5_000);
+    execute_ruling(&env, &market, job_id);
+    approve_remaining(&env, &market, &client_address, &talent_address, job_id);
+
+    // Half of milestone 0 to each side, milestone 1 paid in full; with no
+    // surplus reserved the fee stayed with the talent's deposit
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.state, JobState::Completed);
+    assert_eq!(job.escrow_balance, 0);
+    assert_eq!(token.balance(&talent_address), 15 + 70 + 5);
+    assert_eq!(token.balance(&client_address), 15);
+    assert!(market.verify_job_invariants(&job_id).unwrap());
+}
+
+#[test]
+fn test_milestones_payable_after_client_dispute() {
+    for decision in [true, false] {
+        let env = Env::default();
+        env.mock_all_auths();
+        let (market, token, token_admin, _) = setup_market(&env);
+
+        let client_address = Address::generate(&env);
+        let talent_address = Address::generate(&env);
+        let (job_id, arbitrator) = dispute_first_milestone(
+            &env, &market, &token_admin, &client_address, &talent_address, &client_address, 0,
+        );
+        market.resolve_dispute(&arbitrator, &job_id, &Some(0), &decision);
+        execute_ruling(&env, &market, job_id);
+        approve_remaining(&env, &market, &client_address, &talent_address, job_id);
+
+        // Whoever won, the client's deposit paid the fee and the escrow
+        // still covered every milestone
+        let job = read_job(&env, &market, job_id);
+        assert_eq!(job.state, JobState::Completed);
+        assert_eq!(job.amount_paid, 100);
+        assert_eq!(job.fees_charged, 0);
+        assert_eq!(job.escrow_balance, 0);
+        assert_eq!(token.balance(&market.address), DEFAULT_MIN_STAKE);
+        assert!(market.verify_job_invariants(&job_id).unwrap());
+    }
+}
+
+// ==========================
+// YIELD STRATEGY
+// ==========================
+mod strategy {
+    use soroban_sdk::{contract, contractimpl, symbol_short, token, Address, Env};
+
+    /// One share per token, plus any tip minted to it; `withdraw` fails
+    /// while broken
+    #[contract]
+    pub struct MockStrategy;
+
+    #[contractimpl]
+    impl MockStrategy {
+        pub fn set_broken(env: Env, broken: bool) {
+            env.storage().instance().set(&symbol_short!("BROKEN"), &broken);
+        }
+
+        pub fn deposit(_env: Env, _token: Address, amount: i128) -> i128 {
+            amount
+        }
+
+        pub fn withdraw(env: Env, token: Address, shares: i128, to: Address) -> i128 {
+            if env.storage().instance().get(&symbol_short!("BROKEN")).unwrap_or(false) {
+                panic!("strategy halted");
+            }
+            let token = token::Client::new(&env, &token);
+            let amount = token.balance(&env.current_contract_address()).max(shares);
+            token.transfer(&env.current_contract_address(), &to, &amount);
+            amount
+        }
+    }
+}
+
+fn set_yield_strategy(env: &Env, market: &DecentralizedJobMarketClient, admin: &Address, strategy: &Address, client_share_bps: u32) {
+    let action_id = market.set_yield_strategy(admin, &Some(strategy.clone()), &client_share_bps);
+    env.ledger().with_mut(|l| l.timestamp += DEFAULT_TL_DELAY);
+    market.execute_action(&action_id);
+}
+
+#[test]
+fn test_idle_escrow_earns_yield_until_first_payout() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, admin) = setup_market(&env);
+    let strategy_id = env.register(strategy::MockStrategy, ());
+    set_yield_strategy(&env, &market, &admin, &strategy_id, 5_000);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    assert_eq!(token.balance(&strategy_id), 300);
+    assert_eq!(market.get_yield_position(&job_id).unwrap().principal, 300);
+
+    // The strategy earns 20; the client and treasury split it on unwind
+    token_admin.mint(&strategy_id, &20);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+    assert_eq!(market.get_yield_position(&job_id), None);
+    assert_eq!(token.balance(&talent_address), 100);
+    assert_eq!(token.balance(&client_address), 10);
+    assert_eq!(market.get_accrued_fees(&token.address), 10);
+    assert_eq!(token.balance(&market.address), 210);
+    assert!(market.verify_job_invariants(&job_id).unwrap());
+}
+
+#[test]
+fn test_failing_strategy_does_not_block_payouts() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, admin) = setup_market(&env);
+    let strategy_id = env.register(strategy::MockStrategy, ());
+    let strategy = strategy::MockStrategyClient::new(&env, &strategy_id);
+
+    // Accrued fees available to front a loss
+    token_admin.mint(&market.address, &500);
+    env.as_contract(&market.address, || {
+        let treasury: Map<Address, i128> = map![&env, (token.address.clone(), 500)];
+        env.storage().persistent().set(&AccountKey::Treasury, &treasury);
+    });
+    set_yield_strategy(&env, &market, &admin, &strategy_id, 0);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+
+    // The strategy stops paying back: the treasury fronts the escrow
+    strategy.set_broken(&true);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+    assert_eq!(token.balance(&talent_address), 100);
+    assert_eq!(market.get_accrued_fees(&token.address), 200);
+    assert!(market.get_yield_position(&job_id).unwrap().stranded_at.is_some());
+
+    // Later payouts do not go back to the strategy
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &1);
+    assert_eq!(token.balance(&talent_address), 300);
+
+    // Once it recovers, the admin redeems the position into the treasury
+    assert!(market.try_emergency_unwind(&admin, &job_id).is_err());
+    strategy.set_broken(&false);
+    assert_eq!(market.emergency_unwind(&admin, &job_id), 300);
+    assert_eq!(market.get_yield_position(&job_id), None);
+    assert_eq!(market.get_accrued_fees(&token.address), 500);
+    assert_eq!(token.balance(&market.address), 500);
+    let result = market.try_emergency_unwind(&admin, &job_id);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+}
+
+// ==========================
+// PRICED JOBS
+// ==========================
+mod oracle {
+    use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env};
+
+    /// Returns whatever rate was last set, stamped with its update time
+    #[contract]
+    pub struct MockOracle;
+
+    #[contractimpl]
+    impl MockOracle {
+        pub fn set_price(env: Env, rate: i128) {
+            let updated_at = env.ledger().timestamp();
+            env.storage().instance().set(&symbol_short!("PRICE"), &(rate, updated_at));
+        }
+
+        pub fn price(env: Env, _token: Address) -> (i128, u64) {
+            env.storage().instance().get(&symbol_short!("PRICE")).unwrap()
+        }
+    }
+}
+
+#[test]
+fn test_priced_job_converts_at_payout_within_slippage() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, admin) = setup_market(&env);
+    let oracle = oracle::MockOracleClient::new(&env, &env.register(oracle::MockOracle, ()));
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let terms = PriceTerms { oracle: oracle.address.clone(), max_slippage_bps: 1_000, fallback_rate: RATE_SCALE };
+    let options = JobOptions { pricing: OptPriceTerms::Some(terms), ..Default::default() };
+    let create = |options: &JobOptions| {
+        let now = env.ledger().timestamp();
+        market.try_create_job_with_options(
+            &client_address,
+            &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+            &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32])), Text::Hash(BytesN::from_array(&env, &[3; 32]))],
+            &vec![&env, 100, 200],
+            &vec![&env, now + 1_000, now + 2_000],
+            options,
+        )
+    };
+
+    // Only whitelisted oracles can price a job
+    assert_eq!(create(&options), Err(Ok(Error::InvalidInput.into())));
+    let action_id = market.queue_action(&admin, &AdminAction::SetOracleAllowed(oracle.address.clone(), true));
+    env.ledger().with_mut(|l| l.timestamp += DEFAULT_TL_DELAY);
+    market.execute_action(&action_id);
+
+    // Funding escrows each price at the top of the 10% band
+    oracle.set_price(&RATE_SCALE);
+    let job_id = create(&options).unwrap().unwrap();
+    token_admin.mint(&client_address, &330);
+    market.fund_job(&client_address, &job_id);
+    assert_eq!(read_job(&env, &market, job_id).total_value, 330);
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+
+    // Paid at the oracle rate; the unused escrow goes back to the client
+    oracle.set_price(&(RATE_SCALE * 105 / 100));
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+    assert_eq!(token.balance(&talent_address), 105);
+    assert_eq!(token.balance(&client_address), 5);
+
+    // A rate beyond the band is clamped to it
+    oracle.set_price(&(RATE_SCALE * 2));
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[5; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &1);
+    assert_eq!(token.balance(&talent_address), 105 + 220);
+    assert!(market.verify_job_invariants(&job_id).unwrap());
+}
+
+// ==========================
+// HOOKS
+// ==========================
+mod hook {
+    use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env, Symbol};
+
+    /// Counts notifications; fails every call while `broken` is set
+    #[contract]
+    pub struct Recorder;
+
+    #[contractimpl]
+    impl Recorder {
+        pub fn set_broken(env: Env, broken: bool) {
+            env.storage().instance().set(&symbol_short!("BROKEN"), &broken);
+        }
+
+        pub fn calls(env: Env) -> u32 {
+            env.storage().instance().get(&symbol_short!("CALLS")).unwrap_or(0)
+        }
+
+        pub fn on_event(env: Env, _event_kind: Symbol, _job_id: u32, _actor: Address) {
+            if env.storage().instance().get(&symbol_short!("BROKEN")).unwrap_or(false) {
+                panic!("hook failed");
+            }
+            let calls = Self::calls(env.clone()) + 1;
+            env.storage().instance().set(&symbol_short!("CALLS"), &calls);
+        }
+    }
+}
+
+#[test]
+fn test_register_hook_is_timelocked() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, _, _, admin) = setup_market(&env);
+    let recorder_id = env.register(hook::Recorder, ());
+    let recorder = hook::RecorderClient::new(&env, &recorder_id);
+    let job_created = Symbol::new(&env, "JOB_CRT");
+
+    let action_id = market.register_hook(&admin, &job_created, &recorder_id);
+    assert_eq!(market.get_hooks(&job_created).len(), 0);
+    let result = market.try_execute_action(&action_id);
+    assert_eq!(result, Err(Ok(Error::TimelockNotReady.into())));
+
+    env.ledger().with_mut(|l| l.timestamp += DEFAULT_TL_DELAY);
+    market.execute_action(&action_id);
+    assert_eq!(market.get_hooks(&job_created), vec![&env, recorder_id.clone()]);
+    let result = market.try_register_hook(&admin, &job_created, &recorder_id);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+
+    let client_address = Address::generate(&env);
+    create_xlm_job(&env, &market, &client_address);
+    assert_eq!(recorder.calls(), 1);
+
+    // A failing target does not revert the marketplace call
+    recorder.set_broken(&true);
+    create_xlm_job(&env, &market, &client_address);
+    assert_eq!(recorder.calls(), 1);
+
+    market.unregister_hook(&admin, &job_created, &recorder_id);
+    assert_eq!(market.get_hooks(&job_created).len(), 0);
+}
+
+// ==========================
+// KEEPERS
+// ==========================
+#[test]
+fn test_keepers_run_due_work_for_a_bounty() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, admin) = setup_market(&env);
+    let keeper = Address::generate(&env);
+    let action_id = market.queue_action(&admin, &AdminAction::SetKeeperBounty(5));
+    set_platform_fee(&env, &market, &admin, 1_000);
+    market.execute_action(&action_id);
+
+    // 18 of the 20 fee accrues to the treasury and funds bounties
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let paid_job = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &paid_job, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &paid_job, &1);
+
+    token_admin.mint(&client_address, &10);
+    let terms = RetainerTerms { period: 5_000, payment_per_period: 10, max_periods: 1 };
+    let now = env.ledger().timestamp();
+    let retainer_job = market.create_job_with_options(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32]))],
+        &vec![&env, 100],
+        &vec![&env, now + 1_000],
+        &JobOptions { retainer: OptRetainer::Some(terms), ..Default::default() },
+    );
+    token_admin.mint(&client_address, &100);
+    market.fund_job(&client_address, &retainer_job);
+    token.approve(&client_address, &market.address, &10, &1_000_000);
+    market.offer_job(&client_address, &retainer_job, &talent_address);
+    market.accept_offer(&talent_address, &retainer_job);
+    let unfunded_job = create_xlm_job(&env, &market, &client_address);
+
+    // Nothing is due yet
+    assert_eq!(market.list_actionable_jobs(&0, &10).len(), 0);
+    let result = market.try_perform_upkeep(&keeper, &unfunded_job);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+
+    env.ledger().with_mut(|l| l.timestamp += DEFAULT_FUND_WINDOW);
+    assert_eq!(
+        market.list_actionable_jobs(&0, &10),
+        vec![&env, (retainer_job, KeeperTask::AdvancePeriod), (unfunded_job, KeeperTask::ExpireUnfunded)]
+    );
+    assert_eq!(market.perform_upkeep(&keeper, &retainer_job), KeeperTask::AdvancePeriod);
+    assert_eq!(read_job(&env, &market, retainer_job).milestones.len(), 2);
+    assert_eq!(market.perform_upkeep(&keeper, &unfunded_job), KeeperTask::ExpireUnfunded);
+    assert_eq!(read_job(&env, &market, unfunded_job).state, JobState::Expired);
+    assert_eq!(token.balance(&keeper), 10);
+    assert_eq!(market.get_accrued_fees(&token.address), 18 - 10);
+
+    // The retainer has run its last period
+    assert_eq!(market.list_actionable_jobs(&0, &10).len(), 0);
+}
+
+// ==========================
+// NEGOTIATION
+// ==========================
+#[test]
+fn test_accepted_proposal_creates_job_on_negotiated_terms() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (market, token, token_admin, _) = setup_market(&env);
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let other_talent = Address::generate(&env);
+    let draft_id = market.post_draft(
+        &client_address,
+        &Text::Hash(BytesN::from_array(&env, &[1; 32])),
+        &vec![&env, Text::Hash(BytesN::from_array(&env, &[2; 32])), Text::Hash(BytesN::from_array(&env, &[3; 32]))],
+        &vec![&env, 100, 200],
+        &vec![&env, 1_000u64, 2_000u64],
+    );
+
+    // Only registered talents may counter-propose
+    let result = market.try_propose_terms(&talent_address, &draft_id, &vec![&env, 150, 250], &vec![&env, 1_500u64, 2_500u64]);
+    assert_eq!(result, Err(Ok(Error::TalentNotRegistered.into())));
+    market.register_talent(&talent_address, &vec![&env, symbol_short!("rust")], &BytesN::from_array(&env, &[5; 32]));
+    market.register_talent(&other_talent, &vec![&env, symbol_short!("rust")], &BytesN::from_array(&env, &[6; 32]));
+    let result = market.try_propose_terms(&talent_address, &draft_id, &vec![&env, 150], &vec![&env, 1_500u64]);
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+
+    // Proposing again replaces the earlier proposal
+    assert_eq!(market.propose_terms(&talent_address, &draft_id, &vec![&env, 200, 300], &vec![&env, 1_500u64, 2_500u64]), 0);
+    assert_eq!(market.propose_terms(&other_talent, &draft_id, &vec![&env, 120, 220], &vec![&env, 1_200u64, 2_200u64]), 1);
+    assert_eq!(market.propose_terms(&talent_address, &draft_id, &vec![&env, 150, 250], &vec![&env, 1_500u64, 2_500u64]), 0);
+    assert_eq!(market.get_draft(&draft_id).unwrap().proposals.len(), 2);
+
+    // The job takes the accepted terms and is reserved for the proposer
+    let job_id = market.accept_proposal(&client_address, &draft_id, &0);
+    let job = read_job(&env, &market, job_id);
+    assert_eq!(job.total_value, 400);
+    assert_eq!(job.milestones.get(1).unwrap().deadline, 2_500);
+    assert_eq!(market.get_draft(&draft_id).unwrap().job_id, Some(job_id));
+    let result = market.try_accept_proposal(&client_address, &draft_id, &1);
+    assert_eq!(result, Err(Ok(Error::InvalidState.into())));
+
+    token_admin.mint(&client_address, &400);
+    market.fund_job(&client_address, &job_id);
+    let result = market.try_offer_job(&client_address, &job_id, &other_talent);
+    assert_eq!(result, Err(Ok(Error::Unauthorized.into())));
+    market.offer_job(&client_address, &job_id, &talent_address);
+    market.accept_offer(&talent_address, &job_id);
+    assert_eq!(token.balance(&market.address), 400);
+}
+
+// ==========================
+// PAYOUT SWAPS
+// ==========================
+mod router {
+    use soroban_sdk::{contract, contractimpl, symbol_short, token, Address, Env};
+
+    /// Quotes `rate` output per input and delivers `fill_bps` of the quote
+    /// from its own balance; a zero rate makes the quote fail
+    #[contract]
+    pub struct MockRouter;
+
+    #[contractimpl]
+    impl MockRouter {
+        pub fn set_rate(env: Env, rate: i128, fill_bps: i128) {
+            env.storage().instance().set(&symbol_short!("RATE"), &(rate, fill_bps));
+        }
+
+        pub fn quote(env: Env, _token_in: Address, _token_out: Address, amount_in: i128) -> i128 {
+            let (rate, _): (i128, i128) = env.storage().instance().get(&symbol_short!("RATE")).unwrap();
+            if rate == 0 {
+                panic!("no liquidity");
+            }
+            amount_in * rate
+        }
+
+        pub fn swap(
+            env: Env,
+            from: Address,
+            token_in: Address,
+            token_out: Address,
+            amount_in: i128,
+            _min_out: i128,
+            to: Address,
+        ) -> i128 {
+            let (rate, fill_bps): (i128, i128) = env.storage().instance().get(&symbol_short!("RATE")).unwrap();
+            token::Client::new(&env, &token_in).transfer(&from, &env.current_contract_address(), &amount_in);
+            let amount_out = amount_in * rate * fill_bps / 10_000;
+            token::Client::new(&env, &token_out).transfer(&env.current_contract_address(), &to, &amount_out);
+            amount_out
+        }
+    }
+}
+
+#[test]
+fn test_payout_swapped_into_preferred_token_with_fallback() {
+    let env = Env::default();
+    // The router's pull is authorized by the market, below the root call
+    env.mock_all_auths_allowing_non_root_auth();
+    let (market, token, token_admin, admin) = setup_market(&env);
+    let router = router::MockRouterClient::new(&env, &env.register(router::MockRouter, ()));
+    let usdc = env.register_stellar_asset_contract_v2(admin.clone());
+    StellarAssetClient::new(&env, &usdc.address()).mint(&router.address, &10_000);
+    let usdc_token = TokenClient::new(&env, &usdc.address());
+
+    let client_address = Address::generate(&env);
+    let talent_address = Address::generate(&env);
+    let preference = PayoutPreference { token: usdc.address(), router: router.address.clone(), max_slippage_bps: 100 };
+
+    // Only whitelisted routers can be chosen
+    let result = market.try_set_payout_preference(&talent_address, &Some(preference.clone()));
+    assert_eq!(result, Err(Ok(Error::InvalidInput.into())));
+    let action_id = market.queue_action(&admin, &AdminAction::SetRouterAllowed(router.address.clone(), true));
+    env.ledger().with_mut(|l| l.timestamp += DEFAULT_TL_DELAY);
+    market.execute_action(&action_id);
+    market.set_payout_preference(&talent_address, &Some(preference.clone()));
+    assert_eq!(market.get_payout_preference(&talent_address), Some(preference));
+
+    // Paid in the preferred token at the router's rate
+    router.set_rate(&2, &10_000);
+    let job_id = hire_talent(&env, &market, &token_admin, &client_address, &talent_address);
+    market.submit_milestone(&talent_address, &job_id, &0, &BytesN::from_array(&env, &[3; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    market.approve_milestone(&client_address, &job_id, &0);
+    assert_eq!(usdc_token.balance(&talent_address), 200);
+    assert_eq!(token.balance(&router.address), 100);
+
+    // A fill below the slippage bound reverts the approval
+    router.set_rate(&2, &9_800);
+    market.submit_milestone(&talent_address, &job_id, &1, &BytesN::from_array(&env, &[4; 32]), &String::from_str(&env, "ipfs://deliverable"));
+    let result = market.try_approve_milestone(&client_address, &job_id, &1);
+    assert_eq!(result, Err(Ok(Error::InsufficientFunds.into())));
+
+    // A failing router falls back to the escrow token
+    router.set_rate(&0, &10_000);
+    market.approve_milestone(&client_address, &job_id, &1);
+    assert_eq!(token.balance(&talent_address), 200);
+    assert_eq!(usdc_token.balance(&talent_address), 200);
+}